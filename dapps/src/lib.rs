@@ -85,7 +85,7 @@ use std::collections::HashMap;
 
 use jsonrpc_core::{IoHandler, IoDelegate};
 use router::auth::{Authorization, NoAuth, HttpBasicAuth};
-use ethcore_rpc::Extendable;
+use ethcore_rpc::{Extendable, AccessControl};
 
 static DAPPS_DOMAIN : &'static str = ".parity";
 
@@ -105,6 +105,7 @@ pub struct ServerBuilder {
 	handler: Arc<IoHandler>,
 	registrar: Arc<ContractClient>,
 	sync_status: Arc<SyncStatus>,
+	access: Option<Arc<AccessControl>>,
 }
 
 impl Extendable for ServerBuilder {
@@ -121,6 +122,7 @@ impl ServerBuilder {
 			handler: Arc::new(IoHandler::new()),
 			registrar: registrar,
 			sync_status: Arc::new(|| false),
+			access: None,
 		}
 	}
 
@@ -129,6 +131,11 @@ impl ServerBuilder {
 		self.sync_status = status;
 	}
 
+	/// Enforce given access control policy on RPC requests.
+	pub fn with_access_control(&mut self, access: Arc<AccessControl>) {
+		self.access = Some(access);
+	}
+
 	/// Asynchronously start server with no authentication,
 	/// returns result with `Server` handle on success or an error.
 	pub fn start_unsecured_http(&self, addr: &SocketAddr, hosts: Option<Vec<String>>) -> Result<Server, ServerError> {
@@ -140,6 +147,7 @@ impl ServerBuilder {
 			self.dapps_path.clone(),
 			self.registrar.clone(),
 			self.sync_status.clone(),
+			self.access.clone(),
 		)
 	}
 
@@ -154,6 +162,7 @@ impl ServerBuilder {
 			self.dapps_path.clone(),
 			self.registrar.clone(),
 			self.sync_status.clone(),
+			self.access.clone(),
 		)
 	}
 }
@@ -188,6 +197,7 @@ impl Server {
 		dapps_path: String,
 		registrar: Arc<ContractClient>,
 		sync_status: Arc<SyncStatus>,
+		access: Option<Arc<AccessControl>>,
 	) -> Result<Server, ServerError> {
 		let panic_handler = Arc::new(Mutex::new(None));
 		let authorization = Arc::new(authorization);
@@ -195,7 +205,7 @@ impl Server {
 		let endpoints = Arc::new(apps::all_endpoints(dapps_path));
 		let special = Arc::new({
 			let mut special = HashMap::new();
-			special.insert(router::SpecialEndpoint::Rpc, rpc::rpc(handler, panic_handler.clone()));
+			special.insert(router::SpecialEndpoint::Rpc, rpc::rpc(handler, access, panic_handler.clone()));
 			special.insert(router::SpecialEndpoint::Api, api::RestApi::new(format!("{}", addr), endpoints.clone()));
 			special.insert(router::SpecialEndpoint::Utils, apps::utils());
 			special
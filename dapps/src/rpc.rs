@@ -18,11 +18,18 @@ use std::sync::{Arc, Mutex};
 use hyper;
 use jsonrpc_core::IoHandler;
 use jsonrpc_http_server::{ServerHandler, PanicHandler, AccessControlAllowOrigin};
+use ethcore_rpc::{AccessControl, AccessFilter};
 use endpoint::{Endpoint, EndpointPath, Handler};
+use apps::RPC_PATH;
 
-pub fn rpc(handler: Arc<IoHandler>, panic_handler: Arc<Mutex<Option<Box<Fn() -> () + Send>>>>) -> Box<Endpoint> {
+pub fn rpc(
+	handler: Arc<IoHandler>,
+	access: Option<Arc<AccessControl>>,
+	panic_handler: Arc<Mutex<Option<Box<Fn() -> () + Send>>>>,
+) -> Box<Endpoint> {
 	Box::new(RpcEndpoint {
 		handler: handler,
+		access: access,
 		panic_handler: panic_handler,
 		cors_domain: Some(vec![AccessControlAllowOrigin::Null]),
 		// NOTE [ToDr] We don't need to do any hosts validation here. It's already done in router.
@@ -32,6 +39,7 @@ pub fn rpc(handler: Arc<IoHandler>, panic_handler: Arc<Mutex<Option<Box<Fn() ->
 
 struct RpcEndpoint {
 	handler: Arc<IoHandler>,
+	access: Option<Arc<AccessControl>>,
 	panic_handler: Arc<Mutex<Option<Box<Fn() -> () + Send>>>>,
 	cors_domain: Option<Vec<AccessControlAllowOrigin>>,
 	allowed_hosts: Option<Vec<String>>,
@@ -43,6 +51,18 @@ impl Endpoint for RpcEndpoint {
 	}
 
 	fn to_async_handler(&self, _path: EndpointPath, control: hyper::Control) -> Box<Handler> {
+		if let Some(ref access) = self.access {
+			return Box::new(AccessFilter::new(
+				access.clone(),
+				self.handler.clone(),
+				self.cors_domain.clone(),
+				self.allowed_hosts.clone(),
+				RPC_PATH,
+				self.panic_handler.clone(),
+				control,
+			));
+		}
+
 		let panic_handler = PanicHandler { handler: self.panic_handler.clone() };
 		Box::new(ServerHandler::new(
 				self.handler.clone(),
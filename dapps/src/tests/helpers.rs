@@ -22,6 +22,7 @@ use rustc_serialize::hex::FromHex;
 use ServerBuilder;
 use Server;
 use apps::urlhint::ContractClient;
+use ethcore_rpc::AccessControl;
 use util::{Bytes, Address, Mutex, ToPretty};
 use devtools::http_client;
 
@@ -77,6 +78,15 @@ pub fn serve_with_auth(user: &str, pass: &str) -> Server {
 	builder.start_basic_auth_http(&"127.0.0.1:0".parse().unwrap(), None, user, pass).unwrap()
 }
 
+pub fn serve_with_access(access: AccessControl) -> Server {
+	let registrar = Arc::new(FakeRegistrar::new());
+	let mut dapps_path = env::temp_dir();
+	dapps_path.push("non-existent-dir-to-prevent-fs-files-from-loading");
+	let mut builder = ServerBuilder::new(dapps_path.to_str().unwrap().into(), registrar);
+	builder.with_access_control(Arc::new(access));
+	builder.start_unsecured_http(&"127.0.0.1:0".parse().unwrap(), None).unwrap()
+}
+
 pub fn serve_hosts(hosts: Option<Vec<String>>) -> Server {
 	init_server(hosts).0
 }
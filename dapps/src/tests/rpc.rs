@@ -0,0 +1,76 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::sync::Arc;
+use std::collections::HashMap;
+use jsonrpc_core::IoHandler;
+use ethcore_rpc::{AccessControl, ApiKey};
+use tests::helpers::{serve_with_access, request};
+
+fn access() -> AccessControl {
+	let mut keys = HashMap::new();
+	keys.insert("secret".to_owned(), ApiKey {
+		handler: Arc::new(IoHandler::new()),
+		methods: None,
+		rate_limit: None,
+	});
+	AccessControl::new(keys, None, None)
+}
+
+#[test]
+fn should_require_api_key_for_rpc() {
+	// given
+	let server = serve_with_access(access());
+
+	// when
+	let response = request(server,
+		"\
+			POST /rpc HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n
+			\r\n\
+			{}
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert!(response.body.contains(r#""code":-32060"#), response.body);
+}
+
+#[test]
+fn should_serve_rpc_with_api_key_in_path() {
+	// given
+	let server = serve_with_access(access());
+
+	// when
+	let response = request(server,
+		"\
+			POST /rpc/secret HTTP/1.1\r\n\
+			Host: 127.0.0.1:8080\r\n\
+			Connection: close\r\n\
+			Content-Type: application/json\r\n
+			\r\n\
+			{}
+		"
+	);
+
+	// then
+	assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+	assert!(response.body.contains(r#""code":-32700"#), response.body);
+}
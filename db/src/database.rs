@@ -298,7 +298,7 @@ impl IpcConfig for Database {}
 
 /// Database iterator
 pub struct DatabaseIterator {
-	client: Arc<DatabaseClient<::nanomsg::Socket>>,
+	client: Arc<DatabaseClient<::nanoipc::DefaultSocket>>,
 	handle: IteratorHandle,
 }
 
@@ -136,11 +136,14 @@ struct Verification {
 	bad: Mutex<HashSet<H256>>,
 	more_to_verify: SMutex<()>,
 	empty: SMutex<()>,
+	// blocks which failed verification in the queue, with the reason.
+	failed: Mutex<Vec<(Bytes, String)>>,
 }
 
 impl BlockQueue {
 	/// Creates a new queue instance.
-	pub fn new(config: BlockQueueConfig, engine: Arc<Engine>, message_channel: IoChannel<ClientIoMessage>) -> BlockQueue {
+	/// Senders recovered during verification are shared through `senders`.
+	pub fn new(config: BlockQueueConfig, engine: Arc<Engine>, senders: Arc<SenderCache>, message_channel: IoChannel<ClientIoMessage>) -> BlockQueue {
 		let verification = Arc::new(Verification {
 			unverified: Mutex::new(VecDeque::new()),
 			verified: Mutex::new(VecDeque::new()),
@@ -148,7 +151,7 @@ impl BlockQueue {
 			bad: Mutex::new(HashSet::new()),
 			more_to_verify: SMutex::new(()),
 			empty: SMutex::new(()),
-
+			failed: Mutex::new(Vec::new()),
 		});
 		let more_to_verify = Arc::new(SCondvar::new());
 		let deleting = Arc::new(AtomicBool::new(false));
@@ -165,6 +168,7 @@ impl BlockQueue {
 		for i in 0..thread_count {
 			let verification = verification.clone();
 			let engine = engine.clone();
+			let senders = senders.clone();
 			let more_to_verify = more_to_verify.clone();
 			let ready_signal = ready_signal.clone();
 			let empty = empty.clone();
@@ -175,7 +179,7 @@ impl BlockQueue {
 				.name(format!("Verifier #{}", i))
 				.spawn(move || {
 					panic_handler.catch_panic(move || {
-						BlockQueue::verify(verification, engine, more_to_verify, ready_signal, deleting, empty)
+						BlockQueue::verify(verification, engine, senders, more_to_verify, ready_signal, deleting, empty)
 					}).unwrap()
 				})
 				.expect("Error starting block verification thread")
@@ -196,7 +200,7 @@ impl BlockQueue {
 		}
 	}
 
-	fn verify(verification: Arc<Verification>, engine: Arc<Engine>, wait: Arc<SCondvar>, ready: Arc<QueueSignal>, deleting: Arc<AtomicBool>, empty: Arc<SCondvar>) {
+	fn verify(verification: Arc<Verification>, engine: Arc<Engine>, senders: Arc<SenderCache>, wait: Arc<SCondvar>, ready: Arc<QueueSignal>, deleting: Arc<AtomicBool>, empty: Arc<SCondvar>) {
 		while !deleting.load(AtomicOrdering::Acquire) {
 			{
 				let mut more_to_verify = verification.more_to_verify.lock().unwrap();
@@ -226,7 +230,9 @@ impl BlockQueue {
 			};
 
 			let block_hash = block.header.hash();
-			match verify_block_unordered(block.header, block.bytes, &*engine) {
+			// keep the bytes around to report the block if it's bad.
+			let bytes = block.bytes.clone();
+			match verify_block_unordered(block.header, block.bytes, &*engine, &*senders) {
 				Ok(verified) => {
 					let mut verifying = verification.verifying.lock();
 					for e in verifying.iter_mut() {
@@ -248,6 +254,7 @@ impl BlockQueue {
 					let mut verified = verification.verified.lock();
 					let mut bad = verification.bad.lock();
 					warn!(target: "client", "Stage 2 block verification failed for {}\nError: {:?}", block_hash, err);
+					verification.failed.lock().push((bytes, format!("{:?}", err)));
 					bad.insert(block_hash.clone());
 					verifying.retain(|e| e.hash != block_hash);
 					BlockQueue::drain_verifying(&mut verifying, &mut verified, &mut bad);
@@ -329,6 +336,7 @@ impl BlockQueue {
 			Err(err) => {
 				warn!(target: "client", "Stage 1 block verification failed for {}\nError: {:?}", BlockView::new(&bytes).header_view().sha3(), err);
 				self.verification.bad.lock().insert(h.clone());
+				self.verification.failed.lock().push((bytes, format!("{:?}", err)));
 				Err(err)
 			}
 		}
@@ -361,6 +369,11 @@ impl BlockQueue {
 		*verified = new_verified;
 	}
 
+	/// Takes blocks which failed verification in the queue since the last call, with the reason.
+	pub fn drain_failed(&self) -> Vec<(Bytes, String)> {
+		mem::replace(&mut *self.verification.failed.lock(), Vec::new())
+	}
+
 	/// Mark given block as processed
 	pub fn mark_as_good(&self, block_hashes: &[H256]) {
 		if block_hashes.is_empty() {
@@ -460,7 +473,7 @@ mod tests {
 	fn get_test_queue() -> BlockQueue {
 		let spec = get_test_spec();
 		let engine = spec.engine;
-		BlockQueue::new(BlockQueueConfig::default(), engine, IoChannel::disconnected())
+		BlockQueue::new(BlockQueueConfig::default(), engine, Default::default(), IoChannel::disconnected())
 	}
 
 	#[test]
@@ -468,7 +481,7 @@ mod tests {
 		// TODO better test
 		let spec = Spec::new_test();
 		let engine = spec.engine;
-		let _ = BlockQueue::new(BlockQueueConfig::default(), engine, IoChannel::disconnected());
+		let _ = BlockQueue::new(BlockQueueConfig::default(), engine, Default::default(), IoChannel::disconnected());
 	}
 
 	#[test]
@@ -531,7 +544,7 @@ mod tests {
 		let engine = spec.engine;
 		let mut config = BlockQueueConfig::default();
 		config.max_mem_use = super::MIN_MEM_LIMIT;  // empty queue uses about 15000
-		let queue = BlockQueue::new(config, engine, IoChannel::disconnected());
+		let queue = BlockQueue::new(config, engine, Default::default(), IoChannel::disconnected());
 		assert!(!queue.queue_info().is_full());
 		let mut blocks = get_good_dummy_block_seq(50);
 		for b in blocks.drain(..) {
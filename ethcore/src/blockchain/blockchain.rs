@@ -1129,6 +1129,67 @@ impl BlockChain {
 		BlockView::new(&block.block).header_view().rlp().as_raw().to_vec()
 	}
 
+	/// Check that the extras of a canonical block are present and consistent with the block itself.
+	/// Returns a description of every inconsistency found.
+	pub fn check_block_extras(&self, hash: &H256) -> Vec<String> {
+		let mut problems = Vec::new();
+
+		let header = match self.block_header(hash) {
+			Some(header) => header,
+			None => return vec!["header missing".to_owned()],
+		};
+		let body = match self.block_body(hash) {
+			Some(body) => body,
+			None => return vec!["body missing".to_owned()],
+		};
+		let details = match self.block_details(hash) {
+			Some(details) => details,
+			None => return vec!["block details missing".to_owned()],
+		};
+
+		if details.number != header.number() {
+			problems.push(format!("block details give number {}, header has {}", details.number, header.number()));
+		}
+		if self.block_hash(header.number()) != Some(hash.clone()) {
+			problems.push("block hash index does not point to the block".to_owned());
+		}
+		if details.parent != *header.parent_hash() {
+			problems.push(format!("block details give parent {}, header has {}", details.parent, header.parent_hash()));
+		}
+
+		// the parent of the first block may be unknown, e.g. after snapshot restoration.
+		if header.number() > 0 && *hash != self.first_block() {
+			match self.block_details(header.parent_hash()) {
+				Some(parent) => if parent.total_difficulty + *header.difficulty() != details.total_difficulty {
+					problems.push(format!("total difficulty {} does not match parent's {} and difficulty {}",
+						details.total_difficulty, parent.total_difficulty, header.difficulty()));
+				},
+				None => problems.push("parent block details missing".to_owned()),
+			}
+		}
+
+		let transaction_hashes = BodyView::new(&body).transaction_hashes();
+		// genesis block has no receipts.
+		if header.number() > 0 {
+			match self.block_receipts(hash) {
+				Some(ref receipts) if receipts.receipts.len() != transaction_hashes.len() =>
+					problems.push(format!("{} receipts for {} transactions", receipts.receipts.len(), transaction_hashes.len())),
+				Some(_) => {},
+				None => problems.push("receipts missing".to_owned()),
+			}
+		}
+
+		for (index, tx_hash) in transaction_hashes.iter().enumerate() {
+			match self.transaction_address(tx_hash) {
+				Some(ref address) if address.block_hash == *hash && address.index == index => {},
+				Some(_) => problems.push(format!("transaction {} address points to a different location", tx_hash)),
+				None => problems.push(format!("transaction {} address missing", tx_hash)),
+			}
+		}
+
+		problems
+	}
+
 	/// Get current cache size.
 	pub fn cache_size(&self) -> CacheSize {
 		CacheSize {
@@ -1274,6 +1335,29 @@ mod tests {
 		assert_eq!(bc.block_hash(2), None);
 	}
 
+	#[test]
+	fn check_block_extras() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let first = canon_chain.generate(&mut finalizer).unwrap();
+		let genesis_hash = BlockView::new(&genesis).header_view().sha3();
+		let first_hash = BlockView::new(&first).header_view().sha3();
+
+		let temp = RandomTempPath::new();
+		let db = new_db(temp.as_str());
+		let bc = BlockChain::new(Config::default(), &genesis, db.clone());
+
+		let mut batch = db.transaction();
+		bc.insert_block(&mut batch, &first, vec![]);
+		db.write(batch).unwrap();
+		bc.commit();
+
+		assert!(bc.check_block_extras(&genesis_hash).is_empty());
+		assert!(bc.check_block_extras(&first_hash).is_empty());
+		assert_eq!(bc.check_block_extras(&H256::from(1)), vec!["header missing".to_owned()]);
+	}
+
 	#[test]
 	fn check_ancestry_iter() {
 		let mut canon_chain = ChainGenerator::default();
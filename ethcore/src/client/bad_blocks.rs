@@ -0,0 +1,197 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Record of blocks rejected during import.
+//!
+//! The most recent bad blocks are kept in their own database column, together with the peer they
+//! came from and the reason they were rejected, so that consensus issues can be diagnosed later.
+
+use util::{H256, Bytes, Hashable, Mutex, HashMap, VecDeque, Database, DBTransaction};
+use rlp::{UntrustedRlp, View};
+use views::BlockView;
+use types::bad_block::BadBlock;
+use db::COL_BAD_BLOCKS;
+
+/// Maximal number of bad blocks kept in the database.
+pub const MAX_BAD_BLOCKS: u64 = 64;
+
+/// Maximal number of block origins remembered while the blocks are queued.
+const MAX_ORIGINS: usize = 8192;
+
+fn index_key(index: u64) -> [u8; 8] {
+	let mut key = [0u8; 8];
+	for i in 0..8 {
+		key[i] = (index >> ((7 - i) * 8)) as u8;
+	}
+	key
+}
+
+fn key_index(key: &[u8]) -> u64 {
+	key.iter().take(8).fold(0, |index, b| (index << 8) | *b as u64)
+}
+
+#[derive(Default)]
+struct Origins {
+	peers: HashMap<H256, String>,
+	order: VecDeque<H256>,
+}
+
+/// Keeps track of where queued blocks came from and records the ones which turn out to be bad.
+pub struct BadBlocks {
+	origins: Mutex<Origins>,
+}
+
+impl Default for BadBlocks {
+	fn default() -> Self {
+		BadBlocks {
+			origins: Mutex::new(Origins::default()),
+		}
+	}
+}
+
+impl BadBlocks {
+	/// Remember the peer block with given hash was received from, unless it's already known.
+	/// Returns `false` if the origin of the block was already known.
+	pub fn note_origin(&self, hash: H256, origin: String) -> bool {
+		let mut origins = self.origins.lock();
+		if origins.peers.contains_key(&hash) {
+			return false;
+		}
+		origins.peers.insert(hash.clone(), origin);
+		origins.order.push_back(hash);
+		// forgotten origins are only removed from the map, so some evictions are no-ops.
+		while origins.order.len() > MAX_ORIGINS {
+			let oldest = origins.order.pop_front().expect("order is longer than the limit; qed");
+			origins.peers.remove(&oldest);
+		}
+		true
+	}
+
+	/// Forget origins of blocks which are no longer queued.
+	pub fn forget(&self, hashes: &[H256]) {
+		let mut origins = self.origins.lock();
+		for hash in hashes {
+			origins.peers.remove(hash);
+		}
+	}
+
+	/// Record a rejected block, evicting the oldest record if there are too many.
+	pub fn report(&self, db: &Database, bytes: Bytes, reason: String) {
+		let hash = BlockView::new(&bytes).header_view().sha3();
+		// reports are serialized by the origins lock.
+		let mut origins = self.origins.lock();
+		let origin = origins.peers.remove(&hash);
+
+		let mut next = 0;
+		for (key, value) in db.iter(COL_BAD_BLOCKS) {
+			if UntrustedRlp::new(&value).val_at::<H256>(0).ok().as_ref() == Some(&hash) {
+				return;
+			}
+			next = key_index(&key) + 1;
+		}
+
+		let block = BadBlock {
+			hash: hash,
+			bytes: bytes,
+			origin: origin,
+			reason: reason,
+		};
+
+		let mut batch = DBTransaction::new(db);
+		batch.put(COL_BAD_BLOCKS, &index_key(next), &::rlp::encode(&block));
+		if next >= MAX_BAD_BLOCKS {
+			batch.delete(COL_BAD_BLOCKS, &index_key(next - MAX_BAD_BLOCKS));
+		}
+		if let Err(e) = db.write(batch) {
+			warn!(target: "client", "Failed to record bad block {}: {}", block.hash, e);
+		}
+	}
+
+	/// All recorded bad blocks, oldest first.
+	pub fn blocks(&self, db: &Database) -> Vec<BadBlock> {
+		db.iter(COL_BAD_BLOCKS)
+			.filter_map(|(_, value)| UntrustedRlp::new(&value).as_val().ok())
+			.collect()
+	}
+
+	/// Recorded bad block with given hash.
+	pub fn block(&self, db: &Database, hash: &H256) -> Option<BadBlock> {
+		self.blocks(db).into_iter().find(|block| &block.hash == hash)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use util::{Database, DatabaseConfig, Hashable};
+	use devtools::RandomTempPath;
+	use tests::helpers::get_good_dummy_block;
+	use super::{BadBlocks, MAX_BAD_BLOCKS, index_key, key_index};
+
+	fn new_db(path: &RandomTempPath) -> Arc<Database> {
+		Arc::new(Database::open(&DatabaseConfig::with_columns(::db::NUM_COLUMNS), path.as_str()).unwrap())
+	}
+
+	#[test]
+	fn index_keys_are_ordered() {
+		assert_eq!(key_index(&index_key(0x0102030405060708)), 0x0102030405060708);
+		assert!(index_key(255) < index_key(256));
+	}
+
+	#[test]
+	fn records_bad_blocks_with_origin() {
+		let path = RandomTempPath::new();
+		let db = new_db(&path);
+		let bad_blocks = BadBlocks::default();
+		let bytes = get_good_dummy_block();
+		let hash = ::views::BlockView::new(&bytes).header_view().sha3();
+
+		bad_blocks.note_origin(hash.clone(), "peer".into());
+		bad_blocks.report(&db, bytes.clone(), "InvalidStateRoot".into());
+		// the same block is recorded only once.
+		bad_blocks.report(&db, bytes.clone(), "InvalidStateRoot".into());
+
+		let blocks = bad_blocks.blocks(&db);
+		assert_eq!(blocks.len(), 1);
+		assert_eq!(blocks[0].hash, hash);
+		assert_eq!(blocks[0].bytes, bytes);
+		assert_eq!(blocks[0].origin, Some("peer".into()));
+		assert_eq!(blocks[0].reason, "InvalidStateRoot".to_owned());
+		assert_eq!(bad_blocks.block(&db, &hash), Some(blocks[0].clone()));
+	}
+
+	#[test]
+	fn keeps_most_recent_bad_blocks() {
+		let path = RandomTempPath::new();
+		let db = new_db(&path);
+		let bad_blocks = BadBlocks::default();
+
+		for i in 0..MAX_BAD_BLOCKS + 2 {
+			let mut header = ::header::Header::default();
+			header.set_number(i);
+			let mut block = ::rlp::RlpStream::new_list(3);
+			block.append(&header);
+			block.append_empty_data();
+			block.append_empty_data();
+			bad_blocks.report(&db, block.out(), format!("bad #{}", i));
+		}
+
+		let blocks = bad_blocks.blocks(&db);
+		assert_eq!(blocks.len() as u64, MAX_BAD_BLOCKS);
+		assert_eq!(blocks[0].reason, "bad #2".to_owned());
+		assert_eq!(blocks.last().unwrap().origin, None);
+	}
+}
@@ -0,0 +1,326 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State database integrity checking.
+
+use std::fmt;
+use util::{H256, HashDB, Hashable, SHA3_NULL_RLP, SHA3_EMPTY};
+use util::nibbleslice::NibbleSlice;
+use rlp::{UntrustedRlp, View, Prototype};
+use account_db::Factory as AccountDBFactory;
+use header::BlockNumber;
+
+/// What is wrong with a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+	/// The node is not in the database.
+	Missing,
+	/// The node is in the database, but its data does not hash to its key or is malformed.
+	Corrupt,
+}
+
+/// Where a bad node was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+	/// The account trie.
+	State,
+	/// The storage trie of the account with given address hash.
+	Storage(H256),
+	/// The code of the account with given address hash.
+	Code(H256),
+}
+
+/// A state node which could not be read back correctly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadNode {
+	/// Hash of the node.
+	pub hash: H256,
+	/// Trie the node belongs to.
+	pub location: Location,
+	/// Nibble path leading to the node within its trie. For code, the path of the account.
+	pub path: Vec<u8>,
+	/// What is wrong with it.
+	pub problem: Problem,
+}
+
+impl fmt::Display for BadNode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let problem = match self.problem {
+			Problem::Missing => "missing",
+			Problem::Corrupt => "corrupt",
+		};
+		let path: String = self.path.iter().map(|n| format!("{:x}", n)).collect();
+		match self.location {
+			Location::State => write!(f, "{} state node {} at path 0x{}", problem, self.hash.hex(), path),
+			Location::Storage(ref account) => write!(f, "{} storage node {} of account {} at path 0x{}", problem, self.hash.hex(), account.hex(), path),
+			Location::Code(ref account) => write!(f, "{} code {} of account {}", problem, self.hash.hex(), account.hex()),
+		}
+	}
+}
+
+/// Outcome of a state database check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateCheck {
+	/// Number of the block whose state was checked.
+	pub number: BlockNumber,
+	/// Hash of the block whose state was checked.
+	pub block: H256,
+	/// State root of that block.
+	pub state_root: H256,
+	/// Number of trie nodes visited.
+	pub nodes: usize,
+	/// Number of accounts visited.
+	pub accounts: usize,
+	/// Nodes which could not be read back correctly.
+	pub bad_nodes: Vec<BadNode>,
+}
+
+impl StateCheck {
+	/// Whether no problems were found.
+	pub fn is_ok(&self) -> bool {
+		self.bad_nodes.is_empty()
+	}
+}
+
+/// Walk the state trie with given root, and optionally all storage tries and code, recording every node
+/// which is missing or corrupt. Subtrees below bad nodes are not visited.
+pub fn check_state(db: &HashDB, accountdb: &AccountDBFactory, number: BlockNumber, block: H256, state_root: H256, with_storage: bool) -> StateCheck {
+	let mut check = StateCheck {
+		number: number,
+		block: block,
+		state_root: state_root.clone(),
+		nodes: 0,
+		accounts: 0,
+		bad_nodes: Vec::new(),
+	};
+
+	// storage and code of each account are checked as soon as it's reached, so memory use
+	// does not grow with the number of accounts.
+	walk_trie(db, &state_root, Location::State, &mut check, &mut |node, path, value, check| {
+		check.accounts += 1;
+		let rlp = UntrustedRlp::new(value);
+		match (rlp.val_at::<H256>(2), rlp.val_at::<H256>(3)) {
+			(Ok(storage_root), Ok(code_hash)) => if with_storage {
+				check_account(db, accountdb, path, &storage_root, &code_hash, check);
+			},
+			_ => check.bad_nodes.push(BadNode { hash: node.clone(), location: Location::State, path: path.to_vec(), problem: Problem::Corrupt }),
+		}
+	});
+
+	check
+}
+
+// check storage trie and code of the account at given path of the state trie.
+fn check_account(db: &HashDB, accountdb: &AccountDBFactory, path: &[u8], storage_root: &H256, code_hash: &H256, check: &mut StateCheck) {
+	let address_hash = H256::from_slice(&nibbles_to_bytes(path));
+	let account_db = accountdb.readonly(db, address_hash.clone());
+
+	walk_trie(&*account_db, storage_root, Location::Storage(address_hash.clone()), check, &mut |_, _, _, _| {});
+
+	if *code_hash != SHA3_EMPTY {
+		let problem = match account_db.get(code_hash) {
+			None => Some(Problem::Missing),
+			Some(code) if code.sha3() != *code_hash => Some(Problem::Corrupt),
+			Some(_) => None,
+		};
+		if let Some(problem) = problem {
+			check.bad_nodes.push(BadNode { hash: code_hash.clone(), location: Location::Code(address_hash), path: path.to_vec(), problem: problem });
+		}
+	}
+}
+
+// walk the trie below `root`, calling `on_leaf` with the hash of the enclosing database node,
+// the full key nibbles and the value of every leaf.
+fn walk_trie<F>(db: &HashDB, root: &H256, location: Location, check: &mut StateCheck, on_leaf: &mut F)
+	where F: FnMut(&H256, &[u8], &[u8], &mut StateCheck)
+{
+	if *root == SHA3_NULL_RLP {
+		return;
+	}
+
+	let mut path = Vec::new();
+	walk_hash(db, root, &location, &mut path, check, on_leaf);
+}
+
+fn walk_hash<F>(db: &HashDB, hash: &H256, location: &Location, path: &mut Vec<u8>, check: &mut StateCheck, on_leaf: &mut F)
+	where F: FnMut(&H256, &[u8], &[u8], &mut StateCheck)
+{
+	let problem = match db.get(hash) {
+		None => Some(Problem::Missing),
+		Some(data) if data.sha3() != *hash => Some(Problem::Corrupt),
+		Some(data) => match walk_node(db, hash, data, location, path, check, on_leaf) {
+			true => None,
+			false => Some(Problem::Corrupt),
+		},
+	};
+
+	if let Some(problem) = problem {
+		check.bad_nodes.push(BadNode {
+			hash: hash.clone(),
+			location: location.clone(),
+			path: path.clone(),
+			problem: problem,
+		});
+	}
+}
+
+// walk a node stored under `hash` or inlined into it. returns false if the node is malformed.
+fn walk_node<F>(db: &HashDB, hash: &H256, node: &[u8], location: &Location, path: &mut Vec<u8>, check: &mut StateCheck, on_leaf: &mut F) -> bool
+	where F: FnMut(&H256, &[u8], &[u8], &mut StateCheck)
+{
+	let rlp = UntrustedRlp::new(node);
+	check.nodes += 1;
+
+	match rlp.prototype() {
+		Ok(Prototype::List(2)) => {
+			let (key, child) = match (rlp.at(0).and_then(|r| r.data()), rlp.at(1)) {
+				(Ok(key), Ok(child)) if !key.is_empty() => (key, child),
+				_ => return false,
+			};
+			let (slice, is_leaf) = NibbleSlice::from_encoded(key);
+			let depth = path.len();
+			path.extend(slice.iter());
+			let valid = match is_leaf {
+				true => match child.data() {
+					Ok(value) => { on_leaf(hash, &path[..], value, check); true },
+					Err(_) => false,
+				},
+				false => walk_child(db, hash, child, location, path, check, on_leaf),
+			};
+			path.truncate(depth);
+			valid
+		},
+		Ok(Prototype::List(17)) => {
+			for i in 0..16 {
+				let child = match rlp.at(i) {
+					Ok(child) => child,
+					Err(_) => return false,
+				};
+				if child.is_empty() {
+					continue;
+				}
+				path.push(i as u8);
+				let valid = walk_child(db, hash, child, location, path, check, on_leaf);
+				path.pop();
+				if !valid {
+					return false;
+				}
+			}
+			match rlp.at(16).and_then(|r| r.data()) {
+				Ok(value) if value.is_empty() => true,
+				Ok(value) => { on_leaf(hash, &path[..], value, check); true },
+				Err(_) => false,
+			}
+		},
+		Ok(Prototype::Data(0)) => true,
+		_ => false,
+	}
+}
+
+// follow a child reference: either a hash to be looked up or an inline node.
+fn walk_child<F>(db: &HashDB, hash: &H256, child: UntrustedRlp, location: &Location, path: &mut Vec<u8>, check: &mut StateCheck, on_leaf: &mut F) -> bool
+	where F: FnMut(&H256, &[u8], &[u8], &mut StateCheck)
+{
+	match child.is_data() && child.size() == 32 {
+		true => match child.as_val::<H256>() {
+			Ok(child_hash) => { walk_hash(db, &child_hash, location, path, check, on_leaf); true },
+			Err(_) => false,
+		},
+		false => child.is_list() && walk_node(db, hash, child.as_raw(), location, path, check, on_leaf),
+	}
+}
+
+fn nibbles_to_bytes(nibbles: &[u8]) -> Vec<u8> {
+	nibbles.chunks(2).map(|n| (n[0] << 4) | n.get(1).cloned().unwrap_or(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use util::{H256, U256, MemoryDB, HashDB, Hashable, SHA3_NULL_RLP, SHA3_EMPTY};
+	use util::trie::{TrieDBMut, TrieMut};
+	use rlp::RlpStream;
+	use account_db::Factory as AccountDBFactory;
+	use super::{check_state, Location, Problem};
+
+	fn build_state(db: &mut MemoryDB) -> H256 {
+		let mut root = H256::new();
+		{
+			let mut t = TrieDBMut::new(db, &mut root);
+			for i in 0u8..50 {
+				let mut account = RlpStream::new_list(4);
+				account.append(&U256::from(i)).append(&U256::from(i)).append(&SHA3_NULL_RLP).append(&SHA3_EMPTY);
+				t.insert(&[i; 20].sha3(), &account.out()).unwrap();
+			}
+		}
+		root
+	}
+
+	// some node other than the root.
+	fn inner_node(db: &MemoryDB, root: &H256) -> H256 {
+		db.keys().into_iter().map(|(k, _)| k).filter(|k| k != root).next().unwrap()
+	}
+
+	#[test]
+	fn healthy_state_passes() {
+		let mut db = MemoryDB::new();
+		let root = build_state(&mut db);
+
+		let check = check_state(&db, &AccountDBFactory::default(), 0, H256::new(), root, true);
+		assert!(check.is_ok());
+		assert_eq!(check.accounts, 50);
+	}
+
+	#[test]
+	fn reports_missing_node() {
+		let mut db = MemoryDB::new();
+		let root = build_state(&mut db);
+		let node = inner_node(&db, &root);
+		db.remove_and_purge(&node);
+
+		let check = check_state(&db, &AccountDBFactory::default(), 0, H256::new(), root, false);
+		assert_eq!(check.bad_nodes.len(), 1);
+		assert_eq!(check.bad_nodes[0].hash, node);
+		assert_eq!(check.bad_nodes[0].problem, Problem::Missing);
+		assert_eq!(check.bad_nodes[0].location, Location::State);
+		assert!(!check.bad_nodes[0].path.is_empty());
+		assert!(check.accounts < 50);
+	}
+
+	#[test]
+	fn reports_corrupt_node() {
+		let mut db = MemoryDB::new();
+		let root = build_state(&mut db);
+		let node = inner_node(&db, &root);
+		db.remove(&node);
+		db.emplace(node.clone(), vec![0xc0]);
+
+		let check = check_state(&db, &AccountDBFactory::default(), 0, H256::new(), root, false);
+		assert_eq!(check.bad_nodes.len(), 1);
+		assert_eq!(check.bad_nodes[0].hash, node);
+		assert_eq!(check.bad_nodes[0].problem, Problem::Corrupt);
+	}
+
+	#[test]
+	fn reports_missing_root() {
+		let db = MemoryDB::new();
+		let root = H256::from(1);
+
+		let check = check_state(&db, &AccountDBFactory::default(), 0, H256::new(), root.clone(), true);
+		assert_eq!(check.bad_nodes.len(), 1);
+		assert_eq!(check.bad_nodes[0].hash, root);
+		assert_eq!(check.bad_nodes[0].problem, Problem::Missing);
+	}
+}
@@ -13,6 +13,7 @@
 
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+use std::cmp;
 use std::collections::{HashSet, HashMap, BTreeMap, VecDeque};
 use std::sync::{Arc, Weak};
 use std::path::{Path};
@@ -24,9 +25,10 @@ use time::precise_time_ns;
 // util
 use util::{Bytes, PerfTimer, Itertools, Mutex, RwLock};
 use util::journaldb::{self, JournalDB};
-use util::{U256, H256, Address, H2048, Uint};
+use util::{U256, H256, Address, H2048, Uint, Mismatch, FixedHash};
 use util::sha3::*;
 use util::TrieFactory;
+use util::trie::{Trie, TrieIterator, TrieSpec};
 use util::kvdb::*;
 
 // other
@@ -51,9 +53,11 @@ use block_queue::{BlockQueue, BlockQueueInfo};
 use blockchain::{BlockChain, BlockProvider, TreeRoute, ImportRoute};
 use client::{
 	BlockID, TransactionID, UncleID, TraceId, ClientConfig, BlockChainClient,
-	MiningBlockChainClient, TraceFilter, CallAnalytics, BlockImportError, Mode,
-	ChainNotify
+	MiningBlockChainClient, TraceFilter, CallAnalytics, StateOverride, BlockImportError, Mode,
+	ChainNotify, StateCheck, BadNode, NodeProblem, NodeLocation
 };
+use client::check;
+use client::bad_blocks::BadBlocks;
 use client::Error as ClientError;
 use env_info::EnvInfo;
 use executive::{Executive, Executed, TransactOptions, contract_address};
@@ -63,7 +67,7 @@ use trace;
 use trace::FlatTransactionTraces;
 use evm::Factory as EvmFactory;
 use miner::{Miner, MinerService};
-use snapshot::{self, io as snapshot_io};
+use snapshot::{self, io as snapshot_io, AccountJournal, ManifestData, StateCheckpoints};
 use factory::Factories;
 use rlp::{View, UntrustedRlp};
 
@@ -71,10 +75,15 @@ use rlp::{View, UntrustedRlp};
 // re-export
 pub use types::blockchain_info::BlockChainInfo;
 pub use types::block_status::BlockStatus;
+pub use types::pruning_info::PruningInfo;
+pub use types::bad_block::BadBlock;
 pub use blockchain::CacheSize as BlockChainCacheSize;
 
 const MAX_TX_QUEUE_SIZE: usize = 4096;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
+const MAX_REPLAYED_STATES: usize = 4;
+/// Key of the flag telling whether the state database stores key preimages.
+const FAT_DB_KEY: &'static [u8] = b"fat_db";
 
 impl fmt::Display for BlockChainInfo {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -118,6 +127,39 @@ impl SleepState {
 	}
 }
 
+/// Reason why the state of a block couldn't be retrieved.
+#[derive(Debug)]
+pub enum StateError {
+	/// The block is not known.
+	UnknownBlock,
+	/// The state has been pruned and can't be reconstructed.
+	Pruned,
+	/// Reconstructing the pruned state would require re-executing more blocks than allowed.
+	ReplayLimitExceeded {
+		/// Number of blocks which would have to be re-executed.
+		required: u64,
+		/// Maximum number of blocks allowed to be re-executed.
+		limit: u64,
+	},
+	/// The snapshot checkpoint the pruned state is reconstructed from is being rebuilt.
+	CheckpointPending,
+	/// Reconstructing the pruned state failed.
+	Replay(EthcoreError),
+}
+
+impl fmt::Display for StateError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			StateError::UnknownBlock => write!(f, "Unknown block"),
+			StateError::Pruned => write!(f, "State has been pruned"),
+			StateError::ReplayLimitExceeded { required, limit } =>
+				write!(f, "Reconstructing pruned state requires re-executing {} blocks, limit is {}", required, limit),
+			StateError::CheckpointPending => write!(f, "State checkpoint is being rebuilt, try again later"),
+			StateError::Replay(ref e) => write!(f, "Reconstructing pruned state failed: {}", e),
+		}
+	}
+}
+
 /// Blockchain database client backed by a persistent database. Owns and manages a blockchain and a block queue.
 /// Call `import_block()` to import a block asynchronously; `flush_queue()` flushes the queue.
 pub struct Client {
@@ -142,9 +184,18 @@ pub struct Client {
 	queue_transactions: AtomicUsize,
 	last_hashes: RwLock<VecDeque<H256>>,
 	factories: Factories,
+	account_journal: Mutex<AccountJournal>,
+	checkpoints: RwLock<Option<Weak<StateCheckpoints>>>,
+	replayed_states: Mutex<VecDeque<(H256, State)>>,
+	history: u64,
+	bad_blocks: BadBlocks,
 }
 
-const HISTORY: u64 = 1200;
+/// Default number of recent blocks whose state is kept by pruned databases.
+pub const HISTORY: u64 = 1200;
+
+/// Least number of recent states kept when the journal memory limit is exceeded.
+pub const MIN_HISTORY: u64 = 64;
 
 /// Append a path element to the given path and return the string.
 pub fn append_path<P>(path: P, item: &str) -> String where P: AsRef<Path> {
@@ -170,28 +221,32 @@ impl Client {
 		let chain = Arc::new(BlockChain::new(config.blockchain.clone(), &gb, db.clone()));
 		let tracedb = RwLock::new(try!(TraceDB::new(config.tracing.clone(), db.clone(), chain.clone())));
 
+		let trie_factory = TrieFactory::new(config.trie_spec.clone());
 		let mut state_db = journaldb::new(db.clone(), config.pruning, ::db::COL_STATE);
-		if state_db.is_empty() && try!(spec.ensure_db_good(state_db.as_hashdb_mut())) {
+		if state_db.is_empty() && try!(spec.ensure_db_good(state_db.as_hashdb_mut(), &trie_factory)) {
 			let mut batch = DBTransaction::new(&db);
 			try!(state_db.commit(&mut batch, 0, &spec.genesis_header().hash(), None));
 			try!(db.write(batch).map_err(ClientError::Database));
 		}
 
+		try!(check_fat_db(&db, &config.trie_spec));
+
 		if !chain.block_header(&chain.best_block_hash()).map_or(true, |h| state_db.contains(h.state_root())) {
 			warn!("State root not found for block #{} ({})", chain.best_block_number(), chain.best_block_hash().hex());
 		}
 
 		let engine = spec.engine.clone();
 
-		let block_queue = BlockQueue::new(config.queue.clone(), engine.clone(), message_channel.clone());
+		let block_queue = BlockQueue::new(config.queue.clone(), engine.clone(), miner.sender_cache(), message_channel.clone());
 		let panic_handler = PanicHandler::new_in_arc();
 		panic_handler.forward_from(&block_queue);
 
 		let awake = match config.mode { Mode::Dark(..) => false, _ => true };
+		let history = cmp::max(config.history, MIN_HISTORY);
 
 		let factories = Factories {
 			vm: EvmFactory::new(config.vm_type.clone()),
-			trie: TrieFactory::new(config.trie_spec.clone()),
+			trie: trie_factory,
 			accountdb: Default::default(),
 		};
 
@@ -217,8 +272,15 @@ impl Client {
 			queue_transactions: AtomicUsize::new(0),
 			last_hashes: RwLock::new(VecDeque::new()),
 			factories: factories,
+			history: history,
+			account_journal: Mutex::new(AccountJournal::new(snapshot::DEFAULT_JOURNAL_BLOCKS)),
+			checkpoints: RwLock::new(None),
+			replayed_states: Mutex::new(VecDeque::new()),
+			bad_blocks: BadBlocks::default(),
 		};
-		Ok(Arc::new(client))
+		let client = Arc::new(client);
+		client.engine.register_client(Arc::downgrade(&client));
+		Ok(client)
 	}
 
 	/// Adds an actor to be notified on certain events
@@ -226,6 +288,11 @@ impl Client {
 		self.notify.write().push(Arc::downgrade(&target));
 	}
 
+	/// Set the source of checkpoints pruned states are reconstructed from.
+	pub fn set_state_checkpoints(&self, checkpoints: Weak<StateCheckpoints>) {
+		*self.checkpoints.write() = Some(checkpoints);
+	}
+
 	fn notify<F>(&self, f: F) where F: Fn(&ChainNotify) {
 		for np in self.notify.read().iter() {
 			if let Some(n) = np.upgrade() {
@@ -242,6 +309,102 @@ impl Client {
 		}
 	}
 
+	/// Environment for non-persistent calls on top of given block.
+	fn call_env_info(&self, block: BlockID) -> Result<EnvInfo, CallError> {
+		let header = try!(self.block_header(block).ok_or(CallError::StatePruned));
+		let view = HeaderView::new(&header);
+		let last_hashes = self.build_last_hashes(view.hash());
+		Ok(EnvInfo {
+			number: view.number(),
+			author: view.author(),
+			timestamp: view.timestamp(),
+			difficulty: view.difficulty(),
+			last_hashes: last_hashes,
+			gas_used: U256::zero(),
+			gas_limit: U256::max_value(),
+		})
+	}
+
+	/// State of given block for non-persistent calls.
+	fn call_state(&self, block: BlockID) -> Result<State, CallError> {
+		self.try_state_at(block).map_err(|e| match e {
+			StateError::ReplayLimitExceeded { required, limit } => CallError::ReplayLimitExceeded { required: required, limit: limit },
+			StateError::CheckpointPending => CallError::CheckpointPending,
+			_ => CallError::StatePruned,
+		})
+	}
+
+	/// Execute a non-persistent call on given state, leaving its effects in the state.
+	fn call_on_state(&self, state: &mut State, env_info: &EnvInfo, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError> {
+		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+
+		let sender = try!(t.sender().map_err(|e| {
+			let message = format!("Transaction malformed: {:?}", e);
+			ExecutionError::TransactionMalformed(message)
+		}));
+		let balance = state.balance(&sender);
+		let needed_balance = t.value + t.gas * t.gas_price;
+		if balance < needed_balance {
+			// give the sender a sufficient balance
+			state.add_balance(&sender, &(needed_balance - balance));
+		}
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let mut ret = try!(Executive::new(state, env_info, &*self.engine, &self.factories.vm).transact(t, options));
+
+		// TODO gav move this into Executive.
+		ret.state_diff = original_state.map(|original| state.diff_from(original));
+
+		Ok(ret)
+	}
+
+	/// Re-execute transactions of given block on top of its parent state, in a single pass.
+	/// With `index` given only that transaction is analysed and returned, otherwise all of them are.
+	fn replay_transactions(&self, block: BlockID, analytics: CallAnalytics, index: Option<usize>) -> Result<Vec<Executed>, CallError> {
+		let header_data = try!(self.block_header(block.clone()).ok_or(CallError::StatePruned));
+		let body_data = try!(self.block_body(block.clone()).ok_or(CallError::StatePruned));
+		let mut state = try!(self.state_at_beginning(block).ok_or(CallError::StatePruned));
+		let txs = BodyView::new(&body_data).transactions();
+
+		if index.map_or(false, |index| index >= txs.len()) {
+			return Err(CallError::TransactionNotFound);
+		}
+
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let view = HeaderView::new(&header_data);
+		let last_hashes = self.build_last_hashes(view.hash());
+		let mut env_info = EnvInfo {
+			number: view.number(),
+			author: view.author(),
+			timestamp: view.timestamp(),
+			difficulty: view.difficulty(),
+			last_hashes: last_hashes,
+			gas_used: U256::default(),
+			gas_limit: view.gas_limit(),
+		};
+
+		let mut results = Vec::with_capacity(index.map_or(txs.len(), |_| 1));
+		for (i, t) in txs.iter().enumerate() {
+			match index {
+				Some(index) if i < index => {
+					match Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, Default::default()) {
+						Ok(x) => { env_info.gas_used = env_info.gas_used + x.gas_used; }
+						Err(ee) => { return Err(CallError::Execution(ee)) }
+					}
+				},
+				Some(index) if i > index => break,
+				_ => {
+					let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+					let mut ret = try!(Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, options));
+					ret.state_diff = original_state.map(|original| state.diff_from(original));
+					env_info.gas_used = env_info.gas_used + ret.gas_used;
+					results.push(ret);
+				},
+			}
+		}
+
+		Ok(results)
+	}
+
 	fn build_last_hashes(&self, parent_hash: H256) -> Arc<LastHashes> {
 		{
 			let hashes = self.last_hashes.read();
@@ -251,6 +414,14 @@ impl Client {
 				return Arc::new(res);
 			}
 		}
+		let last_hashes = self.ancestor_hashes(parent_hash);
+		let mut cached_hashes = self.last_hashes.write();
+		*cached_hashes = VecDeque::from(last_hashes.clone());
+		Arc::new(last_hashes)
+	}
+
+	// hashes of the given block and its 255 ancestors, bypassing the cache.
+	fn ancestor_hashes(&self, parent_hash: H256) -> LastHashes {
 		let mut last_hashes = LastHashes::new();
 		last_hashes.resize(256, H256::default());
 		last_hashes[0] = parent_hash;
@@ -263,27 +434,28 @@ impl Client {
 				None => break,
 			}
 		}
-		let mut cached_hashes = self.last_hashes.write();
-		*cached_hashes = VecDeque::from(last_hashes.clone());
-		Arc::new(last_hashes)
+		last_hashes
 	}
 
 	fn check_and_close_block(&self, block: &PreverifiedBlock) -> Result<LockedBlock, ()> {
 		let engine = &*self.engine;
 		let header = &block.header;
 
-		let chain = self.chain.read();
+		// engines may query the state of the parent during family verification,
+		// so don't keep the chain locked.
+		let chain = self.chain.read().clone();
 		// Check the block isn't so old we won't be able to enact it.
 		let best_block_number = chain.best_block_number();
-		if best_block_number >= HISTORY && header.number() <= best_block_number - HISTORY {
+		if header.number() < self.earliest_state(best_block_number) {
 			warn!(target: "client", "Block import failed for #{} ({})\nBlock is ancient (current best block: #{}).", header.number(), header.hash(), best_block_number);
 			return Err(());
 		}
 
 		// Verify Block Family
-		let verify_family_result = self.verifier.verify_block_family(header, &block.bytes, engine, &**chain);
+		let verify_family_result = self.verifier.verify_block_family(header, &block.bytes, engine, &*chain);
 		if let Err(e) = verify_family_result {
 			warn!(target: "client", "Stage 3 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
+			self.report_bad_block(&block.bytes, format!("{:?}", e));
 			return Err(());
 		};
 
@@ -302,6 +474,7 @@ impl Client {
 		let enact_result = enact_verified(block, engine, self.tracedb.read().tracing_enabled(), db, &parent, last_hashes, self.factories.clone());
 		if let Err(e) = enact_result {
 			warn!(target: "client", "Block import failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
+			self.report_bad_block(&block.bytes, format!("{:?}", e));
 			return Err(());
 		};
 
@@ -309,12 +482,36 @@ impl Client {
 		let locked_block = enact_result.unwrap();
 		if let Err(e) = self.verifier.verify_block_final(header, locked_block.block().header()) {
 			warn!(target: "client", "Stage 4 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
+			self.report_bad_block(&block.bytes, format!("{:?}", e));
 			return Err(());
 		}
+		drop(chain);
+
+		// Check senders are permitted to send their transactions, as of the parent block.
+		let parent_id = BlockID::Hash(header.parent_hash().clone());
+		let call_contract = |address: Address, data: Bytes| self.call_contract(parent_id.clone(), address, data);
+		for t in &block.transactions {
+			if let Err(e) = engine.verify_transaction_permission(t, &call_contract) {
+				warn!(target: "client", "Block import failed for #{} ({})\nTransaction {} not permitted: {:?}", header.number(), header.hash(), t.hash(), e);
+				self.report_bad_block(&block.bytes, format!("Transaction {} not permitted: {:?}", t.hash(), e));
+				return Err(());
+			}
+		}
 
 		Ok(locked_block)
 	}
 
+	fn report_bad_block(&self, bytes: &[u8], reason: String) {
+		self.bad_blocks.report(&**self.db.read(), bytes.to_vec(), reason);
+	}
+
+	// record blocks which failed verification in the queue.
+	fn report_failed_blocks(&self) {
+		for (bytes, reason) in self.block_queue.drain_failed() {
+			self.report_bad_block(&bytes, reason);
+		}
+	}
+
 	fn calculate_enacted_retracted(&self, import_results: &[ImportRoute]) -> (Vec<H256>, Vec<H256>) {
 		fn map_to_vec(map: Vec<(H256, bool)>) -> Vec<H256> {
 			map.into_iter().map(|(k, _v)| k).collect()
@@ -352,6 +549,7 @@ impl Client {
 			let _import_lock = self.import_lock.lock();
 			let _timer = PerfTimer::new("import_verified_blocks");
 			let start = precise_time_ns();
+			self.report_failed_blocks();
 			let blocks = self.block_queue.drain(max_blocks_to_import);
 
 			for block in blocks {
@@ -381,9 +579,11 @@ impl Client {
 			{
 				if !invalid_blocks.is_empty() {
 					self.block_queue.mark_as_bad(&invalid_blocks);
+					self.bad_blocks.forget(&invalid_blocks);
 				}
 				if !imported_blocks.is_empty() {
 					self.block_queue.mark_as_good(&imported_blocks);
+					self.bad_blocks.forget(&imported_blocks);
 				}
 			}
 			let duration_ns = precise_time_ns() - start;
@@ -419,13 +619,6 @@ impl Client {
 		let number = block.header().number();
 		let parent = block.header().parent_hash().clone();
 		let chain = self.chain.read();
-		// Are we committing an era?
-		let ancient = if number >= HISTORY {
-			let n = number - HISTORY;
-			Some((n, chain.block_hash(n).unwrap()))
-		} else {
-			None
-		};
 
 		// Commit results
 		let receipts = block.receipts().to_owned();
@@ -436,11 +629,27 @@ impl Client {
 
 		//let traces = From::from(block.traces().clone().unwrap_or_else(Vec::new));
 
+		if self.config.incremental_snapshots {
+			let accounts = block.state().cached_addresses().into_iter().map(|a| a.sha3());
+			self.account_journal.lock().note_block(number, accounts);
+		}
+
 		let mut batch = DBTransaction::new(&self.db.read());
 		// CHECK! I *think* this is fine, even if the state_root is equal to another
 		// already-imported block of the same number.
 		// TODO: Prove it with a test.
-		block.drain().commit(&mut batch, number, hash, ancient).expect("DB commit failed.");
+		let mut state_db = block.drain();
+		// Are we committing an era? Databases tracking their earliest journalled era have ancient eras
+		// marked canonical separately.
+		let ancient = match state_db.earliest_era() {
+			None if number >= self.history => {
+				let n = number - self.history;
+				Some((n, chain.block_hash(n).unwrap()))
+			},
+			_ => None,
+		};
+		state_db.commit(&mut batch, number, hash, ancient).expect("DB commit failed.");
+		self.prune_ancient(&mut *state_db, &mut batch, number, &**chain);
 
 		let route = chain.insert_block(&mut batch, block_data, receipts);
 		self.tracedb.read().import(&mut batch, TraceImportRequest {
@@ -458,6 +667,32 @@ impl Client {
 		route
 	}
 
+	// mark the eras which fell out of the history window canonical, then the oldest remaining ones
+	// for as long as the journal exceeds its memory limit.
+	fn prune_ancient(&self, state_db: &mut JournalDB, batch: &mut DBTransaction, number: BlockNumber, chain: &BlockChain) {
+		while let Some(era) = state_db.earliest_era() {
+			let over_limit = self.config.history_mem > 0 && state_db.journal_size() > self.config.history_mem;
+			if era + self.history > number && !(over_limit && era + MIN_HISTORY <= number) {
+				break;
+			}
+			let hash = match chain.block_hash(era) {
+				Some(hash) => hash,
+				None => break,
+			};
+			trace!(target: "client", "Marking era #{} ({}) canonical", era, hash);
+			state_db.mark_canonical(batch, era, &hash).expect("DB commit failed.");
+		}
+	}
+
+	// number of the earliest block whose state is kept, given the best block number.
+	fn earliest_state(&self, best_block_number: BlockNumber) -> BlockNumber {
+		let window = (best_block_number + 1).saturating_sub(self.history);
+		match self.state_db.read().earliest_era() {
+			Some(era) => cmp::max(era, window),
+			None => window,
+		}
+	}
+
 	fn update_last_hashes(&self, parent: &H256, hash: &H256) {
 		let mut hashes = self.last_hashes.write();
 		if hashes.front().map_or(false, |h| h == parent) {
@@ -481,31 +716,152 @@ impl Client {
 	///
 	/// This will not fail if given BlockID::Latest.
 	/// Otherwise, this can fail (but may not) if the DB prunes state.
+	/// Use `try_state_at` to find out why the state is unavailable.
 	pub fn state_at(&self, id: BlockID) -> Option<State> {
+		match self.try_state_at(id.clone()) {
+			Ok(state) => Some(state),
+			Err(e) => {
+				trace!(target: "client", "State of block {:?} is unavailable: {}", id, e);
+				None
+			}
+		}
+	}
+
+	/// Attempt to get a copy of a specific block's final state, explaining why it's not available otherwise.
+	///
+	/// States pruned from the DB are reconstructed by re-executing blocks on top of the nearest
+	/// checkpoint or previously reconstructed state, as long as no more than the configured
+	/// number of blocks has to be re-executed.
+	pub fn try_state_at(&self, id: BlockID) -> Result<State, StateError> {
 		// fast path for latest state.
 		match id.clone() {
-			BlockID::Pending => return self.miner.pending_state().or_else(|| Some(self.state())),
-			BlockID::Latest => return Some(self.state()),
+			BlockID::Pending => return Ok(self.miner.pending_state().unwrap_or_else(|| self.state())),
+			BlockID::Latest => return Ok(self.state()),
 			_ => {},
 		}
 
-		let block_number = match self.block_number(id.clone()) {
-			Some(num) => num,
-			None => return None,
-		};
+		let block_number = try!(self.block_number(id.clone()).ok_or(StateError::UnknownBlock));
+		let header = try!(self.block_header(id).ok_or(StateError::UnknownBlock));
+		let header = HeaderView::new(&header);
+		let db = self.state_db.read().boxed_clone();
 
-		self.block_header(id).and_then(|header| {
-			let db = self.state_db.read().boxed_clone();
+		// pruned blocks have to be re-executed.
+		if db.is_pruned() && block_number < self.earliest_state(self.chain.read().best_block_number()) {
+			return self.replay_state(block_number, header.hash());
+		}
 
-			// early exit for pruned blocks
-			if db.is_pruned() && self.chain.read().best_block_number() >= block_number + HISTORY {
-				return None;
+		State::from_existing(db, header.state_root(), self.engine.account_start_nonce(), self.factories.clone())
+			.map_err(|_| StateError::Pruned)
+	}
+
+	// reconstruct the pruned state of given block by re-executing blocks on top of the nearest base state.
+	fn replay_state(&self, number: BlockNumber, hash: H256) -> Result<State, StateError> {
+		let limit = self.config.state_replay_limit;
+		if limit == 0 {
+			return Err(StateError::Pruned);
+		}
+
+		let checkpoints = self.checkpoints.read().as_ref().and_then(|c| c.upgrade());
+		let checkpoint = checkpoints.as_ref().and_then(|c| c.checkpoint_at_or_before(number));
+
+		// walk back to the nearest state we can start from, noting the blocks to re-execute.
+		let mut route = Vec::new();
+		let (mut current_number, mut current_hash) = (number, hash);
+		let mut base = None;
+		while base.is_none() {
+			if let Some(state) = self.replayed_state(&current_hash) {
+				base = Some(state);
+				continue;
 			}
 
-			let root = HeaderView::new(&header).state_root();
+			if let Some((checkpoint_number, checkpoint_hash)) = checkpoint {
+				if current_number == checkpoint_number {
+					// the checkpoint may be on a different branch.
+					if current_hash != checkpoint_hash {
+						return Err(StateError::Pruned);
+					}
+					let checkpoints = checkpoints.as_ref().expect("checkpoint is only found with a source; qed");
+					match checkpoints.checkpoint_state(&checkpoint_hash) {
+						Some((db, root)) => base = Some(try!(self.open_checkpoint(db, root))),
+						None => {
+							// rebuilding the checkpoint takes too long to wait for.
+							checkpoints.prepare_checkpoint(&checkpoint_hash);
+							return Err(StateError::CheckpointPending);
+						}
+					}
+					continue;
+				}
+			}
 
-			State::from_existing(db, root, self.engine.account_start_nonce(), self.factories.clone()).ok()
-		})
+			if route.len() as u64 == limit {
+				return Err(match checkpoint {
+					Some((checkpoint_number, _)) => StateError::ReplayLimitExceeded { required: number - checkpoint_number, limit: limit },
+					None => StateError::Pruned,
+				});
+			}
+
+			let parent = match self.chain.read().block_details(&current_hash) {
+				Some(ref details) if current_number > 0 => details.parent.clone(),
+				_ => return Err(StateError::Pruned),
+			};
+			route.push(current_hash);
+			current_number -= 1;
+			current_hash = parent;
+		}
+		let base = base.expect("loop only ends once the base state is found; qed");
+
+		trace!(target: "client", "Re-executing {} blocks to reconstruct state of #{} ({})", route.len(), number, hash);
+
+		let (_, mut db) = base.drop();
+		for block_hash in route.into_iter().rev() {
+			let (header, body, parent) = {
+				let chain = self.chain.read();
+				let header = try!(chain.block_header(&block_hash).ok_or(StateError::Pruned));
+				let body = try!(chain.block_body(&block_hash).ok_or(StateError::Pruned));
+				let parent = try!(chain.block_header(header.parent_hash()).ok_or(StateError::Pruned));
+				(header, body, parent)
+			};
+			let body = BodyView::new(&body);
+			let last_hashes = Arc::new(self.ancestor_hashes(header.parent_hash().clone()));
+
+			let block = try!(enact(&header, &body.transactions(), &body.uncles(), &*self.engine, false, db, &parent, last_hashes, self.factories.clone())
+				.map_err(StateError::Replay));
+
+			let state_root = block.header().state_root().clone();
+			if &state_root != header.state_root() {
+				warn!(target: "client", "Re-executed block #{} ({}) has wrong state root", header.number(), block_hash);
+				let mismatch = Mismatch { expected: header.state_root().clone(), found: state_root };
+				return Err(StateError::Replay(BlockError::InvalidStateRoot(mismatch).into()));
+			}
+
+			db = block.drain();
+		}
+
+		let root = try!(self.block_header(BlockID::Hash(hash)).ok_or(StateError::Pruned));
+		let state = try!(State::from_existing(db, HeaderView::new(&root).state_root(), self.engine.account_start_nonce(), self.factories.clone())
+			.map_err(|e| StateError::Replay(e.into())));
+
+		let mut replayed = self.replayed_states.lock();
+		if replayed.len() == MAX_REPLAYED_STATES {
+			replayed.pop_front();
+		}
+		replayed.push_back((hash, state.clone()));
+
+		Ok(state)
+	}
+
+	// previously reconstructed state of the block with given hash.
+	fn replayed_state(&self, hash: &H256) -> Option<State> {
+		self.replayed_states.lock().iter()
+			.find(|&&(ref h, _)| h == hash)
+			.map(|&(_, ref state)| state.clone())
+	}
+
+	// state of a rebuilt checkpoint.
+	fn open_checkpoint(&self, db: Arc<Database>, root: H256) -> Result<State, StateError> {
+		let db = journaldb::new(db, journaldb::Algorithm::Archive, ::db::COL_STATE);
+		State::from_existing(db, root, self.engine.account_start_nonce(), self.factories.clone())
+			.map_err(|e| StateError::Replay(e.into()))
 	}
 
 	/// Attempt to get a copy of a specific block's beginning state.
@@ -601,20 +957,16 @@ impl Client {
 		let best_block_number = self.chain_info().best_block_number;
 		let block_number = try!(self.block_number(at).ok_or(snapshot::Error::InvalidStartingBlock(at)));
 
-		if best_block_number > HISTORY + block_number && db.is_pruned() {
+		if db.is_pruned() && block_number < self.earliest_state(best_block_number) {
 			return Err(snapshot::Error::OldBlockPrunedDB.into());
 		}
 
 		let start_hash = match at {
 			BlockID::Latest => {
-				let start_num = if best_block_number > 1000 {
-					best_block_number - 1000
-				} else {
-					0
-				};
+				let start_num = cmp::max(best_block_number.saturating_sub(1000), self.earliest_state(best_block_number));
 
 				self.block_hash(BlockID::Number(start_num))
-					.expect("blocks within HISTORY are always stored.")
+					.expect("blocks within the history window are always stored.")
 			}
 			_ => match self.block_hash(at) {
 				Some(hash) => hash,
@@ -622,11 +974,131 @@ impl Client {
 			},
 		};
 
-		try!(snapshot::take_snapshot(&self.chain.read(), start_hash, db.as_hashdb(), writer, p));
+		try!(snapshot::take_snapshot(&*self.engine, &self.chain.read(), start_hash, db.as_hashdb(), writer, p));
+
+		Ok(())
+	}
+
+	/// Get the address hashes of all accounts which may have changed since the snapshot
+	/// described by `base`, up to the block with the given number.
+	/// Returns `None` if the changes were not recorded or the base is no longer canonical.
+	pub fn snapshot_changes(&self, base: &ManifestData, number: BlockNumber) -> Option<HashSet<H256>> {
+		if self.block_hash(BlockID::Number(base.block_number)) != Some(base.block_hash) {
+			return None;
+		}
+
+		self.account_journal.lock().changes_between(base.block_number, number)
+	}
+
+	/// Forget about account changes up to and including the given block, once a snapshot has been taken there.
+	pub fn prune_snapshot_changes(&self, number: BlockNumber) {
+		self.account_journal.lock().prune(number);
+	}
+
+	/// Take an incremental snapshot at the given block on top of the snapshot described by `base`.
+	/// `changes` contains the address hashes of accounts changed since, as given by `snapshot_changes`.
+	pub fn take_incremental_snapshot<W: snapshot_io::SnapshotWriter + Send>(
+		&self,
+		writer: W,
+		base: &ManifestData,
+		changes: &HashSet<H256>,
+		at: BlockID,
+		p: &snapshot::Progress
+	) -> Result<(), EthcoreError> {
+		let db = self.state_db.read().boxed_clone();
+		let best_block_number = self.chain_info().best_block_number;
+		let block_number = try!(self.block_number(at).ok_or(snapshot::Error::InvalidStartingBlock(at)));
+
+		if db.is_pruned() && block_number < self.earliest_state(best_block_number) {
+			return Err(snapshot::Error::OldBlockPrunedDB.into());
+		}
+
+		let start_hash = try!(self.block_hash(at).ok_or(snapshot::Error::InvalidStartingBlock(at)));
+
+		try!(snapshot::take_incremental_snapshot(&*self.engine, &self.chain.read(), base, start_hash, db.as_hashdb(), changes, writer, p));
 
 		Ok(())
 	}
 
+	/// Check that the state trie of the best block can be read back in full, optionally along with
+	/// all account storage tries and code.
+	pub fn check_state(&self, with_storage: bool) -> StateCheck {
+		let chain = self.chain.read();
+		let hash = chain.best_block_hash();
+		let header = chain.block_header(&hash).expect("best block header is always stored; qed");
+		let state_db = self.state_db.read();
+
+		check::check_state(state_db.as_hashdb(), &self.factories.accountdb, header.number(), hash, header.state_root().clone(), with_storage)
+	}
+
+	/// Check the block extras of every canonical block from the best block down to the first one stored.
+	/// Returns the number and description of each inconsistency found.
+	pub fn check_chain(&self) -> Vec<(BlockNumber, String)> {
+		let chain = self.chain.read();
+		let first = chain.first_block_number();
+		let mut problems = Vec::new();
+
+		let mut number = chain.best_block_number();
+		loop {
+			match chain.block_hash(number) {
+				Some(hash) => problems.extend(chain.check_block_extras(&hash).into_iter().map(|p| (number, p))),
+				None => problems.push((number, "block hash index missing".to_owned())),
+			}
+			if number == first {
+				break;
+			}
+			number -= 1;
+		}
+
+		problems
+	}
+
+	/// Write state nodes retrieved from elsewhere in place of bad ones found by `check_state`.
+	/// Nodes whose data does not hash to the expected value are ignored.
+	/// Returns the number of nodes written.
+	pub fn repair_state(&self, bad_nodes: &[BadNode], data: &HashMap<H256, Bytes>) -> Result<usize, ClientError> {
+		let _import_lock = self.import_lock.lock();
+		let db = self.db.read();
+		let mut state_db = self.state_db.write();
+
+		let fixes: Vec<_> = bad_nodes.iter()
+			.filter_map(|node| data.get(&node.hash).map(|data| (node, data)))
+			.filter(|&(node, data)| data.sha3() == node.hash)
+			.collect();
+
+		// corrupt entries have to be removed before they can be written again.
+		{
+			let mut corrupt = 0;
+			for &(node, _) in fixes.iter().filter(|&&(node, _)| node.problem == NodeProblem::Corrupt) {
+				match node.location {
+					NodeLocation::State => state_db.remove(&node.hash),
+					NodeLocation::Storage(ref account) | NodeLocation::Code(ref account) =>
+						self.factories.accountdb.create(state_db.as_hashdb_mut(), account.clone()).remove(&node.hash),
+				}
+				corrupt += 1;
+			}
+			if corrupt > 0 {
+				let mut batch = DBTransaction::new(&db);
+				try!(state_db.inject(&mut batch));
+				try!(db.write(batch).map_err(ClientError::Database));
+			}
+		}
+
+		for &(node, data) in &fixes {
+			match node.location {
+				NodeLocation::State => state_db.emplace(node.hash.clone(), data.clone()),
+				NodeLocation::Storage(ref account) | NodeLocation::Code(ref account) =>
+					self.factories.accountdb.create(state_db.as_hashdb_mut(), account.clone()).emplace(node.hash.clone(), data.clone()),
+			}
+		}
+
+		let mut batch = DBTransaction::new(&db);
+		try!(state_db.inject(&mut batch));
+		try!(db.write(batch).map_err(ClientError::Database));
+
+		Ok(fixes.len())
+	}
+
 	fn block_hash(chain: &BlockChain, id: BlockID) -> Option<H256> {
 		match id {
 			BlockID::Hash(hash) => Some(hash),
@@ -686,84 +1158,111 @@ impl snapshot::DatabaseRestore for Client {
 		*state_db = journaldb::new(db.clone(), self.pruning, ::db::COL_STATE);
 		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone()));
 		*tracedb = try!(TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone()).map_err(ClientError::from));
+		// restored state has no key preimages.
+		let mut batch = DBTransaction::new(&db);
+		batch.put(::db::COL_EXTRA, FAT_DB_KEY, &[0x0]);
+		try!(db.write(batch).map_err(ClientError::Database));
+		try!(check_fat_db(&db, &self.config.trie_spec));
 		Ok(())
 	}
 }
 
+/// Checks that fat DB is not enabled on a database synced without it and stores the current setting.
+fn check_fat_db(db: &Database, trie_spec: &TrieSpec) -> Result<(), ClientError> {
+	let fat = *trie_spec == TrieSpec::Fat;
+	let was_fat = match try!(db.get(::db::COL_EXTRA, FAT_DB_KEY).map_err(ClientError::Database)) {
+		Some(ref value) if value as &[u8] == &[0x1] => true,
+		Some(_) => false,
+		// databases created before the flag was introduced are trusted to match the configuration.
+		None => fat,
+	};
+
+	if fat && !was_fat {
+		return Err(ClientError::FatDbResyncRequired);
+	}
+	if was_fat && !fat {
+		warn!("Fat DB disabled. Preimages of new keys won't be stored and fat DB can't be enabled again without a resync.");
+	}
+
+	let mut batch = DBTransaction::new(db);
+	batch.put(::db::COL_EXTRA, FAT_DB_KEY, &[fat as u8]);
+	db.write(batch).map_err(ClientError::Database)
+}
+
 
 impl BlockChainClient for Client {
-	fn call(&self, t: &SignedTransaction, block: BlockID, analytics: CallAnalytics) -> Result<Executed, CallError> {
-		let header = try!(self.block_header(block).ok_or(CallError::StatePruned));
-		let view = HeaderView::new(&header);
-		let last_hashes = self.build_last_hashes(view.hash());
-		let env_info = EnvInfo {
-			number: view.number(),
-			author: view.author(),
-			timestamp: view.timestamp(),
-			difficulty: view.difficulty(),
-			last_hashes: last_hashes,
-			gas_used: U256::zero(),
-			gas_limit: U256::max_value(),
-		};
+	fn call(&self, t: &SignedTransaction, block: BlockID, analytics: CallAnalytics, state_override: &StateOverride) -> Result<Executed, CallError> {
+		let env_info = try!(self.call_env_info(block.clone()));
 		// that's just a copy of the state.
-		let mut state = try!(self.state_at(block).ok_or(CallError::StatePruned));
-		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+		let mut state = try!(self.call_state(block));
+		state.apply_override(state_override);
 
-		let sender = try!(t.sender().map_err(|e| {
-			let message = format!("Transaction malformed: {:?}", e);
-			ExecutionError::TransactionMalformed(message)
-		}));
-		let balance = state.balance(&sender);
-		let needed_balance = t.value + t.gas * t.gas_price;
-		if balance < needed_balance {
-			// give the sender a sufficient balance
-			state.add_balance(&sender, &(needed_balance - balance));
-		}
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
-		let mut ret = try!(Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, options));
+		self.call_on_state(&mut state, &env_info, t, analytics)
+	}
 
-		// TODO gav move this into Executive.
-		ret.state_diff = original_state.map(|original| state.diff_from(original));
+	fn call_many(&self, transactions: Vec<SignedTransaction>, block: BlockID, analytics: CallAnalytics, state_overrides: Vec<StateOverride>) -> Result<Vec<Executed>, CallError> {
+		let mut env_info = try!(self.call_env_info(block.clone()));
+		// that's just a copy of the state, shared by all the calls.
+		let mut state = try!(self.call_state(block));
 
-		Ok(ret)
+		let mut results = Vec::with_capacity(transactions.len());
+		for (i, t) in transactions.iter().enumerate() {
+			if let Some(state_override) = state_overrides.get(i) {
+				state.apply_override(state_override);
+			}
+			let ret = try!(self.call_on_state(&mut state, &env_info, t, analytics));
+			env_info.gas_used = env_info.gas_used + ret.gas_used;
+			results.push(ret);
+		}
+
+		Ok(results)
 	}
 
 	fn replay(&self, id: TransactionID, analytics: CallAnalytics) -> Result<Executed, CallError> {
 		let address = try!(self.transaction_address(id).ok_or(CallError::TransactionNotFound));
-		let header_data = try!(self.block_header(BlockID::Hash(address.block_hash)).ok_or(CallError::StatePruned));
-		let body_data = try!(self.block_body(BlockID::Hash(address.block_hash)).ok_or(CallError::StatePruned));
-		let mut state = try!(self.state_at_beginning(BlockID::Hash(address.block_hash)).ok_or(CallError::StatePruned));
-		let txs = BodyView::new(&body_data).transactions();
+		let mut results = try!(self.replay_transactions(BlockID::Hash(address.block_hash), analytics, Some(address.index)));
+		results.pop().ok_or(CallError::TransactionNotFound)
+	}
 
-		if address.index >= txs.len() {
-			return Err(CallError::TransactionNotFound);
-		}
+	fn replay_block_transactions(&self, block: BlockID, analytics: CallAnalytics) -> Result<Vec<Executed>, CallError> {
+		self.replay_transactions(block, analytics, None)
+	}
+
+	fn replay_bad_block(&self, hash: H256, analytics: CallAnalytics) -> Result<Vec<Executed>, CallError> {
+		let block = try!(self.bad_blocks.block(&**self.db.read(), &hash).ok_or(CallError::BlockNotFound));
+		let view = BlockView::new(&block.bytes);
+		let header = view.header_view();
+		let mut state = try!(self.call_state(BlockID::Hash(header.parent_hash())));
 
 		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
-		let view = HeaderView::new(&header_data);
-		let last_hashes = self.build_last_hashes(view.hash());
 		let mut env_info = EnvInfo {
-			number: view.number(),
-			author: view.author(),
-			timestamp: view.timestamp(),
-			difficulty: view.difficulty(),
-			last_hashes: last_hashes,
+			number: header.number(),
+			author: header.author(),
+			timestamp: header.timestamp(),
+			difficulty: header.difficulty(),
+			last_hashes: Arc::new(self.ancestor_hashes(header.parent_hash())),
 			gas_used: U256::default(),
-			gas_limit: view.gas_limit(),
+			gas_limit: header.gas_limit(),
 		};
-		for t in txs.iter().take(address.index) {
-			match Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, Default::default()) {
-				Ok(x) => { env_info.gas_used = env_info.gas_used + x.gas_used; }
-				Err(ee) => { return Err(CallError::Execution(ee)) }
+
+		let mut results = Vec::new();
+		for t in view.transactions() {
+			let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+			let result = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(&t, options);
+			match result {
+				Ok(mut executed) => {
+					executed.state_diff = original_state.map(|original| state.diff_from(original));
+					env_info.gas_used = env_info.gas_used + executed.gas_used;
+					results.push(executed);
+				},
+				Err(e) => {
+					trace!(target: "client", "Replaying bad block {} stopped at transaction {}: {}", hash, t.hash(), e);
+					break;
+				},
 			}
 		}
-		let t = &txs[address.index];
 
-		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
-		let mut ret = try!(Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, options));
-		ret.state_diff = original_state.map(|original| state.diff_from(original));
-
-		Ok(ret)
+		Ok(results)
 	}
 
 	fn keep_alive(&self) {
@@ -818,8 +1317,8 @@ impl BlockChainClient for Client {
 		Self::block_hash(&chain, id).and_then(|hash| chain.block_details(&hash)).map(|d| d.total_difficulty)
 	}
 
-	fn nonce(&self, address: &Address, id: BlockID) -> Option<U256> {
-		self.state_at(id).map(|s| s.nonce(address))
+	fn try_nonce(&self, address: &Address, id: BlockID) -> Result<U256, CallError> {
+		self.call_state(id).map(|s| s.nonce(address))
 	}
 
 	fn block_hash(&self, id: BlockID) -> Option<H256> {
@@ -827,16 +1326,101 @@ impl BlockChainClient for Client {
 		Self::block_hash(&chain, id)
 	}
 
-	fn code(&self, address: &Address, id: BlockID) -> Option<Option<Bytes>> {
-		self.state_at(id).map(|s| s.code(address))
+	fn try_code(&self, address: &Address, id: BlockID) -> Result<Option<Bytes>, CallError> {
+		self.call_state(id).map(|s| s.code(address))
 	}
 
-	fn balance(&self, address: &Address, id: BlockID) -> Option<U256> {
-		self.state_at(id).map(|s| s.balance(address))
+	fn try_balance(&self, address: &Address, id: BlockID) -> Result<U256, CallError> {
+		self.call_state(id).map(|s| s.balance(address))
+	}
+
+	fn try_storage_at(&self, address: &Address, position: &H256, id: BlockID) -> Result<H256, CallError> {
+		self.call_state(id).map(|s| s.storage_at(address, position))
+	}
+
+	fn is_fat_db(&self) -> bool {
+		self.factories.trie.is_fat()
+	}
+
+	fn list_accounts(&self, id: BlockID, after: Option<Address>, count: u64) -> Option<Vec<Address>> {
+		if !self.factories.trie.is_fat() {
+			trace!(target: "fatdb", "list_accounts: Not a fat DB");
+			return None;
+		}
+
+		let state = match self.state_at(id) {
+			Some(state) => state,
+			None => return None,
+		};
+
+		let (root, db) = state.drop();
+		let trie = match self.factories.trie.readonly(db.as_hashdb(), &root) {
+			Ok(trie) => trie,
+			Err(e) => {
+				trace!(target: "fatdb", "list_accounts: Couldn't open the DB: {}", e);
+				return None;
+			}
+		};
+
+		let mut iter = trie.iter();
+		if let Some(ref after) = after {
+			if let Err(e) = iter.seek(after) {
+				trace!(target: "fatdb", "list_accounts: Couldn't seek the DB: {}", e);
+				return None;
+			}
+		}
+
+		// the cursor itself comes first if it still exists.
+		let accounts = iter
+			.map(|(key, _)| Address::from_slice(&key))
+			.skip_while(|address| after.as_ref() == Some(address))
+			.take(count as usize)
+			.collect();
+
+		Some(accounts)
 	}
 
-	fn storage_at(&self, address: &Address, position: &H256, id: BlockID) -> Option<H256> {
-		self.state_at(id).map(|s| s.storage_at(address, position))
+	fn list_storage(&self, id: BlockID, account: &Address, after: Option<H256>, count: u64) -> Option<Vec<H256>> {
+		if !self.factories.trie.is_fat() {
+			trace!(target: "fatdb", "list_storage: Not a fat DB");
+			return None;
+		}
+
+		let state = match self.state_at(id) {
+			Some(state) => state,
+			None => return None,
+		};
+
+		let storage_root = match state.storage_root(account) {
+			Some(root) => root,
+			None => return Some(Vec::new()),
+		};
+
+		let (_, db) = state.drop();
+		let account_db = self.factories.accountdb.readonly(db.as_hashdb(), account.sha3());
+		let trie = match self.factories.trie.readonly(account_db.as_hashdb(), &storage_root) {
+			Ok(trie) => trie,
+			Err(e) => {
+				trace!(target: "fatdb", "list_storage: Couldn't open the DB: {}", e);
+				return None;
+			}
+		};
+
+		let mut iter = trie.iter();
+		if let Some(ref after) = after {
+			if let Err(e) = iter.seek(after) {
+				trace!(target: "fatdb", "list_storage: Couldn't seek the DB: {}", e);
+				return None;
+			}
+		}
+
+		let keys = iter
+			.map(|(key, _)| H256::from_slice(&key))
+			.skip_while(|key| after.as_ref() == Some(key))
+			.take(count as usize)
+			.collect();
+
+		Some(keys)
 	}
 
 	fn transaction(&self, id: TransactionID) -> Option<LocalizedTransaction> {
@@ -924,7 +1508,20 @@ impl BlockChainClient for Client {
 				return Err(BlockImportError::Block(BlockError::UnknownParent(header.parent_hash())));
 			}
 		}
-		Ok(try!(self.block_queue.import_block(bytes)))
+		let result = self.block_queue.import_block(bytes);
+		self.report_failed_blocks();
+		Ok(try!(result))
+	}
+
+	fn import_block_from_peer(&self, bytes: Bytes, peer: String) -> Result<H256, BlockImportError> {
+		let hash = BlockView::new(&bytes).header_view().sha3();
+		let noted = self.bad_blocks.note_origin(hash.clone(), peer);
+		let result = self.import_block(bytes);
+		// the block didn't make it to the queue.
+		if noted && result.is_err() {
+			self.bad_blocks.forget(&[hash]);
+		}
+		result
 	}
 
 	fn queue_info(&self) -> BlockQueueInfo {
@@ -946,6 +1543,22 @@ impl BlockChainClient for Client {
 		}
 	}
 
+	fn bad_blocks(&self) -> Vec<BadBlock> {
+		self.bad_blocks.blocks(&**self.db.read())
+	}
+
+	fn pruning_info(&self) -> PruningInfo {
+		let earliest_state = match self.state_db.read().is_pruned() {
+			true => self.earliest_state(self.chain.read().best_block_number()),
+			false => 0,
+		};
+
+		PruningInfo {
+			earliest_state: earliest_state,
+			history_size: self.history,
+		}
+	}
+
 	fn additional_params(&self) -> BTreeMap<String, String> {
 		self.engine.additional_params().into_iter().collect()
 	}
@@ -23,6 +23,7 @@ pub use evm::VMType;
 pub use verification::VerifierType;
 use util::{journaldb, CompactionProfile};
 use util::trie::TrieSpec;
+use client::HISTORY;
 
 /// Client state db compaction profile
 #[derive(Debug, PartialEq)]
@@ -81,7 +82,7 @@ impl Default for Mode {
 }
 
 /// Client configuration. Includes configs for all sub-systems.
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, PartialEq)]
 pub struct ClientConfig {
 	/// Block queue configuration.
 	pub queue: BlockQueueConfig,
@@ -107,6 +108,38 @@ pub struct ClientConfig {
 	pub mode: Mode,
 	/// Type of block verifier used by client.
 	pub verifier_type: VerifierType,
+	/// Record accounts touched by imported blocks, allowing incremental snapshots.
+	pub incremental_snapshots: bool,
+	/// Maximum number of blocks re-executed to reconstruct a pruned state. 0 disables reconstruction.
+	pub state_replay_limit: u64,
+	/// Number of recent blocks whose state is kept by pruned databases.
+	pub history: u64,
+	/// Ideal memory usage of the journal of recent states in bytes. The number of recent states kept
+	/// shrinks below `history` to stay within it. 0 means no limit. Only used with `OverlayRecent` pruning.
+	pub history_mem: usize,
+}
+
+impl Default for ClientConfig {
+	fn default() -> Self {
+		ClientConfig {
+			queue: Default::default(),
+			blockchain: Default::default(),
+			tracing: Default::default(),
+			vm_type: Default::default(),
+			trie_spec: Default::default(),
+			pruning: Default::default(),
+			name: Default::default(),
+			db_cache_size: Default::default(),
+			db_compaction: Default::default(),
+			db_wal: Default::default(),
+			mode: Default::default(),
+			verifier_type: Default::default(),
+			incremental_snapshots: Default::default(),
+			state_replay_limit: Default::default(),
+			history: HISTORY,
+			history_mem: 0,
+		}
+	}
 }
 
 #[cfg(test)]
@@ -4,6 +4,14 @@ use std::fmt::{Display, Formatter, Error as FmtError};
 
 use util::trie::TrieError;
 
+const FAT_DB_RESYNC_ERR: &'static str =
+"Your current parity installation has synced without fat DB.
+To use Parity with fat DB, you'll need to resync with it enabled.
+To do this, remove or move away your current database and restart parity. e.g.:
+
+> mv ~/.parity/906a34e69aec8c0d /tmp
+> parity --fat-db";
+
 /// Client configuration errors.
 #[derive(Debug)]
 pub enum Error {
@@ -15,6 +23,8 @@ pub enum Error {
 	Database(String),
 	/// Util error
 	Util(UtilError),
+	/// Fat DB is enabled, but database does not contain key preimages of existing state.
+	FatDbResyncRequired,
 }
 
 impl From<TraceError> for Error {
@@ -48,6 +58,7 @@ impl Display for Error {
 			Error::Trie(ref err) => write!(f, "{}", err),
 			Error::Util(ref err) => write!(f, "{}", err),
 			Error::Database(ref s) => write!(f, "Database error: {}", s),
+			Error::FatDbResyncRequired => write!(f, "{}", FAT_DB_RESYNC_ERR),
 		}
 	}
 }
@@ -21,18 +21,23 @@ mod error;
 mod test_client;
 mod trace;
 mod client;
+mod check;
+mod bad_blocks;
 
 pub use self::client::*;
 pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockQueueConfig, BlockChainConfig, Switch, VMType};
 pub use self::error::Error;
+pub use self::check::{StateCheck, BadNode, Problem as NodeProblem, Location as NodeLocation};
 pub use types::ids::*;
 pub use self::test_client::{TestBlockChainClient, EachBlockWith};
 pub use types::trace_filter::Filter as TraceFilter;
 pub use executive::{Executed, Executive, TransactOptions};
+pub use verification::SenderCache;
 pub use env_info::{LastHashes, EnvInfo};
 pub use self::chain_notify::{ChainNotify, ChainNotifyClient};
 
 pub use types::call_analytics::CallAnalytics;
+pub use types::state_override::{StateOverride, AccountOverride};
 pub use block_import_error::BlockImportError;
 pub use transaction_import::TransactionImportResult;
 pub use transaction_import::TransactionImportError;
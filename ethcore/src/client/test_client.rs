@@ -24,8 +24,8 @@ use devtools::*;
 use transaction::{Transaction, LocalizedTransaction, SignedTransaction, Action};
 use blockchain::TreeRoute;
 use client::{
-	BlockChainClient, MiningBlockChainClient, BlockChainInfo, BlockStatus, BlockID,
-	TransactionID, UncleID, TraceId, TraceFilter, LastHashes, CallAnalytics, BlockImportError
+	BlockChainClient, MiningBlockChainClient, BlockChainInfo, BlockStatus, BlockID, PruningInfo, HISTORY, BadBlock,
+	TransactionID, UncleID, TraceId, TraceFilter, LastHashes, CallAnalytics, StateOverride, BlockImportError
 };
 use header::{Header as BlockHeader, BlockNumber};
 use filter::Filter;
@@ -65,6 +65,8 @@ pub struct TestBlockChainClient {
 	pub code: RwLock<HashMap<Address, Bytes>>,
 	/// Execution result.
 	pub execution_result: RwLock<Option<Result<Executed, CallError>>>,
+	/// Reason why states of blocks other than the latest one are unavailable.
+	pub state_error: RwLock<CallError>,
 	/// Transaction receipts.
 	pub receipts: RwLock<HashMap<TransactionID, LocalizedReceipt>>,
 	/// Block queue size.
@@ -77,6 +79,8 @@ pub struct TestBlockChainClient {
 	pub vm_factory: EvmFactory,
 	/// Timestamp assigned to latest sealed block
 	pub latest_block_timestamp: RwLock<u64>,
+	/// Blocks rejected during import.
+	pub bad_blocks: RwLock<Vec<BadBlock>>,
 }
 
 #[derive(Clone)]
@@ -113,12 +117,14 @@ impl TestBlockChainClient {
 			storage: RwLock::new(HashMap::new()),
 			code: RwLock::new(HashMap::new()),
 			execution_result: RwLock::new(None),
+			state_error: RwLock::new(CallError::StatePruned),
 			receipts: RwLock::new(HashMap::new()),
 			queue_size: AtomicUsize::new(0),
 			miner: Arc::new(Miner::with_spec(&spec)),
 			spec: spec,
 			vm_factory: EvmFactory::new(VMType::Interpreter),
 			latest_block_timestamp: RwLock::new(10_000_000),
+			bad_blocks: RwLock::new(Vec::new()),
 		};
 		client.add_blocks(1, EachBlockWith::Nothing); // add genesis block
 		client.genesis_hash = client.last_hash.read().clone();
@@ -135,6 +141,11 @@ impl TestBlockChainClient {
 		*self.execution_result.write() = Some(result);
 	}
 
+	/// Set the reason why states of blocks other than the latest one are unavailable.
+	pub fn set_state_error(&self, error: CallError) {
+		*self.state_error.write() = error;
+	}
+
 	/// Set the balance of account `address` to `balance`.
 	pub fn set_balance(&self, address: Address, balance: U256) {
 		self.balances.write().insert(address, balance);
@@ -291,7 +302,7 @@ impl MiningBlockChainClient for TestBlockChainClient {
 		let genesis_header = self.spec.genesis_header();
 		let mut db_result = get_temp_journal_db();
 		let mut db = db_result.take();
-		self.spec.ensure_db_good(db.as_hashdb_mut()).unwrap();
+		self.spec.ensure_db_good(db.as_hashdb_mut(), &Default::default()).unwrap();
 
 		let last_hashes = vec![genesis_header.hash()];
 		let mut open_block = OpenBlock::new(
@@ -320,14 +331,29 @@ impl MiningBlockChainClient for TestBlockChainClient {
 }
 
 impl BlockChainClient for TestBlockChainClient {
-	fn call(&self, _t: &SignedTransaction, _block: BlockID, _analytics: CallAnalytics) -> Result<Executed, CallError> {
+	fn call(&self, _t: &SignedTransaction, _block: BlockID, _analytics: CallAnalytics, _state_override: &StateOverride) -> Result<Executed, CallError> {
 		self.execution_result.read().clone().unwrap()
 	}
 
+	fn call_many(&self, transactions: Vec<SignedTransaction>, _block: BlockID, _analytics: CallAnalytics, _state_overrides: Vec<StateOverride>) -> Result<Vec<Executed>, CallError> {
+		transactions.iter().map(|_| self.execution_result.read().clone().unwrap()).collect()
+	}
+
 	fn replay(&self, _id: TransactionID, _analytics: CallAnalytics) -> Result<Executed, CallError> {
 		self.execution_result.read().clone().unwrap()
 	}
 
+	fn replay_block_transactions(&self, _block: BlockID, _analytics: CallAnalytics) -> Result<Vec<Executed>, CallError> {
+		self.execution_result.read().clone().unwrap().map(|result| vec![result])
+	}
+
+	fn replay_bad_block(&self, hash: H256, _analytics: CallAnalytics) -> Result<Vec<Executed>, CallError> {
+		if !self.bad_blocks.read().iter().any(|block| block.hash == hash) {
+			return Err(CallError::BlockNotFound);
+		}
+		self.execution_result.read().clone().unwrap().map(|result| vec![result])
+	}
+
 	fn block_total_difficulty(&self, _id: BlockID) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -336,10 +362,10 @@ impl BlockChainClient for TestBlockChainClient {
 		Self::block_hash(self, id)
 	}
 
-	fn nonce(&self, address: &Address, id: BlockID) -> Option<U256> {
+	fn try_nonce(&self, address: &Address, id: BlockID) -> Result<U256, CallError> {
 		match id {
-			BlockID::Latest => Some(self.nonces.read().get(address).cloned().unwrap_or(self.spec.params.account_start_nonce)),
-			_ => None,
+			BlockID::Latest => Ok(self.nonces.read().get(address).cloned().unwrap_or(self.spec.params.account_start_nonce)),
+			_ => Err(self.state_error.read().clone()),
 		}
 	}
 
@@ -347,18 +373,18 @@ impl BlockChainClient for TestBlockChainClient {
 		self.nonce(address, BlockID::Latest).unwrap()
 	}
 
-	fn code(&self, address: &Address, id: BlockID) -> Option<Option<Bytes>> {
+	fn try_code(&self, address: &Address, id: BlockID) -> Result<Option<Bytes>, CallError> {
 		match id {
-			BlockID::Latest => Some(self.code.read().get(address).cloned()),
-			_ => None,
+			BlockID::Latest => Ok(self.code.read().get(address).cloned()),
+			_ => Err(self.state_error.read().clone()),
 		}
 	}
 
-	fn balance(&self, address: &Address, id: BlockID) -> Option<U256> {
+	fn try_balance(&self, address: &Address, id: BlockID) -> Result<U256, CallError> {
 		if let BlockID::Latest = id {
-			Some(self.balances.read().get(address).cloned().unwrap_or_else(U256::zero))
+			Ok(self.balances.read().get(address).cloned().unwrap_or_else(U256::zero))
 		} else {
-			None
+			Err(self.state_error.read().clone())
 		}
 	}
 
@@ -366,11 +392,23 @@ impl BlockChainClient for TestBlockChainClient {
 		self.balance(address, BlockID::Latest).unwrap()
 	}
 
-	fn storage_at(&self, address: &Address, position: &H256, id: BlockID) -> Option<H256> {
+	fn is_fat_db(&self) -> bool {
+		false
+	}
+
+	fn list_accounts(&self, _id: BlockID, _after: Option<Address>, _count: u64) -> Option<Vec<Address>> {
+		None
+	}
+
+	fn list_storage(&self, _id: BlockID, _account: &Address, _after: Option<H256>, _count: u64) -> Option<Vec<H256>> {
+		None
+	}
+
+	fn try_storage_at(&self, address: &Address, position: &H256, id: BlockID) -> Result<H256, CallError> {
 		if let BlockID::Latest = id {
-			Some(self.storage.read().get(&(address.clone(), position.clone())).cloned().unwrap_or_else(H256::new))
+			Ok(self.storage.read().get(&(address.clone(), position.clone())).cloned().unwrap_or_else(H256::new))
 		} else {
-			None
+			Err(self.state_error.read().clone())
 		}
 	}
 
@@ -559,6 +597,17 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
+	fn bad_blocks(&self) -> Vec<BadBlock> {
+		self.bad_blocks.read().clone()
+	}
+
+	fn pruning_info(&self) -> PruningInfo {
+		PruningInfo {
+			earliest_state: 0,
+			history_size: HISTORY,
+		}
+	}
+
 	fn filter_traces(&self, _filter: TraceFilter) -> Option<Vec<LocalizedTrace>> {
 		unimplemented!();
 	}
@@ -20,7 +20,7 @@ use blockchain::TreeRoute;
 use block_queue::BlockQueueInfo;
 use block::{OpenBlock, SealedBlock};
 use header::{BlockNumber};
-use transaction::{LocalizedTransaction, SignedTransaction};
+use transaction::{LocalizedTransaction, SignedTransaction, Transaction, Action};
 use log_entry::LocalizedLogEntry;
 use filter::Filter;
 use views::{BlockView};
@@ -33,10 +33,13 @@ use types::trace_filter::Filter as TraceFilter;
 use executive::Executed;
 use env_info::LastHashes;
 use types::call_analytics::CallAnalytics;
+use types::state_override::StateOverride;
 use block_import_error::BlockImportError;
 use ipc::IpcConfig;
 use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
+use types::pruning_info::PruningInfo;
+use types::bad_block::BadBlock;
 
 #[derive(Ipc)]
 #[ipc(client_ident="RemoteClient")]
@@ -63,9 +66,15 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get block total difficulty.
 	fn block_total_difficulty(&self, id: BlockID) -> Option<U256>;
 
+	/// Attempt to get address nonce at given block, explaining why the block's state is unavailable otherwise.
+	/// May not fail on BlockID::Latest.
+	fn try_nonce(&self, address: &Address, id: BlockID) -> Result<U256, CallError>;
+
 	/// Attempt to get address nonce at given block.
 	/// May not fail on BlockID::Latest.
-	fn nonce(&self, address: &Address, id: BlockID) -> Option<U256>;
+	fn nonce(&self, address: &Address, id: BlockID) -> Option<U256> {
+		self.try_nonce(address, id).ok()
+	}
 
 	/// Get address nonce at the latest block's state.
 	fn latest_nonce(&self, address: &Address) -> U256 {
@@ -77,8 +86,13 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get block hash.
 	fn block_hash(&self, id: BlockID) -> Option<H256>;
 
+	/// Get address code at given block's state, explaining why the block's state is unavailable otherwise.
+	fn try_code(&self, address: &Address, id: BlockID) -> Result<Option<Bytes>, CallError>;
+
 	/// Get address code at given block's state.
-	fn code(&self, address: &Address, id: BlockID) -> Option<Option<Bytes>>;
+	fn code(&self, address: &Address, id: BlockID) -> Option<Option<Bytes>> {
+		self.try_code(address, id).ok()
+	}
 
 	/// Get address code at the latest block's state.
 	fn latest_code(&self, address: &Address) -> Option<Bytes> {
@@ -86,11 +100,18 @@ pub trait BlockChainClient : Sync + Send {
 			.expect("code will return Some if given BlockID::Latest; qed")
 	}
 
+	/// Get address balance at the given block's state, explaining why the block's state is unavailable otherwise.
+	///
+	/// May not fail if given BlockID::Latest.
+	fn try_balance(&self, address: &Address, id: BlockID) -> Result<U256, CallError>;
+
 	/// Get address balance at the given block's state.
 	///
 	/// May not return None if given BlockID::Latest.
-	/// Returns None if and only if the block's root hash has been pruned from the DB.
-	fn balance(&self, address: &Address, id: BlockID) -> Option<U256>;
+	/// Returns None if and only if the block's state has been pruned from the DB and can't be reconstructed.
+	fn balance(&self, address: &Address, id: BlockID) -> Option<U256> {
+		self.try_balance(address, id).ok()
+	}
 
 	/// Get address balance at the latest block's state.
 	fn latest_balance(&self, address: &Address) -> U256 {
@@ -99,11 +120,19 @@ pub trait BlockChainClient : Sync + Send {
 			Therefore balance has returned Some; qed")
 	}
 
+	/// Get value of the storage at given position at the given block's state,
+	/// explaining why the block's state is unavailable otherwise.
+	///
+	/// May not fail if given BlockID::Latest.
+	fn try_storage_at(&self, address: &Address, position: &H256, id: BlockID) -> Result<H256, CallError>;
+
 	/// Get value of the storage at given position at the given block's state.
 	///
 	/// May not return None if given BlockID::Latest.
-	/// Returns None if and only if the block's root hash has been pruned from the DB.
-	fn storage_at(&self, address: &Address, position: &H256, id: BlockID) -> Option<H256>;
+	/// Returns None if and only if the block's state has been pruned from the DB and can't be reconstructed.
+	fn storage_at(&self, address: &Address, position: &H256, id: BlockID) -> Option<H256> {
+		self.try_storage_at(address, position, id).ok()
+	}
 
 	/// Get value of the storage at given position at the latest block's state.
 	fn latest_storage_at(&self, address: &Address, position: &H256) -> H256 {
@@ -112,6 +141,17 @@ pub trait BlockChainClient : Sync + Send {
 			Therefore storage_at has returned Some; qed")
 	}
 
+	/// Returns true if the state database stores key preimages, allowing state enumeration.
+	fn is_fat_db(&self) -> bool;
+
+	/// Get up to `count` addresses of accounts existing in the given block's state, starting right after `after`.
+	/// Accounts are ordered by their address hash. Returns `None` if not a fat DB or the state is not available.
+	fn list_accounts(&self, id: BlockID, after: Option<Address>, count: u64) -> Option<Vec<Address>>;
+
+	/// Get up to `count` storage keys of `account` in the given block's state, starting right after `after`.
+	/// Keys are ordered by their hash. Returns `None` if not a fat DB or the state is not available.
+	fn list_storage(&self, id: BlockID, account: &Address, after: Option<H256>, count: u64) -> Option<Vec<H256>>;
+
 	/// Get transaction with given hash.
 	fn transaction(&self, id: TransactionID) -> Option<LocalizedTransaction>;
 
@@ -137,6 +177,14 @@ pub trait BlockChainClient : Sync + Send {
 	/// Import a block into the blockchain.
 	fn import_block(&self, bytes: Bytes) -> Result<H256, BlockImportError>;
 
+	/// Import a block received from `peer` into the blockchain. The peer is recorded if the block turns out to be bad.
+	fn import_block_from_peer(&self, bytes: Bytes, _peer: String) -> Result<H256, BlockImportError> {
+		self.import_block(bytes)
+	}
+
+	/// Get the most recent blocks rejected during import, oldest first.
+	fn bad_blocks(&self) -> Vec<BadBlock>;
+
 	/// Get block queue information.
 	fn queue_info(&self) -> BlockQueueInfo;
 
@@ -146,6 +194,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get blockchain information.
 	fn chain_info(&self) -> BlockChainInfo;
 
+	/// Get information about which recent states are kept.
+	fn pruning_info(&self) -> PruningInfo;
+
 	/// Get the registrar address, if it exists.
 	fn additional_params(&self) -> BTreeMap<String, String>;
 
@@ -158,12 +209,39 @@ pub trait BlockChainClient : Sync + Send {
 	/// Returns logs matching given filter.
 	fn logs(&self, filter: Filter, limit: Option<usize>) -> Vec<LocalizedLogEntry>;
 
-	/// Makes a non-persistent transaction call.
-	fn call(&self, t: &SignedTransaction, block: BlockID, analytics: CallAnalytics) -> Result<Executed, CallError>;
+	/// Makes a non-persistent transaction call, with `state_override` applied to a copy of the state.
+	fn call(&self, t: &SignedTransaction, block: BlockID, analytics: CallAnalytics, state_override: &StateOverride) -> Result<Executed, CallError>;
+
+	/// Makes a sequence of non-persistent transaction calls, each on top of the state left by the previous one.
+	/// `state_overrides[i]`, if present, is applied right before the `i`-th call.
+	fn call_many(&self, transactions: Vec<SignedTransaction>, block: BlockID, analytics: CallAnalytics, state_overrides: Vec<StateOverride>) -> Result<Vec<Executed>, CallError>;
+
+	/// Makes a constant call to the contract at `address` on top of given block, returning its output.
+	fn call_contract(&self, block: BlockID, address: Address, data: Bytes) -> Result<Bytes, String> {
+		let transaction = Transaction {
+			nonce: U256::zero(),
+			action: Action::Call(address),
+			gas: U256::from(50_000_000),
+			gas_price: U256::zero(),
+			value: U256::zero(),
+			data: data,
+		}.fake_sign(Address::default());
+
+		self.call(&transaction, block, Default::default(), &StateOverride::default())
+			.map(|executed| executed.output)
+			.map_err(|e| format!("{}", e))
+	}
 
 	/// Replays a given transaction for inspection.
 	fn replay(&self, t: TransactionID, analytics: CallAnalytics) -> Result<Executed, CallError>;
 
+	/// Replays all transactions of a given block for inspection, executing the block only once.
+	fn replay_block_transactions(&self, block: BlockID, analytics: CallAnalytics) -> Result<Vec<Executed>, CallError>;
+
+	/// Replays the transactions of a recorded bad block on top of its parent's state, stopping at the first
+	/// one which fails to execute.
+	fn replay_bad_block(&self, hash: H256, analytics: CallAnalytics) -> Result<Vec<Executed>, CallError>;
+
 	/// Returns traces matching given filter.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -210,6 +288,25 @@ pub trait BlockChainClient : Sync + Send {
 			Err(())
 		}
 	}
+
+	/// Get the lowest gas price included in each of the last `sample_size` blocks, skipping empty ones.
+	fn lowest_gas_prices(&self, sample_size: usize) -> Vec<U256> {
+		let mut h = self.chain_info().best_block_hash;
+		let mut lowest = Vec::new();
+		for _ in 0..sample_size {
+			let block_bytes = self.block(BlockID::Hash(h)).expect("h is either the best_block_hash or an ancestor; qed");
+			let block = BlockView::new(&block_bytes);
+			let header = block.header_view();
+			if header.number() == 0 {
+				break;
+			}
+			if let Some(price) = block.transaction_views().iter().map(|t| t.gas_price()).min() {
+				lowest.push(price);
+			}
+			h = header.parent_hash().clone();
+		}
+		lowest
+	}
 }
 
 /// Extended client interface used for mining
@@ -16,24 +16,26 @@
 
 //! A blockchain engine that supports a basic, non-BFT proof-of-authority.
 
+use std::sync::Weak;
 use common::*;
 use ethkey::{recover, public_to_address};
 use account_provider::AccountProvider;
 use block::*;
 use spec::CommonParams;
 use engines::Engine;
+use engines::validator_set::{ValidatorSet, SimpleList, new_validator_set};
+use client::{Client, BlockChainClient};
 use evm::Schedule;
 use ethjson;
 
 /// `BasicAuthority` params.
-#[derive(Debug, PartialEq)]
 pub struct BasicAuthorityParams {
 	/// Gas limit divisor.
 	pub gas_limit_bound_divisor: U256,
 	/// Block duration.
 	pub duration_limit: u64,
 	/// Valid signatories.
-	pub authorities: HashSet<Address>,
+	pub validators: Box<ValidatorSet>,
 }
 
 impl From<ethjson::spec::BasicAuthorityParams> for BasicAuthorityParams {
@@ -41,7 +43,12 @@ impl From<ethjson::spec::BasicAuthorityParams> for BasicAuthorityParams {
 		BasicAuthorityParams {
 			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
 			duration_limit: p.duration_limit.into(),
-			authorities: p.authorities.into_iter().map(Into::into).collect::<HashSet<_>>(),
+			validators: match (p.validators, p.authorities) {
+				(Some(validators), _) => new_validator_set(validators),
+				(None, authorities) => Box::new(SimpleList::new(
+					authorities.unwrap_or_else(Vec::new).into_iter().map(Into::into).collect()
+				)),
+			},
 		}
 	}
 }
@@ -52,6 +59,7 @@ pub struct BasicAuthority {
 	params: CommonParams,
 	our_params: BasicAuthorityParams,
 	builtins: BTreeMap<Address, Builtin>,
+	client: RwLock<Option<Weak<Client>>>,
 }
 
 impl BasicAuthority {
@@ -61,8 +69,16 @@ impl BasicAuthority {
 			params: params,
 			our_params: our_params,
 			builtins: builtins,
+			client: RwLock::new(None),
 		}
 	}
+
+	fn signer(header: &Header) -> Result<Address, Error> {
+		use rlp::{UntrustedRlp, View};
+
+		let sig = try!(UntrustedRlp::new(&header.seal()[0]).as_val::<H520>());
+		Ok(public_to_address(&try!(recover(&sig.into(), &header.bare_hash()))))
+	}
 }
 
 impl Engine for BasicAuthority {
@@ -100,7 +116,15 @@ impl Engine for BasicAuthority {
 	fn on_close_block(&self, _block: &mut ExecutedBlock) {}
 
 	fn is_sealer(&self, author: &Address) -> Option<bool> {
-		Some(self.our_params.authorities.contains(author))
+		// check against the validators of the block on top of the current best block.
+		let best = self.client.read().as_ref()
+			.and_then(Weak::upgrade)
+			.map(|client| {
+				let info = client.chain_info();
+				(info.best_block_hash, info.best_block_number)
+			});
+		let (hash, number) = best.unwrap_or_else(|| (H256::default(), 0));
+		Some(self.our_params.validators.contains(&hash, number, author))
 	}
 
 	/// Attempt to seal the block internally.
@@ -108,8 +132,13 @@ impl Engine for BasicAuthority {
 	/// This operation is synchronous and may (quite reasonably) not be available, in which `false` will
 	/// be returned.
 	fn generate_seal(&self, block: &ExecutedBlock, accounts: Option<&AccountProvider>) -> Option<Vec<Bytes>> {
+		let header = block.header();
+		// the validator set may have changed since sealing was enabled.
+		if !self.our_params.validators.contains(header.parent_hash(), header.number().saturating_sub(1), header.author()) {
+			trace!(target: "basicauthority", "generate_seal: FAIL: {} is not a validator", header.author());
+			return None;
+		}
 		if let Some(ap) = accounts {
-			let header = block.header();
 			let message = header.bare_hash();
 			// account should be pernamently unlocked, otherwise sealing will fail
 			if let Ok(signature) = ap.sign(*block.header().author(), message) {
@@ -135,14 +164,8 @@ impl Engine for BasicAuthority {
 	}
 
 	fn verify_block_unordered(&self, header: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
-		use rlp::{UntrustedRlp, View};
-
 		// check the signature is legit.
-		let sig = try!(UntrustedRlp::new(&header.seal()[0]).as_val::<H520>());
-		let signer = public_to_address(&try!(recover(&sig.into(), &header.bare_hash())));
-		if !self.our_params.authorities.contains(&signer) {
-			return try!(Err(BlockError::InvalidSeal));
-		}
+		try!(Self::signer(header));
 		Ok(())
 	}
 
@@ -162,6 +185,12 @@ impl Engine for BasicAuthority {
 		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
 			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() })));
 		}
+
+		// check the signer was a validator as of the parent block.
+		let signer = try!(Self::signer(header));
+		if !self.our_params.validators.contains(&parent.hash(), parent.number(), &signer) {
+			return try!(Err(BlockError::InvalidSeal));
+		}
 		Ok(())
 	}
 
@@ -173,6 +202,11 @@ impl Engine for BasicAuthority {
 	fn verify_transaction(&self, t: &SignedTransaction, _header: &Header) -> Result<(), Error> {
 		t.sender().map(|_|()) // Perform EC recovery and cache sender
 	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		self.our_params.validators.register_call_contract(client.clone());
+		*self.client.write() = Some(client);
+	}
 }
 
 impl Header {
@@ -254,7 +288,7 @@ mod tests {
 		let genesis_header = spec.genesis_header();
 		let mut db_result = get_temp_journal_db();
 		let mut db = db_result.take();
-		spec.ensure_db_good(db.as_hashdb_mut()).unwrap();
+		spec.ensure_db_good(db.as_hashdb_mut(), &Default::default()).unwrap();
 		let last_hashes = Arc::new(vec![genesis_header.hash()]);
 		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes, addr, (3141562.into(), 31415620.into()), vec![]).unwrap();
 		let b = b.close_and_lock();
@@ -262,6 +296,24 @@ mod tests {
 		assert!(b.try_seal(engine, seal).is_ok());
 	}
 
+	#[test]
+	fn does_not_seal_for_non_validators() {
+		let tap = AccountProvider::transient_provider();
+		let stranger = tap.insert_account("1".sha3(), "").unwrap();
+		tap.unlock_account_permanently(stranger, "".into()).unwrap();
+
+		let spec = new_test_authority();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let mut db_result = get_temp_journal_db();
+		let mut db = db_result.take();
+		spec.ensure_db_good(db.as_hashdb_mut(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes, stranger, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock();
+		assert!(engine.generate_seal(b.block(), Some(&tap)).is_none());
+	}
+
 	#[test]
 	fn seals_internally() {
 		let tap = AccountProvider::transient_provider();
@@ -271,4 +323,32 @@ mod tests {
 		assert!(!engine.is_sealer(&Address::default()).unwrap());
 		assert!(engine.is_sealer(&authority).unwrap());
 	}
+
+	#[test]
+	fn rejects_blocks_sealed_by_non_validators() {
+		let tap = AccountProvider::transient_provider();
+		let authority = tap.insert_account("".sha3(), "").unwrap();
+		let stranger = tap.insert_account("1".sha3(), "").unwrap();
+		let engine = new_test_authority().engine;
+
+		let mut parent = Header::default();
+		parent.set_gas_limit(U256::from(3141562));
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_gas_limit(U256::from(3141562));
+		header.set_parent_hash(parent.hash());
+
+		let signature = tap.sign_with_password(authority, "".into(), header.bare_hash()).unwrap();
+		header.set_seal(vec![::rlp::encode(&(&*signature as &[u8])).to_vec()]);
+		assert!(engine.verify_block_unordered(&header, None).is_ok());
+		assert!(engine.verify_block_family(&header, &parent, None).is_ok());
+
+		let signature = tap.sign_with_password(stranger, "".into(), header.bare_hash()).unwrap();
+		header.set_seal(vec![::rlp::encode(&(&*signature as &[u8])).to_vec()]);
+		assert!(engine.verify_block_unordered(&header, None).is_ok());
+		match engine.verify_block_family(&header, &parent, None) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			other => panic!("should be invalid seal error (got {:?})", other),
+		}
+	}
 }
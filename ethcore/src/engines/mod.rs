@@ -19,13 +19,17 @@
 mod null_engine;
 mod instant_seal;
 mod basic_authority;
+mod tx_permission;
+pub mod validator_set;
 
 pub use self::null_engine::NullEngine;
 pub use self::instant_seal::InstantSeal;
 pub use self::basic_authority::BasicAuthority;
 
+use std::sync::Weak;
 use common::*;
 use account_provider::AccountProvider;
+use client::Client;
 use block::ExecutedBlock;
 use spec::CommonParams;
 use evm::Schedule;
@@ -102,6 +106,14 @@ pub trait Engine : Sync + Send {
 	fn verify_transaction_basic(&self, _t: &SignedTransaction, _header: &Header) -> Result<(), Error> { Ok(()) }
 	/// Verify a particular transaction is valid.
 	fn verify_transaction(&self, _t: &SignedTransaction, _header: &Header) -> Result<(), Error> { Ok(()) }
+	/// Verify the sender of a transaction is permitted to send it by the chain's permission contract, if any.
+	/// `call` performs a constant call to a contract in the relevant state.
+	fn verify_transaction_permission(&self, t: &SignedTransaction, call: &Fn(Address, Bytes) -> Result<Bytes, String>) -> Result<(), Error> {
+		match self.params().transaction_permission_contract {
+			Some(ref contract) => tx_permission::check(contract, t, call).map_err(Into::into),
+			None => Ok(()),
+		}
+	}
 
 	/// Verify the seal of a block. This is an auxilliary method that actually just calls other `verify_` methods
 	/// to get the job done. By default it must pass `verify_basic` and `verify_block_unordered`. If more or fewer
@@ -128,5 +140,8 @@ pub trait Engine : Sync + Send {
 	/// Panics if `is_builtin(a)` is not true.
 	fn execute_builtin(&self, a: &Address, input: &[u8], output: &mut [u8]) { self.builtins().get(a).unwrap().execute(input, output); }
 
+	/// Gives the engine access to the client, e.g. for reading state of contracts it depends on.
+	fn register_client(&self, _client: Weak<Client>) {}
+
 	// TODO: sealing stuff - though might want to leave this for later.
 }
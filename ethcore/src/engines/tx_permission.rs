@@ -0,0 +1,123 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction permissioning through a contract.
+//!
+//! The contract is asked via a constant call to `allowedTxTypes(address)`, which returns
+//! a bit mask of the transaction types the given sender is allowed to send.
+
+use common::*;
+use error::TransactionError;
+
+/// Sender may send plain value transfers.
+pub const BASIC: u32 = 0x1;
+/// Sender may call contracts.
+pub const CALL: u32 = 0x2;
+/// Sender may create contracts.
+pub const CREATE: u32 = 0x4;
+
+/// Permission a transaction requires from its sender.
+pub fn required(t: &SignedTransaction) -> u32 {
+	match t.action {
+		Action::Create => CREATE,
+		Action::Call(_) if t.data.is_empty() => BASIC,
+		Action::Call(_) => CALL,
+	}
+}
+
+/// Encodes the `allowedTxTypes(address)` call for given sender.
+pub fn call_data(sender: &Address) -> Bytes {
+	let mut data = "allowedTxTypes(address)".sha3()[..4].to_vec();
+	data.extend_from_slice(&H256::from(sender)[..]);
+	data
+}
+
+/// Decodes the permission mask returned by the contract.
+pub fn decode(output: &[u8]) -> Option<u32> {
+	match output.len() {
+		32 => Some(U256::from(output).low_u32()),
+		_ => None,
+	}
+}
+
+/// Check the sender of given transaction is permitted to send it by the contract at `contract`.
+/// Transactions are rejected if the contract can't be queried.
+pub fn check(contract: &Address, t: &SignedTransaction, call: &Fn(Address, Bytes) -> Result<Bytes, String>) -> Result<(), TransactionError> {
+	let sender = try!(t.sender().map_err(|_| TransactionError::NotPermitted));
+	let allowed = match call(contract.clone(), call_data(&sender)) {
+		Ok(output) => decode(&output).unwrap_or(0),
+		Err(e) => {
+			warn!(target: "txpermission", "Failed to query transaction permission contract {}: {}", contract, e);
+			0
+		},
+	};
+
+	match allowed & required(t) {
+		0 => Err(TransactionError::NotPermitted),
+		_ => Ok(()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use common::*;
+	use error::TransactionError;
+	use super::*;
+
+	fn transaction(action: Action, data: Bytes) -> SignedTransaction {
+		Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 21000.into(),
+			action: action,
+			value: 0.into(),
+			data: data,
+		}.fake_sign(Address::from(0x10u64))
+	}
+
+	fn answering(mask: u32) -> Box<Fn(Address, Bytes) -> Result<Bytes, String>> {
+		Box::new(move |_, _| Ok(H256::from(U256::from(mask)).to_vec()))
+	}
+
+	#[test]
+	fn encodes_call_data() {
+		let data = call_data(&Address::from(0x10u64));
+		assert_eq!(data.len(), 36);
+		assert_eq!(&data[..4], &"allowedTxTypes(address)".sha3()[..4]);
+		assert_eq!(data[35], 0x10);
+	}
+
+	#[test]
+	fn checks_required_permission() {
+		let contract = Address::from(5u64);
+		let transfer = transaction(Action::Call(Address::from(0x20u64)), vec![]);
+		let call = transaction(Action::Call(Address::from(0x20u64)), vec![1]);
+		let create = transaction(Action::Create, vec![1]);
+
+		assert_eq!(check(&contract, &transfer, &*answering(BASIC)), Ok(()));
+		assert_eq!(check(&contract, &call, &*answering(BASIC)), Err(TransactionError::NotPermitted));
+		assert_eq!(check(&contract, &call, &*answering(BASIC | CALL)), Ok(()));
+		assert_eq!(check(&contract, &create, &*answering(BASIC | CALL)), Err(TransactionError::NotPermitted));
+		assert_eq!(check(&contract, &create, &*answering(CREATE)), Ok(()));
+	}
+
+	#[test]
+	fn rejects_when_contract_fails() {
+		let transfer = transaction(Action::Call(Address::from(0x20u64)), vec![]);
+		let failing = |_: Address, _: Bytes| -> Result<Bytes, String> { Err("no state".to_owned()) };
+		assert_eq!(check(&Address::from(5u64), &transfer, &failing), Err(TransactionError::NotPermitted));
+	}
+}
@@ -0,0 +1,142 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Validator set read from a contract.
+//!
+//! The contract is asked via a constant call to `getValidators()`, which returns an `address[]`
+//! of the validators allowed to seal the child of the block whose state is queried.
+
+use std::sync::Weak;
+use common::*;
+use client::{Client, BlockChainClient};
+use ids::BlockID;
+use super::ValidatorSet;
+
+/// Encodes the `getValidators()` call.
+pub fn call_data() -> Bytes {
+	"getValidators()".sha3()[..4].to_vec()
+}
+
+/// Decodes the ABI-encoded `address[]` returned by the contract.
+pub fn decode(output: &[u8]) -> Option<Vec<Address>> {
+	fn word(output: &[u8], index: usize) -> Option<&[u8]> {
+		output.get(index * 32..(index + 1) * 32)
+	}
+	fn to_index(word: &[u8]) -> Option<usize> {
+		let value = U256::from(word);
+		match value > U256::from(usize::max_value() / 32) {
+			true => None,
+			false => Some(value.low_u64() as usize),
+		}
+	}
+
+	let offset = match word(output, 0).and_then(to_index) {
+		Some(offset) if offset % 32 == 0 => offset / 32,
+		_ => return None,
+	};
+	let len = match word(output, offset).and_then(to_index) {
+		Some(len) if len <= output.len() / 32 => len,
+		_ => return None,
+	};
+	let mut validators = Vec::with_capacity(len);
+	for i in 0..len {
+		match word(output, offset + 1 + i) {
+			Some(address) => validators.push(Address::from_slice(&address[12..])),
+			None => return None,
+		}
+	}
+	Some(validators)
+}
+
+/// Validator set kept in the state of a contract at `address`.
+pub struct ValidatorContract {
+	address: Address,
+	client: RwLock<Option<Weak<Client>>>,
+}
+
+impl ValidatorContract {
+	/// Create a new validator set reading from contract at `address`.
+	pub fn new(address: Address) -> Self {
+		ValidatorContract {
+			address: address,
+			client: RwLock::new(None),
+		}
+	}
+
+	fn validators(&self, parent_hash: &H256) -> Result<Vec<Address>, String> {
+		let client = try!(self.client.read().as_ref().and_then(Weak::upgrade).ok_or_else(|| "No client registered".to_owned()));
+		let output = try!(client.call_contract(BlockID::Hash(parent_hash.clone()), self.address.clone(), call_data()));
+		decode(&output).ok_or_else(|| "Invalid output".to_owned())
+	}
+}
+
+impl ValidatorSet for ValidatorContract {
+	fn contains(&self, parent_hash: &H256, _parent_number: BlockNumber, address: &Address) -> bool {
+		match self.validators(parent_hash) {
+			Ok(validators) => validators.contains(address),
+			Err(e) => {
+				warn!(target: "engine", "Failed to query validator contract {} at {}: {}", self.address, parent_hash, e);
+				false
+			},
+		}
+	}
+
+	fn register_call_contract(&self, client: Weak<Client>) {
+		*self.client.write() = Some(client);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use common::*;
+	use super::*;
+
+	fn word(value: u64) -> Vec<u8> {
+		H256::from(U256::from(value)).to_vec()
+	}
+
+	#[test]
+	fn encodes_call_data() {
+		assert_eq!(call_data(), "getValidators()".sha3()[..4].to_vec());
+	}
+
+	#[test]
+	fn decodes_address_array() {
+		let mut output = word(0x20);
+		output.extend(word(2));
+		output.extend(H256::from(Address::from(1u64)).to_vec());
+		output.extend(H256::from(Address::from(2u64)).to_vec());
+
+		assert_eq!(decode(&output), Some(vec![Address::from(1u64), Address::from(2u64)]));
+	}
+
+	#[test]
+	fn decodes_empty_array() {
+		let mut output = word(0x20);
+		output.extend(word(0));
+		assert_eq!(decode(&output), Some(vec![]));
+	}
+
+	#[test]
+	fn rejects_truncated_output() {
+		let mut output = word(0x20);
+		output.extend(word(2));
+		output.extend(H256::from(Address::from(1u64)).to_vec());
+
+		assert_eq!(decode(&output), None);
+		assert_eq!(decode(&[]), None);
+	}
+}
@@ -0,0 +1,53 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Validator sets used by proof-of-authority engines.
+//!
+//! The set responsible for sealing a block is determined by its parent, so that changes
+//! take effect at block boundaries.
+
+mod simple_list;
+mod contract;
+mod multi;
+
+use std::sync::Weak;
+use ethjson::spec::ValidatorSet as ValidatorSpec;
+use util::{Address, H256};
+use header::BlockNumber;
+use client::Client;
+
+pub use self::simple_list::SimpleList;
+pub use self::contract::ValidatorContract;
+pub use self::multi::Multi;
+
+/// Creates a validator set from spec.
+pub fn new_validator_set(spec: ValidatorSpec) -> Box<ValidatorSet> {
+	match spec {
+		ValidatorSpec::List(list) => Box::new(SimpleList::new(list.into_iter().map(Into::into).collect())),
+		ValidatorSpec::Contract(address) => Box::new(ValidatorContract::new(address.into())),
+		ValidatorSpec::Multi(sequence) => Box::new(Multi::new(
+			sequence.into_iter().map(|(block, set)| (block.into(), new_validator_set(set))).collect()
+		)),
+	}
+}
+
+/// A set of addresses allowed to seal blocks.
+pub trait ValidatorSet: Send + Sync {
+	/// Checks if `address` may seal the child of block `parent_hash` with number `parent_number`.
+	fn contains(&self, parent_hash: &H256, parent_number: BlockNumber, address: &Address) -> bool;
+	/// Allows the set to make constant calls to contracts at a given block's state.
+	fn register_call_contract(&self, _client: Weak<Client>) {}
+}
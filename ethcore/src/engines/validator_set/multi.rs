@@ -0,0 +1,103 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Validator set changing at fixed block numbers.
+
+use std::sync::Weak;
+use util::{Address, H256, BTreeMap};
+use header::BlockNumber;
+use client::Client;
+use super::ValidatorSet;
+
+/// Validator set switching between other sets at given block numbers.
+///
+/// Each set seals blocks starting from its block number up to the next transition.
+/// Blocks before the first transition have no validators.
+pub struct Multi {
+	sets: BTreeMap<BlockNumber, Box<ValidatorSet>>,
+}
+
+impl Multi {
+	/// Create a new validator set from a map of first sealed block numbers to sets.
+	pub fn new(sets: BTreeMap<BlockNumber, Box<ValidatorSet>>) -> Self {
+		if !sets.contains_key(&0) && !sets.contains_key(&1) {
+			warn!(target: "engine", "Validator set transitions start at a later block; earlier blocks can't be sealed.");
+		}
+		Multi {
+			sets: sets,
+		}
+	}
+
+	fn set_for(&self, number: BlockNumber) -> Option<&ValidatorSet> {
+		self.sets.iter()
+			.rev()
+			.find(|&(first, _)| *first <= number)
+			.map(|(_, set)| &**set)
+	}
+}
+
+impl ValidatorSet for Multi {
+	fn contains(&self, parent_hash: &H256, parent_number: BlockNumber, address: &Address) -> bool {
+		match self.set_for(parent_number + 1) {
+			Some(set) => set.contains(parent_hash, parent_number, address),
+			None => false,
+		}
+	}
+
+	fn register_call_contract(&self, client: Weak<Client>) {
+		for set in self.sets.values() {
+			set.register_call_contract(client.clone());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::{Address, H256, BTreeMap};
+	use super::super::{ValidatorSet, SimpleList};
+	use super::Multi;
+
+	fn list(addresses: Vec<u64>) -> Box<ValidatorSet> {
+		Box::new(SimpleList::new(addresses.into_iter().map(Address::from).collect()))
+	}
+
+	#[test]
+	fn switches_sets_at_transitions() {
+		let mut sets = BTreeMap::new();
+		sets.insert(1, list(vec![1]));
+		sets.insert(10, list(vec![2, 3]));
+		let multi = Multi::new(sets);
+		let hash = H256::default();
+
+		assert!(multi.contains(&hash, 0, &Address::from(1u64)));
+		assert!(multi.contains(&hash, 8, &Address::from(1u64)));
+		assert!(!multi.contains(&hash, 8, &Address::from(2u64)));
+		// block 10 is the first one sealed by the new set.
+		assert!(!multi.contains(&hash, 9, &Address::from(1u64)));
+		assert!(multi.contains(&hash, 9, &Address::from(2u64)));
+		assert!(multi.contains(&hash, 1000, &Address::from(3u64)));
+	}
+
+	#[test]
+	fn no_validators_before_first_transition() {
+		let mut sets = BTreeMap::new();
+		sets.insert(5, list(vec![1]));
+		let multi = Multi::new(sets);
+
+		assert!(!multi.contains(&H256::default(), 2, &Address::from(1u64)));
+		assert!(multi.contains(&H256::default(), 4, &Address::from(1u64)));
+	}
+}
@@ -0,0 +1,57 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fixed list of validators.
+
+use util::{Address, H256, HashSet};
+use header::BlockNumber;
+use super::ValidatorSet;
+
+/// Validator set which never changes.
+#[derive(Debug, PartialEq)]
+pub struct SimpleList {
+	validators: HashSet<Address>,
+}
+
+impl SimpleList {
+	/// Create a new validator set from given addresses.
+	pub fn new(validators: HashSet<Address>) -> Self {
+		SimpleList {
+			validators: validators,
+		}
+	}
+}
+
+impl ValidatorSet for SimpleList {
+	fn contains(&self, _parent_hash: &H256, _parent_number: BlockNumber, address: &Address) -> bool {
+		self.validators.contains(address)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::{Address, H256};
+	use super::super::ValidatorSet;
+	use super::SimpleList;
+
+	#[test]
+	fn contains_listed_addresses() {
+		let list = SimpleList::new(vec![Address::from(1u64), Address::from(2u64)].into_iter().collect());
+		assert!(list.contains(&H256::default(), 0, &Address::from(1u64)));
+		assert!(list.contains(&H256::from(5u64), 100, &Address::from(2u64)));
+		assert!(!list.contains(&H256::default(), 0, &Address::from(3u64)));
+	}
+}
@@ -63,6 +63,8 @@ pub enum TransactionError {
 	},
 	/// Transaction's gas limit (aka gas) is invalid.
 	InvalidGasLimit(OutOfBounds<U256>),
+	/// Sender is not permitted to send this type of transaction by the chain's permission contract.
+	NotPermitted,
 }
 
 impl fmt::Display for TransactionError {
@@ -81,6 +83,7 @@ impl fmt::Display for TransactionError {
 			GasLimitExceeded { limit, got } =>
 				format!("Gas limit exceeded. Limit={}, Given={}", limit, got),
 			InvalidGasLimit(ref err) => format!("Invalid gas limit. {}", err),
+			NotPermitted => "Sender is not permitted to send this transaction".into(),
 		};
 
 		f.write_fmt(format_args!("Transaction error ({})", msg))
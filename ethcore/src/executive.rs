@@ -41,7 +41,7 @@ pub fn contract_address(address: &Address, nonce: &U256) -> Address {
 }
 
 /// Transaction execution options.
-#[derive(Default)]
+#[derive(Default, Copy, Clone)]
 pub struct TransactOptions {
 	/// Enable call tracing.
 	pub tracing: bool,
@@ -95,6 +95,8 @@ extern crate ethash;
 extern crate ethkey;
 extern crate semver;
 extern crate ethcore_ipc_nano as nanoipc;
+#[cfg(feature="stratum")]
+extern crate ethcore_stratum;
 extern crate ethcore_devtools as devtools;
 extern crate rand;
 extern crate bit_set;
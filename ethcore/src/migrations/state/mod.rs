@@ -17,5 +17,7 @@
 //! State database migrations.
 
 mod v7;
+mod pruning;
 
-pub use self::v7::{ArchiveV7, OverlayRecentV7};
\ No newline at end of file
+pub use self::v7::{ArchiveV7, OverlayRecentV7};
+pub use self::pruning::{ToOverlayRecent, ToArchive};
\ No newline at end of file
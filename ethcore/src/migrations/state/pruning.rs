@@ -0,0 +1,489 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Migrations converting the state database between pruning algorithms.
+//!
+//! Only the state column is rewritten; all other columns are copied unchanged.
+
+use std::collections::HashSet;
+
+use util::{Bytes, H256, SHA3_NULL_RLP, SHA3_EMPTY};
+use util::kvdb::Database;
+use util::migration::{Batch, Config, Error, Migration, Progress};
+use util::nibbleslice::NibbleSlice;
+
+use rlp::{decode, encode, Rlp, RlpStream, UntrustedRlp, Stream, View, Compressible, RlpType, DecoderError};
+use views::HeaderView;
+use header::BlockNumber;
+use db;
+
+// magic numbers and constants shared by the journaldb implementations.
+// re-written here because they may change in the journaldb module.
+const LATEST_ERA_KEY: &'static [u8] = &[ b'l', b'a', b's', b't', 0, 0, 0, 0, 0, 0, 0, 0 ];
+const PADDING : [u8; 10] = [0u8; 10];
+
+// key of the client's fat DB flag in the extras column.
+// re-written here because it may change in the client module.
+const FAT_DB_KEY: &'static [u8] = b"fat_db";
+
+// the database layout version is not changed by a conversion.
+const VERSION: u32 = 10;
+
+// key of the journal record with given era and index.
+fn journal_key(era: u64, index: usize) -> Vec<u8> {
+	let mut r = RlpStream::new_list(3);
+	r.append(&era).append(&index).append(&&PADDING[..]);
+	r.out()
+}
+
+// key under which a node of the account with given address hash is stored.
+// mirrors the mangling done by `AccountDB`.
+fn mangle(address_hash: &H256, key: &H256) -> H256 {
+	let mut dst = key.clone();
+	for (k, a) in dst[12..].iter_mut().zip(&address_hash[12..]) {
+		*k ^= *a;
+	}
+	dst
+}
+
+fn nibbles_to_bytes(nibbles: &[u8]) -> Vec<u8> {
+	nibbles.chunks(2).map(|n| (n[0] << 4) | n.get(1).cloned().unwrap_or(0)).collect()
+}
+
+fn corrupt(e: DecoderError) -> Error {
+	Error::Custom(format!("Corrupt state node: {}", e))
+}
+
+fn copy_column(source: &Database, config: &Config, dest: &mut Database, col: Option<u32>, progress: &mut Progress) -> Result<(), Error> {
+	let mut batch = Batch::new(config, col);
+	for (key, value) in source.iter(col) {
+		progress.tick();
+		try!(batch.insert(key.into_vec(), value.into_vec(), dest));
+	}
+	batch.commit(dest)
+}
+
+/// Visits all nodes of a state stored with plain keys: the account trie, and storage tries
+/// and code of all accounts found in it.
+struct StateWalker<'a> {
+	source: &'a Database,
+	column: Option<u32>,
+}
+
+impl<'a> StateWalker<'a> {
+	fn has(&self, key: &H256) -> Result<bool, Error> {
+		Ok(*key == SHA3_NULL_RLP || try!(self.source.get(self.column, key).map_err(Error::Custom)).is_some())
+	}
+
+	// walk the state with given root. `visit` is called with the database key and value of every node
+	// encountered; nodes below one for which it returns false are skipped.
+	fn walk(&self, root: &H256, visit: &mut FnMut(H256, Bytes) -> Result<bool, Error>) -> Result<(), Error> {
+		if *root == SHA3_NULL_RLP {
+			return Ok(());
+		}
+		self.walk_hash(root, None, &mut Vec::new(), visit)
+	}
+
+	fn walk_hash(&self, hash: &H256, account: Option<&H256>, path: &mut Vec<u8>, visit: &mut FnMut(H256, Bytes) -> Result<bool, Error>) -> Result<(), Error> {
+		let key = account.map_or_else(|| hash.clone(), |a| mangle(a, hash));
+		let node = match try!(self.source.get(self.column, &key).map_err(Error::Custom)) {
+			Some(node) => node.to_vec(),
+			None => return Err(Error::Custom(format!("Missing state node {}", hash))),
+		};
+
+		if try!(visit(key, node.clone())) {
+			try!(self.walk_node(&node, account, path, visit));
+		}
+		Ok(())
+	}
+
+	fn walk_node(&self, node: &[u8], account: Option<&H256>, path: &mut Vec<u8>, visit: &mut FnMut(H256, Bytes) -> Result<bool, Error>) -> Result<(), Error> {
+		let rlp = UntrustedRlp::new(node);
+		match rlp.item_count() {
+			2 => {
+				let encoded_path = try!(try!(rlp.at(0).map_err(corrupt)).data().map_err(corrupt));
+				let (slice, is_leaf) = NibbleSlice::from_encoded(encoded_path);
+				let len = path.len();
+				path.extend(slice.iter());
+				let child = try!(rlp.at(1).map_err(corrupt));
+				if !is_leaf {
+					try!(self.walk_child(child, account, path, visit));
+				} else if account.is_none() {
+					try!(self.walk_account(try!(child.data().map_err(corrupt)), path, visit));
+				}
+				path.truncate(len);
+			},
+			17 => for i in 0..16 {
+				path.push(i as u8);
+				try!(self.walk_child(try!(rlp.at(i).map_err(corrupt)), account, path, visit));
+				path.pop();
+			},
+			0 if rlp.is_empty() => {},
+			_ => return Err(Error::Custom(format!("Invalid state node {:?}", node))),
+		}
+		Ok(())
+	}
+
+	fn walk_child(&self, child: UntrustedRlp, account: Option<&H256>, path: &mut Vec<u8>, visit: &mut FnMut(H256, Bytes) -> Result<bool, Error>) -> Result<(), Error> {
+		if child.is_empty() {
+			Ok(())
+		} else if child.is_data() && child.size() == 32 {
+			self.walk_hash(&try!(child.as_val().map_err(corrupt)), account, path, visit)
+		} else {
+			self.walk_node(child.as_raw(), account, path, visit)
+		}
+	}
+
+	fn walk_account(&self, account: &[u8], path: &[u8], visit: &mut FnMut(H256, Bytes) -> Result<bool, Error>) -> Result<(), Error> {
+		let rlp = UntrustedRlp::new(account);
+		let storage_root: H256 = try!(rlp.val_at(2).map_err(corrupt));
+		let code_hash: H256 = try!(rlp.val_at(3).map_err(corrupt));
+		let address_hash = H256::from_slice(&nibbles_to_bytes(path));
+
+		if storage_root != SHA3_NULL_RLP {
+			try!(self.walk_hash(&storage_root, Some(&address_hash), &mut Vec::new(), visit));
+		}
+
+		if code_hash != SHA3_EMPTY {
+			let key = mangle(&address_hash, &code_hash);
+			match try!(self.source.get(self.column, &key).map_err(Error::Custom)) {
+				Some(code) => { try!(visit(key, code.to_vec())); },
+				None => return Err(Error::Custom(format!("Missing code {}", code_hash))),
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Converts an `ArchiveDB` state into an `OverlayRecentDB` one.
+///
+/// The state of the block `history` blocks behind the best one is written out as ancient,
+/// and the nodes introduced by each later canonical block are put into that block's journal record.
+/// Nodes reachable from neither are dropped. Nodes which became unreachable within the retained
+/// window are not journalled for deletion, so they stay in the database.
+pub struct ToOverlayRecent {
+	history: u64,
+	progress: Progress,
+}
+
+impl ToOverlayRecent {
+	/// Create a new migration keeping the journal for given number of recent blocks.
+	pub fn new(history: u64) -> Self {
+		ToOverlayRecent {
+			history: history,
+			progress: Progress::default(),
+		}
+	}
+
+	// canonical (number, hash, state root) of the blocks within the history window, oldest first.
+	fn recent_blocks(&self, source: &Database) -> Result<Vec<(BlockNumber, H256, H256)>, Error> {
+		let mut hash = match try!(source.get(db::COL_EXTRA, b"best").map_err(Error::Custom)) {
+			Some(best) => H256::from_slice(&best),
+			None => return Ok(Vec::new()),
+		};
+
+		let mut blocks = Vec::new();
+		loop {
+			let header = match try!(source.get(db::COL_HEADERS, &hash).map_err(Error::Custom)) {
+				Some(header) => UntrustedRlp::new(&header).decompress(RlpType::Blocks).to_vec(),
+				None => break,
+			};
+			let view = HeaderView::new(&header);
+			let number = view.number();
+			blocks.push((number, hash, view.state_root()));
+
+			let best = blocks[0].0;
+			if number == 0 || number + self.history <= best {
+				break;
+			}
+			hash = view.parent_hash();
+		}
+		blocks.reverse();
+		Ok(blocks)
+	}
+
+	fn migrate_state(&mut self, source: &Database, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+		let walker = StateWalker { source: source, column: col };
+		let mut blocks = try!(self.recent_blocks(source));
+
+		// the oldest block in the window whose state is still there becomes ancient.
+		let mut ancient = None;
+		for (i, &(number, _, ref root)) in blocks.iter().enumerate() {
+			if try!(walker.has(root)) {
+				ancient = Some((i, number, root.clone()));
+				break;
+			}
+		}
+		let (era, root) = match ancient {
+			Some((i, number, root)) => {
+				blocks.drain(..i + 1);
+				(number, root)
+			},
+			None => return Err(Error::MigrationImpossible),
+		};
+
+		let mut batch = Batch::new(config, col);
+		{
+			let progress = &mut self.progress;
+			let mut visit = |key: H256, value: Bytes| -> Result<bool, Error> {
+				progress.tick();
+				try!(batch.insert(key.to_vec(), value, dest));
+				Ok(true)
+			};
+			try!(walker.walk(&root, &mut visit));
+		}
+		try!(batch.commit(dest));
+
+		let mut latest_era = era;
+		let mut journalled = HashSet::new();
+		for (number, hash, root) in blocks {
+			let mut insertions: Vec<(H256, Bytes)> = Vec::new();
+			{
+				let progress = &mut self.progress;
+				let dest = &*dest;
+				let mut visit = |key: H256, value: Bytes| -> Result<bool, Error> {
+					if journalled.contains(&key) || try!(dest.get(col, &key).map_err(Error::Custom)).is_some() {
+						return Ok(false);
+					}
+					progress.tick();
+					journalled.insert(key.clone());
+					insertions.push((key, value));
+					Ok(true)
+				};
+				try!(walker.walk(&root, &mut visit));
+			}
+
+			let mut r = RlpStream::new_list(3);
+			r.append(&hash);
+			r.begin_list(insertions.len());
+			for (k, v) in insertions {
+				r.begin_list(2).append(&k).append(&v);
+			}
+			r.begin_list(0);
+			try!(batch.insert(journal_key(number, 0), r.out(), dest));
+			latest_era = number;
+		}
+
+		try!(batch.insert(LATEST_ERA_KEY.to_vec(), encode(&latest_era).to_vec(), dest));
+		batch.commit(dest)
+	}
+}
+
+impl Migration for ToOverlayRecent {
+
+	fn pre_columns(&self) -> Option<u32> { db::NUM_COLUMNS }
+
+	fn columns(&self) -> Option<u32> { db::NUM_COLUMNS }
+
+	fn version(&self) -> u32 { VERSION }
+
+	fn migrate(&mut self, source: &Database, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+		if col == db::COL_STATE {
+			return self.migrate_state(source, config, dest, col);
+		}
+
+		try!(copy_column(source, config, dest, col, &mut self.progress));
+		if col == db::COL_EXTRA {
+			// only trie nodes are carried over, so the preimages of a fat DB are gone.
+			let mut batch = Batch::new(config, col);
+			try!(batch.insert(FAT_DB_KEY.to_vec(), vec![0], dest));
+			try!(batch.commit(dest));
+		}
+		Ok(())
+	}
+}
+
+/// Converts an `OverlayRecentDB` state into an `ArchiveDB` one.
+///
+/// All nodes on disk are kept and the insertions of every journal record are written out,
+/// including those of non-canonical blocks. Nothing is pruned from then on.
+#[derive(Default)]
+pub struct ToArchive {
+	progress: Progress,
+}
+
+impl ToArchive {
+	fn migrate_state(&mut self, source: &Database, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+		let mut batch = Batch::new(config, col);
+
+		// copy the nodes and the latest era, leaving the journal behind.
+		for (key, value) in source.iter(col) {
+			if key.len() == 32 || &*key == LATEST_ERA_KEY {
+				self.progress.tick();
+				try!(batch.insert(key.into_vec(), value.into_vec(), dest));
+			}
+		}
+
+		// write out the nodes still in the journal.
+		if let Some(val) = try!(source.get(col, LATEST_ERA_KEY).map_err(Error::Custom)) {
+			let mut era = decode::<u64>(&val);
+			loop {
+				let mut index = 0usize;
+				while let Some(journal_raw) = try!(source.get(col, &journal_key(era, index)).map_err(Error::Custom)) {
+					let rlp = Rlp::new(&journal_raw);
+					for r in rlp.at(1).iter() {
+						self.progress.tick();
+						let key: H256 = r.val_at(0);
+						try!(batch.insert(key.to_vec(), r.val_at(1), dest));
+					}
+					index += 1;
+				}
+
+				if index == 0 || era == 0 {
+					break;
+				}
+				era -= 1;
+			}
+		}
+		batch.commit(dest)
+	}
+}
+
+impl Migration for ToArchive {
+
+	fn pre_columns(&self) -> Option<u32> { db::NUM_COLUMNS }
+
+	fn columns(&self) -> Option<u32> { db::NUM_COLUMNS }
+
+	fn version(&self) -> u32 { VERSION }
+
+	fn migrate(&mut self, source: &Database, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+		match col == db::COL_STATE {
+			true => self.migrate_state(source, config, dest, col),
+			false => copy_column(source, config, dest, col, &mut self.progress),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashSet;
+	use devtools::RandomTempPath;
+	use util::{Address, H256, U256, MemoryDB, HashDB, Hashable, SHA3_NULL_RLP, SHA3_EMPTY};
+	use util::kvdb::{Database, DatabaseConfig, DBTransaction};
+	use util::migration::{Config, Migration};
+	use util::trie::{SecTrieDBMut, TrieMut};
+	use rlp::{decode, Rlp, RlpStream, UntrustedRlp, Stream, View, Compressible, RlpType};
+	use basic_types::Seal;
+	use header::Header;
+	use db;
+	use super::{ToOverlayRecent, ToArchive, journal_key, LATEST_ERA_KEY, FAT_DB_KEY};
+
+	fn state(accounts: &[(u64, u64)]) -> (H256, HashSet<H256>, MemoryDB) {
+		let mut memdb = MemoryDB::new();
+		let mut root = H256::new();
+		{
+			let mut trie = SecTrieDBMut::new(&mut memdb, &mut root);
+			for &(address, balance) in accounts {
+				let mut account = RlpStream::new_list(4);
+				account.append(&U256::zero()).append(&U256::from(balance)).append(&SHA3_NULL_RLP).append(&SHA3_EMPTY);
+				trie.insert(&Address::from(address), &account.out()).unwrap();
+			}
+		}
+		let keys = memdb.keys().into_iter().map(|(k, _)| k).collect();
+		(root, keys, memdb)
+	}
+
+	fn open(path: &RandomTempPath) -> Database {
+		Database::open(&DatabaseConfig::with_columns(db::NUM_COLUMNS), path.as_str()).unwrap()
+	}
+
+	fn migrate<M: Migration>(mut migration: M, source: &Database, dest: &mut Database) {
+		for col in 0..db::NUM_COLUMNS.unwrap() {
+			migration.migrate(source, &Config::default(), dest, Some(col)).unwrap();
+		}
+	}
+
+	#[test]
+	fn converts_between_archive_and_overlay_recent() {
+		let states = vec![state(&[(1, 1)]), state(&[(1, 1), (2, 2)]), state(&[(1, 3), (2, 2)])];
+
+		// an archive database with three blocks.
+		let archive_path = RandomTempPath::new();
+		let archive = open(&archive_path);
+		let mut batch = DBTransaction::new(&archive);
+		let mut parent = H256::new();
+		for (number, &(ref root, _, ref memdb)) in states.iter().enumerate() {
+			for key in memdb.keys().keys() {
+				batch.put(db::COL_STATE, key, &memdb.get(key).unwrap());
+			}
+			let mut header = Header::new();
+			header.set_number(number as u64);
+			header.set_parent_hash(parent);
+			header.set_state_root(root.clone());
+			parent = header.hash();
+			batch.put(db::COL_HEADERS, &parent, &UntrustedRlp::new(&header.rlp(Seal::With)).compress(RlpType::Blocks));
+		}
+		batch.put(db::COL_EXTRA, b"best", &parent);
+		batch.put(db::COL_EXTRA, FAT_DB_KEY, &[1]);
+		batch.put(db::COL_STATE, LATEST_ERA_KEY, &::rlp::encode(&2u64));
+		archive.write(batch).unwrap();
+
+		// keep one block of history.
+		let overlay_path = RandomTempPath::new();
+		let mut overlay = open(&overlay_path);
+		migrate(ToOverlayRecent::new(1), &archive, &mut overlay);
+
+		let ancient = &states[1].1;
+		for key in ancient {
+			assert!(overlay.get(db::COL_STATE, key).unwrap().is_some());
+		}
+		assert!(overlay.get(db::COL_STATE, &states[0].0).unwrap().is_none());
+		assert_eq!(decode::<u64>(&overlay.get(db::COL_STATE, LATEST_ERA_KEY).unwrap().unwrap()), 2);
+
+		let journal = overlay.get(db::COL_STATE, &journal_key(2, 0)).unwrap().unwrap();
+		let journal = Rlp::new(&journal);
+		let inserted: HashSet<H256> = journal.at(1).iter().map(|r| r.val_at(0)).collect();
+		assert_eq!(journal.val_at::<H256>(0), parent);
+		assert_eq!(inserted, states[2].1.difference(ancient).cloned().collect());
+		assert!(overlay.get(db::COL_HEADERS, &parent).unwrap().is_some());
+		assert_eq!(overlay.get(db::COL_EXTRA, FAT_DB_KEY).unwrap().unwrap().to_vec(), vec![0u8]);
+
+		// and back, keeping everything.
+		let back_path = RandomTempPath::new();
+		let mut back = open(&back_path);
+		migrate(ToArchive::default(), &overlay, &mut back);
+
+		for key in states[1].1.iter().chain(states[2].1.iter()) {
+			let value = back.get(db::COL_STATE, key).unwrap().unwrap();
+			assert_eq!(&value.sha3(), key);
+		}
+		assert!(back.get(db::COL_STATE, &journal_key(2, 0)).unwrap().is_none());
+		assert!(back.get(db::COL_STATE, LATEST_ERA_KEY).unwrap().is_some());
+	}
+
+	#[test]
+	fn fails_on_corrupt_state_node() {
+		let node = vec![0xc3, 0x82, 0x01];
+		let root = node.sha3();
+
+		let archive_path = RandomTempPath::new();
+		let archive = open(&archive_path);
+		let mut batch = DBTransaction::new(&archive);
+		batch.put(db::COL_STATE, &root, &node);
+		let mut header = Header::new();
+		header.set_state_root(root);
+		let hash = header.hash();
+		batch.put(db::COL_HEADERS, &hash, &UntrustedRlp::new(&header.rlp(Seal::With)).compress(RlpType::Blocks));
+		batch.put(db::COL_EXTRA, b"best", &hash);
+		archive.write(batch).unwrap();
+
+		let overlay_path = RandomTempPath::new();
+		let mut overlay = open(&overlay_path);
+		let result = ToOverlayRecent::new(1).migrate(&archive, &Config::default(), &mut overlay, db::COL_STATE);
+		assert!(result.is_err());
+	}
+}
@@ -221,6 +221,8 @@ impl OverlayRecentV7 {
 
 impl Migration for OverlayRecentV7 {
 
+	fn pre_columns(&self) -> Option<u32> { None }
+
 	fn columns(&self) -> Option<u32> { None }
 
 	fn version(&self) -> u32 { 7 }
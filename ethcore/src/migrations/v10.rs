@@ -0,0 +1,87 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This migration adds a column for blocks rejected during import.
+
+use util::kvdb::Database;
+use util::migration::{Batch, Config, Error, Migration, Progress};
+
+/// Adds the bad blocks column, copying all existing columns unchanged.
+#[derive(Default)]
+pub struct ToV10 {
+	progress: Progress,
+}
+
+impl Migration for ToV10 {
+	fn pre_columns(&self) -> Option<u32> { Some(5) }
+
+	fn columns(&self) -> Option<u32> { Some(6) }
+
+	fn version(&self) -> u32 { 10 }
+
+	fn migrate(&mut self, source: &Database, config: &Config, dest: &mut Database, col: Option<u32>) -> Result<(), Error> {
+		let mut batch = Batch::new(config, col);
+
+		for (key, value) in source.iter(col) {
+			self.progress.tick();
+			try!(batch.insert(key.to_vec(), value.to_vec(), dest));
+		}
+
+		batch.commit(dest)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ToV10;
+	use db;
+	use devtools::RandomTempPath;
+	use util::kvdb::{Database, DatabaseConfig};
+	use util::migration::{Config, Manager, Migration};
+
+	#[test]
+	fn adds_one_column() {
+		let migration = ToV10::default();
+		assert_eq!(migration.pre_columns(), Some(5));
+		assert_eq!(migration.columns(), db::NUM_COLUMNS);
+	}
+
+	#[test]
+	fn migrates_v9_database() {
+		let dir = RandomTempPath::create_dir();
+		let mut path = dir.as_path().to_owned();
+		path.push("db");
+
+		{
+			let v9 = Database::open(&DatabaseConfig::with_columns(Some(5)), path.to_str().unwrap()).unwrap();
+			let mut batch = v9.transaction();
+			for col in 0..5 {
+				batch.put(Some(col), b"key", &[col as u8]);
+			}
+			v9.write(batch).unwrap();
+		}
+
+		let mut manager = Manager::new(Config::default());
+		manager.add_migration(ToV10::default()).unwrap();
+		let migrated = manager.execute(&path, 9).unwrap();
+
+		let v10 = Database::open(&DatabaseConfig::with_columns(db::NUM_COLUMNS), migrated.to_str().unwrap()).unwrap();
+		for col in 0..5 {
+			assert_eq!(&*v10.get(Some(col), b"key").unwrap().unwrap(), &[col as u8]);
+		}
+		assert!(v10.get(db::COL_BAD_BLOCKS, b"key").unwrap().is_none());
+	}
+}
@@ -51,6 +51,8 @@ impl ToV9 {
 
 impl Migration for ToV9 {
 
+	fn pre_columns(&self) -> Option<u32> { None }
+
 	fn columns(&self) -> Option<u32> { Some(5) }
 
 	fn version(&self) -> u32 { 9 }
@@ -0,0 +1,148 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas price oracle based on recent block inclusion.
+//!
+//! Suggestions are derived from the lowest gas price that made it into each of
+//! the last few blocks and are raised when the pending queue holds more gas than
+//! fits in the next block.
+
+use util::{U256, Uint};
+
+/// Gas price suggestions produced by the oracle.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GasPriceSuggestions {
+	/// Price which was enough to be included in a quarter of recent blocks.
+	pub safe: U256,
+	/// Price which was enough to be included in half of recent blocks.
+	pub standard: U256,
+	/// Price expected to be included in the next block.
+	pub fast: U256,
+}
+
+impl GasPriceSuggestions {
+	/// Suggestions with the same price for every speed.
+	pub fn uniform(price: U256) -> Self {
+		GasPriceSuggestions {
+			safe: price,
+			standard: price,
+			fast: price,
+		}
+	}
+}
+
+fn saturating_add(a: U256, b: U256) -> U256 {
+	match a.overflowing_add(b) {
+		(_, true) => !U256::zero(),
+		(sum, false) => sum,
+	}
+}
+
+fn percentile(sorted: &[U256], percent: usize) -> U256 {
+	sorted[(sorted.len() - 1) * percent / 100]
+}
+
+/// Price of the transaction which no longer fits into a block of `gas_limit`
+/// when `pending` (pairs of gas price and gas, in priority order) are packed into it.
+/// Returns `None` if the whole queue fits into a single block.
+fn clearing_price(pending: &[(U256, U256)], gas_limit: &U256) -> Option<U256> {
+	let mut gas_used = U256::zero();
+	for &(ref gas_price, ref gas) in pending {
+		gas_used = saturating_add(gas_used, *gas);
+		if gas_used > *gas_limit {
+			return Some(*gas_price);
+		}
+	}
+	None
+}
+
+/// Computes gas price suggestions.
+///
+/// `lowest_prices` are the lowest gas prices included in each of the recent non-empty blocks,
+/// `pending` are gas price and gas of queued transactions in priority order and `gas_limit`
+/// is the gas limit of the next block. `fallback` is used when there is no history to sample.
+pub fn suggest(mut lowest_prices: Vec<U256>, pending: &[(U256, U256)], gas_limit: &U256, fallback: U256) -> GasPriceSuggestions {
+	let mut suggestions = if lowest_prices.is_empty() {
+		GasPriceSuggestions::uniform(fallback)
+	} else {
+		lowest_prices.sort();
+		GasPriceSuggestions {
+			safe: percentile(&lowest_prices, 25),
+			standard: percentile(&lowest_prices, 50),
+			fast: percentile(&lowest_prices, 90),
+		}
+	};
+
+	// the queue would not fit into the next block; outbid the transactions left behind.
+	if let Some(price) = clearing_price(pending, gas_limit) {
+		let price = saturating_add(price, U256::one());
+		suggestions.standard = ::std::cmp::max(suggestions.standard, price);
+		suggestions.fast = ::std::cmp::max(suggestions.fast, saturating_add(price, price / 10.into()));
+	}
+	suggestions.fast = ::std::cmp::max(suggestions.fast, suggestions.standard);
+	suggestions
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use util::U256;
+
+	fn prices(p: &[u64]) -> Vec<U256> {
+		p.iter().map(|p| U256::from(*p)).collect()
+	}
+
+	#[test]
+	fn should_fallback_without_history() {
+		let suggestions = suggest(vec![], &[], &U256::from(1_000_000), U256::from(20));
+		assert_eq!(suggestions, GasPriceSuggestions::uniform(U256::from(20)));
+	}
+
+	#[test]
+	fn should_suggest_percentiles_of_lowest_included_prices() {
+		let lowest = prices(&[50, 10, 40, 20, 30, 60, 70, 80, 90]);
+		let suggestions = suggest(lowest, &[], &U256::from(1_000_000), U256::from(1));
+		assert_eq!(suggestions, GasPriceSuggestions {
+			safe: U256::from(30),
+			standard: U256::from(50),
+			fast: U256::from(80),
+		});
+	}
+
+	#[test]
+	fn should_raise_suggestions_when_queue_exceeds_block() {
+		let lowest = prices(&[10, 10, 10]);
+		let pending = [
+			(U256::from(200), U256::from(60_000)),
+			(U256::from(100), U256::from(60_000)),
+			(U256::from(50), U256::from(60_000)),
+		];
+		let suggestions = suggest(lowest, &pending, &U256::from(100_000), U256::from(1));
+		assert_eq!(suggestions, GasPriceSuggestions {
+			safe: U256::from(10),
+			standard: U256::from(101),
+			fast: U256::from(111),
+		});
+	}
+
+	#[test]
+	fn should_ignore_queue_which_fits_into_block() {
+		let lowest = prices(&[10, 10, 10]);
+		let pending = [(U256::from(200), U256::from(60_000))];
+		let suggestions = suggest(lowest, &pending, &U256::from(100_000), U256::from(1));
+		assert_eq!(suggestions, GasPriceSuggestions::uniform(U256::from(10)));
+	}
+}
@@ -22,7 +22,7 @@ use util::using_queue::{UsingQueue, GetAction};
 use account_provider::AccountProvider;
 use views::{BlockView, HeaderView};
 use state::State;
-use client::{MiningBlockChainClient, Executive, Executed, EnvInfo, TransactOptions, BlockID, CallAnalytics};
+use client::{MiningBlockChainClient, Executive, Executed, EnvInfo, TransactOptions, BlockID, CallAnalytics, StateOverride, SenderCache};
 use executive::contract_address;
 use block::{ClosedBlock, SealedBlock, IsBlock, Block};
 use error::*;
@@ -30,10 +30,11 @@ use transaction::{Action, SignedTransaction};
 use receipt::{Receipt, RichReceipt};
 use spec::Spec;
 use engines::Engine;
-use miner::{MinerService, MinerStatus, TransactionQueue, AccountDetails, TransactionOrigin};
-use miner::work_notify::WorkPoster;
+use miner::{MinerService, MinerStatus, TransactionQueue, AccountDetails, TransactionOrigin, QueuedTransaction, NonceGap};
+use miner::work_notify::{WorkPoster, NotifyWork};
 use client::TransactionImportResult;
 use miner::price_info::PriceInfo;
+use miner::gas_oracle::{self, GasPriceSuggestions};
 use header::BlockNumber;
 
 /// Different possible definitions for pending transaction set.
@@ -71,6 +72,8 @@ pub struct MinerOptions {
 	pub work_queue_size: usize,
 	/// Can we submit two different solutions for the same block and expect both to result in an import?
 	pub enable_resubmission: bool,
+	/// Number of recent blocks sampled by the gas price oracle.
+	pub gas_price_oracle_blocks: usize,
 }
 
 impl Default for MinerOptions {
@@ -86,6 +89,7 @@ impl Default for MinerOptions {
 			reseal_min_period: Duration::from_secs(2),
 			work_queue_size: 20,
 			enable_resubmission: true,
+			gas_price_oracle_blocks: 20,
 		}
 	}
 }
@@ -136,6 +140,8 @@ pub enum GasPricer {
 	Fixed(U256),
 	/// Gas price is calibrated according to a fixed amount of USD.
 	Calibrated(GasPriceCalibrator),
+	/// Gas price follows the oracle's safe suggestion, but never drops below the given floor.
+	Oracle(U256),
 }
 
 impl GasPricer {
@@ -147,6 +153,11 @@ impl GasPricer {
 		})
 	}
 
+	/// Create a new `GasPricer` following the gas price oracle.
+	pub fn new_oracle(floor: U256) -> GasPricer {
+		GasPricer::Oracle(floor)
+	}
+
 	/// Create a new Fixed `GasPricer`.
 	pub fn new_fixed(gas_price: U256) -> GasPricer {
 		GasPricer::Fixed(gas_price)
@@ -156,6 +167,8 @@ impl GasPricer {
 		match *self {
 			GasPricer::Fixed(ref max) => set_price(max.clone()),
 			GasPricer::Calibrated(ref mut cal) => cal.recalibrate(set_price),
+			// requires chain access; handled by `Miner::recalibrate_from_oracle`.
+			GasPricer::Oracle(_) => {},
 		}
 	}
 }
@@ -183,17 +196,18 @@ pub struct Miner {
 	engine: Arc<Engine>,
 
 	accounts: Option<Arc<AccountProvider>>,
-	work_poster: Option<WorkPoster>,
+	notifiers: RwLock<Vec<Box<NotifyWork>>>,
 	gas_pricer: Mutex<GasPricer>,
+	sender_cache: Arc<SenderCache>,
 }
 
 impl Miner {
 	/// Creates new instance of miner.
 	fn new_raw(options: MinerOptions, gas_pricer: GasPricer, spec: &Spec, accounts: Option<Arc<AccountProvider>>) -> Miner {
-		let work_poster = match options.new_work_notify.is_empty() {
-			true => None,
-			false => Some(WorkPoster::new(&options.new_work_notify))
-		};
+		let mut notifiers: Vec<Box<NotifyWork>> = Vec::new();
+		if !options.new_work_notify.is_empty() {
+			notifiers.push(Box::new(WorkPoster::new(&options.new_work_notify)));
+		}
 		let txq = Arc::new(Mutex::new(TransactionQueue::with_limits(options.tx_queue_size, options.tx_gas_limit)));
 		Miner {
 			transaction_queue: txq,
@@ -212,11 +226,17 @@ impl Miner {
 			options: options,
 			accounts: accounts,
 			engine: spec.engine.clone(),
-			work_poster: work_poster,
+			notifiers: RwLock::new(notifiers),
 			gas_pricer: Mutex::new(gas_pricer),
+			sender_cache: Default::default(),
 		}
 	}
 
+	/// Cache of recovered transaction senders, to be shared with block verification.
+	pub fn sender_cache(&self) -> Arc<SenderCache> {
+		self.sender_cache.clone()
+	}
+
 	/// Creates new instance of miner without accounts, but with given spec.
 	pub fn with_spec(spec: &Spec) -> Miner {
 		Miner::new_raw(Default::default(), GasPricer::new_fixed(20_000_000_000u64.into()), spec, None)
@@ -227,8 +247,14 @@ impl Miner {
 		Arc::new(Miner::new_raw(options, gas_pricer, spec, accounts))
 	}
 
+	/// Adds a listener that is notified about every new work package; enables sealing.
+	pub fn add_work_listener(&self, notifier: Box<NotifyWork>) {
+		self.notifiers.write().push(notifier);
+		self.sealing_work.lock().enabled = true;
+	}
+
 	fn forced_sealing(&self) -> bool {
-		self.options.force_sealing || !self.options.new_work_notify.is_empty()
+		self.options.force_sealing || !self.notifiers.read().is_empty()
 	}
 
 	/// Clear all pending block states
@@ -246,6 +272,28 @@ impl Miner {
 		self.sealing_work.lock().queue.peek_last_ref().map(|b| b.base().clone())
 	}
 
+	/// Update minimal gas price from the oracle's safe suggestion if the oracle is the gas price source.
+	/// Without recent transactions to sample the minimum returns to the floor.
+	fn recalibrate_from_oracle(&self, chain: &MiningBlockChainClient) {
+		let floor = match *self.gas_pricer.lock() {
+			GasPricer::Oracle(ref floor) => floor.clone(),
+			_ => return,
+		};
+		let price = cmp::max(floor, self.oracle_suggestions(chain, floor).safe);
+		trace!(target: "miner", "prepare_block: oracle gas price {}", price);
+		self.transaction_queue.lock().set_minimal_gas_price(price);
+	}
+
+	/// Ask the gas price oracle, using `fallback` when no recent block has transactions.
+	fn oracle_suggestions(&self, chain: &MiningBlockChainClient, fallback: U256) -> GasPriceSuggestions {
+		let pending = self.transaction_queue.lock().top_transactions()
+			.into_iter()
+			.map(|t| (t.gas_price, t.gas))
+			.collect::<Vec<_>>();
+		let gas_limit = HeaderView::new(&chain.best_block_header()).gas_limit();
+		gas_oracle::suggest(chain.lowest_gas_prices(self.options.gas_price_oracle_blocks), &pending, &gas_limit, fallback)
+	}
+
 	#[cfg_attr(feature="dev", allow(match_same_arms))]
 	/// Prepares new block for sealing including top transactions from queue.
 	fn prepare_block(&self, chain: &MiningBlockChainClient) -> (ClosedBlock, Option<H256>) {
@@ -258,6 +306,7 @@ impl Miner {
 			});
 			trace!(target: "miner", "prepare_block: done recalibration.");
 		}
+		self.recalibrate_from_oracle(chain);
 
 		let (transactions, mut open_block, original_work_hash) = {
 			let transactions = {self.transaction_queue.lock().top_transactions()};
@@ -293,10 +342,14 @@ impl Miner {
 
 		let mut invalid_transactions = HashSet::new();
 		let block_number = open_block.block().fields().header.number();
+		let call_contract = |address: Address, data: Bytes| chain.call_contract(BlockID::Latest, address, data);
 		// TODO: push new uncles, too.
 		for tx in transactions {
 			let hash = tx.hash();
-			match open_block.push_transaction(tx, None) {
+			// permissions might have been revoked since the transaction was queued.
+			let result = self.engine.verify_transaction_permission(&tx, &call_contract)
+				.and_then(|_| open_block.push_transaction(tx, None).map(|_| ()));
+			match result {
 				Err(Error::Execution(ExecutionError::BlockGasLimitReached { gas_limit, gas_used, gas })) => {
 					debug!(target: "miner", "Skipping adding transaction to block because of gas limit: {:?} (limit: {:?}, used: {:?}, gas: {:?})", hash, gas_limit, gas_used, gas);
 					// Exit early if gas left is smaller then min_tx_gas
@@ -338,6 +391,23 @@ impl Miner {
 		(block, original_work_hash)
 	}
 
+	/// Enables or disables internal sealing depending on whether the author is able to seal
+	/// on top of the current best block, since the engine's sealers may change with every block.
+	fn update_internal_sealing_status(&self) {
+		if !self.seals_internally {
+			return;
+		}
+		let can_seal = self.engine.is_sealer(&*self.author.read()).unwrap_or(false);
+		let mut sealing_work = self.sealing_work.lock();
+		if sealing_work.enabled != can_seal {
+			trace!(target: "miner", "update_internal_sealing_status: author can seal={}", can_seal);
+			sealing_work.enabled = can_seal;
+			if !can_seal {
+				sealing_work.queue.reset();
+			}
+		}
+	}
+
 	/// Check is reseal is allowed and necessary.
 	fn requires_reseal(&self, best_block: BlockNumber) -> bool {
 		let has_local_transactions = self.transaction_queue.lock().has_local_pending_transactions();
@@ -414,7 +484,7 @@ impl Miner {
 				let is_new = original_work_hash.map_or(true, |h| block.block().fields().header.hash() != h);
 				sealing_work.queue.push(block);
 				// If push notifications are enabled we assume all work items are used.
-				if self.work_poster.is_some() && is_new {
+				if !self.notifiers.read().is_empty() && is_new {
 					sealing_work.queue.use_last_ref();
 				}
 				(Some((pow_hash, difficulty, number)), is_new)
@@ -425,7 +495,11 @@ impl Miner {
 			(work, is_new)
 		};
 		if is_new {
-			work.map(|(pow_hash, difficulty, number)| self.work_poster.as_ref().map(|p| p.notify(pow_hash, difficulty, number)));
+			work.map(|(pow_hash, difficulty, number)| {
+				for notifier in self.notifiers.read().iter() {
+					notifier.notify(pow_hash, difficulty, number)
+				}
+			});
 		}
 	}
 
@@ -438,13 +512,15 @@ impl Miner {
 	/// Returns true if we had to prepare new pending block.
 	fn prepare_work_sealing(&self, chain: &MiningBlockChainClient) -> bool {
 		trace!(target: "miner", "prepare_work_sealing: entering");
+		// the author may have left the engine's set of sealers since it was set.
+		let can_seal = !self.seals_internally || self.engine.is_sealer(&*self.author.read()).unwrap_or(false);
 		let prepare_new = {
 			let mut sealing_work = self.sealing_work.lock();
 			let have_work = sealing_work.queue.peek_last_ref().is_some();
-			trace!(target: "miner", "prepare_work_sealing: have_work={}", have_work);
+			trace!(target: "miner", "prepare_work_sealing: have_work={}, can_seal={}", have_work, can_seal);
 			if !have_work {
-				sealing_work.enabled = true;
-				true
+				sealing_work.enabled = can_seal;
+				can_seal
 			} else {
 				false
 			}
@@ -475,9 +551,14 @@ impl Miner {
 			nonce: chain.latest_nonce(a),
 			balance: chain.latest_balance(a),
 		};
+		let call_contract = |address: Address, data: Bytes| chain.call_contract(BlockID::Latest, address, data);
 
 		transactions.into_iter()
-			.map(|tx| transaction_queue.add(tx, &fetch_account, origin))
+			.map(|tx| {
+				// transactions the sender isn't permitted to send never enter the queue, so are never relayed.
+				try!(self.engine.verify_transaction_permission(&tx, &call_contract));
+				transaction_queue.add(tx, &fetch_account, origin)
+			})
 			.collect()
 	}
 
@@ -508,7 +589,7 @@ impl MinerService for Miner {
 		}
 	}
 
-	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError> {
+	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics, state_override: &StateOverride) -> Result<Executed, CallError> {
 		let sealing_work = self.sealing_work.lock();
 		match sealing_work.queue.peek_last_ref() {
 			Some(work) => {
@@ -528,6 +609,7 @@ impl MinerService for Miner {
 				};
 				// that's just a copy of the state.
 				let mut state = block.state().clone();
+				state.apply_override(state_override);
 				let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
 
 				let sender = try!(t.sender().map_err(|e| {
@@ -549,7 +631,7 @@ impl MinerService for Miner {
 				Ok(ret)
 			},
 			None => {
-				chain.call(t, BlockID::Latest, analytics)
+				chain.call(t, BlockID::Latest, analytics, state_override)
 			}
 		}
 	}
@@ -614,6 +696,10 @@ impl MinerService for Miner {
 		*self.transaction_queue.lock().minimal_gas_price() * 110.into() / 100.into()
 	}
 
+	fn gas_price_suggestions(&self, chain: &MiningBlockChainClient) -> GasPriceSuggestions {
+		self.oracle_suggestions(chain, self.sensible_gas_price())
+	}
+
 	fn sensible_gas_limit(&self) -> U256 {
 		self.gas_range_target.read().0 / 5.into()
 	}
@@ -653,9 +739,11 @@ impl MinerService for Miner {
 	fn import_external_transactions(
 		&self,
 		chain: &MiningBlockChainClient,
-		transactions: Vec<SignedTransaction>
+		mut transactions: Vec<SignedTransaction>
 	) -> Vec<Result<TransactionImportResult, Error>> {
 
+		// recover senders before locking the queue.
+		self.sender_cache.recover_all_in_parallel(&mut transactions);
 		let results = {
 			let mut transaction_queue = self.transaction_queue.lock();
 			self.add_transactions_to_queue(
@@ -682,6 +770,8 @@ impl MinerService for Miner {
 
 		let hash = transaction.hash();
 		trace!(target: "own_tx", "Importing transaction: {:?}", transaction);
+		// recover the sender before locking the queue; failures are reported by the queue.
+		let _ = self.sender_cache.recover(&transaction);
 
 		let imported = {
 			// Be sure to release the lock before we call prepare_work_sealing
@@ -727,6 +817,32 @@ impl MinerService for Miner {
 		queue.top_transactions()
 	}
 
+	fn queued_transactions(&self) -> (Vec<QueuedTransaction>, Vec<QueuedTransaction>) {
+		let queue = self.transaction_queue.lock();
+		(queue.current_details(), queue.future_details())
+	}
+
+	fn queue_nonce_gaps(&self) -> BTreeMap<Address, NonceGap> {
+		self.transaction_queue.lock().nonce_gaps()
+	}
+
+	fn remove_transaction(&self, chain: &MiningBlockChainClient, hash: &H256) -> Option<SignedTransaction> {
+		let mut queue = self.transaction_queue.lock();
+		let tx = queue.find(hash);
+		if tx.is_some() {
+			let fetch_account = |a: &Address| AccountDetails {
+				nonce: chain.latest_nonce(a),
+				balance: chain.latest_balance(a),
+			};
+			queue.remove_invalid(hash, &fetch_account);
+		}
+		tx
+	}
+
+	fn remove_sender_transactions(&self, sender: &Address) -> usize {
+		self.transaction_queue.lock().remove_sender(sender)
+	}
+
 	fn pending_transactions(&self) -> Vec<SignedTransaction> {
 		let queue = self.transaction_queue.lock();
 		let sw = self.sealing_work.lock();
@@ -822,6 +938,7 @@ impl MinerService for Miner {
 	fn update_sealing(&self, chain: &MiningBlockChainClient) {
 		trace!(target: "miner", "update_sealing");
 
+		self.update_internal_sealing_status();
 		if self.requires_reseal(chain.chain_info().best_block_number) {
 			// --------------------------------------------------------------------------
 			// | NOTE Code below requires transaction_queue and sealing_work locks.     |
@@ -902,7 +1019,8 @@ impl MinerService for Miner {
 			let out_of_chain = retracted
 				.par_iter()
 				.map(|h| fetch_transactions(chain, h));
-			out_of_chain.for_each(|txs| {
+			out_of_chain.for_each(|mut txs| {
+				self.sender_cache.recover_all(&mut txs);
 				let mut transaction_queue = self.transaction_queue.lock();
 				let _ = self.add_transactions_to_queue(
 					chain, txs, TransactionOrigin::External, &mut transaction_queue
@@ -917,6 +1035,7 @@ impl MinerService for Miner {
 				.map(|h: &H256| fetch_transactions(chain, h));
 
 			in_chain.for_each(|mut txs| {
+				self.sender_cache.recover_all(&mut txs);
 				let mut transaction_queue = self.transaction_queue.lock();
 
 				let to_remove = txs.drain(..)
@@ -953,7 +1072,10 @@ mod tests {
 	use types::transaction::{Transaction, SignedTransaction, Action};
 	use block::*;
 	use spec::Spec;
-	use tests::helpers::{generate_dummy_client};
+	use tests::helpers::{generate_dummy_client, get_temp_journal_db};
+	use client::{Client, ClientConfig};
+	use devtools::RandomTempPath;
+	use io::IoChannel;
 
 	#[test]
 	fn should_prepare_block_to_seal() {
@@ -988,6 +1110,10 @@ mod tests {
 	}
 
 	fn miner() -> Miner {
+		miner_with_pricer(GasPricer::new_fixed(0u64.into()))
+	}
+
+	fn miner_with_pricer(gas_pricer: GasPricer) -> Miner {
 		Arc::try_unwrap(Miner::new(
 			MinerOptions {
 				new_work_notify: Vec::new(),
@@ -1000,8 +1126,9 @@ mod tests {
 				pending_set: PendingSet::AlwaysSealing,
 				work_queue_size: 5,
 				enable_resubmission: true,
+				gas_price_oracle_blocks: 20,
 			},
-			GasPricer::new_fixed(0u64.into()),
+			gas_pricer,
 			&Spec::new_test(),
 			None, // accounts provider
 		)).ok().expect("Miner was just created.")
@@ -1070,6 +1197,24 @@ mod tests {
 		assert!(miner.requires_reseal(1u8.into()));
 	}
 
+	#[test]
+	fn oracle_keeps_floor_on_empty_chain() {
+		// given
+		let floor = U256::from(1_000_000_000u64);
+		let miner = miner_with_pricer(GasPricer::new_oracle(floor));
+		let client = TestBlockChainClient::default();
+		client.add_blocks(10, EachBlockWith::Nothing);
+
+		// when
+		for _ in 0..5 {
+			miner.prepare_block(&client);
+		}
+
+		// then
+		assert_eq!(miner.minimal_gas_price(), floor);
+		assert_eq!(miner.sensible_gas_price(), floor * 110.into() / 100.into());
+	}
+
 	#[test]
 	fn internal_seals_without_work() {
 		let miner = Miner::with_spec(&Spec::new_test_instant());
@@ -1091,4 +1236,74 @@ mod tests {
 		assert!(miner.pending_block().is_none());
 		assert_eq!(client.chain_info().best_block_number, 4 as BlockNumber);
 	}
+
+	#[test]
+	fn internal_sealing_follows_validator_contract() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let validator = tap.insert_account("".sha3(), "").unwrap();
+		let next_validator = tap.insert_account("1".sha3(), "").unwrap();
+		tap.unlock_account_permanently(validator, "".into()).unwrap();
+		tap.unlock_account_permanently(next_validator, "".into()).unwrap();
+
+		let spec = Spec::new_validator_contract();
+		let engine = &*spec.engine;
+		let miner = Arc::new(Miner::with_spec(&spec));
+		let dir = RandomTempPath::new();
+		let client = Client::new(
+			ClientConfig::default(),
+			&spec,
+			dir.as_path(),
+			miner.clone(),
+			IoChannel::disconnected(),
+			&DatabaseConfig::with_columns(::db::NUM_COLUMNS)
+		).unwrap();
+
+		miner.set_author(next_validator);
+		assert!(engine.is_sealer(&validator).unwrap());
+		assert!(!engine.is_sealer(&next_validator).unwrap());
+		assert!(!miner.sealing_work.lock().enabled);
+
+		// the first block hands sealing over to the next validator.
+		let genesis_header = spec.genesis_header();
+		let mut db_result = get_temp_journal_db();
+		let mut db = db_result.take();
+		spec.ensure_db_good(db.as_hashdb_mut(), &Default::default()).unwrap();
+		let mut b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, Arc::new(vec![genesis_header.hash()]), validator, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		b.push_transaction(Transaction {
+			nonce: U256::zero(),
+			gas_price: U256::zero(),
+			gas: U256::from(100_000),
+			action: Action::Call(Address::from(5)),
+			data: H256::from(next_validator).to_vec(),
+			value: U256::zero(),
+		}.sign(&"".sha3()), None).unwrap();
+		let b = b.close_and_lock();
+		let seal = engine.generate_seal(b.block(), Some(&*tap)).unwrap();
+		let b = b.seal(engine, seal).unwrap();
+		let first_header = b.header().clone();
+		assert!(engine.verify_block_family(&first_header, &genesis_header, None).is_ok());
+
+		client.import_block(b.rlp_bytes()).unwrap();
+		client.flush_queue();
+		client.import_verified_blocks();
+		assert_eq!(client.chain_info().best_block_number, 1);
+
+		assert!(!engine.is_sealer(&validator).unwrap());
+		assert!(engine.is_sealer(&next_validator).unwrap());
+		assert!(miner.sealing_work.lock().enabled);
+
+		// the second block may only be sealed by the next validator.
+		let b = OpenBlock::new(engine, Default::default(), false, b.drain(), &first_header, Arc::new(vec![genesis_header.hash(), first_header.hash()]), next_validator, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock();
+		let seal = engine.generate_seal(b.block(), Some(&*tap)).unwrap();
+		let mut header = b.seal(engine, seal).unwrap().header().clone();
+		assert!(engine.verify_block_family(&header, &first_header, None).is_ok());
+
+		let signature = tap.sign(validator, header.bare_hash()).unwrap();
+		header.set_seal(vec![::rlp::encode(&(&*signature as &[u8])).to_vec()]);
+		match engine.verify_block_family(&header, &first_header, None) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			other => panic!("should be invalid seal error (got {:?})", other),
+		}
+	}
 }
@@ -46,20 +46,38 @@ mod external;
 mod transaction_queue;
 mod work_notify;
 mod price_info;
+#[cfg(feature="stratum")]
+mod stratum;
+mod gas_oracle;
 
-pub use self::transaction_queue::{TransactionQueue, AccountDetails, TransactionOrigin};
+pub use self::transaction_queue::{TransactionQueue, AccountDetails, TransactionOrigin, QueuedTransaction, NonceGap};
 pub use self::miner::{Miner, MinerOptions, PendingSet, GasPricer, GasPriceCalibratorOptions};
 pub use self::external::{ExternalMiner, ExternalMinerService};
+pub use self::work_notify::NotifyWork;
+#[cfg(feature="stratum")]
+pub use self::stratum::{Stratum, Error as StratumError};
+pub use self::gas_oracle::GasPriceSuggestions;
 pub use client::TransactionImportResult;
 
 use std::collections::BTreeMap;
 use util::{H256, U256, Address, Bytes};
-use client::{MiningBlockChainClient, Executed, CallAnalytics};
+use client::{MiningBlockChainClient, Executed, CallAnalytics, StateOverride};
 use block::ClosedBlock;
 use receipt::{RichReceipt, Receipt};
 use error::{Error, CallError};
 use transaction::SignedTransaction;
 
+/// Configures stratum server options.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StratumOptions {
+	/// Network address to listen on
+	pub listen_addr: String,
+	/// Port to listen on
+	pub port: u16,
+	/// Hash of the secret workers have to authorize with, if any
+	pub secret: Option<H256>,
+}
+
 /// Miner client API
 pub trait MinerService : Send + Sync {
 
@@ -140,6 +158,18 @@ pub trait MinerService : Send + Sync {
 	/// Get a list of all transactions.
 	fn all_transactions(&self) -> Vec<SignedTransaction>;
 
+	/// Get details of current and future transactions in the queue, each ordered by priority.
+	fn queued_transactions(&self) -> (Vec<QueuedTransaction>, Vec<QueuedTransaction>);
+
+	/// Get nonce gaps blocking future transactions of each sender.
+	fn queue_nonce_gaps(&self) -> BTreeMap<Address, NonceGap>;
+
+	/// Remove transaction with given hash from the queue. Subsequent transactions of its sender are moved to future.
+	fn remove_transaction(&self, chain: &MiningBlockChainClient, hash: &H256) -> Option<SignedTransaction>;
+
+	/// Remove all transactions of given sender from the queue. Returns number of removed transactions.
+	fn remove_sender_transactions(&self, sender: &Address) -> usize;
+
 	/// Get a list of all pending transactions.
 	fn pending_transactions(&self) -> Vec<SignedTransaction>;
 
@@ -158,14 +188,19 @@ pub trait MinerService : Send + Sync {
 	/// Suggested gas price.
 	fn sensible_gas_price(&self) -> U256 { 20000000000u64.into() }
 
+	/// Gas price suggestions based on recently included transactions and the pending queue.
+	fn gas_price_suggestions(&self, _chain: &MiningBlockChainClient) -> GasPriceSuggestions {
+		GasPriceSuggestions::uniform(self.sensible_gas_price())
+	}
+
 	/// Suggested gas limit.
 	fn sensible_gas_limit(&self) -> U256 { 21000.into() }
 
 	/// Latest account balance in pending state.
 	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> U256;
 
-	/// Call into contract code using pending state.
-	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError>;
+	/// Call into contract code using pending state, with `state_override` applied to a copy of it.
+	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics, state_override: &StateOverride) -> Result<Executed, CallError>;
 
 	/// Get storage value in pending state.
 	fn storage_at(&self, chain: &MiningBlockChainClient, address: &Address, position: &H256) -> H256;
@@ -0,0 +1,289 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Client-side stratum job dispatcher and mining notifier handler
+
+use ethcore_stratum::{
+	JobDispatcher, PushWorkHandler, WorkerStats,
+	Stratum as StratumService, Error as StratumServiceError,
+};
+
+use std::collections::{HashMap, VecDeque};
+use std::net::{SocketAddr, AddrParseError};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Weak};
+
+use util::{H64, H256, U256, FixedHash, Mutex};
+use ethash::{SeedHashCompute, quick_get_difficulty};
+use ethereum::ethash::Ethash;
+use client::Client;
+use block::IsBlock;
+use miner::{Miner, MinerService, StratumOptions};
+use miner::work_notify::NotifyWork;
+use rlp;
+
+/// Number of recently dispatched work packages solutions are accepted for.
+const RECENT_WORK: usize = 20;
+
+/// Solution submitted by a worker: `[nonce, pow_hash, mix_hash]`, all hex-encoded.
+struct SubmitPayload {
+	nonce: H64,
+	pow_hash: H256,
+	mix_hash: H256,
+}
+
+impl SubmitPayload {
+	fn from_args(payload: Vec<String>) -> Result<Self, PayloadError> {
+		if payload.len() != 3 {
+			return Err(PayloadError::ArgumentsAmountUnexpected(payload.len()));
+		}
+
+		let nonce = try!(parse_hex::<H64>(&payload[0]).map_err(PayloadError::InvalidNonce));
+		let pow_hash = try!(parse_hex::<H256>(&payload[1]).map_err(PayloadError::InvalidPowHash));
+		let mix_hash = try!(parse_hex::<H256>(&payload[2]).map_err(PayloadError::InvalidMixHash));
+
+		Ok(SubmitPayload {
+			nonce: nonce,
+			pow_hash: pow_hash,
+			mix_hash: mix_hash,
+		})
+	}
+}
+
+fn parse_hex<T: FromStr>(value: &str) -> Result<T, String> {
+	let trimmed = if value.starts_with("0x") { &value[2..] } else { value };
+	T::from_str(trimmed).map_err(|_| value.to_owned())
+}
+
+#[derive(Debug)]
+enum PayloadError {
+	ArgumentsAmountUnexpected(usize),
+	InvalidNonce(String),
+	InvalidPowHash(String),
+	InvalidMixHash(String),
+}
+
+impl fmt::Display for PayloadError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			PayloadError::ArgumentsAmountUnexpected(n) => write!(f, "expected 3 arguments, got {}", n),
+			PayloadError::InvalidNonce(ref n) => write!(f, "invalid nonce: {}", n),
+			PayloadError::InvalidPowHash(ref h) => write!(f, "invalid pow hash: {}", h),
+			PayloadError::InvalidMixHash(ref h) => write!(f, "invalid mix hash: {}", h),
+		}
+	}
+}
+
+/// Job dispatcher for stratum service backed by the miner's sealing work.
+pub struct StratumJobDispatcher {
+	seed_compute: Mutex<SeedHashCompute>,
+	/// Recently dispatched work (pow_hash - difficulty), newest last
+	recent_work: Mutex<VecDeque<(H256, U256)>>,
+	client: Weak<Client>,
+	miner: Weak<Miner>,
+}
+
+impl JobDispatcher for StratumJobDispatcher {
+	fn initial(&self) -> Option<String> {
+		self.with_core(|client, miner| miner.map_sealing_work(&*client, |b| {
+			let header = b.header();
+			self.work_params(b.hash(), *header.difficulty(), header.number())
+		}))
+	}
+
+	fn job(&self, _worker_id: String) -> Option<String> {
+		self.with_core(|client, miner| miner.map_sealing_work(&*client, |b| {
+			let header = b.header();
+			self.payload(b.hash(), *header.difficulty(), header.number())
+		}))
+	}
+
+	fn submit(&self, payload: Vec<String>) -> Result<U256, StratumServiceError> {
+		let solution = try!(SubmitPayload::from_args(payload)
+			.map_err(|e| StratumServiceError::InvalidSolution(format!("{}", e))));
+
+		let difficulty = try!(self.recent_work.lock().iter()
+			.find(|&&(ref pow_hash, _)| pow_hash == &solution.pow_hash)
+			.map(|&(_, difficulty)| difficulty)
+			.ok_or_else(|| StratumServiceError::InvalidSolution(format!("unknown work {}", solution.pow_hash))));
+
+		// cheap check of the nonce against the mix hash before bothering the miner
+		let boundary = H256(quick_get_difficulty(&solution.pow_hash.0, solution.nonce.low_u64(), &solution.mix_hash.0));
+		if Ethash::boundary_to_difficulty(&boundary) < difficulty {
+			return Err(StratumServiceError::InvalidSolution(format!("solution does not meet difficulty {}", difficulty)));
+		}
+
+		let client = try!(self.client.upgrade().ok_or_else(|| StratumServiceError::Dispatch("client is gone".to_owned())));
+		let miner = try!(self.miner.upgrade().ok_or_else(|| StratumServiceError::Dispatch("miner is gone".to_owned())));
+
+		let seal = vec![rlp::encode(&solution.mix_hash).to_vec(), rlp::encode(&solution.nonce).to_vec()];
+		miner.submit_seal(&*client, solution.pow_hash, seal)
+			.map(|_| difficulty)
+			.map_err(|e| StratumServiceError::Dispatch(format!("{}", e)))
+	}
+}
+
+impl StratumJobDispatcher {
+	/// New stratum job dispatcher given the miner and client
+	fn new(miner: Weak<Miner>, client: Weak<Client>) -> StratumJobDispatcher {
+		StratumJobDispatcher {
+			seed_compute: Mutex::new(SeedHashCompute::new()),
+			recent_work: Mutex::new(VecDeque::with_capacity(RECENT_WORK)),
+			client: client,
+			miner: miner,
+		}
+	}
+
+	/// Serializes work to the json array pushed to the workers and remembers it for
+	/// validating submitted solutions.
+	fn work_params(&self, pow_hash: H256, difficulty: U256, number: u64) -> String {
+		{
+			let mut recent_work = self.recent_work.lock();
+			if !recent_work.iter().any(|&(ref h, _)| h == &pow_hash) {
+				if recent_work.len() == RECENT_WORK {
+					recent_work.pop_front();
+				}
+				recent_work.push_back((pow_hash, difficulty));
+			}
+		}
+
+		let target = Ethash::difficulty_to_boundary(&difficulty);
+		let seed_hash = &self.seed_compute.lock().get_seedhash(number);
+		let seed_hash = H256::from_slice(&seed_hash[..]);
+		format!(
+			r#"["0x{}","0x{}","0x{}","0x{:x}"]"#,
+			pow_hash.hex(), seed_hash.hex(), target.hex(), number
+		)
+	}
+
+	/// Serializes work to the `mining.notify` message.
+	fn payload(&self, pow_hash: H256, difficulty: U256, number: u64) -> String {
+		format!(
+			r#"{{ "id": null, "method": "mining.notify", "params": {} }}"#,
+			self.work_params(pow_hash, difficulty, number)
+		)
+	}
+
+	fn with_core<F, R>(&self, f: F) -> Option<R> where F: Fn(Arc<Client>, Arc<Miner>) -> Option<R> {
+		self.client.upgrade().and_then(|client| self.miner.upgrade().and_then(|miner| (f)(client, miner)))
+	}
+}
+
+/// Wrapper for dedicated stratum service
+#[derive(Clone)]
+pub struct Stratum {
+	dispatcher: Arc<StratumJobDispatcher>,
+	service: Arc<StratumService>,
+}
+
+#[derive(Debug)]
+/// Stratum error
+pub enum Error {
+	/// IPC sockets error
+	Service(StratumServiceError),
+	/// Invalid network address
+	Address(AddrParseError),
+}
+
+impl From<StratumServiceError> for Error {
+	fn from(service_err: StratumServiceError) -> Error { Error::Service(service_err) }
+}
+
+impl From<AddrParseError> for Error {
+	fn from(err: AddrParseError) -> Error { Error::Address(err) }
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Service(ref e) => write!(f, "Stratum service error: {:?}", e),
+			Error::Address(ref e) => write!(f, "Invalid stratum address: {}", e),
+		}
+	}
+}
+
+impl NotifyWork for Stratum {
+	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64) {
+		trace!(target: "stratum", "Notify work");
+
+		self.service.push_work_all(
+			self.dispatcher.payload(pow_hash, difficulty, number)
+		).unwrap_or_else(
+			|e| warn!(target: "stratum", "Error while pushing work: {:?}", e)
+		);
+	}
+}
+
+impl Stratum {
+	/// New stratum job dispatcher, given the miner, client and dedicated stratum service
+	pub fn start(options: &StratumOptions, miner: Weak<Miner>, client: Weak<Client>) -> Result<Stratum, Error> {
+		let dispatcher = Arc::new(StratumJobDispatcher::new(miner, client));
+		let addr = try!(SocketAddr::from_str(&format!("{}:{}", options.listen_addr, options.port)));
+
+		let stratum_svc = try!(StratumService::start(
+			&addr,
+			dispatcher.clone(),
+			options.secret.clone(),
+		).map_err(|e| StratumServiceError::Io(format!("{:?}", e))));
+
+		Ok(Stratum {
+			dispatcher: dispatcher,
+			service: stratum_svc,
+		})
+	}
+
+	/// Start STRATUM job dispatcher and register it in the miner
+	pub fn register(options: &StratumOptions, miner: Arc<Miner>, client: Weak<Client>) -> Result<Stratum, Error> {
+		let stratum = try!(Stratum::start(options, Arc::downgrade(&miner), client));
+		miner.add_work_listener(Box::new(stratum.clone()) as Box<NotifyWork>);
+		Ok(stratum)
+	}
+
+	/// Solution statistics of the connected workers
+	pub fn worker_stats(&self) -> HashMap<String, WorkerStats> {
+		self.service.worker_stats()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::SubmitPayload;
+	use util::{H64, H256};
+
+	#[test]
+	fn parses_submit_payload() {
+		let payload = SubmitPayload::from_args(vec![
+			"0x0000000000000042".to_owned(),
+			"0x1111111111111111111111111111111111111111111111111111111111111111".to_owned(),
+			"2222222222222222222222222222222222222222222222222222222222222222".to_owned(),
+		]).unwrap();
+
+		assert_eq!(payload.nonce, H64::from(0x42u64));
+		assert_eq!(payload.pow_hash, H256([0x11u8; 32]));
+		assert_eq!(payload.mix_hash, H256([0x22u8; 32]));
+	}
+
+	#[test]
+	fn rejects_malformed_payload() {
+		assert!(SubmitPayload::from_args(vec!["0x0000000000000042".to_owned()]).is_err());
+		assert!(SubmitPayload::from_args(vec![
+			"0xnotanonce".to_owned(),
+			"0x1111111111111111111111111111111111111111111111111111111111111111".to_owned(),
+			"0x2222222222222222222222222222222222222222222222222222222222222222".to_owned(),
+		]).is_err());
+	}
+}
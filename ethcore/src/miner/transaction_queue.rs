@@ -196,6 +196,8 @@ struct VerifiedTransaction {
 	transaction: SignedTransaction,
 	/// transaction origin
 	origin: TransactionOrigin,
+	/// UNIX timestamp of insertion to the queue
+	insertion_time: u64,
 }
 
 impl VerifiedTransaction {
@@ -204,6 +206,7 @@ impl VerifiedTransaction {
 		Ok(VerifiedTransaction {
 			transaction: transaction,
 			origin: origin,
+			insertion_time: ::time::get_time().sec as u64,
 		})
 	}
 
@@ -218,6 +221,15 @@ impl VerifiedTransaction {
 	fn sender(&self) -> Address {
 		self.transaction.sender().expect("Sender is verified in new; qed")
 	}
+
+	fn details(&self) -> QueuedTransaction {
+		QueuedTransaction {
+			transaction: self.transaction.clone(),
+			sender: self.sender(),
+			origin: self.origin,
+			insertion_time: self.insertion_time,
+		}
+	}
 }
 
 #[derive(Debug, Default)]
@@ -378,6 +390,28 @@ pub struct TransactionQueueStatus {
 	pub future: usize,
 }
 
+/// Transaction waiting in the queue along with its queuing details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedTransaction {
+	/// The transaction
+	pub transaction: SignedTransaction,
+	/// Sender of the transaction
+	pub sender: Address,
+	/// Origin of the transaction
+	pub origin: TransactionOrigin,
+	/// UNIX timestamp of insertion to the queue
+	pub insertion_time: u64,
+}
+
+/// Gap in nonces keeping future transactions of a sender from going to block
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonceGap {
+	/// Nonce of the next transaction the sender's future transactions are waiting for
+	pub expected: U256,
+	/// Lowest nonce of the sender's future transactions
+	pub first_future: U256,
+}
+
 /// Details of account
 pub struct AccountDetails {
 	/// Most recent account nonce
@@ -635,6 +669,26 @@ impl TransactionQueue {
 		}
 	}
 
+	/// Removes all transactions of given sender from the queue, both current and future.
+	/// Returns number of removed transactions.
+	pub fn remove_sender(&mut self, sender: &Address) -> usize {
+		let mut removed = 0;
+		for set in &mut [&mut self.current, &mut self.future] {
+			let nonces = match set.by_address.row(sender) {
+				Some(row_map) => row_map.keys().cloned().collect::<Vec<U256>>(),
+				None => vec![],
+			};
+			for nonce in nonces {
+				let order = set.drop(sender, &nonce).expect("iterating over a collection that has been retrieved above; qed");
+				self.by_hash.remove(&order.hash).expect("All transactions in `current` and `future` are always in `by_hash`; qed");
+				removed += 1;
+			}
+		}
+		self.last_nonces.remove(sender);
+		assert_eq!(self.future.by_priority.len() + self.current.by_priority.len(), self.by_hash.len());
+		removed
+	}
+
 	/// Update height of all transactions in future transactions set.
 	fn update_future(&mut self, sender: &Address, current_nonce: U256) {
 		// We need to drain all transactions for current sender from future and reinsert them with updated height
@@ -685,6 +739,39 @@ impl TransactionQueue {
 			.collect()
 	}
 
+	/// Returns details of all transactions from current, ordered by priority.
+	pub fn current_details(&self) -> Vec<QueuedTransaction> {
+		Self::set_details(&self.current, &self.by_hash)
+	}
+
+	/// Returns details of all transactions from future, ordered by priority.
+	pub fn future_details(&self) -> Vec<QueuedTransaction> {
+		Self::set_details(&self.future, &self.by_hash)
+	}
+
+	fn set_details(set: &TransactionSet, by_hash: &HashMap<H256, VerifiedTransaction>) -> Vec<QueuedTransaction> {
+		set.by_priority
+			.iter()
+			.map(|t| by_hash.get(&t.hash).expect("All transactions in `current` and `future` are always included in `by_hash`"))
+			.map(|t| t.details())
+			.collect()
+	}
+
+	/// Returns nonce gaps of all senders with transactions in future.
+	pub fn nonce_gaps(&self) -> BTreeMap<Address, NonceGap> {
+		let mut gaps = BTreeMap::new();
+		for order in &self.future.by_priority {
+			let tx = self.by_hash.get(&order.hash).expect("All transactions in `future` are always included in `by_hash`");
+			let (sender, nonce) = (tx.sender(), tx.nonce());
+			// heights in future are relative to the state nonce.
+			let state_nonce = nonce - order.nonce_height;
+			let expected = self.last_nonces.get(&sender).map_or(state_nonce, |n| *n + U256::one());
+			let gap = gaps.entry(sender).or_insert_with(|| NonceGap { expected: expected, first_future: nonce });
+			gap.first_future = cmp::min(gap.first_future, nonce);
+		}
+		gaps
+	}
+
 	/// Returns hashes of all transactions from current, ordered by priority.
 	pub fn pending_hashes(&self) -> Vec<H256> {
 		self.current.by_priority
@@ -1443,6 +1530,54 @@ mod test {
 		assert_eq!(stats.future, 0);
 	}
 
+	#[test]
+	fn should_remove_all_transactions_of_sender() {
+		// given
+		let mut txq = TransactionQueue::new();
+		let (tx, tx2) = new_tx_pair_default(3.into(), 0.into());
+		let other = new_tx_default();
+		txq.add(tx.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+		txq.add(tx2.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+		txq.add(other.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+
+		// when
+		let removed = txq.remove_sender(&tx.sender().unwrap());
+
+		// then
+		assert_eq!(removed, 2);
+		let stats = txq.status();
+		assert_eq!(stats.pending, 1);
+		assert_eq!(stats.future, 0);
+		assert_eq!(txq.last_nonce(&tx.sender().unwrap()), None);
+		assert_eq!(txq.top_transactions(), vec![other]);
+	}
+
+	#[test]
+	fn should_report_queued_transactions_and_nonce_gaps() {
+		// given
+		let mut txq = TransactionQueue::new();
+		let (tx, tx2) = new_tx_pair_default(3.into(), 0.into());
+		let sender = tx.sender().unwrap();
+		txq.add(tx.clone(), &default_account_details, TransactionOrigin::Local).unwrap();
+		txq.add(tx2.clone(), &default_account_details, TransactionOrigin::External).unwrap();
+
+		// when
+		let current = txq.current_details();
+		let future = txq.future_details();
+		let gaps = txq.nonce_gaps();
+
+		// then
+		assert_eq!(current.len(), 1);
+		assert_eq!(current[0].transaction, tx);
+		assert_eq!(current[0].sender, sender);
+		assert_eq!(current[0].origin, TransactionOrigin::Local);
+		assert_eq!(future.len(), 1);
+		assert_eq!(future[0].transaction, tx2);
+		assert_eq!(future[0].origin, TransactionOrigin::External);
+		assert_eq!(gaps.len(), 1);
+		assert_eq!(gaps[&sender], NonceGap { expected: default_nonce() + U256::one(), first_future: default_nonce() + 3.into() });
+	}
+
 	#[test]
 	fn should_move_transactions_to_future_if_gap_introduced() {
 		// given
@@ -22,7 +22,7 @@ use spec::Spec;
 use error::*;
 use client::{Client, ClientConfig, ChainNotify};
 use miner::Miner;
-use snapshot::ManifestData;
+use snapshot::{ManifestData, StateCheckpoints};
 use snapshot::service::{Service as SnapshotService, ServiceParams as SnapServiceParams};
 use std::sync::atomic::AtomicBool;
 
@@ -48,6 +48,8 @@ pub enum ClientIoMessage {
 	FeedBlockChunk(H256, Bytes),
 	/// Take a snapshot for the block with given number.
 	TakeSnapshot(u64),
+	/// Rebuild the state of the snapshot checkpoint at the block with given hash.
+	RebuildCheckpoint(H256),
 }
 
 /// Client service setup. Creates and registers client and network services with the IO subsystem.
@@ -97,6 +99,7 @@ impl ClientService {
 			db_restore: client.clone(),
 		};
 		let snapshot = Arc::new(try!(SnapshotService::new(snapshot_params)));
+		client.set_state_checkpoints(Arc::downgrade(&(snapshot.clone() as Arc<StateCheckpoints>)));
 
 		panic_handler.forward_from(&*client);
 		let client_io = Arc::new(ClientIoHandler {
@@ -197,6 +200,11 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 					warn!("Failed to take snapshot at block #{}: {}", num, e);
 				}
 			}
+			ClientIoMessage::RebuildCheckpoint(ref hash) => {
+				if let Err(e) = self.snapshot.rebuild_checkpoint(hash) {
+					warn!("Failed to rebuild checkpoint state at {}: {}", hash, e);
+				}
+			}
 			_ => {} // ignore other messages
 		}
 	}
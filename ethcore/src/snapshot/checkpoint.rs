@@ -0,0 +1,38 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sources of state checkpoints historical states can be re-executed from.
+
+use std::sync::Arc;
+
+use header::BlockNumber;
+use util::H256;
+use util::kvdb::Database;
+
+/// A source of complete states at known blocks, used as starting points when
+/// reconstructing states which have been pruned from the client's database.
+pub trait StateCheckpoints: Send + Sync {
+	/// Number and hash of the latest checkpoint at or before the given block number.
+	fn checkpoint_at_or_before(&self, number: BlockNumber) -> Option<(BlockNumber, H256)>;
+
+	/// State of the checkpoint at the block with given hash, if it has already been rebuilt.
+	/// Returns an archive database holding it, along with its state root. Never blocks.
+	fn checkpoint_state(&self, hash: &H256) -> Option<(Arc<Database>, H256)>;
+
+	/// Start rebuilding the state of the checkpoint at the block with given hash in the background.
+	/// Rebuilding the whole state takes long, so it must not happen on the thread asking for it.
+	fn prepare_checkpoint(&self, hash: &H256);
+}
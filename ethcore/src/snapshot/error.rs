@@ -19,6 +19,7 @@
 use std::fmt;
 
 use ids::BlockID;
+use types::snapshot_manifest::{SNAPSHOT_VERSION, MIN_SUPPORTED_VERSION};
 
 use util::H256;
 use util::trie::TrieError;
@@ -39,6 +40,14 @@ pub enum Error {
 	MissingCode(Vec<H256>),
 	/// Unrecognized code encoding.
 	UnrecognizedCodeState(u8),
+	/// Snapshot format version not supported by this client.
+	VersionNotSupported(u64),
+	/// Snapshot was taken with a different consensus engine.
+	IncompatibleEngine(String),
+	/// No snapshot manifests given to restore from.
+	NoManifests,
+	/// Incremental snapshot without the snapshot it is based on.
+	MissingBase(H256),
 	/// Trie error.
 	Trie(TrieError),
 	/// Decoder error.
@@ -57,6 +66,11 @@ impl fmt::Display for Error {
 				a pruned database. Please re-run with the --pruning archive flag."),
 			Error::MissingCode(ref missing) => write!(f, "Incomplete snapshot: {} contract codes not found.", missing.len()),
 			Error::UnrecognizedCodeState(state) => write!(f, "Unrecognized code encoding ({})", state),
+			Error::VersionNotSupported(version) => write!(f, "Snapshot format version {} is not supported. \
+				Supported versions are {} to {}.", version, MIN_SUPPORTED_VERSION, SNAPSHOT_VERSION),
+			Error::IncompatibleEngine(ref engine) => write!(f, "Snapshot was taken with an incompatible engine: {}", engine),
+			Error::NoManifests => write!(f, "No snapshot manifests given to restore from."),
+			Error::MissingBase(ref hash) => write!(f, "Incremental snapshot requires a base snapshot at block {}", hash),
 			Error::Io(ref err) => err.fmt(f),
 			Error::Decoder(ref err) => err.fmt(f),
 			Error::Trie(ref err) => err.fmt(f),
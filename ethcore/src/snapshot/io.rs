@@ -29,7 +29,7 @@ use util::Bytes;
 use util::hash::H256;
 use rlp::{self, Encodable, RlpStream, UntrustedRlp, Stream, View};
 
-use super::ManifestData;
+use super::{ManifestData, Compression, Error as SnapshotError, decode_manifest};
 
 /// Something which can write snapshots.
 /// Writing the same chunk multiple times will lead to implementation-defined
@@ -120,13 +120,19 @@ impl SnapshotWriter for PackedWriter {
 	fn finish(mut self, manifest: ManifestData) -> io::Result<()> {
 		// we ignore the hashes fields of the manifest under the assumption that
 		// they are consistent with ours.
-		let mut stream = RlpStream::new_list(5);
+		let base: Vec<H256> = manifest.base.into_iter().collect();
+
+		let mut stream = RlpStream::new_list(9);
 		stream
+			.append(&manifest.version)
+			.append(&manifest.compression)
+			.append(&manifest.engine)
 			.append(&self.state_hashes)
 			.append(&self.block_hashes)
 			.append(&manifest.state_root)
 			.append(&manifest.block_number)
-			.append(&manifest.block_hash);
+			.append(&manifest.block_hash)
+			.append(&base);
 
 		let manifest_rlp = stream.out();
 
@@ -257,17 +263,35 @@ impl PackedReader {
 
 		let rlp = UntrustedRlp::new(&manifest_buf);
 
-		let state: Vec<ChunkInfo> = try!(rlp.val_at(0));
-		let blocks: Vec<ChunkInfo> = try!(rlp.val_at(1));
+		// unversioned packed snapshots lack the leading version, compression and engine items.
+		let (version, compression, engine, offset) = match rlp.item_count() {
+			5 => (1, Compression::Snappy, String::new(), 0),
+			_ => (try!(rlp.val_at(0)), try!(rlp.val_at(1)), try!(rlp.val_at(2)), 3),
+		};
+
+		let state: Vec<ChunkInfo> = try!(rlp.val_at(offset));
+		let blocks: Vec<ChunkInfo> = try!(rlp.val_at(offset + 1));
+		let base: Vec<H256> = match rlp.item_count() {
+			9 => try!(rlp.val_at(offset + 5)),
+			_ => Vec::new(),
+		};
 
 		let manifest = ManifestData {
+			version: version,
+			compression: compression,
+			engine: engine,
 			state_hashes: state.iter().map(|c| c.0).collect(),
 			block_hashes: blocks.iter().map(|c| c.0).collect(),
-			state_root: try!(rlp.val_at(2)),
-			block_number: try!(rlp.val_at(3)),
-			block_hash: try!(rlp.val_at(4)),
+			state_root: try!(rlp.val_at(offset + 2)),
+			block_number: try!(rlp.val_at(offset + 3)),
+			block_hash: try!(rlp.val_at(offset + 4)),
+			base: base.into_iter().next(),
 		};
 
+		if !manifest.is_supported() {
+			return Err(SnapshotError::VersionNotSupported(manifest.version).into());
+		}
+
 		Ok(Some(PackedReader {
 			file: file,
 			state_hashes: state.into_iter().map(|c| (c.0, (c.1, c.2))).collect(),
@@ -313,7 +337,7 @@ impl LooseReader {
 		let mut manifest_file = try!(File::open(&dir));
 		try!(manifest_file.read_to_end(&mut manifest_buf));
 
-		let manifest = try!(ManifestData::from_rlp(&manifest_buf[..]));
+		let manifest = try!(decode_manifest(&manifest_buf[..]));
 
 		dir.pop();
 
@@ -347,7 +371,7 @@ mod tests {
 	use devtools::RandomTempPath;
 	use util::sha3::Hashable;
 
-	use snapshot::ManifestData;
+	use snapshot::{ManifestData, Compression, SNAPSHOT_VERSION};
 	use super::{SnapshotWriter, SnapshotReader, PackedWriter, PackedReader, LooseWriter, LooseReader};
 
 	const STATE_CHUNKS: &'static [&'static [u8]] = &[b"dog", b"cat", b"hello world", b"hi", b"notarealchunk"];
@@ -374,11 +398,15 @@ mod tests {
 		}
 
 		let manifest = ManifestData {
+			version: SNAPSHOT_VERSION,
+			compression: Compression::Snappy,
+			engine: String::new(),
 			state_hashes: state_hashes,
 			block_hashes: block_hashes,
 			state_root: b"notarealroot".sha3(),
 			block_number: 12345678987654321,
 			block_hash: b"notarealblock".sha3(),
+			base: Some(b"notarealbase".sha3()),
 		};
 
 		writer.finish(manifest.clone()).unwrap();
@@ -412,11 +440,15 @@ mod tests {
 		}
 
 		let manifest = ManifestData {
+			version: SNAPSHOT_VERSION,
+			compression: Compression::Snappy,
+			engine: String::new(),
 			state_hashes: state_hashes,
 			block_hashes: block_hashes,
 			state_root: b"notarealroot".sha3(),
 			block_number: 12345678987654321,
 			block_hash: b"notarealblock".sha3(),
+			base: None,
 		};
 
 		writer.finish(manifest.clone()).unwrap();
@@ -0,0 +1,134 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Journal of accounts touched by imported blocks, used to take incremental snapshots.
+
+use std::collections::{BTreeMap, HashSet};
+
+use util::hash::H256;
+
+/// Default number of blocks to keep journal entries for.
+pub const DEFAULT_JOURNAL_BLOCKS: usize = 30000;
+
+/// Records the hashes of the addresses of all accounts touched by imported blocks.
+///
+/// Entries are kept per block number. Blocks of all branches are recorded, so the set of
+/// accounts reported for a range of blocks is a superset of the accounts which actually
+/// changed along the canonical chain.
+///
+/// The journal is only kept in memory. After a restart nothing before the first imported
+/// block is covered, so the next snapshot taken is a full one.
+pub struct AccountJournal {
+	entries: BTreeMap<u64, HashSet<H256>>,
+	// all blocks with numbers at least this have been recorded.
+	covered_from: Option<u64>,
+	max_blocks: usize,
+}
+
+impl AccountJournal {
+	/// Create a new journal keeping entries for at most `max_blocks` block numbers.
+	pub fn new(max_blocks: usize) -> Self {
+		AccountJournal {
+			entries: BTreeMap::new(),
+			covered_from: None,
+			max_blocks: max_blocks,
+		}
+	}
+
+	/// Note the accounts touched by a block with the given number.
+	pub fn note_block<I>(&mut self, number: u64, accounts: I) where I: IntoIterator<Item=H256> {
+		if self.covered_from.is_none() {
+			self.covered_from = Some(number);
+		}
+
+		self.entries.entry(number).or_insert_with(HashSet::new).extend(accounts);
+
+		while self.entries.len() > self.max_blocks {
+			let oldest = *self.entries.keys().next().expect("entries is non-empty; qed");
+			self.entries.remove(&oldest);
+			self.covered_from = Some(oldest + 1);
+		}
+	}
+
+	/// Get all accounts touched by blocks after `base` up to and including `at`.
+	/// Returns `None` if not all of these blocks have been recorded.
+	pub fn changes_between(&self, base: u64, at: u64) -> Option<HashSet<H256>> {
+		match self.covered_from {
+			Some(from) if from <= base + 1 => {},
+			_ => return None,
+		}
+
+		let mut changes = HashSet::new();
+		for (_, accounts) in self.entries.iter().filter(|&(n, _)| *n > base && *n <= at) {
+			changes.extend(accounts.iter().cloned());
+		}
+
+		Some(changes)
+	}
+
+	/// Drop all entries for blocks up to and including `number`.
+	/// Called once a snapshot has been taken at that block.
+	pub fn prune(&mut self, number: u64) {
+		let stale: Vec<_> = self.entries.keys().take_while(|n| **n <= number).cloned().collect();
+		for n in stale {
+			self.entries.remove(&n);
+		}
+
+		self.covered_from = self.covered_from.map(|from| ::std::cmp::max(from, number + 1));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AccountJournal;
+	use util::hash::H256;
+
+	#[test]
+	fn reports_changes_in_range() {
+		let mut journal = AccountJournal::new(100);
+		let (a, b, c) = (H256::from(1u64), H256::from(2u64), H256::from(3u64));
+
+		journal.note_block(10, vec![a]);
+		journal.note_block(11, vec![b]);
+		journal.note_block(12, vec![c]);
+		// a block on a different branch.
+		journal.note_block(11, vec![c]);
+
+		let changes = journal.changes_between(10, 11).unwrap();
+		assert_eq!(changes.len(), 2);
+		assert!(changes.contains(&b) && changes.contains(&c));
+
+		// blocks before 10 were never recorded.
+		assert!(journal.changes_between(8, 12).is_none());
+		assert_eq!(journal.changes_between(9, 12).unwrap().len(), 3);
+	}
+
+	#[test]
+	fn pruning_and_eviction() {
+		let mut journal = AccountJournal::new(3);
+		for n in 1..6 {
+			journal.note_block(n, vec![H256::from(n)]);
+		}
+
+		// only blocks 3 to 5 are kept.
+		assert!(journal.changes_between(1, 5).is_none());
+		assert_eq!(journal.changes_between(2, 5).unwrap().len(), 3);
+
+		journal.prune(4);
+		assert!(journal.changes_between(3, 5).is_none());
+		assert_eq!(journal.changes_between(4, 5).unwrap().len(), 1);
+	}
+}
@@ -43,12 +43,14 @@ use self::io::SnapshotWriter;
 use crossbeam::{scope, ScopedJoinHandle};
 use rand::{Rng, OsRng};
 
+pub use self::checkpoint::StateCheckpoints;
 pub use self::error::Error;
+pub use self::journal::{AccountJournal, DEFAULT_JOURNAL_BLOCKS};
 
 pub use self::service::{Service, DatabaseRestore};
 pub use self::traits::{SnapshotService, RemoteSnapshotService};
 pub use self::watcher::Watcher;
-pub use types::snapshot_manifest::ManifestData;
+pub use types::snapshot_manifest::{ManifestData, Compression, SNAPSHOT_VERSION, MIN_SUPPORTED_VERSION};
 pub use types::restoration_status::RestorationStatus;
 
 pub mod io;
@@ -56,7 +58,9 @@ pub mod service;
 
 mod account;
 mod block;
+mod checkpoint;
 mod error;
+mod journal;
 mod watcher;
 
 #[cfg(test)]
@@ -107,25 +111,60 @@ impl Progress {
 	pub fn done(&self) -> bool  { self.done.load(Ordering::Acquire) }
 
 }
-/// Take a snapshot using the given blockchain, starting block hash, and database, writing into the given writer.
+/// Take a snapshot using the given engine, blockchain, starting block hash, and database, writing into the given writer.
 pub fn take_snapshot<W: SnapshotWriter + Send>(
+	engine: &Engine,
 	chain: &BlockChain,
 	block_at: H256,
 	state_db: &HashDB,
 	writer: W,
 	p: &Progress
+) -> Result<(), Error> {
+	write_snapshot(engine, chain, block_at, state_db, None, writer, p)
+}
+
+/// Take an incremental snapshot on top of the snapshot described by `base`.
+/// Only the accounts in `changes`, identified by their address hashes, are included in the state chunks.
+/// Block chunks are produced the same way as for full snapshots.
+pub fn take_incremental_snapshot<W: SnapshotWriter + Send>(
+	engine: &Engine,
+	chain: &BlockChain,
+	base: &ManifestData,
+	block_at: H256,
+	state_db: &HashDB,
+	changes: &HashSet<H256>,
+	writer: W,
+	p: &Progress
+) -> Result<(), Error> {
+	write_snapshot(engine, chain, block_at, state_db, Some((base.block_hash, changes)), writer, p)
+}
+
+fn write_snapshot<W: SnapshotWriter + Send>(
+	engine: &Engine,
+	chain: &BlockChain,
+	block_at: H256,
+	state_db: &HashDB,
+	incremental: Option<(H256, &HashSet<H256>)>,
+	writer: W,
+	p: &Progress
 ) -> Result<(), Error> {
 	let start_header = try!(chain.block_header(&block_at)
 		.ok_or(Error::InvalidStartingBlock(BlockID::Hash(block_at))));
 	let state_root = start_header.state_root();
 	let number = start_header.number();
 
-	info!("Taking snapshot starting at block {}", number);
+	match incremental {
+		Some(_) => info!("Taking incremental snapshot starting at block {}", number),
+		None => info!("Taking snapshot starting at block {}", number),
+	}
 
 	let writer = Mutex::new(writer);
 	let (state_hashes, block_hashes) = try!(scope(|scope| {
 		let block_guard = scope.spawn(|| chunk_blocks(chain, (number, block_at), &writer, p));
-		let state_res = chunk_state(state_db, state_root, &writer, p);
+		let state_res = match incremental {
+			Some((_, changes)) => chunk_state_changes(state_db, state_root, changes, &writer, p),
+			None => chunk_state(state_db, state_root, &writer, p),
+		};
 
 		state_res.and_then(|state_hashes| {
 			block_guard.join().map(|block_hashes| (state_hashes, block_hashes))
@@ -135,11 +174,15 @@ pub fn take_snapshot<W: SnapshotWriter + Send>(
 	info!("produced {} state chunks and {} block chunks.", state_hashes.len(), block_hashes.len());
 
 	let manifest_data = ManifestData {
+		version: SNAPSHOT_VERSION,
+		compression: Compression::Snappy,
+		engine: engine.name().to_owned(),
 		state_hashes: state_hashes,
 		block_hashes: block_hashes,
 		state_root: *state_root,
 		block_number: number,
 		block_hash: block_at,
+		base: incremental.map(|(base, _)| base),
 	};
 
 	try!(writer.into_inner().finish(manifest_data));
@@ -149,6 +192,28 @@ pub fn take_snapshot<W: SnapshotWriter + Send>(
 	Ok(())
 }
 
+/// Decode manifest data, rejecting snapshot formats this client can't restore.
+pub fn decode_manifest(raw: &[u8]) -> Result<ManifestData, Error> {
+	let version = try!(ManifestData::version_of(raw));
+	if version < MIN_SUPPORTED_VERSION || version > SNAPSHOT_VERSION {
+		return Err(Error::VersionNotSupported(version));
+	}
+
+	Ok(try!(ManifestData::from_rlp(raw)))
+}
+
+/// Decompress a chunk into the given buffer, returning the length of the raw data.
+pub fn decompress_chunk(compression: Compression, chunk: &[u8], buffer: &mut Bytes) -> Result<usize, ::error::Error> {
+	match compression {
+		Compression::None => {
+			buffer.clear();
+			buffer.extend_from_slice(chunk);
+			Ok(chunk.len())
+		}
+		Compression::Snappy => Ok(try!(snappy::decompress_into(chunk, buffer))),
+	}
+}
+
 /// Used to build block chunks.
 struct BlockChunker<'a> {
 	chain: &'a BlockChain,
@@ -376,6 +441,52 @@ pub fn chunk_state<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter +
 	Ok(chunker.hashes)
 }
 
+/// Create and write out state chunks containing only the given accounts, as they are
+/// in the state with the given root. Accounts which don't exist in that state are
+/// written with an empty body, marking them as removed.
+///
+/// Returns a list of hashes of chunks created, or any error it may
+/// have encountered.
+pub fn chunk_state_changes<'a>(db: &HashDB, root: &H256, changes: &HashSet<H256>, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress) -> Result<Vec<H256>, Error> {
+	let account_trie = try!(TrieDB::new(db, &root));
+
+	let mut chunker = StateChunker {
+		hashes: Vec::new(),
+		rlps: Vec::new(),
+		cur_size: 0,
+		snappy_buffer: vec![0; snappy::max_compressed_len(PREFERRED_CHUNK_SIZE)],
+		writer: writer,
+		progress: progress,
+	};
+
+	let mut used_code = HashSet::new();
+
+	// sort the accounts so chunks are produced in trie order.
+	let mut changes: Vec<_> = changes.iter().cloned().collect();
+	changes.sort();
+
+	for account_key_hash in changes {
+		let compressed_rlp = match try!(account_trie.get(&account_key_hash)) {
+			Some(account_data) => {
+				let account = Account::from_thin_rlp(account_data);
+				let account_db = AccountDB::from_hash(db, account_key_hash);
+
+				let fat_rlp = try!(account.to_fat_rlp(&account_db, &mut used_code));
+				UntrustedRlp::new(&fat_rlp).compress(RlpType::Snapshot).to_vec()
+			}
+			None => ::rlp::NULL_RLP.to_vec(),
+		};
+
+		try!(chunker.push(account_key_hash.to_vec(), compressed_rlp));
+	}
+
+	if chunker.cur_size != 0 {
+		try!(chunker.write_chunk());
+	}
+
+	Ok(chunker.hashes)
+}
+
 /// Used to rebuild the state trie piece by piece.
 pub struct StateRebuilder {
 	db: Box<JournalDB>,
@@ -396,6 +507,7 @@ impl StateRebuilder {
 	}
 
 	/// Feed an uncompressed state chunk into the rebuilder.
+	/// Chunks of incremental snapshots may be fed once the state of their base has been rebuilt.
 	pub fn feed(&mut self, chunk: &[u8]) -> Result<(), ::error::Error> {
 		let rlp = UntrustedRlp::new(chunk);
 		let account_fat_rlps: Vec<_> = rlp.iter().map(|r| r.as_raw()).collect();
@@ -461,7 +573,12 @@ impl StateRebuilder {
 			};
 
 			for (hash, thin_rlp) in pairs {
-				try!(account_trie.insert(&hash, &thin_rlp));
+				// accounts removed since the base snapshot have no body.
+				if thin_rlp.is_empty() {
+					try!(account_trie.remove(&hash));
+				} else {
+					try!(account_trie.insert(&hash, &thin_rlp));
+				}
 			}
 		}
 
@@ -507,7 +624,13 @@ fn rebuild_accounts(
 		let account_rlp = UntrustedRlp::new(account_pair);
 
 		let hash: H256 = try!(account_rlp.val_at(0));
-		let decompressed = try!(account_rlp.at(1)).decompress(RlpType::Snapshot);
+		let body = try!(account_rlp.at(1));
+		if body.is_data() && body.is_empty() {
+			*out = (hash, Vec::new());
+			continue;
+		}
+
+		let decompressed = body.decompress(RlpType::Snapshot);
 		let fat_rlp = UntrustedRlp::new(&decompressed[..]);
 
 		let thin_rlp = {
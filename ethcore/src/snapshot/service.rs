@@ -16,20 +16,22 @@
 
 //! Snapshot network service implementation.
 
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::io::ErrorKind;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-use super::{ManifestData, StateRebuilder, BlockRebuilder, RestorationStatus, SnapshotService};
+use super::{ManifestData, StateRebuilder, BlockRebuilder, RestorationStatus, SnapshotService, StateCheckpoints, decompress_chunk};
+use super::Error as SnapshotError;
 use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
 
 use blockchain::BlockChain;
 use client::Client;
 use engines::Engine;
 use error::Error;
+use header::BlockNumber;
 use ids::BlockID;
 use service::ClientIoMessage;
 
@@ -38,7 +40,6 @@ use io::IoChannel;
 use util::{Bytes, H256, Mutex, RwLock, RwLockReadGuard, UtilError};
 use util::journaldb::Algorithm;
 use util::kvdb::{Database, DatabaseConfig};
-use util::snappy;
 
 /// Helper for removing directories in case of error.
 struct Guard(bool, PathBuf);
@@ -63,21 +64,29 @@ pub trait DatabaseRestore: Send + Sync {
 	fn restore_db(&self, new_db: &str) -> Result<(), Error>;
 }
 
+// Maximum number of incremental snapshots taken on top of a full snapshot.
+const MAX_INCREMENTAL_SNAPSHOTS: usize = 8;
+
 /// State restoration manager.
+///
+/// The state is restored in stages: first from a full snapshot, followed by any
+/// incremental snapshots in order. Blocks are restored from the last snapshot only.
 struct Restoration {
 	manifest: ManifestData,
+	stage: ManifestData,
+	pending_stages: VecDeque<ManifestData>,
 	state_chunks_left: HashSet<H256>,
 	block_chunks_left: HashSet<H256>,
 	state: StateRebuilder,
 	blocks: BlockRebuilder,
 	writer: Option<LooseWriter>,
-	snappy_buffer: Bytes,
+	chunk_buffer: Bytes,
 	final_state_root: H256,
 	guard: Guard,
 }
 
 struct RestorationParams<'a> {
-	manifest: ManifestData, // manifest to base restoration on.
+	manifests: Vec<ManifestData>, // full snapshot manifest, followed by incremental ones.
 	pruning: Algorithm, // pruning algorithm for the database.
 	db_path: PathBuf, // database path
 	db_config: &'a DatabaseConfig, // configuration for the database.
@@ -89,9 +98,11 @@ struct RestorationParams<'a> {
 impl Restoration {
 	// make a new restoration using the given parameters.
 	fn new(params: RestorationParams) -> Result<Self, Error> {
-		let manifest = params.manifest;
+		let mut stages: VecDeque<_> = params.manifests.into_iter().collect();
+		let stage = stages.pop_front().expect("at least one manifest is always given; qed");
+		let manifest = stages.back().cloned().unwrap_or_else(|| stage.clone());
 
-		let state_chunks = manifest.state_hashes.iter().cloned().collect();
+		let state_chunks = stage.state_hashes.iter().cloned().collect();
 		let block_chunks = manifest.block_hashes.iter().cloned().collect();
 
 		let raw_db = Arc::new(try!(Database::open(params.db_config, &*params.db_path.to_string_lossy())
@@ -101,29 +112,55 @@ impl Restoration {
 		let blocks = try!(BlockRebuilder::new(chain, manifest.block_number));
 
 		let root = manifest.state_root.clone();
-		Ok(Restoration {
+		let mut restoration = Restoration {
 			manifest: manifest,
+			stage: stage,
+			pending_stages: stages,
 			state_chunks_left: state_chunks,
 			block_chunks_left: block_chunks,
 			state: StateRebuilder::new(raw_db, params.pruning),
 			blocks: blocks,
 			writer: params.writer,
-			snappy_buffer: Vec::new(),
+			chunk_buffer: Vec::new(),
 			final_state_root: root,
 			guard: params.guard,
-		})
+		};
+
+		try!(restoration.next_stage());
+		Ok(restoration)
 	}
 
 	// feeds a state chunk
 	fn feed_state(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error> {
 		if self.state_chunks_left.remove(&hash) {
-			let len = try!(snappy::decompress_into(chunk, &mut self.snappy_buffer));
+			let len = try!(decompress_chunk(self.stage.compression, chunk, &mut self.chunk_buffer));
 
-			try!(self.state.feed(&self.snappy_buffer[..len]));
+			try!(self.state.feed(&self.chunk_buffer[..len]));
 
 			if let Some(ref mut writer) = self.writer.as_mut() {
 				try!(writer.write_state_chunk(hash, chunk));
 			}
+
+			try!(self.next_stage());
+		}
+
+		Ok(())
+	}
+
+	// move on to the next incremental snapshot once the state of the current one is complete.
+	fn next_stage(&mut self) -> Result<(), Error> {
+		use util::trie::TrieError;
+
+		while self.state_chunks_left.is_empty() && !self.pending_stages.is_empty() {
+			let root = self.state.state_root();
+			if root != self.stage.state_root {
+				warn!("Restored state at #{} has wrong state root: expected {:?}, got {:?}", self.stage.block_number, self.stage.state_root, root);
+				return Err(TrieError::InvalidStateRoot(root).into());
+			}
+
+			self.stage = self.pending_stages.pop_front().expect("checked non-empty above; qed");
+			self.state_chunks_left = self.stage.state_hashes.iter().cloned().collect();
+			trace!(target: "snapshot", "applying incremental snapshot at #{}", self.stage.block_number);
 		}
 
 		Ok(())
@@ -132,9 +169,9 @@ impl Restoration {
 	// feeds a block chunk
 	fn feed_blocks(&mut self, hash: H256, chunk: &[u8], engine: &Engine) -> Result<(), Error> {
 		if self.block_chunks_left.remove(&hash) {
-			let len = try!(snappy::decompress_into(chunk, &mut self.snappy_buffer));
+			let len = try!(decompress_chunk(self.manifest.compression, chunk, &mut self.chunk_buffer));
 
-			try!(self.blocks.feed(&self.snappy_buffer[..len], engine));
+			try!(self.blocks.feed(&self.chunk_buffer[..len], engine));
 			if let Some(ref mut writer) = self.writer.as_mut() {
 				try!(writer.write_block_chunk(hash, chunk));
 			}
@@ -172,7 +209,7 @@ impl Restoration {
 
 	// is everything done?
 	fn is_done(&self) -> bool {
-		self.block_chunks_left.is_empty() && self.state_chunks_left.is_empty()
+		self.block_chunks_left.is_empty() && self.state_chunks_left.is_empty() && self.pending_stages.is_empty()
 	}
 }
 
@@ -215,6 +252,8 @@ pub struct Service {
 	db_restore: Arc<DatabaseRestore>,
 	progress: super::Progress,
 	taking_snapshot: AtomicBool,
+	checkpoint: Mutex<Option<(H256, Arc<Database>, H256)>>,
+	building_checkpoint: AtomicBool,
 }
 
 impl Service {
@@ -235,6 +274,8 @@ impl Service {
 			db_restore: params.db_restore,
 			progress: Default::default(),
 			taking_snapshot: AtomicBool::new(false),
+			checkpoint: Mutex::new(None),
+			building_checkpoint: AtomicBool::new(false),
 		};
 
 		// create the root snapshot dir if it doesn't exist.
@@ -258,6 +299,13 @@ impl Service {
 			}
 		}
 
+		// delete the checkpoint state left by a previous run.
+		if let Err(e) = fs::remove_dir_all(service.checkpoint_dir()) {
+			if e.kind() != ErrorKind::NotFound {
+				return Err(e.into())
+			}
+		}
+
 		let reader = LooseReader::new(service.snapshot_dir()).ok();
 		*service.reader.get_mut() = reader;
 
@@ -292,6 +340,89 @@ impl Service {
 		dir
 	}
 
+	// get the directory incremental snapshots on top of the current one are kept in.
+	fn incremental_dir(&self) -> PathBuf {
+		let mut dir = self.snapshot_root.clone();
+		dir.push("incremental");
+		dir
+	}
+
+	// get the directory the state of a checkpoint is rebuilt in.
+	fn checkpoint_dir(&self) -> PathBuf {
+		let mut dir = self.snapshot_root.clone();
+		dir.push("checkpoint");
+		dir
+	}
+
+	/// Rebuild the state of the checkpoint at the block with given hash, replacing the
+	/// previously rebuilt one. This takes long, see `StateCheckpoints::prepare_checkpoint`.
+	pub fn rebuild_checkpoint(&self, hash: &H256) -> Result<(), Error> {
+		let res = self.rebuild_checkpoint_state(hash);
+		self.building_checkpoint.store(false, Ordering::SeqCst);
+		res
+	}
+
+	fn rebuild_checkpoint_state(&self, hash: &H256) -> Result<(), Error> {
+		use util::trie::TrieError;
+
+		let mut checkpoint = self.checkpoint.lock();
+		if let Some((ref checkpoint_hash, _, _)) = *checkpoint {
+			if checkpoint_hash == hash {
+				return Ok(());
+			}
+		}
+		*checkpoint = None;
+
+		let dir = self.checkpoint_dir();
+		if let Err(e) = fs::remove_dir_all(&dir) {
+			if e.kind() != ErrorKind::NotFound {
+				return Err(e.into())
+			}
+		}
+		try!(fs::create_dir_all(&dir));
+
+		let db = Arc::new(try!(Database::open(&self.db_config, &*dir.to_string_lossy())
+			.map_err(UtilError::SimpleString)));
+		let mut rebuilder = StateRebuilder::new(db.clone(), Algorithm::Archive);
+		let mut buffer = Vec::new();
+		let mut expected_root = None;
+
+		{
+			let full = self.reader.read();
+			let full = try!(full.as_ref().ok_or(SnapshotError::MissingBase(*hash)));
+			let incremental = self.incremental_readers();
+			let readers = Some(full as &SnapshotReader).into_iter()
+				.chain(incremental.iter().map(|r| r as &SnapshotReader));
+
+			// the state of a checkpoint is that of the full snapshot patched by all the incremental ones up to it.
+			for reader in readers {
+				let manifest = reader.manifest();
+				for chunk_hash in &manifest.state_hashes {
+					let chunk = try!(reader.chunk(*chunk_hash));
+					let len = try!(decompress_chunk(manifest.compression, &chunk, &mut buffer));
+					try!(rebuilder.feed(&buffer[..len]));
+				}
+
+				if &manifest.block_hash == hash {
+					expected_root = Some(manifest.state_root);
+					break;
+				}
+			}
+		}
+
+		let expected_root = try!(expected_root.ok_or(SnapshotError::MissingBase(*hash)));
+		let root = rebuilder.state_root();
+		if root != expected_root {
+			warn!("Checkpoint state at {:?} has wrong state root: expected {:?}, got {:?}", hash, expected_root, root);
+			return Err(TrieError::InvalidStateRoot(root).into());
+		}
+		try!(rebuilder.check_missing());
+
+		trace!(target: "snapshot", "rebuilt checkpoint state at {:?}", hash);
+		*checkpoint = Some((*hash, db, root));
+		Ok(())
+	}
+
 	// temporary snapshot recovery path.
 	fn temp_recovery_dir(&self) -> PathBuf {
 		let mut dir = self.restoration_dir();
@@ -312,6 +443,45 @@ impl Service {
 		self.reader.read()
 	}
 
+	/// Get readers for the incremental snapshots taken on top of the current snapshot,
+	/// ordered by block number.
+	pub fn incremental_readers(&self) -> Vec<LooseReader> {
+		let entries = match fs::read_dir(self.incremental_dir()) {
+			Ok(entries) => entries,
+			Err(_) => return Vec::new(),
+		};
+
+		let mut readers: Vec<_> = entries
+			.filter_map(|entry| entry.ok())
+			.filter_map(|entry| LooseReader::new(entry.path()).ok())
+			.collect();
+
+		readers.sort_by_key(|r| r.manifest().block_number);
+		readers
+	}
+
+	// find the snapshot to take the next incremental snapshot on top of, along with the accounts changed since.
+	fn incremental_base(&self, client: &Client, num: u64) -> Option<(ManifestData, HashSet<H256>)> {
+		let incremental = self.incremental_readers();
+		if incremental.len() >= MAX_INCREMENTAL_SNAPSHOTS {
+			return None;
+		}
+
+		let base = match incremental.last() {
+			Some(reader) => reader.manifest().clone(),
+			None => match *self.reader.read() {
+				Some(ref reader) => reader.manifest().clone(),
+				None => return None,
+			},
+		};
+
+		if base.block_number >= num {
+			return None;
+		}
+
+		client.snapshot_changes(&base, num).map(|changes| (base, changes))
+	}
+
 	/// Tick the snapshot service. This will log any active snapshot
 	/// being taken.
 	pub fn tick(&self) {
@@ -322,6 +492,9 @@ impl Service {
 	}
 
 	/// Take a snapshot at the block with the given number.
+	/// If the changes since the latest snapshot are known, an incremental snapshot is taken
+	/// on top of it, until `MAX_INCREMENTAL_SNAPSHOTS` of them have been taken.
+	/// Only the full snapshot is served to peers, incremental ones are kept for local restoration.
 	/// calling this while a restoration is in progress or vice versa
 	/// will lead to a race condition where the first one to finish will
 	/// have their produced snapshot overwritten.
@@ -331,7 +504,6 @@ impl Service {
 			return Ok(());
 		}
 
-		info!("Taking snapshot at #{}", num);
 		self.progress.reset();
 
 		let temp_dir = self.temp_snapshot_dir();
@@ -342,12 +514,40 @@ impl Service {
 		let writer = try!(LooseWriter::new(temp_dir.clone()));
 
 		let guard = Guard::new(temp_dir.clone());
-		let res = client.take_snapshot(writer, BlockID::Number(num), &self.progress);
+		let base = self.incremental_base(client, num);
+		let res = match base {
+			Some((ref base, ref changes)) => {
+				info!("Taking incremental snapshot at #{} on top of #{} ({} accounts changed)", num, base.block_number, changes.len());
+				client.take_incremental_snapshot(writer, base, changes, BlockID::Number(num), &self.progress)
+			}
+			None => {
+				info!("Taking snapshot at #{}", num);
+				client.take_snapshot(writer, BlockID::Number(num), &self.progress)
+			}
+		};
 
 		self.taking_snapshot.store(false, Ordering::SeqCst);
 		try!(res);
 
 		info!("Finished taking snapshot at #{}", num);
+		client.prune_snapshot_changes(num);
+
+		if base.is_some() {
+			let mut incremental_dir = self.incremental_dir();
+			try!(fs::create_dir_all(&incremental_dir));
+			incremental_dir.push(num.to_string());
+
+			if incremental_dir.exists() {
+				try!(fs::remove_dir_all(&incremental_dir));
+			}
+
+			try!(fs::rename(temp_dir, &incremental_dir));
+			guard.disarm();
+			return Ok(());
+		}
+
+		// incremental snapshots taken on top of the old snapshot are useless now.
+		try!(self.remove_incremental());
 
 		let mut reader = self.reader.write();
 
@@ -366,9 +566,51 @@ impl Service {
 		Ok(())
 	}
 
+	// remove all incremental snapshots.
+	fn remove_incremental(&self) -> Result<(), Error> {
+		if let Err(e) = fs::remove_dir_all(self.incremental_dir()) {
+			if e.kind() != ErrorKind::NotFound {
+				return Err(e.into())
+			}
+		}
+
+		Ok(())
+	}
+
 	/// Initialize the restoration synchronously.
 	/// The recover flag indicates whether to recover the restored snapshot.
 	pub fn init_restore(&self, manifest: ManifestData, recover: bool) -> Result<(), Error> {
+		self.init_restore_incremental(vec![manifest], recover)
+	}
+
+	/// Initialize the restoration of a full snapshot followed by a chain of incremental
+	/// snapshots, each based on the previous one.
+	/// Recovery is only possible when restoring a single full snapshot.
+	///
+	/// Incremental snapshots are only restored from local files: they are never advertised
+	/// to or requested from peers, so warp sync always restores the full snapshot alone.
+	pub fn init_restore_incremental(&self, manifests: Vec<ManifestData>, recover: bool) -> Result<(), Error> {
+		if manifests.is_empty() {
+			return Err(SnapshotError::NoManifests.into());
+		}
+
+		let mut base = None;
+		for manifest in &manifests {
+			if !manifest.is_supported() {
+				return Err(SnapshotError::VersionNotSupported(manifest.version).into());
+			}
+			if !manifest.engine.is_empty() && manifest.engine != self.engine.name() {
+				return Err(SnapshotError::IncompatibleEngine(manifest.engine.clone()).into());
+			}
+			if manifest.base != base {
+				let missing = manifest.base.or(base).expect("bases differ so at least one is set; qed");
+				return Err(SnapshotError::MissingBase(missing).into());
+			}
+			base = Some(manifest.block_hash);
+		}
+
+		let recover = recover && manifests.len() == 1;
+
 		let rest_dir = self.restoration_dir();
 
 		let mut res = self.restoration.lock();
@@ -396,7 +638,7 @@ impl Service {
 		};
 
 		let params = RestorationParams {
-			manifest: manifest,
+			manifests: manifests,
 			pruning: self.pruning,
 			db_path: self.restoration_db(),
 			db_config: &self.db_config,
@@ -439,6 +681,7 @@ impl Service {
 
 			trace!(target: "snapshot", "copying restored snapshot files over");
 			try!(fs::rename(self.temp_recovery_dir(), &snapshot_dir));
+			try!(self.remove_incremental());
 
 			*reader = Some(try!(LooseReader::new(snapshot_dir)));
 		}
@@ -554,6 +797,45 @@ impl SnapshotService for Service {
 	}
 }
 
+impl StateCheckpoints for Service {
+	fn checkpoint_at_or_before(&self, number: BlockNumber) -> Option<(BlockNumber, H256)> {
+		let mut manifests = match *self.reader.read() {
+			Some(ref reader) => vec![reader.manifest().clone()],
+			None => return None,
+		};
+		manifests.extend(self.incremental_readers().iter().map(|r| r.manifest().clone()));
+
+		manifests.into_iter()
+			.filter(|m| m.block_number <= number)
+			.last()
+			.map(|m| (m.block_number, m.block_hash))
+	}
+
+	fn checkpoint_state(&self, hash: &H256) -> Option<(Arc<Database>, H256)> {
+		// the lock is held while a checkpoint is being rebuilt.
+		let checkpoint = match self.checkpoint.try_lock() {
+			Some(checkpoint) => checkpoint,
+			None => return None,
+		};
+		match *checkpoint {
+			Some((ref checkpoint_hash, ref db, root)) if checkpoint_hash == hash => Some((db.clone(), root)),
+			_ => None,
+		}
+	}
+
+	fn prepare_checkpoint(&self, hash: &H256) {
+		if self.building_checkpoint.compare_and_swap(false, true, Ordering::SeqCst) {
+			return;
+		}
+
+		trace!(target: "snapshot", "scheduling rebuild of checkpoint state at {:?}", hash);
+		if let Err(e) = self.io_channel.send(ClientIoMessage::RebuildCheckpoint(*hash)) {
+			debug!(target: "snapshot", "Error scheduling checkpoint rebuild: {:?}", e);
+			self.building_checkpoint.store(false, Ordering::SeqCst);
+		}
+	}
+}
+
 impl Drop for Service {
 	fn drop(&mut self) {
 		self.abort_restore();
@@ -569,7 +851,8 @@ mod tests {
 	use tests::helpers::get_test_spec;
 	use util::journaldb::Algorithm;
 	use error::Error;
-	use snapshot::{ManifestData, RestorationStatus, SnapshotService};
+	use snapshot::{ManifestData, RestorationStatus, SnapshotService, Compression, SNAPSHOT_VERSION};
+	use snapshot::Error as SnapshotError;
 	use super::*;
 
 	struct NoopDBRestore;
@@ -607,11 +890,15 @@ mod tests {
 		assert_eq!(service.status(), RestorationStatus::Inactive);
 
 		let manifest = ManifestData {
+			version: SNAPSHOT_VERSION,
+			compression: Compression::Snappy,
+			engine: String::new(),
 			state_hashes: vec![],
 			block_hashes: vec![],
 			state_root: Default::default(),
 			block_number: 0,
 			block_hash: Default::default(),
+			base: None,
 		};
 
 		service.begin_restore(manifest);
@@ -619,4 +906,32 @@ mod tests {
 		service.restore_state_chunk(Default::default(), vec![]);
 		service.restore_block_chunk(Default::default(), vec![]);
 	}
+
+	#[test]
+	fn restoring_without_manifests_fails() {
+		let service = IoService::<ClientIoMessage>::start().unwrap();
+		let spec = get_test_spec();
+
+		let dir = RandomTempPath::new();
+		let mut dir = dir.as_path().to_owned();
+		dir.push("snapshot");
+
+		let snapshot_params = ServiceParams {
+			engine: spec.engine.clone(),
+			genesis_block: spec.genesis_block(),
+			db_config: Default::default(),
+			pruning: Algorithm::Archive,
+			channel: service.channel(),
+			snapshot_root: dir,
+			db_restore: Arc::new(NoopDBRestore),
+		};
+
+		let service = Service::new(snapshot_params).unwrap();
+
+		match service.init_restore_incremental(Vec::new(), false) {
+			Err(Error::Snapshot(SnapshotError::NoManifests)) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+		assert_eq!(service.status(), RestorationStatus::Inactive);
+	}
 }
\ No newline at end of file
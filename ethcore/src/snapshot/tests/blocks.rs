@@ -59,11 +59,15 @@ fn chunk_and_restore(amount: u64) {
 	let writer = Mutex::new(PackedWriter::new(&snapshot_path).unwrap());
 	let block_hashes = chunk_blocks(&bc, (amount, best_hash), &writer, &Progress::default()).unwrap();
 	writer.into_inner().finish(::snapshot::ManifestData {
+		version: ::snapshot::SNAPSHOT_VERSION,
+		compression: ::snapshot::Compression::Snappy,
+		engine: String::new(),
 		state_hashes: Vec::new(),
 		block_hashes: block_hashes,
 		state_root: Default::default(),
 		block_number: amount,
 		block_hash: best_hash,
+		base: None,
 	}).unwrap();
 
 	// restore it.
@@ -22,17 +22,57 @@ mod service;
 
 pub mod helpers;
 
-use super::ManifestData;
+use super::{ManifestData, Compression, Error, SNAPSHOT_VERSION, decode_manifest};
+use rlp::{RlpStream, Stream};
 
 #[test]
 fn manifest_rlp() {
 	let manifest = ManifestData {
+		version: SNAPSHOT_VERSION,
+		compression: Compression::Snappy,
+		engine: "Ethash".into(),
 		block_hashes: Vec::new(),
 		state_hashes: Vec::new(),
 		block_number: 1234567,
 		state_root: Default::default(),
 		block_hash: Default::default(),
+		base: None,
 	};
 	let raw = manifest.clone().into_rlp();
 	assert_eq!(ManifestData::from_rlp(&raw).unwrap(), manifest);
-}
\ No newline at end of file
+}
+
+#[test]
+fn legacy_manifest_rlp() {
+	let manifest = ManifestData {
+		version: 1,
+		compression: Compression::Snappy,
+		engine: String::new(),
+		block_hashes: vec![Default::default()],
+		state_hashes: Vec::new(),
+		block_number: 1234567,
+		state_root: Default::default(),
+		block_hash: Default::default(),
+		base: None,
+	};
+	let raw = manifest.to_legacy_rlp().unwrap();
+	assert_eq!(ManifestData::version_of(&raw).unwrap(), 1);
+	assert_eq!(decode_manifest(&raw).unwrap(), manifest);
+
+	let uncompressed = ManifestData { compression: Compression::None, ..manifest };
+	assert!(uncompressed.to_legacy_rlp().is_none());
+}
+
+#[test]
+fn unsupported_manifest_version() {
+	let mut stream = RlpStream::new_list(8);
+	stream.append(&(SNAPSHOT_VERSION + 1)).append(&7u32).append(&"Ethash");
+	stream.append(&Vec::<::util::H256>::new()).append(&Vec::<::util::H256>::new());
+	stream.append(&::util::H256::default()).append(&0u64).append(&::util::H256::default());
+	let raw = stream.out();
+
+	match decode_manifest(&raw) {
+		Err(Error::VersionNotSupported(v)) => assert_eq!(v, SNAPSHOT_VERSION + 1),
+		other => panic!("unexpected result: {:?}", other),
+	}
+}
@@ -21,7 +21,7 @@ use std::sync::Arc;
 use client::{BlockChainClient, Client};
 use ids::BlockID;
 use snapshot::service::{Service, ServiceParams};
-use snapshot::{self, ManifestData, SnapshotService};
+use snapshot::{self, ManifestData, SnapshotService, Compression, SNAPSHOT_VERSION};
 use spec::Spec;
 use tests::helpers::generate_dummy_client_with_spec_and_data;
 
@@ -122,11 +122,15 @@ fn guards_delete_folders() {
 	path.push("restoration");
 
 	let manifest = ManifestData {
+		version: SNAPSHOT_VERSION,
+		compression: Compression::Snappy,
+		engine: String::new(),
 		state_hashes: vec![],
 		block_hashes: vec![],
 		block_number: 0,
 		block_hash: Default::default(),
 		state_root: Default::default(),
+		base: None,
 	};
 
 	service.init_restore(manifest.clone(), true).unwrap();
@@ -140,4 +144,37 @@ fn guards_delete_folders() {
 
 	drop(service);
 	assert!(!path.exists());
-}
\ No newline at end of file
+}
+#[test]
+fn rejects_incompatible_manifests() {
+	let spec = Spec::new_null();
+	let path = RandomTempPath::create_dir();
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.as_path().clone(),
+		db_restore: Arc::new(NoopDBRestore),
+	};
+
+	let service = Service::new(service_params).unwrap();
+
+	let manifest = ManifestData {
+		version: SNAPSHOT_VERSION,
+		compression: Compression::Snappy,
+		engine: "SomeOtherEngine".into(),
+		state_hashes: vec![],
+		block_hashes: vec![],
+		block_number: 0,
+		block_hash: Default::default(),
+		state_root: Default::default(),
+		base: None,
+	};
+
+	assert!(service.init_restore(manifest.clone(), false).is_err());
+	assert!(service.init_restore(ManifestData { version: SNAPSHOT_VERSION + 1, engine: String::new(), ..manifest.clone() }, false).is_err());
+	assert!(service.init_restore(ManifestData { base: Some(Default::default()), engine: String::new(), ..manifest.clone() }, false).is_err());
+	assert!(service.init_restore(ManifestData { engine: spec.engine.name().to_owned(), ..manifest }, false).is_ok());
+}
@@ -16,18 +16,20 @@
 
 //! State snapshotting tests.
 
-use snapshot::{chunk_state, Progress, StateRebuilder};
+use snapshot::{chunk_state, chunk_state_changes, Progress, StateRebuilder};
 use snapshot::io::{PackedReader, PackedWriter, SnapshotReader, SnapshotWriter};
 use super::helpers::{compare_dbs, StateProducer};
 
 use rand::{XorShiftRng, SeedableRng};
-use util::hash::H256;
+use util::hash::{FixedHash, H256};
+use util::trie::{TrieDB, TrieDBMut, Trie, TrieMut};
 use util::journaldb::{self, Algorithm};
 use util::kvdb::{Database, DatabaseConfig};
 use util::memorydb::MemoryDB;
 use util::Mutex;
 use devtools::RandomTempPath;
 
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 #[test]
@@ -51,11 +53,15 @@ fn snap_and_restore() {
 	let state_hashes = chunk_state(&old_db, &state_root, &writer, &Progress::default()).unwrap();
 
 	writer.into_inner().finish(::snapshot::ManifestData {
+		version: ::snapshot::SNAPSHOT_VERSION,
+		compression: ::snapshot::Compression::Snappy,
+		engine: String::new(),
 		state_hashes: state_hashes,
 		block_hashes: Vec::new(),
 		state_root: state_root,
 		block_number: 0,
 		block_hash: H256::default(),
+		base: None,
 	}).unwrap();
 
 	let mut db_path = snap_dir.as_path().to_owned();
@@ -82,3 +88,98 @@ fn snap_and_restore() {
 
 	compare_dbs(&old_db, new_db.as_hashdb());
 }
+
+#[test]
+fn incremental_snap_and_restore() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+	let mut old_db = MemoryDB::new();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	for _ in 0..100 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let base_root = producer.state_root();
+	let base_accounts: HashMap<H256, Vec<u8>> = TrieDB::new(&old_db, &base_root).unwrap().iter()
+		.map(|(k, v)| (H256::from_slice(&k), v.to_vec()))
+		.collect();
+
+	for _ in 0..20 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	// remove one of the base accounts.
+	let removed = base_accounts.keys().next().cloned().unwrap();
+	let mut new_root = producer.state_root();
+	TrieDBMut::from_existing(&mut old_db, &mut new_root).unwrap().remove(&removed).unwrap();
+
+	let changes: HashSet<H256> = {
+		let trie = TrieDB::new(&old_db, &new_root).unwrap();
+		let mut changes: HashSet<_> = trie.iter()
+			.filter(|&(ref k, v)| base_accounts.get(&H256::from_slice(k)).map_or(true, |old| &old[..] != v))
+			.map(|(k, _)| H256::from_slice(&k))
+			.collect();
+		changes.insert(removed);
+		changes
+	};
+	assert!(changes.len() < base_accounts.len());
+
+	let snap_dir = RandomTempPath::create_dir();
+	let mut base_file = snap_dir.as_path().to_owned();
+	base_file.push("BASE");
+	let mut incremental_file = snap_dir.as_path().to_owned();
+	incremental_file.push("INCREMENTAL");
+
+	let writer = Mutex::new(PackedWriter::new(&base_file).unwrap());
+	let state_hashes = chunk_state(&old_db, &base_root, &writer, &Progress::default()).unwrap();
+	writer.into_inner().finish(::snapshot::ManifestData {
+		version: ::snapshot::SNAPSHOT_VERSION,
+		compression: ::snapshot::Compression::Snappy,
+		engine: String::new(),
+		state_hashes: state_hashes,
+		block_hashes: Vec::new(),
+		state_root: base_root,
+		block_number: 0,
+		block_hash: H256::default(),
+		base: None,
+	}).unwrap();
+
+	let writer = Mutex::new(PackedWriter::new(&incremental_file).unwrap());
+	let state_hashes = chunk_state_changes(&old_db, &new_root, &changes, &writer, &Progress::default()).unwrap();
+	writer.into_inner().finish(::snapshot::ManifestData {
+		version: ::snapshot::SNAPSHOT_VERSION,
+		compression: ::snapshot::Compression::Snappy,
+		engine: String::new(),
+		state_hashes: state_hashes,
+		block_hashes: Vec::new(),
+		state_root: new_root,
+		block_number: 1,
+		block_hash: H256::from(1u64),
+		base: Some(H256::default()),
+	}).unwrap();
+
+	let mut db_path = snap_dir.as_path().to_owned();
+	db_path.push("db");
+	let new_db = Arc::new(Database::open(&db_cfg, &db_path.to_string_lossy()).unwrap());
+	let mut rebuilder = StateRebuilder::new(new_db.clone(), Algorithm::Archive);
+
+	for (file, root) in vec![(base_file, base_root), (incremental_file, new_root)] {
+		let reader = PackedReader::new(&file).unwrap().unwrap();
+		for chunk_hash in &reader.manifest().state_hashes {
+			let raw = reader.chunk(*chunk_hash).unwrap();
+			let chunk = ::util::snappy::decompress(&raw).unwrap();
+
+			rebuilder.feed(&chunk).unwrap();
+		}
+
+		assert_eq!(rebuilder.state_root(), root);
+	}
+
+	rebuilder.check_missing().unwrap();
+
+	let new_db = journaldb::new(new_db, Algorithm::Archive, ::db::COL_STATE);
+	let restored = TrieDB::new(new_db.as_hashdb(), &new_root).unwrap();
+	assert!(!restored.contains(&removed).unwrap());
+	assert_eq!(restored.iter().count(), TrieDB::new(&old_db, &new_root).unwrap().iter().count());
+}
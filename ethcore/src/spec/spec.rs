@@ -40,6 +40,8 @@ pub struct CommonParams {
 	pub min_gas_limit: U256,
 	/// Fork block to check.
 	pub fork_block: Option<(BlockNumber, H256)>,
+	/// Contract deciding which transactions senders are permitted to send.
+	pub transaction_permission_contract: Option<Address>,
 }
 
 impl From<ethjson::spec::Params> for CommonParams {
@@ -50,6 +52,7 @@ impl From<ethjson::spec::Params> for CommonParams {
 			network_id: p.network_id.into(),
 			min_gas_limit: p.min_gas_limit.into(),
 			fork_block: if let (Some(n), Some(h)) = (p.fork_block, p.fork_hash) { Some((n.into(), h.into())) } else { None },
+			transaction_permission_contract: p.transaction_permission_contract.map(Into::into),
 		}
 	}
 }
@@ -226,17 +229,17 @@ impl Spec {
 	}
 
 	/// Ensure that the given state DB has the trie nodes in for the genesis state.
-	pub fn ensure_db_good(&self, db: &mut HashDB) -> Result<bool, Box<TrieError>> {
+	pub fn ensure_db_good(&self, db: &mut HashDB, trie_factory: &TrieFactory) -> Result<bool, Box<TrieError>> {
 		if !db.contains(&self.state_root()) {
 			let mut root = H256::new();
 			{
-				let mut t = SecTrieDBMut::new(db, &mut root);
+				let mut t = trie_factory.create(db, &mut root);
 				for (address, account) in self.genesis_state.get().iter() {
 					try!(t.insert(&**address, &account.rlp()));
 				}
 			}
 			for (address, account) in self.genesis_state.get().iter() {
-				account.insert_additional(&mut AccountDBMut::new(db, address));
+				account.insert_additional(&mut AccountDBMut::new(db, address), trie_factory);
 			}
 			assert!(db.contains(&self.state_root()));
 			Ok(true)
@@ -265,6 +268,13 @@ impl Spec {
 	pub fn new_test_instant() -> Self {
 		Spec::load(include_bytes!("../../res/instant_seal.json") as &[u8]).expect("instant_seal.json is invalid")
 	}
+
+	/// Create a new Spec with BasicAuthority consensus whose validator is read from a contract.
+	/// The validator is the address whose secret is sha3('') until a call with another address in its data
+	/// is made to the contract.
+	pub fn new_validator_contract() -> Self {
+		Spec::load(include_bytes!("../../res/validator_contract.json") as &[u8]).expect("validator_contract.json is invalid")
+	}
 }
 
 #[cfg(test)]
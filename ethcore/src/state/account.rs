@@ -258,6 +258,18 @@ impl Account {
 		self.filth = Filth::Dirty;
 	}
 
+	/// Replace the nonce of the account.
+	pub fn set_nonce(&mut self, nonce: U256) {
+		self.nonce = nonce;
+		self.filth = Filth::Dirty;
+	}
+
+	/// Replace the balance of the account.
+	pub fn set_balance(&mut self, balance: U256) {
+		self.balance = balance;
+		self.filth = Filth::Dirty;
+	}
+
 	/// Increment the nonce of the account by one.
 	pub fn add_balance(&mut self, x: &U256) {
 		if !x.is_zero() {
@@ -23,6 +23,7 @@ use trace::FlatTrace;
 use pod_account::*;
 use pod_state::{self, PodState};
 use types::state_diff::StateDiff;
+use types::state_override::StateOverride;
 
 mod account;
 mod substate;
@@ -191,6 +192,11 @@ impl State {
 		}))
 	}
 
+	/// Get the storage root of account `a`, or `None` if it doesn't exist.
+	pub fn storage_root(&self, a: &Address) -> Option<H256> {
+		self.ensure_cached(a, false, |a| a.as_ref().and_then(|account| account.storage_root().cloned()))
+	}
+
 	/// Mutate storage of account `a` so that it is `value` for `key`.
 	pub fn code(&self, a: &Address) -> Option<Bytes> {
 		self.ensure_cached(a, true,
@@ -236,6 +242,27 @@ impl State {
 		self.require_or_from(a, true, || Account::new_contract(0.into(), self.account_start_nonce), |_|{}).reset_code(code);
 	}
 
+	/// Apply account overrides to the cached state. Meant for throwaway copies of the state
+	/// used by non-persistent calls.
+	pub fn apply_override(&mut self, state_override: &StateOverride) {
+		for (address, account) in &state_override.accounts {
+			trace!(target: "state", "apply_override({}): {:?}", address, account);
+			if let Some(ref code) = account.code {
+				self.reset_code(address, code.clone());
+			}
+			let mut acc = self.require(address, false);
+			if let Some(balance) = account.balance {
+				acc.set_balance(balance);
+			}
+			if let Some(nonce) = account.nonce {
+				acc.set_nonce(nonce);
+			}
+			for (key, value) in &account.storage {
+				acc.set_storage(key.clone(), value.clone());
+			}
+		}
+	}
+
 	/// Execute a given transaction.
 	/// This will change the state accordingly.
 	pub fn apply(&mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool) -> ApplyResult {
@@ -299,6 +326,11 @@ impl State {
 		Self::commit_into(&self.factories, self.db.as_hashdb_mut(), &mut self.root, &mut *self.cache.borrow_mut())
 	}
 
+	/// Addresses of all accounts loaded or modified since the cache was last cleared.
+	pub fn cached_addresses(&self) -> Vec<Address> {
+		self.cache.borrow().keys().cloned().collect()
+	}
+
 	/// Clear state cache
 	pub fn clear(&mut self) {
 		self.cache.borrow_mut().clear();
@@ -446,6 +478,7 @@ use transaction::*;
 use util::log::init_log;
 use trace::{FlatTrace, TraceError, trace};
 use types::executed::CallType;
+use types::state_override::{StateOverride, AccountOverride};
 
 #[test]
 fn should_apply_create_transaction() {
@@ -1477,6 +1510,39 @@ fn snapshot_nested() {
 	assert_eq!(state.balance(&a), U256::from(0));
 }
 
+#[test]
+fn apply_override() {
+	let mut state_result = get_temp_state();
+	let mut state = state_result.reference_mut();
+	let a = Address::zero();
+	let b = 1u64.into();
+	state.add_balance(&a, &U256::from(69u64));
+	state.set_storage(&a, H256::from(&U256::from(1u64)), H256::from(&U256::from(1u64)));
+	state.set_storage(&a, H256::from(&U256::from(2u64)), H256::from(&U256::from(2u64)));
+	state.commit().unwrap();
+	let root = state.root().clone();
+
+	let mut state_override = StateOverride::default();
+	state_override.accounts.insert(a, AccountOverride {
+		balance: Some(U256::from(1u64)),
+		nonce: Some(U256::from(42u64)),
+		code: None,
+		storage: vec![(H256::from(&U256::from(2u64)), H256::from(&U256::from(3u64)))].into_iter().collect(),
+	});
+	state_override.accounts.insert(b, AccountOverride {
+		code: Some(vec![0x60, 0x00]),
+		..Default::default()
+	});
+	state.apply_override(&state_override);
+
+	assert_eq!(state.balance(&a), U256::from(1u64));
+	assert_eq!(state.nonce(&a), U256::from(42u64));
+	assert_eq!(state.storage_at(&a, &H256::from(&U256::from(1u64))), H256::from(&U256::from(1u64)));
+	assert_eq!(state.storage_at(&a, &H256::from(&U256::from(2u64))), H256::from(&U256::from(3u64)));
+	assert_eq!(state.code(&b), Some(vec![0x60, 0x00]));
+	assert_eq!(state.root(), &root);
+}
+
 #[test]
 fn create_empty() {
 	let mut state_result = get_temp_state();
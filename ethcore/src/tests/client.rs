@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use io::IoChannel;
-use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockID};
+use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockID, MIN_HISTORY};
 use ethereum;
 use block::IsBlock;
 use tests::helpers::*;
@@ -222,6 +222,50 @@ fn can_handle_long_fork() {
 	assert_eq!(2000, client.chain_info().best_block_number);
 }
 
+fn client_with_history(dir: &RandomTempPath, history: u64, history_mem: usize) -> Arc<Client> {
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let mut config = ClientConfig::default();
+	config.history = history;
+	config.history_mem = history_mem;
+
+	let client = Client::new(
+		config,
+		&spec,
+		dir.as_path(),
+		Arc::new(Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+		&db_config
+	).unwrap();
+
+	push_blocks_to_client(&client, 53, 1, 100);
+	client.flush_queue();
+	client.import_verified_blocks();
+	assert_eq!(100, client.chain_info().best_block_number);
+	client
+}
+
+#[test]
+fn keeps_configured_state_history() {
+	let dir = RandomTempPath::new();
+	let client = client_with_history(&dir, 70, 0);
+
+	let pruning = client.pruning_info();
+	assert_eq!(pruning.history_size, 70);
+	assert_eq!(pruning.earliest_state, 31);
+	assert!(client.state_at(BlockID::Number(31)).is_some());
+}
+
+#[test]
+fn shrinks_state_history_over_memory_limit() {
+	let dir = RandomTempPath::new();
+	let client = client_with_history(&dir, 1200, 1);
+
+	let pruning = client.pruning_info();
+	assert_eq!(pruning.history_size, 1200);
+	assert_eq!(pruning.earliest_state, 101 - MIN_HISTORY);
+}
+
 #[test]
 fn can_mine() {
 	let dummy_blocks = get_good_dummy_block_seq(2);
@@ -232,3 +276,374 @@ fn can_mine() {
 
 	assert_eq!(*b.block().header().parent_hash(), BlockView::new(&dummy_blocks[0]).header_view().sha3());
 }
+
+#[test]
+fn can_call_many_on_same_state() {
+	use client::CallAnalytics;
+	use transaction::{Transaction, Action};
+	use types::account_diff::Diff;
+
+	let client_result = generate_dummy_client(0);
+	let client = client_result.reference();
+	let sender = Address::from(0xfeedu64);
+	let recipient = Address::from(0xbeefu64);
+	let transaction = Transaction {
+		nonce: 0.into(),
+		action: Action::Call(recipient),
+		gas: 21000.into(),
+		gas_price: 0.into(),
+		value: 10.into(),
+		data: vec![],
+	}.fake_sign(sender);
+	let analytics = CallAnalytics { transaction_tracing: false, vm_tracing: false, state_diffing: true };
+
+	let results = client.call_many(vec![transaction.clone(), transaction], BlockID::Latest, analytics, vec![]).unwrap();
+
+	assert_eq!(results.len(), 2);
+	let diff = results[1].state_diff.as_ref().unwrap();
+	assert_eq!(diff.raw[&recipient].balance, Diff::Changed(10.into(), 20.into()));
+	assert_eq!(client.latest_balance(&recipient), 0.into());
+}
+
+#[test]
+#[cfg_attr(feature="dev", allow(useless_vec))]
+fn can_replay_block_transactions() {
+	use client::{CallAnalytics, TransactionID};
+	use types::account_diff::Diff;
+
+	let client_result = generate_dummy_client_with_data(2, 3, &vec_into![1, 2, 3, 4, 5, 6]);
+	let client = client_result.reference();
+	let analytics = CallAnalytics { transaction_tracing: true, vm_tracing: false, state_diffing: true };
+
+	let results = client.replay_block_transactions(BlockID::Number(2), analytics).unwrap();
+
+	assert_eq!(results.len(), 3);
+	for (i, result) in results.into_iter().enumerate() {
+		let id = TransactionID::Location(BlockID::Number(2), i);
+		let receipt = client.transaction_receipt(id.clone()).unwrap();
+		let transaction = client.transaction(id.clone()).unwrap();
+		let sender = transaction.sender().unwrap();
+
+		// replayed execution matches what was recorded on import.
+		assert_eq!(result.gas_used, receipt.gas_used);
+		assert_eq!(result.cumulative_gas_used, receipt.cumulative_gas_used);
+		let diff = result.state_diff.as_ref().unwrap();
+		assert_eq!(diff.raw[&sender].nonce, Diff::Changed(transaction.nonce, transaction.nonce + U256::one()));
+		assert!(diff.raw.contains_key(&receipt.contract_address.unwrap()));
+
+		let single = client.replay(id, analytics).unwrap();
+		assert_eq!(result, single);
+	}
+}
+
+#[test]
+fn explains_unavailable_state() {
+	use client::StateError;
+
+	let client_result = generate_dummy_client(3);
+	let client = client_result.reference();
+
+	assert!(client.try_state_at(BlockID::Number(3)).is_ok());
+	match client.try_state_at(BlockID::Number(4)) {
+		Err(StateError::UnknownBlock) => {},
+		other => panic!("unexpected result: {:?}", other.map(|_| ())),
+	}
+}
+
+mod replay {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use client::{BlockChainClient, Client, ClientConfig, BlockID, StateError, MIN_HISTORY};
+	use snapshot::StateCheckpoints;
+	use tests::helpers::*;
+	use common::*;
+	use devtools::*;
+	use io::IoChannel;
+	use miner::Miner;
+
+	// checkpoint source with the genesis state, or none at all if `ready` is not set.
+	struct GenesisCheckpoint {
+		hash: H256,
+		root: H256,
+		db: Arc<Database>,
+		ready: bool,
+		prepared: AtomicUsize,
+		_temp: RandomTempPath,
+	}
+
+	impl GenesisCheckpoint {
+		fn new(ready: bool) -> GenesisCheckpoint {
+			let temp = RandomTempPath::new();
+			let db = Arc::new(Database::open(&DatabaseConfig::with_columns(::db::NUM_COLUMNS), temp.as_str()).unwrap());
+			let spec = get_test_spec();
+			let mut state_db = journaldb::new(db.clone(), journaldb::Algorithm::Archive, ::db::COL_STATE);
+			spec.ensure_db_good(state_db.as_hashdb_mut(), &Default::default()).unwrap();
+			state_db.commit_batch(0, &spec.genesis_header().hash(), None).unwrap();
+
+			GenesisCheckpoint {
+				hash: spec.genesis_header().hash(),
+				root: spec.state_root(),
+				db: db,
+				ready: ready,
+				prepared: AtomicUsize::new(0),
+				_temp: temp,
+			}
+		}
+	}
+
+	impl StateCheckpoints for GenesisCheckpoint {
+		fn checkpoint_at_or_before(&self, _number: BlockNumber) -> Option<(BlockNumber, H256)> {
+			Some((0, self.hash))
+		}
+
+		fn checkpoint_state(&self, hash: &H256) -> Option<(Arc<Database>, H256)> {
+			match self.ready && hash == &self.hash {
+				true => Some((self.db.clone(), self.root)),
+				false => None,
+			}
+		}
+
+		fn prepare_checkpoint(&self, _hash: &H256) {
+			self.prepared.fetch_add(1, Ordering::SeqCst);
+		}
+	}
+
+	// client keeping `MIN_HISTORY` recent states of a chain longer than that.
+	fn pruned_client(replay_limit: u64, checkpoints: &Arc<StateCheckpoints>) -> (GuardedTempResult<Arc<Client>>, GuardedTempResult<Arc<Client>>) {
+		let source = generate_dummy_client(MIN_HISTORY as u32 + 4);
+		let dir = RandomTempPath::new();
+		let spec = get_test_spec();
+		let mut config = ClientConfig::default();
+		config.pruning = journaldb::Algorithm::OverlayRecent;
+		config.history = MIN_HISTORY;
+		config.state_replay_limit = replay_limit;
+
+		let client = Client::new(
+			config,
+			&spec,
+			dir.as_path(),
+			Arc::new(Miner::with_spec(&spec)),
+			IoChannel::disconnected(),
+			&DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		).unwrap();
+		for number in 1..(MIN_HISTORY + 5) {
+			client.import_block(source.reference().block(BlockID::Number(number)).unwrap()).unwrap();
+		}
+		client.flush_queue();
+		client.import_verified_blocks();
+		client.set_state_checkpoints(Arc::downgrade(checkpoints));
+
+		(source, GuardedTempResult { _temp: dir, result: Some(client) })
+	}
+
+	fn state_root(client: &Client, number: BlockNumber) -> H256 {
+		HeaderView::new(&client.block_header(BlockID::Number(number)).unwrap()).state_root()
+	}
+
+	#[test]
+	fn replays_pruned_state() {
+		let checkpoints: Arc<StateCheckpoints> = Arc::new(GenesisCheckpoint::new(true));
+		let (_source, client) = pruned_client(MIN_HISTORY, &checkpoints);
+		let client = client.reference();
+
+		match client.try_state_at(BlockID::Number(2)) {
+			Ok(state) => assert_eq!(state.root(), &state_root(client, 2)),
+			Err(e) => panic!("unexpected error: {}", e),
+		}
+		// re-uses the reconstructed state
+		let state = client.try_state_at(BlockID::Number(3)).ok().expect("state of block 3 follows from block 2");
+		assert_eq!(state.root(), &state_root(client, 3));
+	}
+
+	#[test]
+	fn refuses_to_replay_too_many_blocks() {
+		let checkpoints: Arc<StateCheckpoints> = Arc::new(GenesisCheckpoint::new(true));
+		let (_source, client) = pruned_client(2, &checkpoints);
+		let client = client.reference();
+
+		match client.try_state_at(BlockID::Number(4)) {
+			Err(StateError::ReplayLimitExceeded { required: 4, limit: 2 }) => {},
+			other => panic!("unexpected result: {:?}", other.map(|_| ())),
+		}
+		assert!(client.try_state_at(BlockID::Number(2)).is_ok());
+	}
+
+	#[test]
+	fn schedules_checkpoint_rebuild_instead_of_waiting() {
+		let checkpoint = Arc::new(GenesisCheckpoint::new(false));
+		let checkpoints: Arc<StateCheckpoints> = checkpoint.clone();
+		let (_source, client) = pruned_client(MIN_HISTORY, &checkpoints);
+		let client = client.reference();
+
+		match client.try_state_at(BlockID::Number(2)) {
+			Err(StateError::CheckpointPending) => {},
+			other => panic!("unexpected result: {:?}", other.map(|_| ())),
+		}
+		assert_eq!(checkpoint.prepared.load(Ordering::SeqCst), 1);
+	}
+}
+
+#[test]
+fn lists_accounts_and_storage_in_fat_db() {
+	use util::trie::TrieSpec;
+
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let mut config = ClientConfig::default();
+	config.trie_spec = TrieSpec::Fat;
+	config.pruning = journaldb::Algorithm::Archive;
+
+	let client = Client::new(
+		config,
+		&spec,
+		dir.as_path(),
+		Arc::new(Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+		&db_config
+	).unwrap();
+
+	assert!(client.is_fat_db());
+	let accounts = client.list_accounts(BlockID::Latest, None, 100).unwrap();
+	assert_eq!(accounts.len(), 5);
+	assert!(accounts.contains(&"102e61f5d8f9bc71d0ad4a084df4e65e05ce0e1c".into()));
+
+	let first = client.list_accounts(BlockID::Latest, None, 2).unwrap();
+	let rest = client.list_accounts(BlockID::Latest, Some(first[1]), 100).unwrap();
+	assert_eq!(first.into_iter().chain(rest.into_iter()).collect::<Vec<_>>(), accounts);
+
+	assert_eq!(client.list_storage(BlockID::Latest, &accounts[0], None, 100), Some(Vec::new()));
+}
+
+#[test]
+fn refuses_to_enable_fat_db_on_synced_database() {
+	use util::trie::TrieSpec;
+	use client::Error as ClientError;
+
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let open = |trie_spec| {
+		let mut config = ClientConfig::default();
+		config.trie_spec = trie_spec;
+		config.pruning = journaldb::Algorithm::Archive;
+		Client::new(config, &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected(), &db_config)
+	};
+
+	{
+		let client = open(TrieSpec::Secure).unwrap();
+		client.import_block(get_good_dummy_block()).unwrap();
+		client.flush_queue();
+		client.import_verified_blocks();
+		assert_eq!(client.chain_info().best_block_number, 1);
+	}
+
+	match open(TrieSpec::Fat) {
+		Err(ClientError::FatDbResyncRequired) => {},
+		other => panic!("unexpected result: {:?}", other.map(|_| ())),
+	}
+	// database keeps working without fat DB.
+	assert!(open(TrieSpec::Secure).is_ok());
+}
+
+#[test]
+fn opens_fat_database_without_flag() {
+	use util::trie::TrieSpec;
+
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let open = || {
+		let mut config = ClientConfig::default();
+		config.trie_spec = TrieSpec::Fat;
+		config.pruning = journaldb::Algorithm::Archive;
+		Client::new(config, &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected(), &db_config)
+	};
+
+	{
+		let client = open().unwrap();
+		client.import_block(get_good_dummy_block()).unwrap();
+		client.flush_queue();
+		client.import_verified_blocks();
+		assert_eq!(client.chain_info().best_block_number, 1);
+	}
+
+	// a database synced with fat DB before the flag was stored.
+	{
+		let db = Database::open(&db_config, dir.as_str()).unwrap();
+		let mut batch = DBTransaction::new(&db);
+		batch.delete(::db::COL_EXTRA, b"fat_db");
+		db.write(batch).unwrap();
+	}
+
+	let client = open().unwrap();
+	assert!(client.is_fat_db());
+	assert_eq!(client.chain_info().best_block_number, 1);
+}
+
+#[test]
+fn does_not_list_accounts_without_fat_db() {
+	let client_result = generate_dummy_client(1);
+	let client = client_result.reference();
+
+	assert!(!client.is_fat_db());
+	assert_eq!(client.list_accounts(BlockID::Latest, None, 100), None);
+	assert_eq!(client.list_storage(BlockID::Latest, &Address::default(), None, 100), None);
+}
+
+#[test]
+#[cfg_attr(feature="dev", allow(useless_vec))]
+fn checks_state_and_chain() {
+	let client_result = generate_dummy_client_with_data(2, 3, &vec_into![1, 2, 3, 4, 5, 6]);
+	let client = client_result.reference();
+
+	let check = client.check_state(true);
+	assert!(check.is_ok());
+	assert_eq!(check.number, 2);
+	assert!(check.accounts > 0);
+	assert_eq!(client.check_chain(), vec![]);
+}
+
+#[test]
+fn repairs_missing_state_node() {
+	use client::{NodeProblem, NodeLocation};
+
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let open = || {
+		let mut config = ClientConfig::default();
+		config.pruning = journaldb::Algorithm::Archive;
+		Client::new(config, &spec, dir.as_path(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected(), &db_config).unwrap()
+	};
+
+	let (root, data) = {
+		let client = open();
+		assert!(client.check_state(true).is_ok());
+		let root = HeaderView::new(&client.best_block_header()).state_root();
+		let data = client.state_data(&root).unwrap();
+		(root, data)
+	};
+
+	// drop the state root from the database behind the client's back.
+	{
+		let db = Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap();
+		let mut batch = DBTransaction::new(&db);
+		batch.delete(::db::COL_STATE, &root);
+		db.write(batch).unwrap();
+	}
+
+	let client = open();
+	let check = client.check_state(true);
+	assert_eq!(check.bad_nodes.len(), 1);
+	assert_eq!(check.bad_nodes[0].hash, root);
+	assert_eq!(check.bad_nodes[0].location, NodeLocation::State);
+	assert_eq!(check.bad_nodes[0].problem, NodeProblem::Missing);
+
+	let mut retrieved = HashMap::new();
+	retrieved.insert(root.clone(), data);
+	assert_eq!(client.repair_state(&check.bad_nodes, &retrieved).unwrap(), 1);
+
+	let check = client.check_state(true);
+	assert!(check.is_ok());
+	assert!(check.accounts > 0);
+}
@@ -0,0 +1,74 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bad block type definition
+
+use util::{H256, Bytes};
+use rlp::*;
+
+/// A block rejected during import.
+#[derive(Debug, Clone, PartialEq, Binary)]
+pub struct BadBlock {
+	/// Hash of the block.
+	pub hash: H256,
+	/// Block RLP.
+	pub bytes: Bytes,
+	/// Peer the block was received from, if known.
+	pub origin: Option<String>,
+	/// Why the block was rejected.
+	pub reason: String,
+}
+
+impl Encodable for BadBlock {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(4);
+		s.append(&self.hash);
+		s.append(&self.bytes);
+		s.append(&self.origin);
+		s.append(&self.reason);
+	}
+}
+
+impl Decodable for BadBlock {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let d = decoder.as_rlp();
+		Ok(BadBlock {
+			hash: try!(d.val_at(0)),
+			bytes: try!(d.val_at(1)),
+			origin: try!(d.val_at(2)),
+			reason: try!(d.val_at(3)),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::BadBlock;
+
+	#[test]
+	fn encodes_and_decodes() {
+		let block = BadBlock {
+			hash: 5.into(),
+			bytes: vec![0xc0],
+			origin: Some("Geth/v1.4.18/linux/go1.7.3 (127.0.0.1:30303)".into()),
+			reason: "Block(InvalidStateRoot)".into(),
+		};
+		assert_eq!(::rlp::decode::<BadBlock>(&::rlp::encode(&block)), block);
+
+		let block = BadBlock { origin: None, ..block };
+		assert_eq!(::rlp::decode::<BadBlock>(&::rlp::encode(&block)), block);
+	}
+}
@@ -172,8 +172,19 @@ impl fmt::Display for ExecutionError {
 pub enum CallError {
 	/// Couldn't find the transaction in the chain.
 	TransactionNotFound,
+	/// Couldn't find the block.
+	BlockNotFound,
 	/// Couldn't find requested block's state in the chain.
 	StatePruned,
+	/// Requested block's state has been pruned and reconstructing it would take too long.
+	ReplayLimitExceeded {
+		/// Number of blocks which would have to be re-executed.
+		required: u64,
+		/// Maximum number of blocks allowed to be re-executed.
+		limit: u64,
+	},
+	/// Requested block's state has been pruned and the checkpoint to reconstruct it from is being rebuilt.
+	CheckpointPending,
 	/// Error executing.
 	Execution(ExecutionError),
 }
@@ -190,7 +201,10 @@ impl fmt::Display for CallError {
 
 		let msg = match *self {
 			TransactionNotFound => "Transaction couldn't be found in the chain".into(),
+			BlockNotFound => "Block couldn't be found".into(),
 			StatePruned => "Couldn't find the transaction block's state in the chain".into(),
+			ReplayLimitExceeded { required, limit } => format!("Reconstructing the block's pruned state requires re-executing {} blocks, limit is {}", required, limit),
+			CheckpointPending => "The block's pruned state is being reconstructed, try again later".into(),
 			Execution(ref e) => format!("{}", e),
 		};
 
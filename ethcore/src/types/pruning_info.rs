@@ -0,0 +1,29 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pruning info type definition
+
+use header::BlockNumber;
+
+/// Information about which recent states the client keeps.
+#[derive(Debug, Clone, PartialEq, Binary)]
+pub struct PruningInfo {
+	/// The earliest block whose state can be queried without re-executing blocks.
+	pub earliest_state: BlockNumber,
+	/// Maximum number of recent states kept. The actual number may be lower when the journal
+	/// exceeds its memory limit.
+	pub history_size: u64,
+}
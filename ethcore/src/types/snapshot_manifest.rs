@@ -20,9 +20,52 @@ use util::hash::H256;
 use rlp::*;
 use util::Bytes;
 
+/// Current snapshot format version.
+/// Version 2 adds compression and engine information, version 3 adds incremental snapshots.
+pub const SNAPSHOT_VERSION: u64 = 3;
+
+/// Oldest snapshot format version which can still be restored.
+/// Version 1 manifests carry no version information and always use snappy compression.
+pub const MIN_SUPPORTED_VERSION: u64 = 1;
+
+/// Compression algorithm applied to snapshot chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Binary)]
+pub enum Compression {
+	/// Chunks are stored uncompressed.
+	None,
+	/// Chunks are snappy-compressed.
+	Snappy,
+}
+
+impl Encodable for Compression {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		let v = match *self {
+			Compression::None => 0u32,
+			Compression::Snappy => 1,
+		};
+		s.append(&v);
+	}
+}
+
+impl Decodable for Compression {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		decoder.as_rlp().as_val().and_then(|v| Ok(match v {
+			0u32 => Compression::None,
+			1 => Compression::Snappy,
+			_ => return Err(DecoderError::Custom("Unknown snapshot compression")),
+		}))
+	}
+}
+
 /// Manifest data.
 #[derive(Debug, Clone, PartialEq, Eq, Binary)]
 pub struct ManifestData {
+	/// Snapshot format version.
+	pub version: u64,
+	/// Compression used for all chunks.
+	pub compression: Compression,
+	/// Name of the consensus engine the snapshot was taken with. Empty if unknown.
+	pub engine: String,
 	/// List of state chunk hashes.
 	pub state_hashes: Vec<H256>,
 	/// List of block chunk hashes.
@@ -33,38 +76,103 @@ pub struct ManifestData {
 	pub block_number: u64,
 	/// Block hash this snapshot was taken at.
 	pub block_hash: H256,
+	/// For incremental snapshots, the block hash of the snapshot this one is based on.
+	/// Such snapshots only contain the accounts changed since their base.
+	pub base: Option<H256>,
 }
 
 impl ManifestData {
 	/// Encode the manifest data to rlp.
 	pub fn into_rlp(self) -> Bytes {
-		let mut stream = RlpStream::new_list(5);
+		let base: Vec<H256> = self.base.into_iter().collect();
+
+		let mut stream = RlpStream::new_list(9);
+		stream.append(&self.version);
+		stream.append(&self.compression);
+		stream.append(&self.engine);
 		stream.append(&self.state_hashes);
 		stream.append(&self.block_hashes);
 		stream.append(&self.state_root);
 		stream.append(&self.block_number);
 		stream.append(&self.block_hash);
+		stream.append(&base);
 
 		stream.out()
 	}
 
+	/// Encode the manifest data in the unversioned format understood by older clients.
+	/// Returns `None` if the chunks can't be read by them.
+	pub fn to_legacy_rlp(&self) -> Option<Bytes> {
+		if self.compression != Compression::Snappy || self.base.is_some() {
+			return None;
+		}
+
+		let mut stream = RlpStream::new_list(5);
+		stream.append(&self.state_hashes);
+		stream.append(&self.block_hashes);
+		stream.append(&self.state_root);
+		stream.append(&self.block_number);
+		stream.append(&self.block_hash);
+
+		Some(stream.out())
+	}
+
+	/// Read the format version of encoded manifest data without decoding the rest of it.
+	pub fn version_of(raw: &[u8]) -> Result<u64, DecoderError> {
+		let decoder = UntrustedRlp::new(raw);
+		if !decoder.is_list() {
+			return Err(DecoderError::RlpExpectedToBeList);
+		}
+		match decoder.item_count() {
+			5 => Ok(1),
+			_ => decoder.val_at(0),
+		}
+	}
+
 	/// Try to restore manifest data from raw bytes, interpreted as RLP.
+	/// Both the versioned and the legacy unversioned encodings are accepted.
 	pub fn from_rlp(raw: &[u8]) -> Result<Self, DecoderError> {
 		let decoder = UntrustedRlp::new(raw);
+		if !decoder.is_list() {
+			return Err(DecoderError::RlpExpectedToBeList);
+		}
+
+		let (version, compression, engine, offset) = match decoder.item_count() {
+			5 => (1, Compression::Snappy, String::new(), 0),
+			8 | 9 => (try!(decoder.val_at(0)), try!(decoder.val_at(1)), try!(decoder.val_at(2)), 3),
+			_ => return Err(DecoderError::RlpIncorrectListLen),
+		};
 
-		let state_hashes: Vec<H256> = try!(decoder.val_at(0));
-		let block_hashes: Vec<H256> = try!(decoder.val_at(1));
-		let state_root: H256 = try!(decoder.val_at(2));
-		let block_number: u64 = try!(decoder.val_at(3));
-		let block_hash: H256 = try!(decoder.val_at(4));
+		let state_hashes: Vec<H256> = try!(decoder.val_at(offset));
+		let block_hashes: Vec<H256> = try!(decoder.val_at(offset + 1));
+		let state_root: H256 = try!(decoder.val_at(offset + 2));
+		let block_number: u64 = try!(decoder.val_at(offset + 3));
+		let block_hash: H256 = try!(decoder.val_at(offset + 4));
+		let base: Vec<H256> = match decoder.item_count() {
+			9 => try!(decoder.val_at(offset + 5)),
+			_ => Vec::new(),
+		};
 
 		Ok(ManifestData {
+			version: version,
+			compression: compression,
+			engine: engine,
 			state_hashes: state_hashes,
 			block_hashes: block_hashes,
 			state_root: state_root,
 			block_number: block_number,
 			block_hash: block_hash,
+			base: base.into_iter().next(),
 		})
 	}
-}
 
+	/// Whether this is an incremental snapshot which needs a base to be restored.
+	pub fn is_incremental(&self) -> bool {
+		self.base.is_some()
+	}
+
+	/// Whether this client is able to restore snapshots of this format version.
+	pub fn is_supported(&self) -> bool {
+		self.version >= MIN_SUPPORTED_VERSION && self.version <= SNAPSHOT_VERSION
+	}
+}
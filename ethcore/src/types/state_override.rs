@@ -0,0 +1,48 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State overrides for non-persistent calls.
+
+use std::collections::BTreeMap;
+use util::{Address, U256, H256, Bytes};
+
+/// Account fields replaced before making a call.
+#[derive(Debug, Default, Clone, PartialEq, Binary)]
+pub struct AccountOverride {
+	/// Replaces the balance.
+	pub balance: Option<U256>,
+	/// Replaces the nonce.
+	pub nonce: Option<U256>,
+	/// Replaces the code.
+	pub code: Option<Bytes>,
+	/// Replaces values of given storage slots. Other slots are left untouched.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// Changes applied to a copy of the state before making a call.
+/// They never reach the state database.
+#[derive(Debug, Default, Clone, PartialEq, Binary)]
+pub struct StateOverride {
+	/// Overrides per account.
+	pub accounts: BTreeMap<Address, AccountOverride>,
+}
+
+impl StateOverride {
+	/// Returns true if nothing is overridden.
+	pub fn is_empty(&self) -> bool {
+		self.accounts.is_empty()
+	}
+}
@@ -312,6 +312,16 @@ impl SignedTransaction {
 		}
 	}
 
+	/// Returns transaction sender if it's already known, without recovering it.
+	pub fn known_sender(&self) -> Option<Address> {
+		self.sender.get()
+	}
+
+	/// Sets the sender previously recovered from this transaction's signature, so it doesn't have to be recovered again.
+	pub fn set_sender(&self, sender: Address) {
+		self.sender.set(Some(sender));
+	}
+
 	/// Do basic validation, checking for valid signature and minimum gas,
 	// TODO: consider use in block validation.
 	#[cfg(test)]
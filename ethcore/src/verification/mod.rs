@@ -18,11 +18,13 @@ pub mod verification;
 pub mod verifier;
 mod canon_verifier;
 mod noop_verifier;
+mod sender_cache;
 
 pub use self::verification::*;
 pub use self::verifier::Verifier;
 pub use self::canon_verifier::CanonVerifier;
 pub use self::noop_verifier::NoopVerifier;
+pub use self::sender_cache::{SenderCache, DEFAULT_SENDER_CACHE_SIZE};
 
 /// Verifier type.
 #[derive(Debug, PartialEq, Clone)]
@@ -0,0 +1,239 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cache of recovered transaction senders.
+//!
+//! Shared between the transaction queue and block verification, so that the sender
+//! of a transaction is recovered only once, no matter how many times it is seen.
+
+use common::*;
+use crossbeam::scope;
+
+/// Default number of senders kept in the cache.
+pub const DEFAULT_SENDER_CACHE_SIZE: usize = 16384;
+
+/// Minimal number of transactions worth recovering in a separate thread.
+const MIN_PER_THREAD: usize = 16;
+
+struct Senders {
+	map: HashMap<H256, Address>,
+	order: VecDeque<H256>,
+}
+
+/// Bounded cache of transaction senders keyed by transaction hash.
+/// Oldest entries are evicted first.
+pub struct SenderCache {
+	senders: Mutex<Senders>,
+	limit: usize,
+}
+
+impl SenderCache {
+	/// Creates new cache holding at most `limit` senders.
+	pub fn new(limit: usize) -> Self {
+		SenderCache {
+			senders: Mutex::new(Senders {
+				map: HashMap::new(),
+				order: VecDeque::new(),
+			}),
+			limit: limit,
+		}
+	}
+
+	/// Number of cached senders.
+	pub fn len(&self) -> usize {
+		self.senders.lock().map.len()
+	}
+
+	/// Returns `true` if there are no cached senders.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	fn get(&self, hash: &H256) -> Option<Address> {
+		self.senders.lock().map.get(hash).cloned()
+	}
+
+	fn insert(&self, hash: H256, sender: Address) {
+		if self.limit == 0 {
+			return;
+		}
+		let mut senders = self.senders.lock();
+		if senders.map.insert(hash.clone(), sender).is_none() {
+			senders.order.push_back(hash);
+		}
+		while senders.order.len() > self.limit {
+			let oldest = senders.order.pop_front().expect("order is longer than the limit; qed");
+			senders.map.remove(&oldest);
+		}
+	}
+
+	/// Returns the sender of given transaction, recovering it only if it's not cached yet.
+	pub fn recover(&self, t: &SignedTransaction) -> Result<Address, Error> {
+		// senders set up front (e.g. by `fake_sign`) aren't derived from the hash, so they're never cached.
+		if let Some(sender) = t.known_sender() {
+			return Ok(sender);
+		}
+		let hash = t.hash();
+		if let Some(sender) = self.get(&hash) {
+			t.set_sender(sender.clone());
+			return Ok(sender);
+		}
+		let sender = try!(t.sender());
+		self.insert(hash, sender.clone());
+		Ok(sender)
+	}
+
+	/// Recovers senders of all given transactions on the calling thread.
+	/// Failures are not reported here; they surface again once the sender is requested.
+	pub fn recover_all(&self, transactions: &mut [SignedTransaction]) {
+		for t in self.uncached(transactions) {
+			let _ = self.recover(t);
+		}
+	}
+
+	/// Recovers senders of all given transactions, spreading the work over available cores.
+	/// Meant for large batches arriving on a single thread; callers which already run on a pool
+	/// of workers (block verification, retracted blocks) should use `recover_all` instead.
+	pub fn recover_all_in_parallel(&self, transactions: &mut [SignedTransaction]) {
+		let mut pending = self.uncached(transactions);
+
+		let threads = min(::num_cpus::get(), pending.len() / MIN_PER_THREAD);
+		if threads <= 1 {
+			for t in &pending {
+				let _ = self.recover(t);
+			}
+			return;
+		}
+
+		let chunk_size = (pending.len() + threads - 1) / threads;
+		scope(|scope| {
+			for chunk in pending.chunks_mut(chunk_size) {
+				scope.spawn(move || {
+					for t in chunk.iter() {
+						let _ = self.recover(t);
+					}
+				});
+			}
+		});
+	}
+
+	// transactions whose sender is neither known nor cached; cached senders are set on the way.
+	fn uncached<'a>(&self, transactions: &'a mut [SignedTransaction]) -> Vec<&'a mut SignedTransaction> {
+		transactions.iter_mut()
+			.filter(|t| t.known_sender().is_none())
+			.filter(|t| match self.get(&t.hash()) {
+				Some(sender) => {
+					t.set_sender(sender);
+					false
+				},
+				None => true,
+			})
+			.collect()
+	}
+}
+
+impl Default for SenderCache {
+	fn default() -> Self {
+		SenderCache::new(DEFAULT_SENDER_CACHE_SIZE)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use common::*;
+	use ethkey::{Random, Generator};
+	use super::SenderCache;
+
+	fn transaction(nonce: u64) -> SignedTransaction {
+		let key = Random.generate().unwrap();
+		Transaction {
+			nonce: nonce.into(),
+			gas_price: 0.into(),
+			gas: 21000.into(),
+			action: Action::Call(Address::default()),
+			value: 0.into(),
+			data: vec![],
+		}.sign(&key.secret())
+	}
+
+	fn without_sender(t: &SignedTransaction) -> SignedTransaction {
+		::rlp::decode(&::rlp::encode(t))
+	}
+
+	#[test]
+	fn caches_recovered_senders() {
+		let cache = SenderCache::new(8);
+		let t = transaction(0);
+		let sender = t.sender().unwrap();
+
+		let decoded = without_sender(&t);
+		assert_eq!(cache.recover(&decoded).unwrap(), sender);
+		assert_eq!(cache.len(), 1);
+
+		let decoded = without_sender(&t);
+		assert_eq!(decoded.known_sender(), None);
+		assert_eq!(cache.recover(&decoded).unwrap(), sender);
+		assert_eq!(cache.len(), 1);
+	}
+
+	#[test]
+	fn never_caches_fake_senders() {
+		let cache = SenderCache::new(8);
+		let t = transaction(0);
+		let fake = Transaction::clone(&t).fake_sign(Address::from(5u64));
+
+		assert_eq!(cache.recover(&fake).unwrap(), Address::from(5u64));
+		assert!(cache.is_empty());
+	}
+
+	#[test]
+	fn evicts_oldest_senders() {
+		let cache = SenderCache::new(2);
+		let transactions: Vec<_> = (0..3).map(transaction).collect();
+		for t in &transactions {
+			cache.recover(&without_sender(t)).unwrap();
+		}
+		assert_eq!(cache.len(), 2);
+		assert!(cache.get(&transactions[0].hash()).is_none());
+		assert!(cache.get(&transactions[2].hash()).is_some());
+	}
+
+	#[test]
+	fn recovers_senders_in_bulk() {
+		let cache = SenderCache::new(1024);
+		let transactions: Vec<_> = (0..100).map(transaction).collect();
+		let mut decoded: Vec<_> = transactions.iter().map(without_sender).collect();
+
+		cache.recover_all(&mut decoded);
+		assert_eq!(cache.len(), 100);
+		for (t, d) in transactions.iter().zip(decoded.iter()) {
+			assert_eq!(d.known_sender(), Some(t.sender().unwrap()));
+		}
+	}
+
+	#[test]
+	fn recovers_senders_in_parallel() {
+		let cache = SenderCache::new(1024);
+		let transactions: Vec<_> = (0..100).map(transaction).collect();
+		let mut decoded: Vec<_> = transactions.iter().map(without_sender).collect();
+
+		cache.recover_all_in_parallel(&mut decoded);
+		assert_eq!(cache.len(), 100);
+		for (t, d) in transactions.iter().zip(decoded.iter()) {
+			assert_eq!(d.known_sender(), Some(t.sender().unwrap()));
+		}
+	}
+}
@@ -25,6 +25,7 @@ use common::*;
 use engines::Engine;
 use blockchain::*;
 use rlp::{UntrustedRlp, View};
+use super::SenderCache;
 
 /// Preprocessed block data gathered in `verify_block_unordered` call
 pub struct PreverifiedBlock {
@@ -58,19 +59,18 @@ pub fn verify_block_basic(header: &Header, bytes: &[u8], engine: &Engine) -> Res
 /// Phase 2 verification. Perform costly checks such as transaction signatures and block nonce for ethash.
 /// Still operates on a individual block
 /// Returns a `PreverifiedBlock` structure populated with transactions
-pub fn verify_block_unordered(header: Header, bytes: Bytes, engine: &Engine) -> Result<PreverifiedBlock, Error> {
+/// Transaction senders are recovered serially on the calling verifier thread through `SenderCache::recover_all`,
+/// skipping those already in `senders`.
+pub fn verify_block_unordered(header: Header, bytes: Bytes, engine: &Engine, senders: &SenderCache) -> Result<PreverifiedBlock, Error> {
 	try!(engine.verify_block_unordered(&header, Some(&bytes)));
 	for u in try!(UntrustedRlp::new(&bytes).at(2)).iter().map(|rlp| rlp.as_val::<Header>()) {
 		try!(engine.verify_block_unordered(&try!(u), None));
 	}
 	// Verify transactions.
-	let mut transactions = Vec::new();
-	{
-		let v = BlockView::new(&bytes);
-		for t in v.transactions() {
-			try!(engine.verify_transaction(&t, &header));
-			transactions.push(t);
-		}
+	let mut transactions = BlockView::new(&bytes).transactions();
+	senders.recover_all(&mut transactions);
+	for t in &transactions {
+		try!(engine.verify_transaction(t, &header));
 	}
 	Ok(PreverifiedBlock {
 		header: header,
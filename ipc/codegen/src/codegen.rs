@@ -136,8 +136,13 @@ fn push_invoke_signature_aster(
 		(None, vec![], vec![])
 	};
 
-	let return_type_ty = match named_signature.sig.decl.output {
+	let (return_type_ty, deferred) = match named_signature.sig.decl.output {
 		FunctionRetTy::Ty(ref ty) => {
+			// deferred methods transfer the value they complete with
+			let (ty, deferred) = match deferred_payload_ty(ty) {
+				Some(payload_ty) => (payload_ty, true),
+				None => (ty.clone(), false),
+			};
 			let name_str = format!("{}_output", named_signature.ident.name.as_str());
 			let tree = builder.item()
 				.attr().word("derive(Binary)")
@@ -145,9 +150,9 @@ fn push_invoke_signature_aster(
 				.struct_(name_str.as_str())
 				.field(format!("payload")).ty().build(ty.clone());
 			push(Annotatable::Item(tree.build()));
-			Some(ty.clone())
+			(Some(ty), deferred)
 		}
-		_ => None
+		_ => (None, false)
 	};
 
 	Dispatch {
@@ -156,15 +161,33 @@ fn push_invoke_signature_aster(
 		input_arg_names: input_arg_names,
 		input_arg_tys: input_arg_tys,
 		return_type_ty: return_type_ty,
+		deferred: deferred,
 	}
 }
 
+/// returns `T` if the method returns `Deferred<T>`
+fn deferred_payload_ty(ty: &P<Ty>) -> Option<P<Ty>> {
+	if let ast::TyKind::Path(_, ref path) = ty.node {
+		if let Some(segment) = path.segments.last() {
+			if segment.identifier.name.as_str() == "Deferred" {
+				if let ast::PathParameters::AngleBracketed(ref data) = segment.parameters {
+					return data.types.get(0).cloned();
+				}
+			}
+		}
+	}
+	None
+}
+
 struct Dispatch {
 	function_name: String,
 	input_type_name: Option<String>,
 	input_arg_names: Vec<String>,
 	input_arg_tys: Vec<P<Ty>>,
+	/// type of the value sent back to the client
 	return_type_ty: Option<P<Ty>>,
+	/// method returns `Deferred<return_type_ty>`
+	deferred: bool,
 }
 
 //	This is the expanded version of this:
@@ -182,13 +205,7 @@ fn implement_dispatch_arm_invoke_stmt(
 ) -> ast::Stmt
 {
 	let function_name = builder.id(dispatch.function_name.as_str());
-
-	let input_args_exprs = dispatch.input_arg_names.iter().enumerate().map(|(arg_index, arg_name)| {
-		let arg_ident = builder.id(arg_name);
-		let expr = quote_expr!(cx, input. $arg_ident);
-		if has_ptr(&dispatch.input_arg_tys[arg_index]) { quote_expr!(cx, & $expr) }
-		else { expr }
-	}).collect::<Vec<P<ast::Expr>>>();
+	let input_args_exprs = implement_input_args_exprs(cx, builder, dispatch);
 
 	let ext_cx = &*cx;
 	::quasi::parse_stmt_panic(&mut ::syntax::parse::new_parser_from_tts(
@@ -264,7 +281,11 @@ fn implement_dispatch_arm_invoke(
 		)
 	};
 
-	let invoke_serialize_stmt = implement_dispatch_arm_invoke_stmt(cx, builder, dispatch);
+	let invoke_serialize_stmt = if dispatch.deferred {
+		implement_dispatch_arm_wait_stmt(cx, builder, dispatch)
+	} else {
+		implement_dispatch_arm_invoke_stmt(cx, builder, dispatch)
+	};
 	dispatch.input_type_name.as_ref().map(|val| {
 			let input_type_id = builder.id(val.clone().as_str());
 			quote_expr!(cx, {
@@ -274,6 +295,28 @@ fn implement_dispatch_arm_invoke(
 		}).unwrap_or(quote_expr!(cx, { $invoke_serialize_stmt }))
 }
 
+/// blocking dispatch waits for the deferred value,
+/// cancelled value is logged and answered with an empty response
+fn implement_dispatch_arm_wait_stmt(
+	cx: &ExtCtxt,
+	builder: &aster::AstBuilder,
+	dispatch: &Dispatch,
+) -> ast::Stmt
+{
+	let call_stmt = implement_deferred_call_stmt(cx, builder, dispatch);
+	let cancelled = literal!(builder, "Deferred result of {} was cancelled: {{:?}}", &dispatch.function_name);
+	quote_stmt!(cx, {
+		$call_stmt
+		match result.wait() {
+			Ok(payload) => ::ipc::binary::serialize(&payload).unwrap(),
+			Err(e) => {
+				warn!(target: "ipc", $cancelled, e);
+				Vec::new()
+			}
+		}
+	}).unwrap()
+}
+
 /// generates dispatch match for method id
 fn implement_dispatch_arm(
 	cx: &ExtCtxt,
@@ -304,6 +347,100 @@ fn implement_dispatch_arms(
 		.map(|dispatch| { index = index + 1; implement_dispatch_arm(cx, builder, index as u32, dispatch, buffer) }).collect()
 }
 
+/// arguments of the method invocation, taken from the deserialized `input`
+fn implement_input_args_exprs(
+	cx: &ExtCtxt,
+	builder: &aster::AstBuilder,
+	dispatch: &Dispatch,
+) -> Vec<P<ast::Expr>>
+{
+	dispatch.input_arg_names.iter().enumerate().map(|(arg_index, arg_name)| {
+		let arg_ident = builder.id(arg_name);
+		let expr = quote_expr!(cx, input. $arg_ident);
+		if has_ptr(&dispatch.input_arg_tys[arg_index]) { quote_expr!(cx, & $expr) }
+		else { expr }
+	}).collect()
+}
+
+//	Expanded version of this:
+//
+//	quote_stmt!(cx, let result = self. $function_name ($hand_param_a, $hand_param_b));
+fn implement_deferred_call_stmt(
+	cx: &ExtCtxt,
+	builder: &aster::AstBuilder,
+	dispatch: &Dispatch,
+) -> ast::Stmt
+{
+	let function_name = builder.id(dispatch.function_name.as_str());
+	let input_args_exprs = implement_input_args_exprs(cx, builder, dispatch);
+
+	let ext_cx = &*cx;
+	::quasi::parse_stmt_panic(&mut ::syntax::parse::new_parser_from_tts(
+		ext_cx.parse_sess(),
+		ext_cx.cfg(),
+		{
+			let _sp = ext_cx.call_site();
+			let mut tt = ::std::vec::Vec::new();
+
+			tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Ident(ext_cx.ident_of("let"))));
+			tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Ident(ext_cx.ident_of("result"))));
+			tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Eq));
+			tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Ident(ext_cx.ident_of("self"))));
+			tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Dot));
+			tt.extend(::quasi::ToTokens::to_tokens(&function_name, ext_cx).into_iter());
+			tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::OpenDelim(::syntax::parse::token::Paren)));
+
+			for arg_expr in input_args_exprs {
+				tt.extend(::quasi::ToTokens::to_tokens(&arg_expr, ext_cx).into_iter());
+				tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Comma));
+			}
+
+			tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::CloseDelim(::syntax::parse::token::Paren)));
+			tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Semi));
+
+			tt
+		})).unwrap()
+}
+
+/// generates non-blocking dispatch match arm for the method returning `Deferred`
+fn implement_deferred_dispatch_arm(
+	cx: &ExtCtxt,
+	builder: &aster::AstBuilder,
+	index: u32,
+	dispatch: &Dispatch,
+) -> ast::Arm
+{
+	let index_ident = builder.id(format!("{}", index + (RESERVED_MESSAGE_IDS as u32)).as_str());
+	let input_stmts = dispatch.input_type_name.as_ref().map(|val| {
+		let input_type_id = builder.id(val.clone().as_str());
+		quote_stmt!(cx,
+			let input: $input_type_id = ::ipc::binary::deserialize(buf)
+				.unwrap_or_else(|e| { panic!("ipc error while deserializing payload, aborting \n payload: {:?}, \n error: {:?}", buf, e); } );
+		).unwrap()
+	}).into_iter().collect::<Vec<ast::Stmt>>();
+	let call_stmt = implement_deferred_call_stmt(cx, builder, dispatch);
+	let trace = literal!(builder, "Dispatching deferred: {}", &dispatch.function_name);
+
+	quote_arm!(cx, $index_ident => {
+		trace!(target: "ipc", $trace);
+		$input_stmts
+		$call_stmt
+		result.with_deadline(deadline).map(|payload| ::ipc::binary::serialize(&payload).unwrap())
+	})
+}
+
+fn implement_deferred_dispatch_arms(
+	cx: &ExtCtxt,
+	builder: &aster::AstBuilder,
+	dispatches: &[Dispatch],
+) -> Vec<ast::Arm>
+{
+	dispatches.iter().enumerate()
+		.filter(|&(_, dispatch)| dispatch.deferred)
+		.map(|(index, dispatch)| implement_deferred_dispatch_arm(cx, builder, index as u32, dispatch))
+		.collect()
+}
+
 pub fn strip_ptr(ty: &P<ast::Ty>) -> P<ast::Ty> {
 	if let ast::TyKind::Rptr(_, ref ptr_mut) = ty.node {
 		ptr_mut.ty.clone()
@@ -346,6 +483,9 @@ fn implement_client_method_body(
 {
 	let dispatch = &interface_map.dispatches[index as usize];
 	let index_ident = builder.id(format!("{}", index + RESERVED_MESSAGE_IDS).as_str());
+	// untagged responses cannot be told apart from the tagged ones read meanwhile
+	let tagged = interface_map.is_tagged();
+	let awaited = dispatch.return_type_ty.is_some();
 
 	let request = if dispatch.input_arg_names.len() > 0 {
 
@@ -415,32 +555,51 @@ fn implement_client_method_body(
 				}))
 			});
 
-		request_serialization_statements.push(
-			quote_stmt!(cx, let mut socket = self.socket.write().unwrap(); ));
-
 		request_serialization_statements.push(
 			quote_stmt!(cx, let serialized_payload = ::ipc::binary::serialize(&payload).unwrap()));
 
-		request_serialization_statements.push(
-			quote_stmt!(cx, ::ipc::invoke($index_ident, &Some(serialized_payload), &mut *socket)));
-
+		if tagged {
+			request_serialization_statements.extend(implement_tagged_invoke_stmts(cx, index_ident, quote_expr!(cx, Some(serialized_payload)), awaited));
+		}
+		else {
+			request_serialization_statements.push(
+				quote_stmt!(cx, let mut socket = self.socket.write().unwrap(); ));
+			request_serialization_statements.push(
+				quote_stmt!(cx, ::ipc::invoke($index_ident, &Some(serialized_payload), &mut *socket)));
+		}
 
 		request_serialization_statements
 	}
 	else {
 		let mut request_serialization_statements = Vec::new();
-		request_serialization_statements.push(
-			quote_stmt!(cx, let mut socket = self.socket.write().unwrap(); ));
-		request_serialization_statements.push(
-			quote_stmt!(cx, ::ipc::invoke($index_ident, &None, &mut *socket)));
+		if tagged {
+			request_serialization_statements.extend(implement_tagged_invoke_stmts(cx, index_ident, quote_expr!(cx, None), awaited));
+		}
+		else {
+			request_serialization_statements.push(
+				quote_stmt!(cx, let mut socket = self.socket.write().unwrap(); ));
+			request_serialization_statements.push(
+				quote_stmt!(cx, ::ipc::invoke($index_ident, &None, &mut *socket)));
+		}
 		request_serialization_statements
 	};
 
 	let trace = literal!(builder, "Invoking: {}", &dispatch.function_name);
 	if let Some(ref return_ty) = dispatch.return_type_ty {
-		let return_expr = quote_expr!(cx,
-			::ipc::binary::deserialize_from::<$return_ty, _>(&mut *socket).unwrap()
-		);
+		let return_expr = if dispatch.deferred {
+			// responses are routed by id, so the socket is not held until this one arrives
+			quote_expr!(cx,
+				::ipc::receive_tagged::<$return_ty, _>(request_id, response, &self.pending, &self.socket, &self.reader)
+			)
+		} else if tagged {
+			quote_expr!(cx,
+				::ipc::receive_tagged::<$return_ty, _>(request_id, response, &self.pending, &self.socket, &self.reader).wait().unwrap()
+			)
+		} else {
+			quote_expr!(cx,
+				::ipc::binary::deserialize_from::<$return_ty, _>(&mut *socket).unwrap()
+			)
+		};
 		quote_expr!(cx, {
 			trace!(target: "ipc", $trace);
 			$request;
@@ -455,6 +614,27 @@ fn implement_client_method_body(
 	}
 }
 
+/// Registers the request tagged with the next request id and the caller timeout
+/// and sends it, holding the socket only for the write
+/// Response to the request which is not `awaited` is skipped by whoever reads it
+fn implement_tagged_invoke_stmts(
+	cx: &ExtCtxt,
+	index_ident: ast::Ident,
+	payload: P<ast::Expr>,
+	awaited: bool,
+) -> Vec<Option<ast::Stmt>>
+{
+	let mut stmts = vec![
+		quote_stmt!(cx, let request_id = self.request_id.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed) as u64; ),
+		quote_stmt!(cx, let request_timeout = self.request_timeout.load(::std::sync::atomic::Ordering::Relaxed) as u32; ),
+	];
+	if awaited {
+		stmts.push(quote_stmt!(cx, let response = self.pending.register(request_id, request_timeout); ));
+	}
+	stmts.push(quote_stmt!(cx, ::ipc::invoke_tagged(request_id, request_timeout, $index_ident, &$payload, &mut *self.socket.write().unwrap()); ));
+	stmts
+}
+
 /// Generates signature and body (see `implement_client_method_body`)
 /// for the client (signature is identical to the original method)
 fn implement_client_method(
@@ -500,7 +680,18 @@ fn implement_client_method(
 
 				if let Some(ref return_ty) = dispatch.return_type_ty {
 					tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::RArrow));
-					tt.extend(::quasi::ToTokens::to_tokens(return_ty, ext_cx).into_iter());
+					if dispatch.deferred {
+						tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::ModSep));
+						tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Ident(ext_cx.ident_of("ipc"))));
+						tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::ModSep));
+						tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Ident(ext_cx.ident_of("Deferred"))));
+						tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Lt));
+						tt.extend(::quasi::ToTokens::to_tokens(return_ty, ext_cx).into_iter());
+						tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::Gt));
+					}
+					else {
+						tt.extend(::quasi::ToTokens::to_tokens(return_ty, ext_cx).into_iter());
+					}
 				}
 
 				tt.push(::syntax::ast::TokenTree::Token(_sp, ::syntax::parse::token::OpenDelim(::syntax::parse::token::Brace)));
@@ -547,7 +738,11 @@ fn push_client_struct(cx: &ExtCtxt, builder: &aster::AstBuilder, interface_map:
 
 	let client_struct_item = quote_item!(cx,
 		pub struct $client_short_ident $generics {
-			socket: ::std::sync::RwLock<S>,
+			socket: ::std::sync::Arc<::std::sync::RwLock<S>>,
+			reader: ::std::sync::Arc<::std::sync::Mutex<Option<S>>>,
+			request_id: ::std::sync::atomic::AtomicUsize,
+			request_timeout: ::std::sync::atomic::AtomicUsize,
+			pending: ::std::sync::Arc<::ipc::PendingRequests>,
 			phantom: $phantom,
 		});
 
@@ -577,13 +772,28 @@ fn push_with_socket_client_implementation(
 	let where_clause = &generics.where_clause;
 	let client_short_ident = interface_map.ident_map.client_ident(cx, builder, &interface_map.original_item);
 
+	// tagged responses are read from the split off read half while requests are being written
+	let init_item = if interface_map.is_tagged() {
+		quote_impl_item!(cx,
+			fn init(mut socket: S) -> $client_ident {
+				let reader = ::ipc::IpcSocket::try_split(&mut socket);
+				$client_short_ident::with_reader(socket, reader)
+			}
+		).unwrap()
+	} else {
+		quote_impl_item!(cx,
+			fn init(socket: S) -> $client_ident {
+				$client_short_ident::with_reader(socket, None)
+			}
+		).unwrap()
+	};
+
 	let implement = quote_item!(cx,
 		impl $generics ::ipc::WithSocket<S> for $client_ident $where_clause {
-			fn init(socket: S) -> $client_ident {
-				$client_short_ident {
-					socket: ::std::sync::RwLock::new(socket),
-					phantom: ::std::marker::PhantomData,
-				}
+			$init_item
+
+			fn set_request_timeout(&self, timeout_ms: u32) {
+				self.request_timeout.store(timeout_ms as usize, ::std::sync::atomic::Ordering::Relaxed);
 			}
 		}).unwrap();
 	push(Annotatable::Item(implement));
@@ -606,35 +816,74 @@ fn push_client_implementation(
 	let where_clause = &generics.where_clause;
 	let endpoint = interface_map.endpoint;
 
-	let handshake_item = quote_impl_item!(cx,
-		pub fn handshake(&self) -> Result<(), ::ipc::Error> {
-			let payload = ::ipc::Handshake {
-				protocol_version: $endpoint::protocol_version(),
-				api_version: $endpoint::api_version(),
-			};
-
-			::ipc::invoke(
-				0,
-				&Some(::ipc::binary::serialize(&::ipc::BinHandshake::from(payload)).unwrap()),
-				&mut *self.socket.write().unwrap());
-
-			let mut result = vec![0u8; 1];
-			if try!(self.socket.write().unwrap().read(&mut result).map_err(|_| ::ipc::Error::HandshakeFailed)) == 1 {
-				match result[0] {
-					1 => Ok(()),
-					_ => Err(::ipc::Error::RemoteServiceUnsupported),
+	let handshake_item = if interface_map.is_tagged() {
+		quote_impl_item!(cx,
+			pub fn handshake(&self) -> Result<(), ::ipc::Error> {
+				let payload = ::ipc::Handshake {
+					protocol_version: $endpoint::protocol_version(),
+					api_version: $endpoint::api_version(),
+				};
+
+				let request_id = self.request_id.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed) as u64;
+				let request_timeout = self.request_timeout.load(::std::sync::atomic::Ordering::Relaxed) as u32;
+				let response = self.pending.register(request_id, request_timeout);
+				::ipc::invoke_tagged(
+					request_id,
+					request_timeout,
+					0,
+					&Some(::ipc::binary::serialize(&::ipc::BinHandshake::from(payload)).unwrap()),
+					&mut *self.socket.write().unwrap());
+
+				match ::ipc::receive_tagged::<bool, _>(request_id, response, &self.pending, &self.socket, &self.reader).wait() {
+					Ok(true) => Ok(()),
+					Ok(false) => Err(::ipc::Error::RemoteServiceUnsupported),
+					Err(_) => Err(::ipc::Error::HandshakeFailed),
 				}
+			}).unwrap()
+	} else {
+		quote_impl_item!(cx,
+			pub fn handshake(&self) -> Result<(), ::ipc::Error> {
+				let payload = ::ipc::Handshake {
+					protocol_version: $endpoint::protocol_version(),
+					api_version: $endpoint::api_version(),
+				};
+
+				::ipc::invoke(
+					0,
+					&Some(::ipc::binary::serialize(&::ipc::BinHandshake::from(payload)).unwrap()),
+					&mut *self.socket.write().unwrap());
+
+				let mut result = vec![0u8; 1];
+				if try!(self.socket.write().unwrap().read(&mut result).map_err(|_| ::ipc::Error::HandshakeFailed)) == 1 {
+					match result[0] {
+						1 => Ok(()),
+						_ => Err(::ipc::Error::RemoteServiceUnsupported),
+					}
+				}
+				else { Err(::ipc::Error::HandshakeFailed) }
+			}).unwrap()
+	};
+
+	let client_short_ident = interface_map.ident_map.client_ident(cx, builder, &interface_map.original_item);
+	let with_reader_item = quote_impl_item!(cx,
+		fn with_reader(socket: S, reader: Option<S>) -> $client_ident {
+			$client_short_ident {
+				socket: ::std::sync::Arc::new(::std::sync::RwLock::new(socket)),
+				reader: ::std::sync::Arc::new(::std::sync::Mutex::new(reader)),
+				request_id: ::std::sync::atomic::AtomicUsize::new(0),
+				request_timeout: ::std::sync::atomic::AtomicUsize::new(0),
+				pending: ::std::sync::Arc::new(::ipc::PendingRequests::new()),
+				phantom: ::std::marker::PhantomData,
 			}
-			else { Err(::ipc::Error::HandshakeFailed) }
 		}).unwrap();
 
 	let socket_item = quote_impl_item!(cx,
 		#[cfg(test)]
 		pub fn socket(&self) -> &::std::sync::RwLock<S> {
-			&self.socket
+			&*self.socket
 		}).unwrap();
 
-	let generic_items = vec![P(handshake_item), P(socket_item)];
+	let generic_items = vec![P(handshake_item), P(with_reader_item), P(socket_item)];
 
 	if interface_map.impl_trait.is_some() {
 		let trait_ty = builder.id(
@@ -757,6 +1006,14 @@ struct InterfaceMap {
 	pub endpoint: Ident,
 }
 
+impl InterfaceMap {
+	/// Clients of interfaces with methods returning `Deferred` send every request tagged,
+	/// since responses can arrive out of order
+	fn is_tagged(&self) -> bool {
+		self.dispatches.iter().any(|dispatch| dispatch.deferred)
+	}
+}
+
 struct IdentMap {
 	original_path: ast::Path,
 }
@@ -841,6 +1098,20 @@ fn implement_interface(
 
 	let dispatch_arms = implement_dispatch_arms(cx, builder, &dispatch_table, false);
 	let dispatch_arms_buffered = implement_dispatch_arms(cx, builder, &dispatch_table, true);
+	let deferred_arms = implement_deferred_dispatch_arms(cx, builder, &dispatch_table);
+
+	// methods returning `Deferred` are not waited for by the worker
+	let deferred_items = if deferred_arms.is_empty() { Vec::new() } else {
+		vec![P(quote_impl_item!(cx,
+			fn dispatch_deferred(&self, method_num: u16, buf: &[u8], deadline: Option<::std::time::Instant>) -> ::ipc::Deferred<Vec<u8>>
+			{
+				match method_num {
+					$deferred_arms
+					_ => ::ipc::Deferred::ready(self.dispatch_buf(method_num, buf))
+				}
+			}
+		).unwrap())]
+	};
 
 	let (handshake_arm, handshake_arm_buf) = implement_handshake_arm(cx);
 
@@ -880,6 +1151,8 @@ fn implement_interface(
 					_ => vec![]
 				}
 			}
+
+			$deferred_items
 		}
 	).unwrap();
 
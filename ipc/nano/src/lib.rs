@@ -14,98 +14,167 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-//! IPC over nanomsg transport
+//! IPC over nanomsg or Unix-domain socket transport
 
 extern crate ethcore_ipc as ipc;
+#[cfg(feature="nano")]
 extern crate nanomsg;
+#[cfg(unix)]
+extern crate mio;
 #[macro_use] extern crate log;
 #[macro_use] extern crate lazy_static;
 
+#[cfg(feature="nano")]
+mod nano;
+#[cfg(unix)]
+mod unix;
+
 pub use ipc::{WithSocket, IpcInterface, IpcConfig};
-pub use nanomsg::Socket as NanoSocket;
+#[cfg(feature="nano")]
+pub use nano::{Nano, NanoSocket};
+#[cfg(unix)]
+pub use unix::{Unix, UnixSocket};
 
 use std::sync::*;
-use nanomsg::{Socket, Protocol, Error, Endpoint, PollRequest, PollFd, PollInOut};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::ops::Deref;
+use std::time::{Duration, Instant};
 
 const POLL_TIMEOUT: isize = 200;
+/// Poll timeout used while deferred requests are in progress, so that their responses are not delayed
+const DEFERRED_POLL_TIMEOUT: isize = 10;
 const DEFAULT_CONNECTION_TIMEOUT: isize = 30000;
 const DEBUG_CONNECTION_TIMEOUT: isize = 5000;
 
+/// Transport used by the worker and client constructors unless specified otherwise
+/// Request-reply connections are answered out of order only by this transport
+#[cfg(unix)]
+pub type DefaultTransport = Unix;
+
+/// Transport used by the worker and client constructors unless specified otherwise
+#[cfg(all(not(unix), feature="nano"))]
+pub type DefaultTransport = Nano;
+
+/// Socket of the default transport, as seen by generated clients
+pub type DefaultSocket = <DefaultTransport as Transport>::Socket;
+
+/// Message transport between ipc workers and clients
+pub trait Transport: Sized {
+	/// Socket handed over to generated clients
+	type Socket: ipc::IpcSocket;
+	/// Keeps client connection open for as long as it is held
+	type Guard;
+	/// Service (binded) sockets polled by the worker
+	type Listeners: Default;
+	/// Identifies the connection the message came from, so that it can be answered later
+	type Peer: Clone + Send + 'static;
+
+	/// Connects client socket to the service at `addr`
+	/// `duplex` requests exclusive (paired) connection
+	fn connect(addr: &str, duplex: bool, receive_timeout: Option<isize>) -> Result<(Self::Socket, Self::Guard), SocketError>;
+
+	/// Binds service socket at `addr` and adds it to `listeners`
+	/// `duplex` allows only one connection over this address
+	fn bind(listeners: &mut Self::Listeners, addr: &str, duplex: bool) -> Result<(), SocketError>;
+
+	/// Waits up to `timeout` milliseconds for incoming messages on any of the `listeners`
+	/// and answers every message with what `handler` returns for it
+	fn poll(listeners: &mut Self::Listeners, timeout: isize, handler: &mut FnMut(&Self::Peer, &[u8]) -> Option<Vec<u8>>);
+
+	/// Sends the response to the `peer` outside of the `poll` handler
+	fn send(listeners: &mut Self::Listeners, peer: &Self::Peer, message: &[u8]);
+
+	/// Returns true if the `peer` accepts responses out of order
+	fn defers(peer: &Self::Peer) -> bool;
+
+	/// Spawns client <`S`> over specified address
+	/// for duplex (paired) connections with the service
+	fn init_duplex_client<S>(socket_addr: &str) -> Result<GuardedSocket<S, Self>, SocketError> where S: WithSocket<Self::Socket> {
+		let (socket, guard) = try!(Self::connect(socket_addr, true, Some(DEFAULT_CONNECTION_TIMEOUT)));
+		let client = S::init(socket);
+		client.set_request_timeout(DEFAULT_CONNECTION_TIMEOUT as u32);
+		Ok(GuardedSocket {
+			client: Arc::new(client),
+			_guard: guard,
+		})
+	}
+
+	/// Spawns client <`S`> over specified address
+	/// for request-reply connections to the service
+	fn client<S>(socket_addr: &str, receive_timeout: Option<isize>) -> Result<GuardedSocket<S, Self>, SocketError> where S: WithSocket<Self::Socket> {
+		let (socket, guard) = try!(Self::connect(socket_addr, false, receive_timeout));
+		let client = S::init(socket);
+		if let Some(timeout) = receive_timeout {
+			// the service can abandon deferred requests the client is no longer waiting for
+			client.set_request_timeout(timeout as u32);
+		}
+		trace!(target: "ipc", "Created client for {}", socket_addr);
+		Ok(GuardedSocket {
+			client: Arc::new(client),
+			_guard: guard,
+		})
+	}
+
+	/// Client with no default timeout on operations
+	fn generic_client<S>(socket_addr: &str) -> Result<GuardedSocket<S, Self>, SocketError> where S: WithSocket<Self::Socket> {
+		if *DEBUG_FLAG {
+			Self::client(socket_addr, Some(DEBUG_CONNECTION_TIMEOUT))
+		} else {
+			Self::client(socket_addr, None)
+		}
+	}
+
+	/// Client over interface that is supposed to give quick almost non-blocking responses
+	fn fast_client<S>(socket_addr: &str) -> Result<GuardedSocket<S, Self>, SocketError> where S: WithSocket<Self::Socket> {
+		if *DEBUG_FLAG {
+			Self::client(socket_addr, Some(DEBUG_CONNECTION_TIMEOUT))
+		} else {
+			Self::client(socket_addr, Some(DEFAULT_CONNECTION_TIMEOUT))
+		}
+	}
+}
+
 /// Generic worker to handle service (binded) sockets
-pub struct Worker<S: ?Sized> where S: IpcInterface {
+pub struct Worker<S: ?Sized, T = DefaultTransport> where S: IpcInterface, T: Transport {
 	service: Arc<S>,
-	sockets: Vec<(Socket, Endpoint)>,
-	polls: Vec<PollFd>,
-	buf: Vec<u8>,
+	sockets: T::Listeners,
+	/// Responses to deferred requests completed since the last poll
+	completed: Arc<Mutex<Vec<(T::Peer, Vec<u8>)>>>,
+	/// Number of deferred requests still in progress
+	in_flight: Arc<AtomicUsize>,
 }
 
-/// struct for guarding `_endpoint` (so that it wont drop)
+/// struct for guarding the transport connection (so that it wont drop)
 /// derefs to client `S`
-pub struct GuardedSocket<S> where S: WithSocket<Socket> {
+pub struct GuardedSocket<S, T = DefaultTransport> where S: WithSocket<T::Socket>, T: Transport {
 	client: Arc<S>,
-	_endpoint: Endpoint,
+	_guard: T::Guard,
 }
 
-impl<S> GuardedSocket<S> where S: WithSocket<Socket> {
+impl<S, T> GuardedSocket<S, T> where S: WithSocket<T::Socket>, T: Transport {
 	pub fn service(&self) -> Arc<S> {
 		self.client.clone()
 	}
 }
 
-impl<S> Deref for GuardedSocket<S> where S: WithSocket<Socket> {
-    type Target = Arc<S>;
+impl<S, T> Deref for GuardedSocket<S, T> where S: WithSocket<T::Socket>, T: Transport {
+	type Target = Arc<S>;
 
-    fn deref(&self) -> &Arc<S> {
-        &self.client
-    }
+	fn deref(&self) -> &Arc<S> {
+		&self.client
+	}
 }
 
-/// Spawns client <`S`> over specified address
-/// creates socket and connects endpoint to it
+/// Spawns client <`S`> over specified address using the default transport
 /// for duplex (paired) connections with the service
-pub fn init_duplex_client<S>(socket_addr: &str) -> Result<GuardedSocket<S>, SocketError> where S: WithSocket<Socket> {
-	let mut socket = try!(Socket::new(Protocol::Pair).map_err(|e| {
-		warn!(target: "ipc", "Failed to create ipc socket: {:?}", e);
-		SocketError::DuplexLink
-	}));
-
-	socket.set_receive_timeout(DEFAULT_CONNECTION_TIMEOUT).unwrap();
-
-	let endpoint = try!(socket.connect(socket_addr).map_err(|e| {
-		warn!(target: "ipc", "Failed to bind socket to address '{}': {:?}", socket_addr, e);
-		SocketError::DuplexLink
-	}));
-
-	Ok(GuardedSocket {
-		client: Arc::new(S::init(socket)),
-		_endpoint: endpoint,
-	})
+pub fn init_duplex_client<S>(socket_addr: &str) -> Result<GuardedSocket<S>, SocketError> where S: WithSocket<DefaultSocket> {
+	DefaultTransport::init_duplex_client(socket_addr)
 }
 
-/// Spawns client <`S`> over specified address
-/// creates socket and connects endpoint to it
+/// Spawns client <`S`> over specified address using the default transport
 /// for request-reply connections to the service
-pub fn client<S>(socket_addr: &str, receive_timeout: Option<isize>) -> Result<GuardedSocket<S>, SocketError> where S: WithSocket<Socket> {
-	let mut socket = try!(Socket::new(Protocol::Req).map_err(|e| {
-		warn!(target: "ipc", "Failed to create ipc socket: {:?}", e);
-		SocketError::RequestLink
-	}));
-
-	if let Some(timeout) = receive_timeout {
-		socket.set_receive_timeout(timeout).unwrap();
-	}
-
-	let endpoint = try!(socket.connect(socket_addr).map_err(|e| {
-		warn!(target: "ipc", "Failed to bind socket to address '{}': {:?}", socket_addr, e);
-		SocketError::RequestLink
-	}));
-
-	trace!(target: "ipc", "Created client for {}", socket_addr);
-	Ok(GuardedSocket {
-		client: Arc::new(S::init(socket)),
-		_endpoint: endpoint,
-	})
+pub fn client<S>(socket_addr: &str, receive_timeout: Option<isize>) -> Result<GuardedSocket<S>, SocketError> where S: WithSocket<DefaultSocket> {
+	DefaultTransport::client(socket_addr, receive_timeout)
 }
 
 lazy_static! {
@@ -121,21 +190,13 @@ lazy_static! {
 }
 
 /// Client with no default timeout on operations
-pub fn generic_client<S>(socket_addr: &str) -> Result<GuardedSocket<S>, SocketError> where S: WithSocket<Socket> {
-	if *DEBUG_FLAG {
-		client(socket_addr, Some(DEBUG_CONNECTION_TIMEOUT))
-	} else {
-		client(socket_addr, None)
-	}
+pub fn generic_client<S>(socket_addr: &str) -> Result<GuardedSocket<S>, SocketError> where S: WithSocket<DefaultSocket> {
+	DefaultTransport::generic_client(socket_addr)
 }
 
 /// Client over interface that is supposed to give quick almost non-blocking responses
-pub fn fast_client<S>(socket_addr: &str) -> Result<GuardedSocket<S>, SocketError> where S: WithSocket<Socket> {
-	if *DEBUG_FLAG {
-		client(socket_addr, Some(DEBUG_CONNECTION_TIMEOUT))
-	} else {
-		client(socket_addr, Some(DEFAULT_CONNECTION_TIMEOUT))
-	}
+pub fn fast_client<S>(socket_addr: &str) -> Result<GuardedSocket<S>, SocketError> where S: WithSocket<DefaultSocket> {
+	DefaultTransport::fast_client(socket_addr)
 }
 
 /// Error occurred while establising socket or endpoint
@@ -147,82 +208,131 @@ pub enum SocketError {
 	RequestLink,
 }
 
+/// Counts deferred request as being in progress for as long as it is alive
+struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+	fn new(counter: &Arc<AtomicUsize>) -> InFlight {
+		counter.fetch_add(1, Ordering::SeqCst);
+		InFlight(counter.clone())
+	}
+}
+
+impl Drop for InFlight {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+/// Splits the message into method number and payload and dispatches it to the `service`
+/// Tagged requests from peers accepting responses out of order are answered
+/// through `completed` once the result is available
+fn dispatch<S: ?Sized, T>(
+	service: &S,
+	peer: &T::Peer,
+	message: &[u8],
+	completed: &Arc<Mutex<Vec<(T::Peer, Vec<u8>)>>>,
+	in_flight: &Arc<AtomicUsize>,
+) -> Option<Vec<u8>> where S: IpcInterface, T: Transport {
+	if message.len() < 2 {
+		warn!(target: "ipc", "Failed to read method signature from socket: unexpected message length({})", message.len());
+		return None;
+	}
+
+	// method_num
+	let method_num = message[0] as u16 * 256 + message[1] as u16;
+	// payload
+	let payload = &message[2..];
+
+	if method_num != ipc::TAGGED_METHOD_NUM {
+		// dispatching for ipc interface
+		return Some(service.dispatch_buf(method_num, payload));
+	}
+
+	let (header, params) = match ipc::decode_tagged_request(payload) {
+		Some(request) => request,
+		None => {
+			warn!(target: "ipc", "Failed to read tagged request header: unexpected message length({})", message.len());
+			return None;
+		}
+	};
+
+	let id = header.id;
+	let deadline = match header.timeout_ms {
+		0 => None,
+		timeout => Some(Instant::now() + Duration::from_millis(timeout as u64)),
+	};
+	let result = service.dispatch_deferred(header.method_num, params, deadline);
+
+	if !T::defers(peer) {
+		return match result.wait() {
+			Ok(result) => Some(ipc::tagged_response(id, &result)),
+			Err(e) => {
+				warn!(target: "ipc", "Request {} for method {} was cancelled: {:?}", id, header.method_num, e);
+				Some(ipc::cancelled_response(id))
+			}
+		};
+	}
+
+	let in_flight = InFlight::new(in_flight);
+	let completed = completed.clone();
+	let peer = peer.clone();
+	result.on_complete(move |result| {
+		let _in_flight = in_flight;
+		if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+			trace!(target: "ipc", "Dropping response to request {} after caller timeout", id);
+			return;
+		}
+		completed.lock().unwrap().push((peer, ipc::tagged_response(id, &result)));
+	});
+	None
+}
+
 impl<S: ?Sized> Worker<S> where S: IpcInterface {
-	/// New worker over specified `service`
+	/// New worker over specified `service` using the default transport
 	pub fn new(service: &Arc<S>) -> Worker<S> {
-		Worker::<S> {
+		Worker::with_transport(service)
+	}
+}
+
+impl<S: ?Sized, T> Worker<S, T> where S: IpcInterface, T: Transport {
+	/// New worker over specified `service` using transport `T`
+	pub fn with_transport(service: &Arc<S>) -> Worker<S, T> {
+		Worker::<S, T> {
 			service: service.clone(),
-			sockets: Vec::new(),
-			polls: Vec::new(),
-			buf: Vec::new(),
+			sockets: Default::default(),
+			completed: Arc::new(Mutex::new(Vec::new())),
+			in_flight: Arc::new(AtomicUsize::new(0)),
 		}
 	}
 
 	/// Polls all sockets, reads and dispatches method invocations
+	/// and sends responses to the completed deferred requests
 	pub fn poll(&mut self) {
-		use std::io::Write;
-
-		let mut request = PollRequest::new(&mut self.polls[..]);
- 		let _result_guard = Socket::poll(&mut request, POLL_TIMEOUT);
-
-		for (fd_index, fd) in request.get_fds().iter().enumerate() {
-			if fd.can_read() {
-				let (ref mut socket, _) = self.sockets[fd_index];
-				unsafe { self.buf.set_len(0); }
-				match socket.nb_read_to_end(&mut self.buf) {
-					Ok(method_sign_len) => {
-						if method_sign_len >= 2 {
-
-							// method_num
-							let method_num = self.buf[0] as u16 * 256 + self.buf[1] as u16;
-							// payload
-							let payload = &self.buf[2..];
-
-							// dispatching for ipc interface
-							let result = self.service.dispatch_buf(method_num, payload);
-
-							if let Err(e) = socket.write(&result) {
-								warn!(target: "ipc", "Failed to write response: {:?}", e);
-							}
-						}
-						else {
-							warn!(target: "ipc", "Failed to read method signature from socket: unexpected message length({})", method_sign_len);
-						}
-					},
-					Err(Error::TryAgain) => {
-					},
-					Err(x) => {
-						warn!(target: "ipc", "Error polling connections {:?}", x);
-						panic!();
-					}
-				}
-			}
-		}
+		self.send_completed();
+
+		let timeout = if self.in_flight.load(Ordering::SeqCst) > 0 { DEFERRED_POLL_TIMEOUT } else { POLL_TIMEOUT };
+		let service = &self.service;
+		let completed = &self.completed;
+		let in_flight = &self.in_flight;
+		T::poll(&mut self.sockets, timeout, &mut |peer: &T::Peer, message: &[u8]| {
+			dispatch::<S, T>(&**service, peer, message, completed, in_flight)
+		});
+
+		self.send_completed();
 	}
 
-	/// Stores nanomsg poll request for reuse
-	fn rebuild_poll_request(&mut self) {
-		self.polls = self.sockets.iter()
-			.map(|&(ref socket, _)| socket.new_pollfd(PollInOut::In))
-			.collect::<Vec<PollFd>>();
+	fn send_completed(&mut self) {
+		let completed = ::std::mem::replace(&mut *self.completed.lock().unwrap(), Vec::new());
+		for (peer, response) in completed {
+			T::send(&mut self.sockets, &peer, &response);
+		}
 	}
 
 	/// Add exclusive socket for paired client
 	/// Only one connection over this address is allowed
 	pub fn add_duplex(&mut self, addr: &str) -> Result<(), SocketError>  {
-		let mut socket = try!(Socket::new(Protocol::Pair).map_err(|e| {
-			warn!(target: "ipc", "Failed to create ipc socket: {:?}", e);
-			SocketError::DuplexLink
-		}));
-
-		let endpoint = try!(socket.bind(addr).map_err(|e| {
-			warn!(target: "ipc", "Failed to bind socket to address '{}': {:?}", addr, e);
-			SocketError::DuplexLink
-		}));
-
-		self.sockets.push((socket, endpoint));
-
-		self.rebuild_poll_request();
+		try!(T::bind(&mut self.sockets, addr, true));
 
 		trace!(target: "ipc", "Started duplex worker at {}", addr);
 
@@ -232,30 +342,17 @@ impl<S: ?Sized> Worker<S> where S: IpcInterface {
 	/// Add generic socket for request-reply style communications
 	/// with multiple clients
 	pub fn add_reqrep(&mut self, addr: &str) -> Result<(), SocketError>  {
-		let mut socket = try!(Socket::new(Protocol::Rep).map_err(|e| {
-			warn!(target: "ipc", "Failed to create ipc socket: {:?}", e);
-			SocketError::DuplexLink
-		}));
-
-
-		let endpoint = try!(socket.bind(addr).map_err(|e| {
-			warn!(target: "ipc", "Failed to bind socket to address '{}': {:?}", addr, e);
-			SocketError::DuplexLink
-		}));
-
-		self.sockets.push((socket, endpoint));
-
-		self.rebuild_poll_request();
+		try!(T::bind(&mut self.sockets, addr, false));
 
 		trace!(target: "ipc", "Started request-reply worker at {}", addr);
 		Ok(())
 	}
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature="nano"))]
 mod service_tests {
 
-	use super::Worker;
+	use super::{Worker, Nano};
 	use ipc::*;
 	use std::io::{Read, Write};
 	use std::sync::{Arc, RwLock};
@@ -301,13 +398,13 @@ mod service_tests {
 
 	#[test]
 	fn can_create_worker() {
-		let worker = Worker::<DummyService>::new(&Arc::new(DummyService::new()));
+		let worker = Worker::<DummyService, Nano>::with_transport(&Arc::new(DummyService::new()));
 		assert_eq!(0, worker.sockets.len());
 	}
 
 	#[test]
 	fn can_add_duplex_socket_to_worker() {
-		let mut worker = Worker::<DummyService>::new(&Arc::new(DummyService::new()));
+		let mut worker = Worker::<DummyService, Nano>::with_transport(&Arc::new(DummyService::new()));
 		worker.add_duplex("ipc:///tmp/parity-test10.ipc").unwrap();
 		assert_eq!(1, worker.sockets.len());
 	}
@@ -315,7 +412,7 @@ mod service_tests {
 	#[test]
 	fn worker_can_poll_empty() {
 		let service = Arc::new(DummyService::new());
-		let mut worker = Worker::<DummyService>::new(&service);
+		let mut worker = Worker::<DummyService, Nano>::with_transport(&service);
 		worker.add_duplex("ipc:///tmp/parity-test20.ipc").unwrap();
 		worker.poll();
 		assert_eq!(0, service.methods_stack.read().unwrap().len());
@@ -325,7 +422,7 @@ mod service_tests {
 	fn worker_can_poll() {
 		let url = "ipc:///tmp/parity-test30.ipc";
 
-		let mut worker = Worker::<DummyService>::new(&Arc::new(DummyService::new()));
+		let mut worker = Worker::<DummyService, Nano>::with_transport(&Arc::new(DummyService::new()));
 		worker.add_duplex(url).unwrap();
 
 		let (_socket, _endpoint) = dummy_write(url, &vec![0, 0, 7, 7, 6, 6]);
@@ -340,7 +437,7 @@ mod service_tests {
 	fn worker_can_poll_long() {
 		let url = "ipc:///tmp/parity-test40.ipc";
 
-		let mut worker = Worker::<DummyService>::new(&Arc::new(DummyService::new()));
+		let mut worker = Worker::<DummyService, Nano>::with_transport(&Arc::new(DummyService::new()));
 		worker.add_duplex(url).unwrap();
 
 		let message = [0u8; 1024*1024];
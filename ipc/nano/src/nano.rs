@@ -0,0 +1,146 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! nanomsg transport
+
+pub use nanomsg::Socket as NanoSocket;
+
+use nanomsg::{Socket, Protocol, Error, Endpoint, PollRequest, PollFd, PollInOut};
+use super::{Transport, SocketError};
+
+/// Transport over nanomsg pair and request-reply sockets
+pub struct Nano;
+
+/// Service (binded) nanomsg socket
+pub struct Listener {
+	socket: Socket,
+	_endpoint: Endpoint,
+	duplex: bool,
+}
+
+/// Listener the message came from
+/// Only pair (duplex) sockets can be answered out of order
+#[derive(Clone)]
+pub struct Peer {
+	listener: usize,
+	duplex: bool,
+}
+
+impl Transport for Nano {
+	type Socket = Socket;
+	type Guard = Endpoint;
+	type Listeners = Vec<Listener>;
+	type Peer = Peer;
+
+	fn connect(addr: &str, duplex: bool, receive_timeout: Option<isize>) -> Result<(Socket, Endpoint), SocketError> {
+		let (protocol, error) = if duplex {
+			(Protocol::Pair, SocketError::DuplexLink)
+		} else {
+			(Protocol::Req, SocketError::RequestLink)
+		};
+
+		let mut socket = match Socket::new(protocol) {
+			Ok(socket) => socket,
+			Err(e) => {
+				warn!(target: "ipc", "Failed to create ipc socket: {:?}", e);
+				return Err(error);
+			}
+		};
+
+		if let Some(timeout) = receive_timeout {
+			socket.set_receive_timeout(timeout).unwrap();
+		}
+
+		let endpoint = try!(socket.connect(addr).map_err(|e| {
+			warn!(target: "ipc", "Failed to bind socket to address '{}': {:?}", addr, e);
+			error
+		}));
+
+		Ok((socket, endpoint))
+	}
+
+	fn bind(listeners: &mut Vec<Listener>, addr: &str, duplex: bool) -> Result<(), SocketError> {
+		let protocol = if duplex { Protocol::Pair } else { Protocol::Rep };
+		let mut socket = try!(Socket::new(protocol).map_err(|e| {
+			warn!(target: "ipc", "Failed to create ipc socket: {:?}", e);
+			SocketError::DuplexLink
+		}));
+
+		let endpoint = try!(socket.bind(addr).map_err(|e| {
+			warn!(target: "ipc", "Failed to bind socket to address '{}': {:?}", addr, e);
+			SocketError::DuplexLink
+		}));
+
+		listeners.push(Listener {
+			socket: socket,
+			_endpoint: endpoint,
+			duplex: duplex,
+		});
+		Ok(())
+	}
+
+	fn poll(listeners: &mut Vec<Listener>, timeout: isize, handler: &mut FnMut(&Peer, &[u8]) -> Option<Vec<u8>>) {
+		use std::io::Write;
+
+		let mut polls = listeners.iter()
+			.map(|listener| listener.socket.new_pollfd(PollInOut::In))
+			.collect::<Vec<PollFd>>();
+		let mut request = PollRequest::new(&mut polls[..]);
+		let _result_guard = Socket::poll(&mut request, timeout);
+
+		let mut buf = Vec::new();
+		for (fd_index, fd) in request.get_fds().iter().enumerate() {
+			if fd.can_read() {
+				let listener = &mut listeners[fd_index];
+				let peer = Peer {
+					listener: fd_index,
+					duplex: listener.duplex,
+				};
+				buf.clear();
+				match listener.socket.nb_read_to_end(&mut buf) {
+					Ok(_) => {
+						if let Some(result) = handler(&peer, &buf) {
+							if let Err(e) = listener.socket.write(&result) {
+								warn!(target: "ipc", "Failed to write response: {:?}", e);
+							}
+						}
+					},
+					Err(Error::TryAgain) => {
+					},
+					Err(x) => {
+						warn!(target: "ipc", "Error polling connections {:?}", x);
+						panic!();
+					}
+				}
+			}
+		}
+	}
+
+	fn send(listeners: &mut Vec<Listener>, peer: &Peer, message: &[u8]) {
+		use std::io::Write;
+
+		if let Err(e) = listeners[peer.listener].socket.write(message) {
+			warn!(target: "ipc", "Failed to write response: {:?}", e);
+		}
+	}
+
+	/// Rep sockets answer requests strictly in order, so a slow deferred request on a
+	/// request-reply connection holds back every other caller of the worker;
+	/// use duplex connections or the Unix transport for such services
+	fn defers(peer: &Peer) -> bool {
+		peer.duplex
+	}
+}
@@ -0,0 +1,529 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Unix-domain socket transport
+//!
+//! Messages are exchanged as length-prefixed frames (see `ipc::framed`),
+//! so no native messaging library is required.
+
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixStream, UnixListener};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, thread};
+use mio::{Poll, Token, EventSet, PollOpt};
+use mio::unix::EventedFd;
+use ipc::framed::{self, FramedSocket};
+use super::{Transport, SocketError};
+
+/// Framed Unix-domain socket as seen by generated clients
+pub type UnixSocket = FramedSocket<UnixStream>;
+
+/// Size of the chunk read from the connection at once
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Connection is dropped once this many response bytes wait for the client to read them
+const MAX_PENDING_OUTPUT: usize = 2 * framed::MAX_FRAME_LEN;
+
+/// Transport over Unix-domain sockets
+pub struct Unix;
+
+/// Accepts nanomsg-style `ipc://` urls as well as plain paths
+fn socket_path(addr: &str) -> PathBuf {
+	PathBuf::from(addr.trim_left_matches("ipc://"))
+}
+
+fn connect_stream(path: &Path, receive_timeout: Option<isize>) -> io::Result<UnixStream> {
+	let stream = try!(UnixStream::connect(path));
+	if let Some(timeout) = receive_timeout {
+		try!(stream.set_read_timeout(Some(Duration::from_millis(timeout as u64))));
+	}
+	Ok(stream)
+}
+
+/// Connection the message came from
+#[derive(Clone)]
+pub struct Peer {
+	listener: usize,
+	connection: usize,
+}
+
+/// Client connection accepted by the listener
+struct Connection {
+	token: Token,
+	stream: UnixStream,
+	/// Received data not forming a complete message yet
+	input: Vec<u8>,
+	/// Response data the client did not take yet
+	output: Vec<u8>,
+	/// Whether the connection is registered for writability
+	writable: bool,
+}
+
+impl Connection {
+	/// Sends pending responses, reads everything available on the connection
+	/// and answers every complete message
+	/// Returns number of handled messages
+	fn process(&mut self, peer: &Peer, poll: &mut Poll, handler: &mut FnMut(&Peer, &[u8]) -> Option<Vec<u8>>) -> io::Result<usize> {
+		try!(self.flush());
+		let handled = try!(self.receive(peer, handler));
+		try!(self.update_interest(poll));
+		Ok(handled)
+	}
+
+	fn receive(&mut self, peer: &Peer, handler: &mut FnMut(&Peer, &[u8]) -> Option<Vec<u8>>) -> io::Result<usize> {
+		let mut chunk = [0u8; READ_CHUNK];
+		let mut closed = false;
+		loop {
+			match self.stream.read(&mut chunk) {
+				Ok(0) => {
+					closed = true;
+					break;
+				},
+				Ok(read) => self.input.extend_from_slice(&chunk[..read]),
+				Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+				Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+				Err(e) => return Err(e),
+			}
+		}
+
+		let mut handled = 0;
+		while let Some(message) = try!(framed::take_frame(&mut self.input)) {
+			handled += 1;
+			if closed {
+				continue;
+			}
+			if let Some(result) = handler(peer, &message) {
+				try!(self.respond(&result));
+			}
+		}
+
+		if closed {
+			return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed by client"));
+		}
+		Ok(handled)
+	}
+
+	/// Queues the response frame and sends as much as the connection takes without blocking
+	fn respond(&mut self, result: &[u8]) -> io::Result<()> {
+		self.output.extend_from_slice(&framed::frame(result));
+		if self.output.len() > MAX_PENDING_OUTPUT {
+			return Err(io::Error::new(io::ErrorKind::Other, "client does not read responses"));
+		}
+		self.flush()
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		while !self.output.is_empty() {
+			match self.stream.write(&self.output) {
+				Ok(0) => return Err(io::Error::new(io::ErrorKind::WriteZero, "connection closed by client")),
+				Ok(written) => {
+					self.output.drain(..written);
+				},
+				Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+				Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(())
+	}
+
+	/// Waits for writability only while there are responses pending
+	fn update_interest(&mut self, poll: &mut Poll) -> io::Result<()> {
+		let writable = !self.output.is_empty();
+		if writable != self.writable {
+			let interest = if writable { EventSet::readable() | EventSet::writable() } else { EventSet::readable() };
+			try!(poll.reregister(&EventedFd(&self.stream.as_raw_fd()), self.token, interest, PollOpt::level()));
+			self.writable = writable;
+		}
+		Ok(())
+	}
+}
+
+/// Service (binded) Unix-domain socket with its accepted connections
+pub struct Listener {
+	listener: UnixListener,
+	token: Token,
+	path: PathBuf,
+	duplex: bool,
+	connections: Vec<Connection>,
+}
+
+impl Listener {
+	fn accept(&mut self, poll: &mut Poll, next_token: &mut usize) {
+		loop {
+			match self.listener.accept() {
+				Ok((stream, _)) => {
+					if self.duplex && !self.connections.is_empty() {
+						warn!(target: "ipc", "Rejected second connection to duplex socket {:?}", self.path);
+						continue;
+					}
+					let token = Token(*next_token);
+					let registered = stream.set_nonblocking(true)
+						.and_then(|_| poll.register(&EventedFd(&stream.as_raw_fd()), token, EventSet::readable(), PollOpt::level()));
+					if let Err(e) = registered {
+						warn!(target: "ipc", "Failed to set up connection to {:?}: {:?}", self.path, e);
+						continue;
+					}
+					*next_token += 1;
+					self.connections.push(Connection {
+						token: token,
+						stream: stream,
+						input: Vec::new(),
+						output: Vec::new(),
+						writable: false,
+					});
+				},
+				Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+				Err(e) => {
+					warn!(target: "ipc", "Error accepting connection to {:?}: {:?}", self.path, e);
+					break;
+				}
+			}
+		}
+	}
+
+	/// Handles connections and accepts new ones if they are `ready`
+	/// Connections are handled first, so that a client reconnecting to a duplex socket
+	/// is not rejected because its previous connection was not seen closed yet
+	/// Returns number of handled messages
+	fn poll(&mut self, listener_index: usize, ready: &HashSet<usize>, poll: &mut Poll, next_token: &mut usize, handler: &mut FnMut(&Peer, &[u8]) -> Option<Vec<u8>>) -> usize {
+		let mut handled = 0;
+		let mut index = 0;
+		while index < self.connections.len() {
+			let token = self.connections[index].token;
+			if !ready.contains(&token.0) {
+				index += 1;
+				continue;
+			}
+			let peer = Peer {
+				listener: listener_index,
+				connection: token.0,
+			};
+			match self.connections[index].process(&peer, poll, handler) {
+				Ok(count) => {
+					handled += count;
+					index += 1;
+				},
+				Err(e) => {
+					trace!(target: "ipc", "Dropping connection to {:?}: {:?}", self.path, e);
+					let connection = self.connections.swap_remove(index);
+					let _ = poll.deregister(&EventedFd(&connection.stream.as_raw_fd()));
+				}
+			}
+		}
+
+		if ready.contains(&self.token.0) {
+			self.accept(poll, next_token);
+		}
+		handled
+	}
+
+	fn send(&mut self, peer: &Peer, message: &[u8], poll: &mut Poll) {
+		let index = match self.connections.iter().position(|connection| connection.token.0 == peer.connection) {
+			Some(index) => index,
+			None => {
+				trace!(target: "ipc", "Dropping response to closed connection to {:?}", self.path);
+				return;
+			}
+		};
+
+		let result = {
+			let connection = &mut self.connections[index];
+			connection.respond(message).and_then(|_| connection.update_interest(poll))
+		};
+		if let Err(e) = result {
+			warn!(target: "ipc", "Failed to write response to {:?}: {:?}", self.path, e);
+			let connection = self.connections.swap_remove(index);
+			let _ = poll.deregister(&EventedFd(&connection.stream.as_raw_fd()));
+		}
+	}
+}
+
+impl Drop for Listener {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+/// Service sockets of a worker and the selector waiting for them to become ready
+#[derive(Default)]
+pub struct Listeners {
+	/// Created along with the first listener
+	poll: Option<Poll>,
+	listeners: Vec<Listener>,
+	next_token: usize,
+}
+
+impl Transport for Unix {
+	type Socket = UnixSocket;
+	type Guard = ();
+	type Listeners = Listeners;
+	type Peer = Peer;
+
+	fn connect(addr: &str, duplex: bool, receive_timeout: Option<isize>) -> Result<(UnixSocket, ()), SocketError> {
+		let error = if duplex { SocketError::DuplexLink } else { SocketError::RequestLink };
+		let path = socket_path(addr);
+
+		let stream = match connect_stream(&path, receive_timeout) {
+			Ok(stream) => stream,
+			Err(e) => {
+				warn!(target: "ipc", "Failed to connect socket to address '{}': {:?}", addr, e);
+				return Err(error);
+			}
+		};
+
+		let reconnect = Box::new(move || connect_stream(&path, receive_timeout));
+		Ok((FramedSocket::with_reconnect(stream, reconnect), ()))
+	}
+
+	fn bind(listeners: &mut Listeners, addr: &str, duplex: bool) -> Result<(), SocketError> {
+		let path = socket_path(addr);
+		// socket file left over by the previous run would make bind fail
+		let _ = fs::remove_file(&path);
+
+		if listeners.poll.is_none() {
+			listeners.poll = Some(try!(Poll::new().map_err(|e| {
+				warn!(target: "ipc", "Failed to create selector for address '{}': {:?}", addr, e);
+				SocketError::DuplexLink
+			})));
+		}
+
+		let listener = try!(UnixListener::bind(&path).map_err(|e| {
+			warn!(target: "ipc", "Failed to bind socket to address '{}': {:?}", addr, e);
+			SocketError::DuplexLink
+		}));
+
+		let token = Token(listeners.next_token);
+		let poll = listeners.poll.as_mut().expect("selector is created above; qed");
+		try!(listener.set_nonblocking(true)
+			.and_then(|_| poll.register(&EventedFd(&listener.as_raw_fd()), token, EventSet::readable(), PollOpt::level()))
+			.map_err(|e| {
+				warn!(target: "ipc", "Failed to set up socket at address '{}': {:?}", addr, e);
+				SocketError::DuplexLink
+			}));
+
+		listeners.next_token += 1;
+		listeners.listeners.push(Listener {
+			listener: listener,
+			token: token,
+			path: path,
+			duplex: duplex,
+			connections: Vec::new(),
+		});
+		Ok(())
+	}
+
+	fn poll(listeners: &mut Listeners, timeout: isize, handler: &mut FnMut(&Peer, &[u8]) -> Option<Vec<u8>>) {
+		let Listeners { ref mut poll, ref mut listeners, ref mut next_token } = *listeners;
+		let poll = match *poll {
+			Some(ref mut poll) => poll,
+			None => {
+				// nothing to wait for
+				thread::sleep(Duration::from_millis(timeout as u64));
+				return;
+			}
+		};
+
+		let ready: HashSet<usize> = match poll.poll(Some(timeout as usize)) {
+			Ok(count) => (0..count).map(|index| poll.event(index).token().0).collect(),
+			Err(e) => {
+				warn!(target: "ipc", "Failed to wait for ipc sockets: {:?}", e);
+				return;
+			}
+		};
+
+		for (index, listener) in listeners.iter_mut().enumerate() {
+			listener.poll(index, &ready, poll, next_token, handler);
+		}
+	}
+
+	fn send(listeners: &mut Listeners, peer: &Peer, message: &[u8]) {
+		let Listeners { ref mut poll, ref mut listeners, .. } = *listeners;
+		if let Some(ref mut poll) = *poll {
+			listeners[peer.listener].send(peer, message, poll);
+		}
+	}
+
+	fn defers(_peer: &Peer) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Unix;
+	use super::super::{Worker, Transport};
+	use ipc::*;
+	use ipc::framed::{self, FramedSocket};
+	use std::io::{Read, Write};
+	use std::os::unix::net::UnixStream;
+	use std::sync::{Arc, RwLock, Mutex};
+	use std::time::{Duration, Instant};
+	use std::thread;
+
+	/// Method completed only when the test says so
+	const DEFERRED_METHOD_NUM: u16 = 20;
+
+	struct DummyService {
+		methods_stack: RwLock<Vec<(u16, Vec<u8>)>>,
+		pending: Mutex<Vec<Completer<Vec<u8>>>>,
+	}
+
+	impl DummyService {
+		fn new() -> DummyService {
+			DummyService {
+				methods_stack: RwLock::new(Vec::new()),
+				pending: Mutex::new(Vec::new()),
+			}
+		}
+	}
+
+	impl IpcInterface for DummyService {
+		fn dispatch<R>(&self, _r: &mut R) -> Vec<u8> where R: Read {
+			vec![]
+		}
+		fn dispatch_buf(&self, method_num: u16, buf: &[u8]) -> Vec<u8> {
+			self.methods_stack.write().unwrap().push((method_num, buf.to_vec()));
+			vec![method_num as u8]
+		}
+		fn dispatch_deferred(&self, method_num: u16, buf: &[u8], deadline: Option<Instant>) -> Deferred<Vec<u8>> {
+			if method_num != DEFERRED_METHOD_NUM {
+				return Deferred::ready(self.dispatch_buf(method_num, buf));
+			}
+			let (completer, result) = deferred();
+			self.pending.lock().unwrap().push(completer);
+			result.with_deadline(deadline)
+		}
+	}
+
+	impl IpcConfig for DummyService {}
+
+	fn worker(addr: &str) -> Worker<DummyService, Unix> {
+		let mut worker = Worker::with_transport(&Arc::new(DummyService::new()));
+		worker.add_duplex(addr).unwrap();
+		worker
+	}
+
+	#[test]
+	fn worker_can_poll_empty() {
+		let mut worker = worker("ipc:///tmp/parity-unix-test10.ipc");
+		worker.poll();
+		assert_eq!(0, worker.service.methods_stack.read().unwrap().len());
+	}
+
+	#[test]
+	fn worker_can_poll() {
+		let mut worker = worker("ipc:///tmp/parity-unix-test20.ipc");
+
+		let mut socket = FramedSocket::new(UnixStream::connect("/tmp/parity-unix-test20.ipc").unwrap());
+		socket.write(&[0, 5, 7, 7, 6, 6]).unwrap();
+		worker.poll();
+
+		assert_eq!(vec![(5, vec![7, 7, 6, 6])], *worker.service.methods_stack.read().unwrap());
+		let mut response = Vec::new();
+		socket.read_to_end(&mut response).unwrap();
+		assert_eq!(vec![5], response);
+	}
+
+	#[test]
+	fn worker_can_poll_long() {
+		let mut worker = worker("ipc:///tmp/parity-unix-test30.ipc");
+
+		let message = vec![0u8; 1024*1024];
+		let mut stream = UnixStream::connect("/tmp/parity-unix-test30.ipc").unwrap();
+		let writer = ::std::thread::spawn(move || {
+			stream.write_all(&framed::frame(&message)).unwrap();
+			stream
+		});
+		while worker.service.methods_stack.read().unwrap().is_empty() {
+			worker.poll();
+		}
+		let _stream = writer.join().unwrap();
+
+		assert_eq!(1, worker.service.methods_stack.read().unwrap().len());
+		assert_eq!(vec![0u8; 1024*1024-2], worker.service.methods_stack.read().unwrap()[0].1);
+	}
+
+	#[test]
+	fn worker_answers_deferred_requests_out_of_order() {
+		let mut worker = worker("ipc:///tmp/parity-unix-test40.ipc");
+
+		let mut socket = FramedSocket::new(UnixStream::connect("/tmp/parity-unix-test40.ipc").unwrap());
+		invoke_tagged(1, 0, DEFERRED_METHOD_NUM, &None, &mut socket);
+		invoke_tagged(2, 0, DEFERRED_METHOD_NUM, &None, &mut socket);
+		while worker.service.pending.lock().unwrap().len() < 2 {
+			worker.poll();
+		}
+
+		let second = worker.service.pending.lock().unwrap().pop().unwrap();
+		let first = worker.service.pending.lock().unwrap().pop().unwrap();
+		second.complete(vec![2]);
+		first.complete(vec![1]);
+		worker.poll();
+
+		let mut response = Vec::new();
+		socket.read_to_end(&mut response).unwrap();
+		assert_eq!(tagged_response(2, &[2]), response);
+		response.clear();
+		socket.read_to_end(&mut response).unwrap();
+		assert_eq!(tagged_response(1, &[1]), response);
+	}
+
+	#[test]
+	fn worker_drops_responses_after_caller_timeout() {
+		let mut worker = worker("ipc:///tmp/parity-unix-test50.ipc");
+
+		let mut socket = FramedSocket::new(UnixStream::connect("/tmp/parity-unix-test50.ipc").unwrap());
+		invoke_tagged(1, 1, DEFERRED_METHOD_NUM, &None, &mut socket);
+		while worker.service.pending.lock().unwrap().is_empty() {
+			worker.poll();
+		}
+
+		thread::sleep(Duration::from_millis(10));
+		let completer = worker.service.pending.lock().unwrap().pop().unwrap();
+		assert!(completer.is_cancelled());
+		completer.complete(vec![1]);
+		worker.poll();
+
+		socket.get_ref().unwrap().set_read_timeout(Some(Duration::from_millis(50))).unwrap();
+		assert!(socket.read_to_end(&mut Vec::new()).is_err());
+	}
+
+	#[test]
+	fn client_reconnects_after_timeout() {
+		let mut worker = worker("ipc:///tmp/parity-unix-test60.ipc");
+
+		let (mut socket, _) = Unix::connect("ipc:///tmp/parity-unix-test60.ipc", true, Some(50)).unwrap();
+		invoke_tagged(1, 0, DEFERRED_METHOD_NUM, &None, &mut socket);
+		while worker.service.pending.lock().unwrap().is_empty() {
+			worker.poll();
+		}
+		assert!(socket.read_to_end(&mut Vec::new()).is_err());
+		assert!(socket.get_ref().is_none());
+
+		socket.write(&[0, 5]).unwrap();
+		while worker.service.methods_stack.read().unwrap().is_empty() {
+			worker.poll();
+		}
+
+		let mut response = Vec::new();
+		socket.read_to_end(&mut response).unwrap();
+		assert_eq!(vec![5], response);
+	}
+}
@@ -0,0 +1,285 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Results of ipc methods which complete asynchronously
+
+use std::sync::{Arc, Mutex, Condvar};
+use std::time::Instant;
+use std::mem;
+use interface::Error;
+
+enum State<T> {
+	/// Neither the value nor the callback is there yet
+	Pending,
+	/// Value is waiting to be picked up
+	Ready(T),
+	/// Callback is waiting for the value
+	Waiting(Box<FnMut(T) + Send>),
+	/// Value was consumed or one of the sides was dropped
+	Done,
+}
+
+struct Shared<T> {
+	state: Mutex<State<T>>,
+	ready: Condvar,
+	deadline: Mutex<Option<Instant>>,
+}
+
+impl<T> Shared<T> {
+	fn new(state: State<T>) -> Arc<Shared<T>> {
+		Arc::new(Shared {
+			state: Mutex::new(state),
+			ready: Condvar::new(),
+			deadline: Mutex::new(None),
+		})
+	}
+}
+
+/// Value of type `T` which will be provided later by the `Completer`,
+/// possibly from another thread
+///
+/// Methods of `#[derive(Ipc)]` services returning `Deferred<T>` do not block
+/// the worker while the value is being produced.
+pub struct Deferred<T> where T: Send + 'static {
+	shared: Arc<Shared<T>>,
+	/// Makes progress towards the value while it is being waited for
+	driver: Option<Box<Fn() + Send>>,
+}
+
+/// Producing side of the `Deferred`
+/// Dropping it without completing cancels the deferred value
+pub struct Completer<T> where T: Send + 'static {
+	shared: Arc<Shared<T>>,
+}
+
+/// Creates connected pair of the completer and the deferred value
+pub fn deferred<T>() -> (Completer<T>, Deferred<T>) where T: Send + 'static {
+	let shared = Shared::new(State::Pending);
+	(Completer { shared: shared.clone() }, Deferred { shared: shared, driver: None })
+}
+
+impl<T> Deferred<T> where T: Send + 'static {
+	/// Already completed value
+	pub fn ready(value: T) -> Deferred<T> {
+		Deferred { shared: Shared::new(State::Ready(value)), driver: None }
+	}
+
+	/// Value which will never complete
+	pub fn cancelled() -> Deferred<T> {
+		Deferred { shared: Shared::new(State::Done), driver: None }
+	}
+
+	/// Sets the moment after which the caller is no longer interested in the value
+	pub fn with_deadline(self, deadline: Option<Instant>) -> Deferred<T> {
+		*self.shared.deadline.lock().unwrap() = deadline;
+		self
+	}
+
+	/// Sets the `driver` called repeatedly by `wait` until the value is available,
+	/// for values which are produced only while someone is waiting for them
+	/// `driver` is expected to block until it makes some progress
+	pub fn driven_by(mut self, driver: Box<Fn() + Send>) -> Deferred<T> {
+		self.driver = Some(driver);
+		self
+	}
+
+	fn is_pending(&self) -> bool {
+		match *self.shared.state.lock().unwrap() {
+			State::Pending => true,
+			_ => false,
+		}
+	}
+
+	/// Calls `f` with the value once it is available
+	/// `f` is never called if the value is cancelled
+	pub fn on_complete<F>(self, f: F) where F: FnOnce(T) + Send + 'static {
+		let value = {
+			let mut state = self.shared.state.lock().unwrap();
+			match mem::replace(&mut *state, State::Done) {
+				State::Ready(value) => value,
+				State::Pending => {
+					let mut f = Some(f);
+					*state = State::Waiting(Box::new(move |value| {
+						if let Some(f) = f.take() {
+							f(value);
+						}
+					}));
+					return;
+				},
+				_ => return,
+			}
+		};
+		f(value);
+	}
+
+	/// Deferred value transformed with `f`
+	pub fn map<U, F>(mut self, f: F) -> Deferred<U> where U: Send + 'static, F: FnOnce(T) -> U + Send + 'static {
+		let (completer, mut mapped) = deferred();
+		mapped.driver = self.driver.take();
+		self.on_complete(move |value| completer.complete(f(value)));
+		mapped
+	}
+
+	/// Blocks until the value is available
+	pub fn wait(mut self) -> Result<T, Error> {
+		if let Some(driver) = self.driver.take() {
+			while self.is_pending() {
+				driver();
+			}
+		}
+
+		let mut state = self.shared.state.lock().unwrap();
+		loop {
+			match mem::replace(&mut *state, State::Done) {
+				State::Ready(value) => return Ok(value),
+				State::Done => return Err(Error::RequestCancelled),
+				other => *state = other,
+			}
+			state = self.shared.ready.wait(state).unwrap();
+		}
+	}
+}
+
+impl<T> Drop for Deferred<T> where T: Send + 'static {
+	fn drop(&mut self) {
+		let mut state = self.shared.state.lock().unwrap();
+		if let State::Pending = *state {
+			*state = State::Done;
+		}
+	}
+}
+
+impl<T> Completer<T> where T: Send + 'static {
+	/// Provides the value to the waiting side
+	pub fn complete(self, value: T) {
+		let mut callback = {
+			let mut state = self.shared.state.lock().unwrap();
+			match mem::replace(&mut *state, State::Done) {
+				State::Pending => {
+					*state = State::Ready(value);
+					self.shared.ready.notify_all();
+					return;
+				},
+				State::Waiting(callback) => callback,
+				_ => return,
+			}
+		};
+		callback(value);
+	}
+
+	/// Moment after which the caller is no longer interested in the value
+	pub fn deadline(&self) -> Option<Instant> {
+		*self.shared.deadline.lock().unwrap()
+	}
+
+	/// Returns true if the value is not needed anymore,
+	/// either because the caller timed out or because the deferred value was dropped
+	pub fn is_cancelled(&self) -> bool {
+		if self.deadline().map_or(false, |deadline| Instant::now() >= deadline) {
+			return true;
+		}
+
+		match *self.shared.state.lock().unwrap() {
+			State::Done => true,
+			_ => false,
+		}
+	}
+}
+
+impl<T> Drop for Completer<T> where T: Send + 'static {
+	fn drop(&mut self) {
+		let mut state = self.shared.state.lock().unwrap();
+		match *state {
+			State::Pending | State::Waiting(_) => {
+				*state = State::Done;
+				self.shared.ready.notify_all();
+			},
+			_ => {},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::{Arc, Mutex};
+	use std::thread;
+	use std::time::{Duration, Instant};
+
+	#[test]
+	fn ready_value_can_be_waited() {
+		assert_eq!(5, Deferred::ready(5).wait().unwrap());
+	}
+
+	#[test]
+	fn waits_for_value_completed_in_another_thread() {
+		let (completer, deferred) = deferred();
+		thread::spawn(move || completer.complete(10u32));
+
+		assert_eq!(10, deferred.map(|value| value + 1).wait().unwrap());
+	}
+
+	#[test]
+	fn calls_back_once_completed() {
+		let result = Arc::new(Mutex::new(None));
+		let (completer, deferred) = deferred();
+		let callback_result = result.clone();
+		deferred.on_complete(move |value| *callback_result.lock().unwrap() = Some(value));
+
+		assert_eq!(None, *result.lock().unwrap());
+		completer.complete(7u32);
+		assert_eq!(Some(7), *result.lock().unwrap());
+	}
+
+	#[test]
+	fn dropped_completer_cancels_value() {
+		let (completer, deferred) = deferred::<u32>();
+		drop(completer);
+
+		assert!(deferred.wait().is_err());
+	}
+
+	#[test]
+	fn driver_produces_value_while_waiting() {
+		let (completer, deferred) = deferred();
+		let completer = Mutex::new(Some(completer));
+		let deferred = deferred.driven_by(Box::new(move || {
+			if let Some(completer) = completer.lock().unwrap().take() {
+				completer.complete(3u32);
+			}
+		}));
+
+		assert_eq!(4, deferred.map(|value| value + 1).wait().unwrap());
+	}
+
+	#[test]
+	fn completer_knows_about_cancellation() {
+		let (completer, deferred) = deferred::<u32>();
+		let deferred = deferred.with_deadline(Some(Instant::now() + Duration::from_millis(50)));
+		assert!(!completer.is_cancelled());
+
+		drop(deferred);
+		assert!(completer.is_cancelled());
+	}
+
+	#[test]
+	fn completer_knows_about_expired_deadline() {
+		let (completer, deferred) = deferred::<u32>();
+		let _deferred = deferred.with_deadline(Some(Instant::now()));
+
+		assert!(completer.is_cancelled());
+	}
+}
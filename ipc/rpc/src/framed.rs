@@ -0,0 +1,410 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Length-prefixed framing for stream transports
+//!
+//! Generated clients expect message semantics from the socket (one read returns
+//! exactly one response), which nanomsg provides natively. Plain streams such as
+//! Unix-domain sockets get the same behaviour by prefixing every message with
+//! its length as a big-endian `u32`.
+
+use std::io::{self, Read, Write};
+use std::cmp;
+use std::sync::{Arc, Mutex};
+use interface::IpcSocket;
+
+/// Length of the frame header
+pub const HEADER_LEN: usize = 4;
+
+/// Largest payload accepted in a single frame
+pub const MAX_FRAME_LEN: usize = 256 * 1024 * 1024;
+
+fn frame_len(header: &[u8]) -> io::Result<usize> {
+	let len = (header[0] as usize) << 24 | (header[1] as usize) << 16 | (header[2] as usize) << 8 | header[3] as usize;
+	if len > MAX_FRAME_LEN {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, format!("frame of {} bytes exceeds the limit", len)));
+	}
+	Ok(len)
+}
+
+/// Prepends the frame header to `payload`
+pub fn frame(payload: &[u8]) -> Vec<u8> {
+	let len = payload.len();
+	let mut buf = Vec::with_capacity(HEADER_LEN + len);
+	buf.push((len >> 24) as u8);
+	buf.push((len >> 16) as u8);
+	buf.push((len >> 8) as u8);
+	buf.push(len as u8);
+	buf.extend_from_slice(payload);
+	buf
+}
+
+/// Removes the first complete frame from `buf` and returns its payload
+/// Returns `None` if `buf` does not hold a complete frame yet
+pub fn take_frame(buf: &mut Vec<u8>) -> io::Result<Option<Vec<u8>>> {
+	if buf.len() < HEADER_LEN {
+		return Ok(None);
+	}
+
+	let len = try!(frame_len(&buf[..HEADER_LEN]));
+	if buf.len() < HEADER_LEN + len {
+		return Ok(None);
+	}
+
+	let payload = buf[HEADER_LEN..HEADER_LEN + len].to_vec();
+	buf.drain(..HEADER_LEN + len);
+	Ok(Some(payload))
+}
+
+/// Opens a new stream to the same endpoint
+pub type Connect<S> = Box<Fn() -> io::Result<S> + Send + Sync>;
+
+/// Stream the frames are exchanged over
+pub trait FrameStream: Read + Write + Sized {
+	/// Another handle to the same stream, used by the read half of the split socket
+	fn try_clone(&self) -> io::Result<Self>;
+}
+
+#[cfg(unix)]
+impl FrameStream for ::std::os::unix::net::UnixStream {
+	fn try_clone(&self) -> io::Result<Self> {
+		::std::os::unix::net::UnixStream::try_clone(self)
+	}
+}
+
+impl FrameStream for ::devtools::TestSocket {
+	fn try_clone(&self) -> io::Result<Self> {
+		Err(io::Error::new(io::ErrorKind::Other, "test socket cannot be shared"))
+	}
+}
+
+fn not_connected() -> io::Error {
+	io::Error::new(io::ErrorKind::NotConnected, "connection was dropped after an incomplete exchange")
+}
+
+fn read_frame<S>(stream: &mut S) -> io::Result<Vec<u8>> where S: Read {
+	let mut header = [0u8; HEADER_LEN];
+	try!(stream.read_exact(&mut header));
+	let len = try!(frame_len(&header));
+	let mut payload = vec![0u8; len];
+	try!(stream.read_exact(&mut payload));
+	Ok(payload)
+}
+
+/// Connection shared by the halves of the split socket
+struct Link<S> {
+	/// Incremented every time the connection is reopened
+	generation: usize,
+	/// Handle to the current stream, `None` once either half saw it fail
+	stream: Option<S>,
+}
+
+/// Blocking stream wrapper with message semantics
+/// Every `write` sends one frame and every `read` consumes one frame;
+/// like with nanomsg, the part of the frame that does not fit the buffer is discarded
+/// A failed or timed out read or write leaves the stream in an unknown position, so the
+/// stream is dropped; the next write opens a new one if the socket knows how to reconnect
+/// Once the read half is split off, a failure seen by either half drops the stream for both
+pub struct FramedSocket<S> where S: FrameStream {
+	stream: Option<S>,
+	connect: Option<Connect<S>>,
+	link: Option<Arc<Mutex<Link<S>>>>,
+	generation: usize,
+}
+
+impl<S> FramedSocket<S> where S: FrameStream {
+	/// New framed socket over the connected `stream`
+	pub fn new(stream: S) -> Self {
+		FramedSocket {
+			stream: Some(stream),
+			connect: None,
+			link: None,
+			generation: 0,
+		}
+	}
+
+	/// New framed socket over the connected `stream`, replaced using `connect` once it breaks
+	pub fn with_reconnect(stream: S, connect: Connect<S>) -> Self {
+		FramedSocket {
+			stream: Some(stream),
+			connect: Some(connect),
+			link: None,
+			generation: 0,
+		}
+	}
+
+	/// Underlying stream, unless it was dropped and not reconnected yet
+	pub fn get_ref(&self) -> Option<&S> {
+		self.stream.as_ref()
+	}
+
+	/// Splits off the read half of the socket, which reads frames from the same connection
+	/// while this socket writes them; only this socket reconnects, and the read half
+	/// follows it to the new connection
+	pub fn split(&mut self) -> io::Result<FramedSocket<S>> {
+		let stream = try!(try!(self.connected()).try_clone());
+		let link = match self.link.clone() {
+			Some(link) => link,
+			None => {
+				let link = Arc::new(Mutex::new(Link {
+					generation: self.generation,
+					stream: Some(try!(stream.try_clone())),
+				}));
+				self.link = Some(link.clone());
+				link
+			},
+		};
+
+		Ok(FramedSocket {
+			stream: Some(stream),
+			connect: None,
+			link: Some(link),
+			generation: self.generation,
+		})
+	}
+
+	fn reconnect(&self) -> io::Result<S> {
+		match self.connect {
+			Some(ref connect) => connect(),
+			None => Err(not_connected()),
+		}
+	}
+
+	fn connected(&mut self) -> io::Result<&mut S> {
+		if let Some(link) = self.link.clone() {
+			let mut link = link.lock().unwrap();
+			// the other half might have seen the stream fail or might have reopened it
+			if link.generation != self.generation || link.stream.is_none() {
+				self.stream = None;
+			}
+			if self.stream.is_none() {
+				if link.stream.is_none() {
+					let stream = try!(self.reconnect());
+					link.stream = Some(try!(stream.try_clone()));
+					link.generation += 1;
+				}
+				let stream = try!(link.stream.as_ref().expect("stream is set above; qed").try_clone());
+				self.stream = Some(stream);
+				self.generation = link.generation;
+			}
+		} else if self.stream.is_none() {
+			let stream = try!(self.reconnect());
+			self.stream = Some(stream);
+		}
+		Ok(self.stream.as_mut().expect("stream is set above; qed"))
+	}
+
+	/// Drops the stream after a failure, for the other half as well
+	fn fail(&mut self) {
+		self.stream = None;
+		if let Some(ref link) = self.link {
+			let mut link = link.lock().unwrap();
+			if link.generation == self.generation {
+				link.stream = None;
+			}
+		}
+	}
+
+	/// Reads the next whole frame from the stream
+	pub fn read_frame(&mut self) -> io::Result<Vec<u8>> {
+		let result = if self.link.is_some() {
+			// the read half picks up the stream reopened by the other half
+			self.connected().and_then(read_frame)
+		} else {
+			match self.stream {
+				Some(ref mut stream) => read_frame(stream),
+				None => Err(not_connected()),
+			}
+		};
+		if result.is_err() {
+			self.fail();
+		}
+		result
+	}
+
+	/// Writes `payload` to the stream as a single frame
+	/// Reconnects first if the previous stream was dropped
+	pub fn write_frame(&mut self, payload: &[u8]) -> io::Result<()> {
+		if payload.len() > MAX_FRAME_LEN {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("frame of {} bytes exceeds the limit", payload.len())));
+		}
+		let result = self.connected().and_then(|stream| stream.write_all(&frame(payload)));
+		if result.is_err() {
+			self.fail();
+		}
+		result
+	}
+}
+
+impl<S> Read for FramedSocket<S> where S: FrameStream {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let payload = try!(self.read_frame());
+		let len = cmp::min(buf.len(), payload.len());
+		buf[..len].clone_from_slice(&payload[..len]);
+		Ok(len)
+	}
+
+	fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+		let payload = try!(self.read_frame());
+		buf.extend_from_slice(&payload);
+		Ok(payload.len())
+	}
+}
+
+impl<S> Write for FramedSocket<S> where S: FrameStream {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		try!(self.write_frame(buf));
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		match self.stream {
+			Some(ref mut stream) => stream.flush(),
+			None => Ok(()),
+		}
+	}
+}
+
+impl<S> IpcSocket for FramedSocket<S> where S: FrameStream + Sync + Send + 'static {
+	fn try_split(&mut self) -> Option<Self> {
+		self.split().ok()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::{Read, Write};
+	use devtools::TestSocket;
+
+	#[test]
+	fn can_take_frame() {
+		let mut buf = frame(&[1, 2, 3]);
+		buf.extend_from_slice(&frame(&[4]));
+
+		assert_eq!(Some(vec![1, 2, 3]), take_frame(&mut buf).unwrap());
+		assert_eq!(Some(vec![4]), take_frame(&mut buf).unwrap());
+		assert_eq!(None, take_frame(&mut buf).unwrap());
+	}
+
+	#[test]
+	fn waits_for_complete_frame() {
+		let mut buf = frame(&[1, 2, 3]);
+		buf.pop();
+
+		assert_eq!(None, take_frame(&mut buf).unwrap());
+		assert_eq!(6, buf.len());
+	}
+
+	#[test]
+	fn rejects_oversized_frame() {
+		let mut buf = vec![0xff, 0xff, 0xff, 0xff];
+		assert!(take_frame(&mut buf).is_err());
+	}
+
+	#[test]
+	fn writes_one_frame_per_message() {
+		let mut socket = FramedSocket::new(TestSocket::new());
+		socket.write(&[7, 7]).unwrap();
+
+		assert_eq!(vec![0, 0, 0, 2, 7, 7], socket.get_ref().unwrap().write_buffer);
+	}
+
+	#[test]
+	fn reads_one_frame_per_message() {
+		let mut data = frame(&[1, 2, 3]);
+		data.extend_from_slice(&frame(&[4, 5]));
+		let mut socket = FramedSocket::new(TestSocket::new_ready(data));
+
+		let mut buf = [0u8; 2];
+		assert_eq!(2, socket.read(&mut buf).unwrap());
+		assert_eq!([1, 2], buf);
+
+		let mut rest = Vec::new();
+		assert_eq!(2, socket.read_to_end(&mut rest).unwrap());
+		assert_eq!(vec![4, 5], rest);
+	}
+
+	#[test]
+	fn drops_stream_after_partial_read() {
+		let mut data = frame(&[1, 2, 3]);
+		data.pop();
+		let mut socket = FramedSocket::new(TestSocket::new_ready(data));
+
+		assert!(socket.read_to_end(&mut Vec::new()).is_err());
+		assert!(socket.get_ref().is_none());
+		assert!(socket.write(&[7]).is_err());
+	}
+
+	#[test]
+	fn reconnects_on_write_after_failure() {
+		let mut socket = FramedSocket::with_reconnect(TestSocket::new_ready(vec![0, 0]), Box::new(|| Ok(TestSocket::new())));
+
+		assert!(socket.read_to_end(&mut Vec::new()).is_err());
+		socket.write(&[7]).unwrap();
+
+		assert_eq!(vec![0, 0, 0, 1, 7], socket.get_ref().unwrap().write_buffer);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn split_halves_share_connection() {
+		use std::os::unix::net::UnixStream;
+
+		let (stream, mut service) = UnixStream::pair().unwrap();
+		let mut socket = FramedSocket::new(stream);
+		let mut reader = socket.split().unwrap();
+
+		socket.write(&[1, 2]).unwrap();
+		let mut request = vec![0u8; 6];
+		service.read_exact(&mut request).unwrap();
+		assert_eq!(frame(&[1, 2]), request);
+
+		service.write_all(&frame(&[3])).unwrap();
+		let mut response = Vec::new();
+		reader.read_to_end(&mut response).unwrap();
+		assert_eq!(vec![3], response);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn read_half_follows_reconnected_stream() {
+		use std::os::unix::net::UnixStream;
+		use std::sync::Mutex;
+
+		let (stream, service) = UnixStream::pair().unwrap();
+		let (next_stream, mut next_service) = UnixStream::pair().unwrap();
+		let next_stream = Mutex::new(Some(next_stream));
+		let mut socket = FramedSocket::with_reconnect(stream, Box::new(move || {
+			next_stream.lock().unwrap().take().ok_or_else(super::not_connected)
+		}));
+		let mut reader = socket.split().unwrap();
+
+		// the service goes away in the middle of the exchange
+		drop(service);
+		assert!(reader.read_to_end(&mut Vec::new()).is_err());
+
+		socket.write(&[7]).unwrap();
+		let mut request = vec![0u8; 5];
+		next_service.read_exact(&mut request).unwrap();
+		assert_eq!(frame(&[7]), request);
+
+		next_service.write_all(&frame(&[8])).unwrap();
+		let mut response = Vec::new();
+		reader.read_to_end(&mut response).unwrap();
+		assert_eq!(vec![8], response);
+	}
+}
@@ -16,9 +16,33 @@
 
 //! IPC RPC interface
 
+use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::marker::Sync;
+use std::sync::{Arc, Mutex, RwLock, Condvar, TryLockError};
+use std::time::{Duration, Instant};
 use semver::Version;
+use binary::{self, BinaryConvertable};
+use deferred::{Deferred, Completer, deferred};
+
+/// System method carrying a tagged request, which can be answered out of order
+pub const TAGGED_METHOD_NUM: u16 = 1;
+
+/// Length of the tagged request header (request id, timeout and method number)
+const TAGGED_HEADER_LEN: usize = 14;
+
+/// Length of the tagged response header (request id and status)
+const TAGGED_RESPONSE_HEADER_LEN: usize = 9;
+
+/// Status of the tagged response carrying the result
+const RESPONSE_OK: u8 = 0;
+
+/// Status of the tagged response to the request the service gave up on
+const RESPONSE_CANCELLED: u8 = 1;
+
+/// Time in milliseconds the caller waits for its response to be routed by another caller
+/// before checking if it should read the responses itself
+const ROUTED_WAIT_MS: u64 = 20;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 /// Handshake for client and server to negotiate api/protocol version
@@ -56,6 +80,7 @@ pub enum Error {
 	ClientUnsupported,
 	RemoteServiceUnsupported,
 	HandshakeFailed,
+	RequestCancelled,
 }
 
 /// Allows implementor to be attached to generic worker and dispatch rpc requests
@@ -67,6 +92,35 @@ pub trait IpcInterface : IpcConfig {
 	/// deserializes the payload from buffer, dispatches invoke and returns serialized result
 	/// (for non-blocking io)
 	fn dispatch_buf(&self, method_num: u16, buf: &[u8]) -> Vec<u8>;
+
+	/// deserializes the payload from buffer, dispatches invoke and returns serialized result
+	/// once it is available; methods returning `Deferred` do not block the caller
+	/// `deadline` is the moment after which the caller is no longer waiting for the result
+	fn dispatch_deferred(&self, method_num: u16, buf: &[u8], _deadline: Option<Instant>) -> Deferred<Vec<u8>> {
+		Deferred::ready(self.dispatch_buf(method_num, buf))
+	}
+}
+
+/// Header of the tagged request
+#[derive(Debug, PartialEq, Clone)]
+pub struct RequestHeader {
+	/// Id the response is tagged with, unique within the connection
+	pub id: u64,
+	/// Time in milliseconds the caller is going to wait for the response (0 for no limit)
+	pub timeout_ms: u32,
+	/// Invoked method
+	pub method_num: u16,
+}
+
+fn write_be(value: u64, buf: &mut [u8]) {
+	let len = buf.len();
+	for (i, byte) in buf.iter_mut().enumerate() {
+		*byte = (value >> (8 * (len - i - 1))) as u8;
+	}
+}
+
+fn read_be(buf: &[u8]) -> u64 {
+	buf.iter().fold(0, |value, byte| value << 8 | *byte as u64)
 }
 
 /// serializes method invocation (method_num and parameters) to the stream specified by `w`
@@ -91,16 +145,195 @@ pub fn invoke<W>(method_num: u16, params: &Option<Vec<u8>>, w: &mut W) where W:
 	}
 }
 
+/// serializes tagged method invocation to the stream specified by `w`
+/// the response is tagged with `id` and can be read with `receive_tagged`
+pub fn invoke_tagged<W>(id: u64, timeout_ms: u32, method_num: u16, params: &Option<Vec<u8>>, w: &mut W) where W: Write {
+	let params_len = params.as_ref().map_or(0, |val| val.len());
+	let mut buf = vec![0u8; TAGGED_HEADER_LEN + params_len];
+
+	write_be(id, &mut buf[0..8]);
+	write_be(timeout_ms as u64, &mut buf[8..12]);
+	write_be(method_num as u64, &mut buf[12..14]);
+
+	if let Some(ref val) = *params {
+		buf[TAGGED_HEADER_LEN..].clone_from_slice(val);
+	}
+
+	invoke(TAGGED_METHOD_NUM, &Some(buf), w);
+}
+
+/// splits the payload of the tagged invocation into header and method parameters
+pub fn decode_tagged_request(buf: &[u8]) -> Option<(RequestHeader, &[u8])> {
+	if buf.len() < TAGGED_HEADER_LEN {
+		return None;
+	}
+
+	let header = RequestHeader {
+		id: read_be(&buf[0..8]),
+		timeout_ms: read_be(&buf[8..12]) as u32,
+		method_num: read_be(&buf[12..14]) as u16,
+	};
+	Some((header, &buf[TAGGED_HEADER_LEN..]))
+}
+
+/// tags serialized result of the invocation with request `id`
+pub fn tagged_response(id: u64, result: &[u8]) -> Vec<u8> {
+	let mut buf = vec![0u8; TAGGED_RESPONSE_HEADER_LEN + result.len()];
+	write_be(id, &mut buf[0..8]);
+	buf[8] = RESPONSE_OK;
+	buf[TAGGED_RESPONSE_HEADER_LEN..].clone_from_slice(result);
+	buf
+}
+
+/// response telling the caller that request `id` will not produce any result
+pub fn cancelled_response(id: u64) -> Vec<u8> {
+	let mut buf = vec![0u8; TAGGED_RESPONSE_HEADER_LEN];
+	write_be(id, &mut buf[0..8]);
+	buf[8] = RESPONSE_CANCELLED;
+	buf
+}
+
+/// Tagged requests sent over the connection and still waiting for the response
+/// Responses can arrive in any order and are routed to the requests by id
+#[derive(Default)]
+pub struct PendingRequests {
+	requests: Mutex<HashMap<u64, Completer<Vec<u8>>>>,
+	/// Notified every time a response is read
+	routed: Condvar,
+}
+
+impl PendingRequests {
+	pub fn new() -> PendingRequests {
+		PendingRequests::default()
+	}
+
+	/// Registers request `id` the caller is going to wait `timeout_ms` for (0 for no limit)
+	/// Returns serialized result of the request
+	pub fn register(&self, id: u64, timeout_ms: u32) -> Deferred<Vec<u8>> {
+		let deadline = match timeout_ms {
+			0 => None,
+			timeout => Some(Instant::now() + Duration::from_millis(timeout as u64)),
+		};
+		let (completer, result) = deferred();
+		let mut requests = self.requests.lock().unwrap();
+		Self::drop_cancelled(&mut requests);
+		requests.insert(id, completer);
+		result.with_deadline(deadline)
+	}
+
+	/// Returns true if request `id` still waits for the response
+	pub fn is_pending(&self, id: u64) -> bool {
+		let mut requests = self.requests.lock().unwrap();
+		Self::drop_cancelled(&mut requests);
+		requests.contains_key(&id)
+	}
+
+	/// Reads single response from the stream specified by `r` and completes the request it belongs to
+	/// Responses to the requests abandoned by the caller are skipped
+	/// All requests are cancelled once the stream fails, since their responses are lost with it
+	pub fn receive<R>(&self, r: &mut R) where R: Read {
+		let mut message = Vec::new();
+		if r.read_to_end(&mut message).is_err() || message.len() < TAGGED_RESPONSE_HEADER_LEN {
+			self.requests.lock().unwrap().clear();
+			self.routed.notify_all();
+			return;
+		}
+
+		let id = read_be(&message[0..8]);
+		let completer = self.requests.lock().unwrap().remove(&id);
+		if let Some(completer) = completer {
+			// dropped completer cancels the request
+			if message[8] == RESPONSE_OK {
+				completer.complete(message[TAGGED_RESPONSE_HEADER_LEN..].to_vec());
+			}
+		}
+		self.routed.notify_all();
+	}
+
+	/// Waits up to `timeout` for the response to request `id` to be read by another caller
+	pub fn wait_routed(&self, id: u64, timeout: Duration) {
+		let requests = self.requests.lock().unwrap();
+		if requests.contains_key(&id) {
+			let _ = self.routed.wait_timeout(requests, timeout).unwrap();
+		}
+	}
+
+	fn drop_cancelled(requests: &mut HashMap<u64, Completer<Vec<u8>>>) {
+		let cancelled = requests.iter()
+			.filter(|&(_, completer)| completer.is_cancelled())
+			.map(|(id, _)| *id)
+			.collect::<Vec<_>>();
+		for id in cancelled {
+			requests.remove(&id);
+		}
+	}
+}
+
+/// Deserialized `response` to request `id` registered in `pending`
+/// Responses are read by whichever caller waits for its result, and each of them is routed to the
+/// request it belongs to, so that any number of requests can be in flight
+/// Responses are read from the `reader` half of the connection if the socket could be split, so that
+/// requests are written to the `socket` meanwhile; otherwise the socket is locked while a response is read
+pub fn receive_tagged<T, S>(
+	id: u64,
+	response: Deferred<Vec<u8>>,
+	pending: &Arc<PendingRequests>,
+	socket: &Arc<RwLock<S>>,
+	reader: &Arc<Mutex<Option<S>>>,
+) -> Deferred<T> where T: BinaryConvertable + Send + 'static, S: IpcSocket
+{
+	let (completer, result) = deferred();
+	response.on_complete(move |payload| {
+		// undecodable response cancels the request
+		if let Ok(value) = binary::deserialize::<T>(&payload) {
+			completer.complete(value);
+		}
+	});
+
+	let pending = pending.clone();
+	let socket = socket.clone();
+	let reader = reader.clone();
+	result.driven_by(Box::new(move || {
+		let mut reader = match reader.try_lock() {
+			Ok(reader) => reader,
+			// another caller is reading, and routes the response here once it arrives
+			Err(TryLockError::WouldBlock) => {
+				pending.wait_routed(id, Duration::from_millis(ROUTED_WAIT_MS));
+				return;
+			},
+			Err(TryLockError::Poisoned(_)) => panic!("ipc reader poisoned by a panicking caller"),
+		};
+		// the response could have been read by another caller meanwhile
+		if !pending.is_pending(id) {
+			return;
+		}
+		match *reader {
+			Some(ref mut reader) => pending.receive(reader),
+			None => pending.receive(&mut *socket.write().unwrap()),
+		}
+	}))
+}
+
 /// IpcSocket, read/write generalization
-pub trait IpcSocket: Read + Write + Sync + Send {
+pub trait IpcSocket: Read + Write + Sync + Send + 'static {
+	/// Splits off the handle reading from the same connection, so that responses can be
+	/// waited for without holding back the requests written to this socket meanwhile
+	/// Returns `None` if the connection cannot be shared, so it has to be locked for reads as well
+	fn try_split(&mut self) -> Option<Self> where Self: Sized {
+		None
+	}
 }
 
 /// Basically something that needs only socket to be spawned
 pub trait WithSocket<S: IpcSocket> {
 	fn init(socket: S) -> Self;
+
+	/// Time in milliseconds the service is asked to spend on deferred requests
+	fn set_request_timeout(&self, _timeout_ms: u32) {}
 }
 
 
 impl IpcSocket for ::devtools::TestSocket {}
 
+#[cfg(feature="nanomsg")]
 impl IpcSocket for ::nanomsg::Socket {}
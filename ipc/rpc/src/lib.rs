@@ -18,10 +18,16 @@
 
 extern crate ethcore_devtools as devtools;
 extern crate semver;
+#[cfg(feature="nanomsg")]
 extern crate nanomsg;
 extern crate ethcore_util as util;
 
 pub mod interface;
 pub mod binary;
+pub mod framed;
+pub mod deferred;
 pub use interface::{IpcInterface, IpcSocket, invoke, IpcConfig, Handshake, Error, WithSocket};
+pub use interface::{RequestHeader, TAGGED_METHOD_NUM, invoke_tagged, decode_tagged_request, tagged_response, cancelled_response, receive_tagged, PendingRequests};
 pub use binary::{BinaryConvertable, BinaryConvertError, BinHandshake};
+pub use framed::FramedSocket;
+pub use deferred::{Deferred, Completer, deferred};
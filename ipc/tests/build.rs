@@ -19,6 +19,7 @@ extern crate ethcore_ipc_codegen as codegen;
 pub fn main() {
 	codegen::derive_ipc("nested.rs.in").unwrap();
 	codegen::derive_ipc("service.rs.in").unwrap();
+	codegen::derive_ipc("deferred_service.rs.in").unwrap();
 	codegen::derive_ipc("with_attrs.rs.in").unwrap();
 	codegen::derive_binary("binary.rs.in").unwrap();
 }
@@ -18,6 +18,7 @@
 mod tests {
 
 	use super::super::service::*;
+	use super::super::deferred_service::*;
 	use super::super::binary::*;
 	use super::super::nested::{DBClient, DBWriter};
 	use ipc::*;
@@ -76,6 +77,106 @@ mod tests {
 		assert_eq!(10, result);
 	}
 
+	#[test]
+	fn call_deferred_service() {
+		let service = DeferredService::new();
+		let payload = vec![
+			0, 0, 0, 0, 0, 0, 0, 0,
+			4, 0, 0, 0, 0, 0, 0, 0,
+			10, 0, 0, 0];
+
+		let blocking = service.dispatch_buf(17, &payload);
+		let deferred = service.dispatch_deferred(17, &payload, None).wait().unwrap();
+
+		assert_eq!(vec![10, 0, 0, 0], blocking);
+		assert_eq!(vec![10, 0, 0, 0], deferred);
+		assert_eq!(20, *service.commits.read().unwrap());
+	}
+
+	#[test]
+	fn call_deferred_service_client() {
+		let mut socket = TestSocket::new();
+		// response tagged with request id 0
+		socket.read_buffer = vec![
+			0, 0, 0, 0, 0, 0, 0, 0,
+			0,
+			10, 0, 0, 0];
+		let service_client = DeferredServiceClient::init(socket);
+
+		let result = service_client.deferred_commit(5).wait();
+
+		assert_eq!(vec![
+			0, 1,
+			// request id, timeout and method num
+			0, 0, 0, 0, 0, 0, 0, 0,
+			0, 0, 0, 0,
+			0, 17,
+			0, 0, 0, 0, 0, 0, 0, 0,
+			4, 0, 0, 0, 0, 0, 0, 0,
+			5, 0, 0, 0],
+			service_client.socket().write().unwrap().write_buffer.clone());
+		assert_eq!(10, result.unwrap());
+	}
+
+	#[test]
+	fn deferred_service_client_routes_responses_by_id() {
+		let mut socket = TestSocket::new();
+		socket.read_buffer = [
+			framed::frame(&tagged_response(1, &[7, 0, 0, 0])),
+			framed::frame(&tagged_response(0, &[6, 0, 0, 0])),
+		].concat();
+		let service_client = DeferredServiceClient::init(FramedSocket::new(socket));
+
+		let first = service_client.deferred_commit(5);
+		let second = service_client.deferred_commit(6);
+
+		assert_eq!(6, first.wait().unwrap());
+		assert_eq!(7, second.wait().unwrap());
+	}
+
+	#[test]
+	fn deferred_service_client_sees_cancelled_request() {
+		let mut socket = TestSocket::new();
+		socket.read_buffer = framed::frame(&cancelled_response(0));
+		let service_client = DeferredServiceClient::init(FramedSocket::new(socket));
+
+		assert!(service_client.deferred_commit(5).wait().is_err());
+	}
+
+	#[test]
+	fn deferred_service_client_tags_every_call() {
+		let mut socket = TestSocket::new();
+		// the client has deferred methods, so every response is tagged with request id
+		socket.read_buffer = tagged_response(0, &[10, 0, 0, 0]);
+		let service_client = DeferredServiceClient::init(socket);
+
+		let result = service_client.commit(5);
+
+		assert_eq!(
+			vec![0, 1,
+				// request id, timeout and method num
+				0, 0, 0, 0, 0, 0, 0, 0,
+				0, 0, 0, 0,
+				0, 16,
+				0, 0, 0, 0, 0, 0, 0, 0,
+				4, 0, 0, 0, 0, 0, 0, 0,
+				5, 0, 0, 0],
+			service_client.socket().write().unwrap().write_buffer.clone());
+		assert_eq!(10, result);
+	}
+
+	#[test]
+	fn deferred_service_client_tags_handshake() {
+		let mut socket = TestSocket::new();
+		socket.read_buffer = tagged_response(0, &[1]);
+		let service_client = DeferredServiceClient::init(socket);
+
+		let result = service_client.handshake();
+
+		assert!(result.is_ok());
+		assert_eq!(&[0, 1], &service_client.socket().write().unwrap().write_buffer[0..2]);
+	}
+
 	#[test]
 	fn query_default_version() {
 		let ver = Service::protocol_version();
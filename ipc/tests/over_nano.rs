@@ -19,7 +19,7 @@ mod tests {
 
 	use super::super::service::*;
 	use super::super::with_attrs::PrettyNamedClient;
-	use nanoipc;
+	use nanoipc::{self, Transport, Nano};
 	use std::sync::Arc;
 	use std::io::Write;
 	use std::sync::atomic::{Ordering, AtomicBool};
@@ -32,21 +32,21 @@ mod tests {
 	}
 
 
-	fn init_worker(addr: &str) -> nanoipc::Worker<Service> {
-		let mut worker = nanoipc::Worker::<Service>::new(&Arc::new(Service::new()));
+	fn init_worker(addr: &str) -> nanoipc::Worker<Service, Nano> {
+		let mut worker = nanoipc::Worker::with_transport(&Arc::new(Service::new()));
 		worker.add_duplex(addr).unwrap();
 		worker
 	}
 
 	#[test]
 	fn can_create_client() {
-		let client = nanoipc::init_duplex_client::<ServiceClient<_>>("ipc:///tmp/parity-nano-test10.ipc");
+		let client = Nano::init_duplex_client::<ServiceClient<_>>("ipc:///tmp/parity-nano-test10.ipc");
 		assert!(client.is_ok());
 	}
 
 	#[test]
 	fn can_create_renamed_client() {
-		let client = nanoipc::init_duplex_client::<PrettyNamedClient<_>>("ipc:///tmp/parity-nano-test10.ipc");
+		let client = Nano::init_duplex_client::<PrettyNamedClient<_>>("ipc:///tmp/parity-nano-test10.ipc");
 		assert!(client.is_ok());
 	}
 
@@ -67,7 +67,7 @@ mod tests {
 		});
 
 		while !worker_is_ready.load(Ordering::Relaxed) { }
-		let client = nanoipc::init_duplex_client::<ServiceClient<_>>(url).unwrap();
+		let client = Nano::init_duplex_client::<ServiceClient<_>>(url).unwrap();
 
 		let hs = client.handshake();
 
@@ -0,0 +1,149 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod tests {
+
+	use super::super::service::*;
+	use super::super::deferred_service::*;
+	use nanoipc::{self, Transport, Unix};
+	use std::sync::Arc;
+	use std::sync::atomic::{Ordering, AtomicBool};
+	use std::thread;
+	use std::time::{Duration, Instant};
+
+	fn init_worker(addr: &str) -> nanoipc::Worker<Service, Unix> {
+		let mut worker = nanoipc::Worker::with_transport(&Arc::new(Service::new()));
+		worker.add_duplex(addr).unwrap();
+		worker
+	}
+
+	fn init_deferred_worker(addr: &str) -> nanoipc::Worker<DeferredService, Unix> {
+		let mut worker = nanoipc::Worker::with_transport(&Arc::new(DeferredService::new()));
+		worker.add_duplex(addr).unwrap();
+		worker
+	}
+
+	#[test]
+	fn can_call_handshake() {
+		let url = "ipc:///tmp/parity-test-unix-20.ipc";
+		let worker_should_exit = Arc::new(AtomicBool::new(false));
+		let worker_is_ready = Arc::new(AtomicBool::new(false));
+		let c_worker_should_exit = worker_should_exit.clone();
+		let c_worker_is_ready = worker_is_ready.clone();
+
+		::std::thread::spawn(move || {
+			let mut worker = init_worker(url);
+			while !c_worker_should_exit.load(Ordering::Relaxed) {
+				worker.poll();
+				c_worker_is_ready.store(true, Ordering::Relaxed);
+			}
+		});
+
+		while !worker_is_ready.load(Ordering::Relaxed) { }
+		let client = Unix::init_duplex_client::<ServiceClient<_>>(url).unwrap();
+
+		let hs = client.handshake();
+
+		worker_should_exit.store(true, Ordering::Relaxed);
+		assert!(hs.is_ok());
+	}
+
+	#[test]
+	fn can_call_method() {
+		let url = "ipc:///tmp/parity-test-unix-30.ipc";
+		let worker_should_exit = Arc::new(AtomicBool::new(false));
+		let worker_is_ready = Arc::new(AtomicBool::new(false));
+		let c_worker_should_exit = worker_should_exit.clone();
+		let c_worker_is_ready = worker_is_ready.clone();
+
+		::std::thread::spawn(move || {
+			let mut worker = init_worker(url);
+			while !c_worker_should_exit.load(Ordering::Relaxed) {
+				worker.poll();
+				c_worker_is_ready.store(true, Ordering::Relaxed);
+			}
+		});
+
+		while !worker_is_ready.load(Ordering::Relaxed) { }
+		let client = Unix::init_duplex_client::<ServiceClient<_>>(url).unwrap();
+
+		let result = client.commit(5);
+
+		worker_should_exit.store(true, Ordering::Relaxed);
+		assert_eq!(5, result);
+	}
+
+	#[test]
+	fn can_call_deferred_method() {
+		let url = "ipc:///tmp/parity-test-unix-40.ipc";
+		let worker_should_exit = Arc::new(AtomicBool::new(false));
+		let worker_is_ready = Arc::new(AtomicBool::new(false));
+		let c_worker_should_exit = worker_should_exit.clone();
+		let c_worker_is_ready = worker_is_ready.clone();
+
+		::std::thread::spawn(move || {
+			let mut worker = init_deferred_worker(url);
+			while !c_worker_should_exit.load(Ordering::Relaxed) {
+				worker.poll();
+				c_worker_is_ready.store(true, Ordering::Relaxed);
+			}
+		});
+
+		while !worker_is_ready.load(Ordering::Relaxed) { }
+		let client = Unix::init_duplex_client::<DeferredServiceClient<_>>(url).unwrap();
+
+		let result = client.deferred_commit(5).wait();
+
+		worker_should_exit.store(true, Ordering::Relaxed);
+		assert_eq!(5, result.unwrap());
+	}
+
+	#[test]
+	fn slow_deferred_method_does_not_hold_back_other_calls() {
+		let url = "ipc:///tmp/parity-test-unix-50.ipc";
+		let worker_should_exit = Arc::new(AtomicBool::new(false));
+		let worker_is_ready = Arc::new(AtomicBool::new(false));
+		let c_worker_should_exit = worker_should_exit.clone();
+		let c_worker_is_ready = worker_is_ready.clone();
+
+		::std::thread::spawn(move || {
+			let mut worker = init_deferred_worker(url);
+			while !c_worker_should_exit.load(Ordering::Relaxed) {
+				worker.poll();
+				c_worker_is_ready.store(true, Ordering::Relaxed);
+			}
+		});
+
+		while !worker_is_ready.load(Ordering::Relaxed) { }
+		let client = Unix::init_duplex_client::<DeferredServiceClient<_>>(url).unwrap();
+
+		// another caller is blocked reading until the slow response arrives
+		let slow_client = client.service();
+		let slow = thread::spawn(move || slow_client.delayed_commit(7, 1000).wait());
+		thread::sleep(Duration::from_millis(100));
+
+		let started = Instant::now();
+		let result = client.commit(5);
+		let elapsed = started.elapsed();
+
+		let slow_result = slow.join().unwrap();
+		worker_should_exit.store(true, Ordering::Relaxed);
+		assert_eq!(5, result);
+		assert!(elapsed < Duration::from_millis(500));
+		assert_eq!(7, slow_result.unwrap());
+	}
+}
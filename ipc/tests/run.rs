@@ -25,8 +25,11 @@ extern crate ethcore_util as util;
 #[macro_use] extern crate log;
 
 pub mod service;
+pub mod deferred_service;
 mod examples;
 mod over_nano;
+#[cfg(unix)]
+mod over_unix;
 mod nested;
 mod binary;
 mod with_attrs;
@@ -16,20 +16,50 @@
 
 //! Authority params deserialization.
 
+use serde::{Deserialize, Deserializer, Error};
 use uint::Uint;
 use hash::Address;
+use spec::ValidatorSet;
 
 /// Authority params deserialization.
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, PartialEq)]
 pub struct BasicAuthorityParams {
 	/// Gas limit divisor.
-	#[serde(rename="gasLimitBoundDivisor")]
 	pub gas_limit_bound_divisor: Uint,
 	/// Block duration.
-	#[serde(rename="durationLimit")]
 	pub duration_limit: Uint,
-	/// Valid authorities
-	pub authorities: Vec<Address>,
+	/// Valid authorities. Superseded by `validators` if both are given.
+	pub authorities: Option<Vec<Address>>,
+	/// Validator set, which may change over time.
+	pub validators: Option<ValidatorSet>,
+}
+
+// params as they appear in json, before checking that signatories are given.
+#[derive(Deserialize)]
+struct Params {
+	#[serde(rename="gasLimitBoundDivisor")]
+	gas_limit_bound_divisor: Uint,
+	#[serde(rename="durationLimit")]
+	duration_limit: Uint,
+	authorities: Option<Vec<Address>>,
+	validators: Option<ValidatorSet>,
+}
+
+impl Deserialize for BasicAuthorityParams {
+	fn deserialize<D>(deserializer: &mut D) -> Result<Self, D::Error>
+		where D: Deserializer {
+		let p = try!(Params::deserialize(deserializer));
+		if p.authorities.is_none() && p.validators.is_none() {
+			return Err(Error::custom("Either authorities or validators have to be specified."));
+		}
+
+		Ok(BasicAuthorityParams {
+			gas_limit_bound_divisor: p.gas_limit_bound_divisor,
+			duration_limit: p.duration_limit,
+			authorities: p.authorities,
+			validators: p.validators,
+		})
+	}
 }
 
 /// Authority engine deserialization.
@@ -42,7 +72,9 @@ pub struct BasicAuthority {
 #[cfg(test)]
 mod tests {
 	use serde_json;
+	use hash::Address;
 	use spec::basic_authority::BasicAuthority;
+	use spec::validator_set::ValidatorSet;
 
 	#[test]
 	fn basic_authority_deserialization() {
@@ -56,4 +88,34 @@ mod tests {
 
 		let _deserialized: BasicAuthority = serde_json::from_str(s).unwrap();
 	}
+
+	#[test]
+	fn basic_authority_validators_deserialization() {
+		let s = r#"{
+			"params": {
+				"gasLimitBoundDivisor": "0x0400",
+				"durationLimit": "0x0d",
+				"validators": {
+					"contract": "0xc6d9d2cd449a754c494264e1809c50e34d64562b"
+				}
+			}
+		}"#;
+
+		let deserialized: BasicAuthority = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.authorities, None);
+		assert_eq!(deserialized.params.validators, Some(ValidatorSet::Contract(Address("c6d9d2cd449a754c494264e1809c50e34d64562b".into()))));
+	}
+
+	#[test]
+	fn basic_authority_without_signatories_is_rejected() {
+		let s = r#"{
+			"params": {
+				"gasLimitBoundDivisor": "0x0400",
+				"durationLimit": "0x0d"
+			}
+		}"#;
+
+		let deserialized: Result<BasicAuthority, _> = serde_json::from_str(s);
+		assert!(deserialized.is_err());
+	}
 }
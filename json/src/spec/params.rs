@@ -17,7 +17,7 @@
 //! Spec params deserialization.
 
 use uint::Uint;
-use hash::H256;
+use hash::{H256, Address};
 
 /// Spec params.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -40,12 +40,17 @@ pub struct Params {
 	/// Expected fork block hash.
 	#[serde(rename="forkCanonHash")]
 	pub fork_hash: Option<H256>,
+	/// Contract deciding which transactions senders are permitted to send.
+	#[serde(rename="transactionPermissionContract")]
+	pub transaction_permission_contract: Option<Address>,
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use spec::params::Params;
+	use hash::Address;
+	use util::hash::H160;
 
 	#[test]
 	fn params_deserialization() {
@@ -54,10 +59,12 @@ mod tests {
 			"maximumExtraDataSize": "0x20",
 			"networkID" : "0x1",
 			"minGasLimit": "0x1388",
-			"accountStartNonce": "0x00"
+			"accountStartNonce": "0x00",
+			"transactionPermissionContract": "0x0000000000000000000000000000000000000005"
 		}"#;
 
-		let _deserialized: Params = serde_json::from_str(s).unwrap();
+		let deserialized: Params = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.transaction_permission_contract, Some(Address(H160::from(5u64))));
 		// TODO: validate all fields
 	}
 }
@@ -0,0 +1,72 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Validator set deserialization.
+
+use std::collections::BTreeMap;
+use uint::Uint;
+use hash::Address;
+
+/// Different ways of specifying validators.
+#[derive(Debug, PartialEq, Deserialize)]
+pub enum ValidatorSet {
+	/// A simple list of authorities.
+	#[serde(rename="list")]
+	List(Vec<Address>),
+	/// Address of a contract that indicates the list of authorities.
+	#[serde(rename="contract")]
+	Contract(Address),
+	/// A map of starting blocks for each validator set.
+	#[serde(rename="multi")]
+	Multi(BTreeMap<Uint, ValidatorSet>),
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use uint::Uint;
+	use util::U256;
+	use hash::Address;
+	use spec::validator_set::ValidatorSet;
+
+	#[test]
+	fn validator_set_deserialization() {
+		let s = r#"[{
+			"list" : ["0xc5d2460186f7233c927e7db2dcc703c0e500b653"]
+		}, {
+			"contract" : "0xc6d9d2cd449a754c494264e1809c50e34d64562b"
+		}, {
+			"multi": {
+				"0": { "list": ["0xc5d2460186f7233c927e7db2dcc703c0e500b653"] },
+				"0x0a": { "contract": "0xc6d9d2cd449a754c494264e1809c50e34d64562b" }
+			}
+		}]"#;
+
+		let deserialized: Vec<ValidatorSet> = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.len(), 3);
+
+		assert_eq!(deserialized[0], ValidatorSet::List(vec![Address("c5d2460186f7233c927e7db2dcc703c0e500b653".into())]));
+		assert_eq!(deserialized[1], ValidatorSet::Contract(Address("c6d9d2cd449a754c494264e1809c50e34d64562b".into())));
+		match deserialized[2] {
+			ValidatorSet::Multi(ref map) => {
+				assert_eq!(map.len(), 2);
+				assert!(map.contains_key(&Uint(U256::from(0))));
+				assert!(map.contains_key(&Uint(U256::from(10))));
+			},
+			_ => assert!(false),
+		}
+	}
+}
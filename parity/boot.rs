@@ -24,7 +24,7 @@ use hypervisor::HypervisorServiceClient;
 use hypervisor::service::IpcModuleId;
 use ctrlc::CtrlC;
 use std::sync::atomic::{AtomicBool, Ordering};
-use nanoipc::{IpcInterface, GuardedSocket, NanoSocket};
+use nanoipc::{IpcInterface, GuardedSocket, DefaultSocket};
 use ipc::WithSocket;
 use ethcore_logger::{Config as LogConfig, setup_log};
 use docopt::Docopt;
@@ -60,7 +60,7 @@ pub fn payload<B: ipc::BinaryConvertable>() -> Result<B, BootError> {
 	ipc::binary::deserialize::<B>(&buffer).map_err(BootError::DecodeArgs)
 }
 
-pub fn register(hv_url: &str, control_url: &str, module_id: IpcModuleId) -> GuardedSocket<HypervisorServiceClient<NanoSocket>>{
+pub fn register(hv_url: &str, control_url: &str, module_id: IpcModuleId) -> GuardedSocket<HypervisorServiceClient<DefaultSocket>>{
 	let hypervisor_client = nanoipc::fast_client::<HypervisorServiceClient<_>>(hv_url).unwrap();
 	hypervisor_client.handshake().unwrap();
 	hypervisor_client.module_ready(module_id, control_url.to_owned());
@@ -68,7 +68,7 @@ pub fn register(hv_url: &str, control_url: &str, module_id: IpcModuleId) -> Guar
 	hypervisor_client
 }
 
-pub fn dependency<C: WithSocket<NanoSocket>>(url: &str)
+pub fn dependency<C: WithSocket<DefaultSocket>>(url: &str)
 	-> Result<GuardedSocket<C>, BootError>
 {
 	nanoipc::generic_client::<C>(url).map_err(BootError::DependencyConnect)
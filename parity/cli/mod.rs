@@ -32,6 +32,9 @@ usage! {
 		cmd_snapshot: bool,
 		cmd_restore: bool,
 		cmd_ui: bool,
+		cmd_db: bool,
+		cmd_check: bool,
+		cmd_convert: bool,
 
 		// Arguments
 		arg_pid_file: String,
@@ -116,6 +119,8 @@ usage! {
 			or |c: &Config| otry!(c.network).id.clone().map(Some),
 		flag_bootnodes: Option<String> = None,
 			or |c: &Config| otry!(c.network).bootnodes.clone().map(|vec| Some(vec.join(","))),
+		flag_dns_trees: Option<String> = None,
+			or |c: &Config| otry!(c.network).dns_trees.clone().map(|vec| Some(vec.join(","))),
 		flag_no_discovery: bool = false,
 			or |c: &Config| otry!(c.network).discovery.map(|d| !d).clone(),
 		flag_node_key: Option<String> = None,
@@ -124,6 +129,14 @@ usage! {
 			or |c: &Config| otry!(c.network).reserved_peers.clone().map(Some),
 		flag_reserved_only: bool = false,
 			or |c: &Config| otry!(c.network).reserved_only.clone(),
+		flag_max_upload: Option<u32> = None,
+			or |c: &Config| otry!(c.network).max_upload.clone().map(Some),
+		flag_max_download: Option<u32> = None,
+			or |c: &Config| otry!(c.network).max_download.clone().map(Some),
+		flag_max_peer_upload: Option<u32> = None,
+			or |c: &Config| otry!(c.network).max_peer_upload.clone().map(Some),
+		flag_max_peer_download: Option<u32> = None,
+			or |c: &Config| otry!(c.network).max_peer_download.clone().map(Some),
 
 		// -- API and Console Options
 		// RPC
@@ -139,6 +152,12 @@ usage! {
 			or |c: &Config| otry!(c.rpc).apis.clone().map(|vec| vec.join(",")),
 		flag_jsonrpc_hosts: String = "none",
 			or |c: &Config| otry!(c.rpc).hosts.clone().map(|vec| vec.join(",")),
+		flag_jsonrpc_api_keys: Option<String> = None,
+			or |c: &Config| otry!(c.rpc).api_keys.clone().map(Some),
+		flag_jsonrpc_max_batch_size: Option<usize> = None,
+			or |c: &Config| otry!(c.rpc).max_batch_size.clone().map(Some),
+		flag_jsonrpc_max_payload: Option<usize> = None,
+			or |c: &Config| otry!(c.rpc).max_payload.clone().map(Some),
 
 		// IPC
 		flag_no_ipc: bool = false,
@@ -185,6 +204,10 @@ usage! {
 			or |c: &Config| otry!(c.mining).usd_per_eth.clone(),
 		flag_price_update_period: String = "hourly",
 			or |c: &Config| otry!(c.mining).price_update_period.clone(),
+		flag_gas_price_source: String = "usd",
+			or |c: &Config| otry!(c.mining).gas_price_source.clone(),
+		flag_gas_price_oracle_blocks: usize = 20usize,
+			or |c: &Config| otry!(c.mining).gas_price_oracle_blocks.clone(),
 		flag_gas_floor_target: String = "4700000",
 			or |c: &Config| otry!(c.mining).gas_floor_target.clone(),
 		flag_gas_cap: String = "6283184",
@@ -197,12 +220,26 @@ usage! {
 			or |c: &Config| otry!(c.mining).remove_solved.clone(),
 		flag_notify_work: Option<String> = None,
 			or |c: &Config| otry!(c.mining).notify_work.clone().map(|vec| Some(vec.join(","))),
+		flag_stratum: bool = false,
+			or |c: &Config| otry!(c.mining).stratum.clone(),
+		flag_stratum_interface: String = "local",
+			or |c: &Config| otry!(c.mining).stratum_interface.clone(),
+		flag_stratum_port: u16 = 8008u16,
+			or |c: &Config| otry!(c.mining).stratum_port.clone(),
+		flag_stratum_secret: Option<String> = None,
+			or |c: &Config| otry!(c.mining).stratum_secret.clone().map(Some),
 
 		// -- Footprint Options
 		flag_tracing: String = "auto",
 			or |c: &Config| otry!(c.footprint).tracing.clone(),
 		flag_pruning: String = "auto",
 			or |c: &Config| otry!(c.footprint).pruning.clone(),
+		flag_pruning_history: u64 = 1200u64,
+			or |c: &Config| otry!(c.footprint).pruning_history.clone(),
+		flag_pruning_memory: usize = 0usize,
+			or |c: &Config| otry!(c.footprint).pruning_memory.clone(),
+		flag_state_replay_limit: u64 = 0u64,
+			or |c: &Config| otry!(c.footprint).state_replay_limit.clone(),
 		flag_cache_size_db: u32 = 64u32,
 			or |c: &Config| otry!(c.footprint).cache_size_db.clone(),
 		flag_cache_size_blocks: u32 = 8u32,
@@ -227,6 +264,19 @@ usage! {
 		flag_at: String = "latest", or |_| None,
 		flag_no_periodic_snapshot: bool = false,
 			or |c: &Config| otry!(c.snapshots).disable_periodic.clone(),
+		flag_incremental_snapshots: bool = false,
+			or |c: &Config| otry!(c.snapshots).incremental.clone(),
+		flag_no_snapshot_serving: bool = false,
+			or |c: &Config| otry!(c.snapshots).disable_serving.clone(),
+		flag_snapshot_requests: usize = 8usize,
+			or |c: &Config| otry!(c.snapshots).max_requests.clone(),
+		flag_snapshot_peer_requests: usize = 2usize,
+			or |c: &Config| otry!(c.snapshots).max_peer_requests.clone(),
+
+		// -- Database Check Options
+		flag_check_storage: bool = false, or |_| None,
+		flag_repair: bool = false, or |_| None,
+		flag_repair_timeout: u64 = 600u64, or |_| None,
 
 		// -- Virtual Machine Options
 		flag_jitvm: bool = false,
@@ -296,10 +346,15 @@ struct Network {
 	nat: Option<String>,
 	id: Option<String>,
 	bootnodes: Option<Vec<String>>,
+	dns_trees: Option<Vec<String>>,
 	discovery: Option<bool>,
 	node_key: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
+	max_upload: Option<u32>,
+	max_download: Option<u32>,
+	max_peer_upload: Option<u32>,
+	max_peer_download: Option<u32>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -310,6 +365,9 @@ struct Rpc {
 	cors: Option<String>,
 	apis: Option<Vec<String>>,
 	hosts: Option<Vec<String>>,
+	api_keys: Option<String>,
+	max_batch_size: Option<usize>,
+	max_payload: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -342,18 +400,27 @@ struct Mining {
 	usd_per_tx: Option<String>,
 	usd_per_eth: Option<String>,
 	price_update_period: Option<String>,
+	gas_price_source: Option<String>,
+	gas_price_oracle_blocks: Option<usize>,
 	gas_floor_target: Option<String>,
 	gas_cap: Option<String>,
 	extra_data: Option<String>,
 	tx_queue_size: Option<usize>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
+	stratum: Option<bool>,
+	stratum_interface: Option<String>,
+	stratum_port: Option<u16>,
+	stratum_secret: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Footprint {
 	tracing: Option<String>,
 	pruning: Option<String>,
+	pruning_history: Option<u64>,
+	pruning_memory: Option<usize>,
+	state_replay_limit: Option<u64>,
 	fast_and_loose: Option<bool>,
 	cache_size: Option<u32>,
 	cache_size_db: Option<u32>,
@@ -366,6 +433,10 @@ struct Footprint {
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Snapshots {
 	disable_periodic: Option<bool>,
+	incremental: Option<bool>,
+	disable_serving: Option<bool>,
+	max_requests: Option<usize>,
+	max_peer_requests: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -441,6 +512,9 @@ mod tests {
 			cmd_snapshot: false,
 			cmd_restore: false,
 			cmd_ui: false,
+			cmd_db: false,
+			cmd_check: false,
+			cmd_convert: false,
 
 			// Arguments
 			arg_pid_file: "".into(),
@@ -476,10 +550,15 @@ mod tests {
 			flag_nat: "any".into(),
 			flag_network_id: Some("0x1".into()),
 			flag_bootnodes: Some("".into()),
+			flag_dns_trees: Some("".into()),
 			flag_no_discovery: false,
 			flag_node_key: None,
 			flag_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
+			flag_max_upload: Some(2048),
+			flag_max_download: Some(4096),
+			flag_max_peer_upload: Some(256),
+			flag_max_peer_download: Some(512),
 
 			// -- API and Console Options
 			// RPC
@@ -489,6 +568,9 @@ mod tests {
 			flag_jsonrpc_cors: Some("null".into()),
 			flag_jsonrpc_apis: "web3,eth,net,personal,ethcore,traces,rpc".into(),
 			flag_jsonrpc_hosts: "none".into(),
+			flag_jsonrpc_api_keys: Some("$HOME/.parity/rpc_keys.json".into()),
+			flag_jsonrpc_max_batch_size: Some(100),
+			flag_jsonrpc_max_payload: Some(5242880),
 
 			// IPC
 			flag_no_ipc: false,
@@ -515,16 +597,25 @@ mod tests {
 			flag_usd_per_tx: "0".into(),
 			flag_usd_per_eth: "auto".into(),
 			flag_price_update_period: "hourly".into(),
+			flag_gas_price_source: "usd".into(),
+			flag_gas_price_oracle_blocks: 20usize,
 			flag_gas_floor_target: "4700000".into(),
 			flag_gas_cap: "6283184".into(),
 			flag_extra_data: Some("Parity".into()),
 			flag_tx_queue_size: 1024usize,
 			flag_remove_solved: false,
 			flag_notify_work: Some("http://localhost:3001".into()),
+			flag_stratum: false,
+			flag_stratum_interface: "local".into(),
+			flag_stratum_port: 8008u16,
+			flag_stratum_secret: None,
 
 			// -- Footprint Options
 			flag_tracing: "auto".into(),
 			flag_pruning: "auto".into(),
+			flag_pruning_history: 1200u64,
+			flag_pruning_memory: 0usize,
+			flag_state_replay_limit: 0u64,
 			flag_cache_size_db: 64u32,
 			flag_cache_size_blocks: 8u32,
 			flag_cache_size_queue: 50u32,
@@ -541,6 +632,15 @@ mod tests {
 			// -- Snapshot Optons
 			flag_at: "latest".into(),
 			flag_no_periodic_snapshot: false,
+			flag_incremental_snapshots: false,
+			flag_no_snapshot_serving: false,
+			flag_snapshot_requests: 8usize,
+			flag_snapshot_peer_requests: 2usize,
+
+			// -- Database Check Options
+			flag_check_storage: false,
+			flag_repair: false,
+			flag_repair_timeout: 600u64,
 
 			// -- Virtual Machine Options
 			flag_jitvm: false,
@@ -629,10 +729,15 @@ mod tests {
 				nat: Some("any".into()),
 				id: None,
 				bootnodes: None,
+				dns_trees: None,
 				discovery: Some(true),
 				node_key: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
+				max_upload: None,
+				max_download: None,
+				max_peer_upload: None,
+				max_peer_download: None,
 			}),
 			rpc: Some(Rpc {
 				disable: Some(true),
@@ -641,6 +746,9 @@ mod tests {
 				cors: None,
 				apis: None,
 				hosts: None,
+				api_keys: None,
+				max_batch_size: None,
+				max_payload: None,
 			}),
 			ipc: Some(Ipc {
 				disable: None,
@@ -666,6 +774,8 @@ mod tests {
 				usd_per_tx: None,
 				usd_per_eth: None,
 				price_update_period: Some("hourly".into()),
+				gas_price_source: None,
+				gas_price_oracle_blocks: None,
 				gas_floor_target: None,
 				gas_cap: None,
 				tx_queue_size: Some(2048),
@@ -673,10 +783,17 @@ mod tests {
 				extra_data: None,
 				remove_solved: None,
 				notify_work: None,
+				stratum: None,
+				stratum_interface: None,
+				stratum_port: None,
+				stratum_secret: None,
 			}),
 			footprint: Some(Footprint {
 				tracing: Some("on".into()),
 				pruning: Some("fast".into()),
+				pruning_history: None,
+				pruning_memory: None,
+				state_replay_limit: None,
 				fast_and_loose: None,
 				cache_size: None,
 				cache_size_db: Some(128),
@@ -687,6 +804,10 @@ mod tests {
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
+				incremental: None,
+				disable_serving: None,
+				max_requests: None,
+				max_peer_requests: None,
 			}),
 			vm: Some(VM {
 				jit: Some(false),
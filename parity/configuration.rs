@@ -24,23 +24,24 @@ use util::{Hashable, U256, Uint, Bytes, version_data, Secret, Address};
 use util::log::Colour;
 use ethsync::{NetworkConfiguration, is_valid_node_url};
 use ethcore::client::{VMType, Mode};
-use ethcore::miner::MinerOptions;
+use ethcore::miner::{MinerOptions, StratumOptions};
 
 use rpc::{IpcConfiguration, HttpConfiguration};
 use ethcore_rpc::NetworkSettings;
 use cache::CacheConfig;
 use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, replace_home,
-geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address};
+geth_ipc_path, parity_ipc_path, to_bootnodes, to_dns_trees, to_addresses, to_address};
 use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, SpecType};
 use ethcore_logger::Config as LogConfig;
 use dir::Directories;
 use dapps::Configuration as DappsConfiguration;
 use signer::Configuration as SignerConfiguration;
-use run::RunCmd;
+use run::{RunCmd, SnapshotServing};
 use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat};
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ImportAccounts};
 use snapshot::{self, SnapshotCommand};
+use db::{DbCmd, CheckDatabase, ConvertDatabase};
 
 #[derive(Debug, PartialEq)]
 pub enum Cmd {
@@ -51,6 +52,7 @@ pub enum Cmd {
 	Blockchain(BlockchainCmd),
 	SignerToken(String),
 	Snapshot(SnapshotCommand),
+	Db(DbCmd),
 }
 
 #[derive(Debug, PartialEq)]
@@ -189,6 +191,35 @@ impl Configuration {
 				block_at: try!(to_block_id("latest")), // unimportant.
 			};
 			Cmd::Snapshot(restore_cmd)
+		} else if self.args.cmd_db && self.args.cmd_check {
+			let check_cmd = CheckDatabase {
+				spec: spec,
+				logger_config: logger_config,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				compaction: compaction,
+				wal: wal,
+				mode: mode,
+				tracing: tracing,
+				check_storage: self.args.flag_check_storage,
+				repair: self.args.flag_repair,
+				repair_timeout: self.args.flag_repair_timeout,
+				net_conf: net_conf,
+				custom_bootnodes: self.args.flag_bootnodes.is_some(),
+				network_id: network_id,
+			};
+			Cmd::Db(DbCmd::Check(check_cmd))
+		} else if self.args.cmd_db && self.args.cmd_convert {
+			let convert_cmd = ConvertDatabase {
+				spec: spec,
+				logger_config: logger_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: self.args.flag_pruning_history,
+				compaction: compaction,
+			};
+			Cmd::Db(DbCmd::Convert(convert_cmd))
 		} else {
 			let daemon = if self.args.cmd_daemon {
 				Some(self.args.arg_pid_file.clone())
@@ -226,6 +257,17 @@ impl Configuration {
 				name: self.args.flag_identity,
 				custom_bootnodes: self.args.flag_bootnodes.is_some(),
 				no_periodic_snapshot: self.args.flag_no_periodic_snapshot,
+				incremental_snapshots: self.args.flag_incremental_snapshots,
+				state_replay_limit: self.args.flag_state_replay_limit,
+				pruning_history: self.args.flag_pruning_history,
+				pruning_memory: self.args.flag_pruning_memory,
+				fat_db: self.args.flag_fat_db,
+				stratum: try!(self.stratum_options()),
+				snapshot_serving: SnapshotServing {
+					enabled: !self.args.flag_no_snapshot_serving,
+					max_requests: self.args.flag_snapshot_requests,
+					max_peer_requests: self.args.flag_snapshot_peer_requests,
+				},
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -336,6 +378,7 @@ impl Configuration {
 			reseal_min_period: Duration::from_millis(self.args.flag_reseal_min_period),
 			work_queue_size: self.args.flag_work_queue_size,
 			enable_resubmission: !self.args.flag_remove_solved,
+			gas_price_oracle_blocks: self.args.flag_gas_price_oracle_blocks,
 		};
 
 		Ok(options)
@@ -364,6 +407,17 @@ impl Configuration {
 	}
 
 	fn gas_pricer_config(&self) -> Result<GasPricerConfig, String> {
+		match self.args.flag_gas_price_source.as_str() {
+			"usd" => {},
+			"oracle" => return Ok(GasPricerConfig::Oracle {
+				floor: match self.args.flag_gasprice {
+					Some(ref d) => try!(to_u256(d)),
+					None => U256::zero(),
+				},
+			}),
+			other => return Err(format!("Invalid gas price source given: {}", other)),
+		}
+
 		if let Some(d) = self.args.flag_gasprice.as_ref() {
 			return Ok(GasPricerConfig::Fixed(try!(to_u256(d))));
 		}
@@ -433,6 +487,7 @@ impl Configuration {
 		let mut ret = NetworkConfiguration::new();
 		ret.nat_enabled = self.args.flag_nat == "any" || self.args.flag_nat == "upnp";
 		ret.boot_nodes = try!(to_bootnodes(&self.args.flag_bootnodes));
+		ret.dns_trees = try!(to_dns_trees(&self.args.flag_dns_trees));
 		let (listen, public) = try!(self.net_addresses());
 		ret.listen_address = listen.map(|l| format!("{}", l));
 		ret.public_address = public.map(|p| format!("{}", p));
@@ -447,6 +502,10 @@ impl Configuration {
 		ret.net_config_path = Some(net_specific_path.to_str().unwrap().to_owned());
 		ret.reserved_nodes = try!(self.init_reserved_nodes());
 		ret.allow_non_reserved = !self.args.flag_reserved_only;
+		ret.max_upload = self.args.flag_max_upload.map(|k| k as u64 * 1024);
+		ret.max_download = self.args.flag_max_download.map(|k| k as u64 * 1024);
+		ret.max_peer_upload = self.args.flag_max_peer_upload.map(|k| k as u64 * 1024);
+		ret.max_peer_download = self.args.flag_max_peer_download.map(|k| k as u64 * 1024);
 		Ok(ret)
 	}
 
@@ -514,6 +573,9 @@ impl Configuration {
 			apis: try!(self.rpc_apis().parse()),
 			hosts: self.rpc_hosts(),
 			cors: self.rpc_cors(),
+			api_keys: self.args.flag_jsonrpc_api_keys.as_ref().map(|path| replace_home(path)),
+			max_batch_size: self.args.flag_jsonrpc_max_batch_size,
+			max_payload: self.args.flag_jsonrpc_max_payload,
 		};
 
 		Ok(conf)
@@ -600,6 +662,28 @@ impl Configuration {
 		}.into()
 	}
 
+	fn stratum_interface(&self) -> String {
+		match self.args.flag_stratum_interface.as_str() {
+			"local" => "127.0.0.1",
+			x => x,
+		}.into()
+	}
+
+	fn stratum_options(&self) -> Result<Option<StratumOptions>, String> {
+		if !self.args.flag_stratum {
+			return Ok(None);
+		}
+		if !cfg!(feature = "stratum") {
+			return Err("--stratum is not supported by this build. Rebuild Parity with the `stratum` feature enabled.".into());
+		}
+
+		Ok(Some(StratumOptions {
+			listen_addr: self.stratum_interface(),
+			port: self.args.flag_stratum_port,
+			secret: self.args.flag_stratum_secret.as_ref().map(|s| s.sha3()),
+		}))
+	}
+
 	fn dapps_interface(&self) -> String {
 		match self.args.flag_dapps_interface.as_str() {
 			"local" => "127.0.0.1",
@@ -636,6 +720,9 @@ mod tests {
 	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat};
 	use presale::ImportWallet;
 	use account::{AccountCmd, NewAccount, ImportAccounts};
+	use db::{DbCmd, CheckDatabase, ConvertDatabase};
+	use params::Pruning;
+	use util::journaldb::Algorithm;
 	use devtools::{RandomTempPath};
 	use std::io::Write;
 	use std::fs::{File, create_dir};
@@ -760,6 +847,43 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_db_check() {
+		let args = vec!["parity", "db", "check", "--check-storage", "--repair"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap(), Cmd::Db(DbCmd::Check(CheckDatabase {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			compaction: Default::default(),
+			wal: true,
+			mode: Default::default(),
+			tracing: Default::default(),
+			check_storage: true,
+			repair: true,
+			repair_timeout: 600,
+			net_conf: default_network_config(),
+			custom_bootnodes: false,
+			network_id: None,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_convert() {
+		let args = vec!["parity", "db", "convert", "--pruning", "archive"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap(), Cmd::Db(DbCmd::Convert(ConvertDatabase {
+			spec: Default::default(),
+			logger_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Pruning::Specific(Algorithm::Archive),
+			pruning_history: 1200,
+			compaction: Default::default(),
+		})));
+	}
+
 	#[test]
 	fn test_command_signer_new_token() {
 		let args = vec!["parity", "signer", "new-token"];
@@ -802,9 +926,34 @@ mod tests {
 			name: "".into(),
 			custom_bootnodes: false,
 			no_periodic_snapshot: false,
+			incremental_snapshots: false,
+			state_replay_limit: 0,
+			pruning_history: 1200,
+			pruning_memory: 0,
+			fat_db: false,
+			stratum: None,
+			snapshot_serving: Default::default(),
 		}));
 	}
 
+	#[test]
+	#[cfg(feature = "stratum")]
+	fn should_parse_stratum_options() {
+		let conf = parse(&["parity", "--stratum", "--stratum-port", "8010"]);
+		assert_eq!(conf.stratum_options(), Ok(Some(StratumOptions {
+			listen_addr: "127.0.0.1".into(),
+			port: 8010,
+			secret: None,
+		})));
+	}
+
+	#[test]
+	#[cfg(not(feature = "stratum"))]
+	fn should_reject_stratum_without_feature() {
+		let conf = parse(&["parity", "--stratum"]);
+		assert!(conf.stratum_options().unwrap_err().contains("`stratum` feature"));
+	}
+
 	#[test]
 	fn should_parse_network_settings() {
 		// given
@@ -823,6 +972,21 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_parse_bandwidth_limits() {
+		// given
+
+		// when
+		let conf = parse(&["parity", "--max-upload", "100", "--max-peer-download", "8"]);
+
+		// then
+		let net = conf.net_config().unwrap();
+		assert_eq!(net.max_upload, Some(100 * 1024));
+		assert_eq!(net.max_download, None);
+		assert_eq!(net.max_peer_upload, None);
+		assert_eq!(net.max_peer_download, Some(8 * 1024));
+	}
+
 	#[test]
 	fn should_parse_rpc_settings_with_geth_compatiblity() {
 		// given
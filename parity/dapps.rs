@@ -19,6 +19,7 @@ use io::PanicHandler;
 use rpc_apis;
 use ethcore::client::Client;
 use ethsync::SyncProvider;
+use ethcore_rpc::AccessControl;
 use helpers::replace_home;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -51,6 +52,7 @@ pub struct Dependencies {
 	pub apis: Arc<rpc_apis::Dependencies>,
 	pub client: Arc<Client>,
 	pub sync: Arc<SyncProvider>,
+	pub access: Option<Arc<AccessControl>>,
 }
 
 pub fn new(configuration: Configuration, deps: Dependencies) -> Result<Option<WebappServer>, String> {
@@ -125,6 +127,9 @@ mod server {
 		);
 		let sync = deps.sync.clone();
 		server.with_sync_status(Arc::new(move || sync.status().is_major_syncing()));
+		if let Some(access) = deps.access.clone() {
+			server.with_access_control(access);
+		}
 		let server = rpc_apis::setup_rpc(server, deps.apis.clone(), rpc_apis::ApiSet::UnsafeContext);
 		let start_result = match auth {
 			None => {
@@ -171,7 +176,7 @@ mod server {
 				data: data,
 			}.fake_sign(from);
 
-			self.client.call(&transaction, BlockID::Latest, Default::default())
+			self.client.call(&transaction, BlockID::Latest, Default::default(), &Default::default())
 				.map_err(|e| format!("{:?}", e))
 				.map(|executed| {
 					executed.output
@@ -0,0 +1,221 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database maintenance commands.
+
+use std::collections::HashMap;
+use std::time::{Instant, Duration};
+use std::thread::sleep;
+use std::sync::Arc;
+use ethcore_logger::{setup_log, Config as LogConfig};
+use io::PanicHandler;
+use util::U256;
+use ethcore::service::ClientService;
+use ethcore::client::{Client, Mode, DatabaseCompactionProfile, Switch, VMType, StateCheck, ChainNotify};
+use ethcore::miner::Miner;
+use ethsync::{EthSync, SyncConfig, NetworkConfiguration};
+use cache::CacheConfig;
+use params::{SpecType, Pruning};
+use helpers::{to_client_config, execute_upgrades, convertible_db};
+use migration::convert;
+use dir::Directories;
+use fdlimit;
+
+#[derive(Debug, PartialEq)]
+pub enum DbCmd {
+	Check(CheckDatabase),
+	Convert(ConvertDatabase),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CheckDatabase {
+	pub spec: SpecType,
+	pub logger_config: LogConfig,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub mode: Mode,
+	pub tracing: Switch,
+	pub check_storage: bool,
+	pub repair: bool,
+	pub repair_timeout: u64,
+	pub net_conf: NetworkConfiguration,
+	pub custom_bootnodes: bool,
+	pub network_id: Option<U256>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ConvertDatabase {
+	pub spec: SpecType,
+	pub logger_config: LogConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub compaction: DatabaseCompactionProfile,
+}
+
+pub fn execute(cmd: DbCmd) -> Result<String, String> {
+	match cmd {
+		DbCmd::Check(check_cmd) => execute_check(check_cmd),
+		DbCmd::Convert(convert_cmd) => execute_convert(convert_cmd),
+	}
+}
+
+fn execute_convert(cmd: ConvertDatabase) -> Result<String, String> {
+	// load spec file
+	let spec = try!(cmd.spec.spec());
+
+	// load genesis hash
+	let genesis_hash = spec.genesis_header().hash();
+
+	// Setup logging
+	let _logger = setup_log(&cmd.logger_config);
+
+	fdlimit::raise_fd_limit();
+
+	let to = match cmd.pruning {
+		Pruning::Specific(algorithm) => algorithm,
+		Pruning::Auto => return Err("Specify the pruning method to convert the database to with --pruning.".into()),
+	};
+
+	let fork_name = spec.fork_name.as_ref();
+	if cmd.dirs.client_path(genesis_hash, fork_name, to).exists() {
+		return Err(format!("A database with {} pruning already exists.", to));
+	}
+
+	let from = try!(convertible_db(&cmd.dirs, genesis_hash, fork_name, to)
+		.ok_or_else(|| format!("No database which can be converted to {} pruning was found.", to)));
+	let from_path = cmd.dirs.db_version_path(genesis_hash, fork_name, from);
+	let to_path = cmd.dirs.db_version_path(genesis_hash, fork_name, to);
+
+	try!(convert(&from_path, from, &to_path, to, cmd.pruning_history, cmd.compaction.compaction_profile()).map_err(|e| format!("{}", e)));
+
+	Ok(format!("Converted the database from {} to {} pruning. The {} database at {} was kept and can be removed.", from, to, from, from_path.display()))
+}
+
+fn execute_check(cmd: CheckDatabase) -> Result<String, String> {
+	// Setup panic handler
+	let panic_handler = PanicHandler::new_in_arc();
+
+	// load spec file
+	let spec = try!(cmd.spec.spec());
+
+	// load genesis hash
+	let genesis_hash = spec.genesis_header().hash();
+
+	// Setup logging
+	let _logger = setup_log(&cmd.logger_config);
+
+	fdlimit::raise_fd_limit();
+
+	// select pruning algorithm
+	let algorithm = cmd.pruning.to_algorithm(&cmd.dirs, genesis_hash, spec.fork_name.as_ref());
+
+	// prepare client and snapshot paths.
+	let client_path = cmd.dirs.client_path(genesis_hash, spec.fork_name.as_ref(), algorithm);
+	let snapshot_path = cmd.dirs.snapshot_path(genesis_hash, spec.fork_name.as_ref());
+
+	// execute upgrades
+	try!(execute_upgrades(&cmd.dirs, genesis_hash, spec.fork_name.as_ref(), algorithm, cmd.compaction.compaction_profile()));
+
+	// prepare client config
+	let client_config = to_client_config(&cmd.cache_config, &cmd.dirs, genesis_hash, cmd.mode, cmd.tracing, cmd.pruning, cmd.compaction, cmd.wal, VMType::default(), "".into(), spec.fork_name.as_ref());
+
+	let service = try!(ClientService::start(
+		client_config,
+		&spec,
+		&client_path,
+		&snapshot_path,
+		&cmd.dirs.ipc_path(),
+		Arc::new(Miner::with_spec(&spec)),
+	).map_err(|e| format!("Client service error: {:?}", e)));
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	info!("Checking block extras");
+	let chain_problems = client.check_chain();
+	for &(number, ref problem) in &chain_problems {
+		warn!("Block #{}: {}", number, problem);
+	}
+
+	info!("Checking state{}", if cmd.check_storage { ", storage and code" } else { "" });
+	let mut check = client.check_state(cmd.check_storage);
+	for node in &check.bad_nodes {
+		warn!("{}", node);
+	}
+
+	if cmd.repair && !check.is_ok() {
+		let mut sync_config = SyncConfig::default();
+		sync_config.network_id = cmd.network_id.unwrap_or_else(|| spec.network_id());
+		sync_config.fork_block = spec.fork_block();
+		// only fetch state nodes; importing blocks would move the state being repaired.
+		sync_config.import_blocks = false;
+
+		let mut net_conf = cmd.net_conf;
+		if !cmd.custom_bootnodes {
+			net_conf.boot_nodes = spec.nodes.clone();
+		}
+
+		let sync = try!(EthSync::new(sync_config, client.clone(), service.snapshot_service(), net_conf)
+			.map_err(|e| format!("Sync error: {}", e)));
+		check = try!(repair(&client, &sync, check, cmd.check_storage, Duration::from_secs(cmd.repair_timeout)));
+	}
+
+	let report = format!("Checked {} state nodes of {} accounts at block #{} ({}): {} bad nodes, {} block extras problems.",
+		check.nodes, check.accounts, check.number, check.block.hex(), check.bad_nodes.len(), chain_problems.len());
+
+	match check.is_ok() && chain_problems.is_empty() {
+		true => Ok(report),
+		false => Err(report),
+	}
+}
+
+// download bad nodes from peers and write them back until the state checks out or no progress is made.
+fn repair(client: &Client, sync: &EthSync, mut check: StateCheck, check_storage: bool, timeout: Duration) -> Result<StateCheck, String> {
+	sync.start();
+
+	let mut retrieved = HashMap::new();
+	let mut last_progress = Instant::now();
+	while !check.is_ok() && last_progress.elapsed() < timeout {
+		let wanted = check.bad_nodes.iter()
+			.map(|node| node.hash.clone())
+			.filter(|hash| !retrieved.contains_key(hash))
+			.collect();
+		sync.request_state_nodes(wanted);
+
+		sleep(Duration::from_secs(1));
+		retrieved.extend(sync.take_state_nodes());
+
+		let repaired = try!(client.repair_state(&check.bad_nodes, &retrieved).map_err(|e| format!("Error writing repaired nodes: {}", e)));
+		if repaired > 0 {
+			info!("Repaired {} state nodes, checking again", repaired);
+			retrieved.clear();
+			last_progress = Instant::now();
+
+			// previously unreachable nodes below repaired ones are visited now.
+			check = client.check_state(check_storage);
+			for node in &check.bad_nodes {
+				warn!("{}", node);
+			}
+		}
+	}
+
+	sync.stop();
+	Ok(check)
+}
@@ -28,7 +28,7 @@ use dir::Directories;
 use params::Pruning;
 use upgrade::upgrade;
 use migration::migrate;
-use ethsync::is_valid_node_url;
+use ethsync::{is_valid_node_url, is_valid_dns_tree_url};
 
 pub fn to_duration(s: &str) -> Result<Duration, String> {
 	to_seconds(s).map(Duration::from_secs)
@@ -167,6 +167,20 @@ pub fn to_bootnodes(bootnodes: &Option<String>) -> Result<Vec<String>, String> {
 	}
 }
 
+/// Validates and formats DNS node lists option.
+pub fn to_dns_trees(dns_trees: &Option<String>) -> Result<Vec<String>, String> {
+	match *dns_trees {
+		Some(ref x) if !x.is_empty() => x.split(',').map(|s| {
+			if is_valid_dns_tree_url(s) {
+				Ok(s.to_owned())
+			} else {
+				Err(format!("Invalid DNS node list URL given: {}", s))
+			}
+		}).collect(),
+		_ => Ok(vec![]),
+	}
+}
+
 #[cfg(test)]
 pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 	use ethsync::NetworkConfiguration;
@@ -179,11 +193,16 @@ pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 		nat_enabled: true,
 		discovery_enabled: true,
 		boot_nodes: Vec::new(),
+		dns_trees: Vec::new(),
 		use_secret: None,
 		max_peers: 50,
 		min_peers: 25,
 		reserved_nodes: Vec::new(),
 		allow_non_reserved: true,
+		max_upload: None,
+		max_download: None,
+		max_peer_upload: None,
+		max_peer_download: None,
 	}
 }
 
@@ -247,10 +266,26 @@ pub fn execute_upgrades(
 		_ => {},
 	}
 
+	if let Some(existing) = convertible_db(dirs, genesis_hash, fork_name, pruning) {
+		warn!("Found a database synced with {} pruning. Run `parity db convert --pruning {}` to convert it instead of syncing from scratch.", existing, pruning);
+	}
+
 	let client_path = dirs.db_version_path(genesis_hash, fork_name, pruning);
 	migrate(&client_path, pruning, compaction_profile).map_err(|e| format!("{}", e))
 }
 
+/// Returns the pruning method of an existing database which can be converted to `pruning`,
+/// if there is no database using `pruning` yet.
+pub fn convertible_db(dirs: &Directories, genesis_hash: H256, fork_name: Option<&String>, pruning: Algorithm) -> Option<Algorithm> {
+	if !pruning.is_stable() || dirs.client_path(genesis_hash, fork_name, pruning).exists() {
+		return None;
+	}
+
+	Algorithm::all_types().into_iter()
+		.filter(|algo| *algo != pruning && algo.is_stable())
+		.find(|algo| dirs.client_path(genesis_hash, fork_name, *algo).exists())
+}
+
 /// Prompts user asking for password.
 pub fn password_prompt() -> Result<String, String> {
 	use rpassword::read_password;
@@ -301,7 +336,7 @@ mod tests {
 	use util::{U256};
 	use ethcore::client::{Mode, BlockID};
 	use ethcore::miner::PendingSet;
-	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes};
+	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, to_dns_trees};
 
 	#[test]
 	fn test_to_duration() {
@@ -417,5 +452,15 @@ mod tests {
 		assert_eq!(to_bootnodes(&Some(one_bootnode.into())), Ok(vec![one_bootnode.into()]));
 		assert_eq!(to_bootnodes(&Some(two_bootnodes.into())), Ok(vec![one_bootnode.into(), one_bootnode.into()]));
 	}
+
+	#[test]
+	fn test_to_dns_trees() {
+		let tree = "enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@nodes.example.org";
+
+		assert_eq!(to_dns_trees(&Some("".into())), Ok(vec![]));
+		assert_eq!(to_dns_trees(&None), Ok(vec![]));
+		assert_eq!(to_dns_trees(&Some(tree.into())), Ok(vec![tree.into()]));
+		assert!(to_dns_trees(&Some("enrtree://nodes.example.org".into())).is_err());
+	}
 }
 
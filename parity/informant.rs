@@ -32,6 +32,7 @@ pub struct Informant {
 	chain_info: RwLock<Option<BlockChainInfo>>,
 	cache_info: RwLock<Option<BlockChainCacheSize>>,
 	report: RwLock<Option<ClientReport>>,
+	traffic: RwLock<Option<(u64, u64)>>,
 	last_tick: RwLock<Instant>,
 	with_color: bool,
 	client: Arc<Client>,
@@ -60,6 +61,7 @@ impl Informant {
 			chain_info: RwLock::new(None),
 			cache_info: RwLock::new(None),
 			report: RwLock::new(None),
+			traffic: RwLock::new(None),
 			last_tick: RwLock::new(Instant::now()),
 			with_color: with_color,
 			client: client,
@@ -89,6 +91,7 @@ impl Informant {
 		let queue_info = self.client.queue_info();
 		let cache_info = self.client.blockchain_cache_info();
 		let network_config = self.net.as_ref().map(|n| n.network_config());
+		let traffic = self.net.as_ref().map(|n| n.network_traffic()).map(|t| (t.bytes_received, t.bytes_sent));
 		let sync_status = self.sync.as_ref().map(|s| s.status());
 
 		let importing = queue_info.unverified_queue_size + queue_info.verified_queue_size > 3
@@ -99,6 +102,14 @@ impl Informant {
 
 		*self.last_tick.write() = Instant::now();
 
+		let rates = match (traffic, *self.traffic.read()) {
+			(Some((recv, sent)), Some((last_recv, last_sent))) => {
+				let ms = ::std::cmp::max(elapsed.as_milliseconds(), 1);
+				Some((recv.saturating_sub(last_recv) * 1000 / ms, sent.saturating_sub(last_sent) * 1000 / ms))
+			},
+			_ => None,
+		};
+
 		let mut write_report = self.report.write();
 		let report = self.client.report();
 
@@ -126,7 +137,7 @@ impl Informant {
 				false => String::new(),
 			},
 			match (&sync_status, &network_config) {
-				(&Some(ref sync_info), &Some(ref net_config)) => format!("{}{}/{}/{} peers",
+				(&Some(ref sync_info), &Some(ref net_config)) => format!("{}{}/{}/{} peers{}",
 					match importing {
 						true => format!("{}   ", paint(Green.bold(), format!("{:>8}", format!("#{}", sync_info.last_imported_block_number.unwrap_or(chain_info.best_block_number))))),
 						false => String::new(),
@@ -134,6 +145,13 @@ impl Informant {
 					paint(Cyan.bold(), format!("{:2}", sync_info.num_active_peers)),
 					paint(Cyan.bold(), format!("{:2}", sync_info.num_peers)),
 					paint(Cyan.bold(), format!("{:2}", sync_info.current_max_peers(net_config.min_peers, net_config.max_peers))),
+					match rates {
+						Some((down, up)) => format!(" {}/s down {}/s up",
+							paint(Cyan.bold(), format!("{:>8}", Informant::format_bytes(down as usize))),
+							paint(Cyan.bold(), format!("{:>8}", Informant::format_bytes(up as usize))),
+						),
+						None => String::new(),
+					},
 				),
 				_ => String::new(),
 			},
@@ -151,6 +169,7 @@ impl Informant {
 		*self.chain_info.write() = Some(chain_info);
 		*self.cache_info.write() = Some(cache_info);
 		*write_report = Some(report);
+		*self.traffic.write() = traffic;
 	}
 }
 
@@ -101,6 +101,7 @@ mod account;
 mod blockchain;
 mod presale;
 mod snapshot;
+mod db;
 mod run;
 #[cfg(feature="ipc")]
 mod sync;
@@ -127,6 +128,7 @@ fn execute(command: Cmd) -> Result<String, String> {
 		Cmd::Blockchain(blockchain_cmd) => blockchain::execute(blockchain_cmd),
 		Cmd::SignerToken(path) => signer::new_token(path),
 		Cmd::Snapshot(snapshot_cmd) => snapshot::execute(snapshot_cmd),
+		Cmd::Db(db_cmd) => db::execute(db_cmd),
 	}
 }
 
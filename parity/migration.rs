@@ -29,7 +29,7 @@ use ethcore::migrations::Extract;
 /// Database is assumed to be at default version, when no version file is found.
 const DEFAULT_VERSION: u32 = 5;
 /// Current version of database models.
-const CURRENT_VERSION: u32 = 9;
+const CURRENT_VERSION: u32 = 10;
 /// First version of the consolidated database.
 const CONSOLIDATION_VERSION: u32 = 9;
 /// Defines how many items are migrated to the new version of database at once.
@@ -50,6 +50,8 @@ pub enum Error {
 	MigrationImpossible,
 	/// Migration unexpectadly failed.
 	MigrationFailed,
+	/// Database to convert into already exists.
+	DatabaseExists,
 	/// Migration was completed succesfully,
 	/// but there was a problem with io.
 	Io(IoError),
@@ -63,6 +65,7 @@ impl Display for Error {
 			Error::FutureDBVersion => "Database was created with newer client version. Upgrade your client or delete DB and resync.".into(),
 			Error::MigrationImpossible => format!("Database migration to version {} is not possible.", CURRENT_VERSION),
 			Error::MigrationFailed => "Database migration unexpectedly failed".into(),
+			Error::DatabaseExists => "Database to convert into already exists. Remove it before converting.".into(),
 			Error::Io(ref err) => format!("Unexpected io error on DB migration: {}.", err),
 		};
 
@@ -140,7 +143,8 @@ pub fn default_migration_settings(compaction_profile: &CompactionProfile) -> Mig
 
 /// Migrations on the consolidated database.
 fn consolidated_database_migrations(compaction_profile: &CompactionProfile) -> Result<MigrationManager, Error> {
-	let manager = MigrationManager::new(default_migration_settings(compaction_profile));
+	let mut manager = MigrationManager::new(default_migration_settings(compaction_profile));
+	try!(manager.add_migration(migrations::ToV10::default()).map_err(|_| Error::MigrationImpossible));
 	Ok(manager)
 }
 
@@ -252,9 +256,10 @@ pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionPr
 		println!("Migration finished");
 	}
 
-	// Further migrations
-	if version >= CONSOLIDATION_VERSION && version < CURRENT_VERSION && exists(&consolidated_database_path(path)) {
-		println!("Migrating database from version {} to {}", ::std::cmp::max(CONSOLIDATION_VERSION, version), CURRENT_VERSION);
+	// Further migrations; a freshly consolidated database is at the consolidation version.
+	let version = ::std::cmp::max(CONSOLIDATION_VERSION, version);
+	if version < CURRENT_VERSION && exists(&consolidated_database_path(path)) {
+		println!("Migrating database from version {} to {}", version, CURRENT_VERSION);
 		try!(migrate_database(version, consolidated_database_path(path), try!(consolidated_database_migrations(&compaction_profile))));
 		println!("Migration finished");
 	}
@@ -263,6 +268,56 @@ pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionPr
 	update_version(path)
 }
 
+/// Converts the database at `from_path`, created with `from` pruning, into one using `to` pruning at `to_path`,
+/// keeping `history` recent states when converting to `OverlayRecent`. The old database is left untouched.
+pub fn convert(from_path: &Path, from: Algorithm, to_path: &Path, to: Algorithm, history: u64, compaction_profile: CompactionProfile) -> Result<(), Error> {
+	let mut migration: Box<Migration> = match (from, to) {
+		(Algorithm::Archive, Algorithm::OverlayRecent) => Box::new(migrations::state::ToOverlayRecent::new(history)),
+		(Algorithm::OverlayRecent, Algorithm::Archive) => Box::new(migrations::state::ToArchive::default()),
+		_ => return Err(Error::UnsuportedPruningMethod),
+	};
+
+	let new_db_path = consolidated_database_path(to_path);
+	if new_db_path.exists() {
+		return Err(Error::DatabaseExists);
+	}
+
+	// bring the old database up to date first.
+	try!(migrate(from_path, from, compaction_profile));
+
+	println!("Converting database from {} to {} pruning", from, to);
+	let config = default_migration_settings(&compaction_profile);
+	let db_config = DatabaseConfig {
+		max_open_files: 64,
+		cache_size: None,
+		compaction: config.compaction_profile,
+		columns: db::NUM_COLUMNS,
+		wal: true,
+	};
+
+	// convert into a temporary database, so that an interrupted conversion is never picked up.
+	let mut temp_path = to_path.to_owned();
+	temp_path.push("temp_conversion");
+	let _ = fs::remove_dir_all(&temp_path);
+	try!(fs::create_dir_all(to_path));
+
+	{
+		let old_path_str = try!(consolidated_database_path(from_path).to_str().map(ToOwned::to_owned).ok_or(Error::MigrationImpossible));
+		let temp_path_str = try!(temp_path.to_str().ok_or(Error::MigrationImpossible));
+		let cur_db = try!(Database::open(&db_config, &old_path_str).map_err(|_| Error::MigrationFailed));
+		let mut new_db = try!(Database::open(&db_config, temp_path_str).map_err(|_| Error::MigrationFailed));
+
+		for col in 0..db::NUM_COLUMNS.unwrap_or(0) {
+			try!(migration.migrate(&cur_db, &config, &mut new_db, Some(col)));
+		}
+	}
+
+	try!(fs::rename(&temp_path, &new_db_path));
+	try!(update_version(to_path));
+	println!("Conversion finished");
+	Ok(())
+}
+
 /// Old migrations utilities
 mod legacy {
 	use super::*;
@@ -58,9 +58,9 @@ mod no_ipc_deps {
 
 #[cfg(feature="ipc")]
 pub type SyncModules = (
-	GuardedSocket<SyncClient<NanoSocket>>,
-	GuardedSocket<NetworkManagerClient<NanoSocket>>,
-	GuardedSocket<ChainNotifyClient<NanoSocket>>
+	GuardedSocket<SyncClient<DefaultSocket>>,
+	GuardedSocket<NetworkManagerClient<DefaultSocket>>,
+	GuardedSocket<ChainNotifyClient<DefaultSocket>>
 );
 
 #[cfg(not(feature="ipc"))]
@@ -71,7 +71,7 @@ mod ipc_deps {
 	pub use ethsync::{SyncClient, NetworkManagerClient, ServiceConfiguration};
 	pub use ethcore::client::ChainNotifyClient;
 	pub use hypervisor::{SYNC_MODULE_ID, BootArgs, HYPERVISOR_IPC_URL};
-	pub use nanoipc::{GuardedSocket, NanoSocket, generic_client, fast_client};
+	pub use nanoipc::{GuardedSocket, DefaultSocket, generic_client, fast_client};
 	pub use ipc::IpcSocket;
 	pub use ipc::binary::serialize;
 }
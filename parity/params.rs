@@ -176,7 +176,10 @@ pub enum GasPricerConfig {
 	Calibrated {
 		usd_per_tx: f32,
 		recalibration_period: Duration,
-	}
+	},
+	Oracle {
+		floor: U256,
+	},
 }
 
 impl Default for GasPricerConfig {
@@ -197,7 +200,8 @@ impl Into<GasPricer> for GasPricerConfig {
 					usd_per_tx: usd_per_tx,
 					recalibration_period: recalibration_period,
 				})
-			}
+			},
+			GasPricerConfig::Oracle { floor } => GasPricer::new_oracle(floor),
 		}
 	}
 }
@@ -15,17 +15,27 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::fmt;
+use std::fs::File;
+use std::io::Read;
 use std::sync::Arc;
 use std::net::SocketAddr;
+use std::collections::HashMap;
 use io::PanicHandler;
-use ethcore_rpc::{RpcServerError, RpcServer as Server};
+use ethcore_rpc::{RpcServerError, RpcServer as Server, AccessControl, AccessControlledServer, ApiKey};
 use jsonipc;
 use rpc_apis;
 use rpc_apis::ApiSet;
 use helpers::parity_ipc_path;
 
 pub use jsonipc::Server as IpcServer;
-pub use ethcore_rpc::Server as HttpServer;
+
+/// Running HTTP JSON-RPC server.
+pub enum HttpServer {
+	/// Server without any request limits.
+	Open(::ethcore_rpc::Server),
+	/// Server enforcing API keys and request limits.
+	AccessControlled(AccessControlledServer),
+}
 
 #[derive(Debug, PartialEq)]
 pub struct HttpConfiguration {
@@ -35,6 +45,9 @@ pub struct HttpConfiguration {
 	pub apis: ApiSet,
 	pub cors: Option<Vec<String>>,
 	pub hosts: Option<Vec<String>>,
+	pub api_keys: Option<String>,
+	pub max_batch_size: Option<usize>,
+	pub max_payload: Option<usize>,
 }
 
 impl Default for HttpConfiguration {
@@ -46,6 +59,9 @@ impl Default for HttpConfiguration {
 			apis: ApiSet::UnsafeContext,
 			cors: None,
 			hosts: Some(Vec::new()),
+			api_keys: None,
+			max_batch_size: None,
+			max_payload: None,
 		}
 	}
 }
@@ -82,14 +98,42 @@ pub struct Dependencies {
 	pub apis: Arc<rpc_apis::Dependencies>,
 }
 
-pub fn new_http(conf: HttpConfiguration, deps: &Dependencies) -> Result<Option<HttpServer>, String> {
+pub fn new_http(conf: HttpConfiguration, deps: &Dependencies, access: Option<Arc<AccessControl>>) -> Result<Option<HttpServer>, String> {
 	if !conf.enabled {
 		return Ok(None);
 	}
 
 	let url = format!("{}:{}", conf.interface, conf.port);
 	let addr = try!(url.parse().map_err(|_| format!("Invalid JSONRPC listen host/port given: {}", url)));
-	Ok(Some(try!(setup_http_rpc_server(deps, &addr, conf.cors, conf.hosts, conf.apis))))
+	Ok(Some(try!(setup_http_rpc_server(deps, &addr, conf.cors, conf.hosts, conf.apis, access))))
+}
+
+/// Access control policy for HTTP JSON-RPC endpoints (including the dapps server) or `None` if nothing is restricted.
+pub fn access_control(conf: &HttpConfiguration, deps: &Dependencies) -> Result<Option<Arc<AccessControl>>, String> {
+	let mut keys = HashMap::new();
+	if let Some(ref path) = conf.api_keys {
+		let mut file = try!(File::open(path).map_err(|e| format!("Cannot open API keys file {}: {}", path, e)));
+		let mut json = String::new();
+		try!(file.read_to_string(&mut json).map_err(|e| format!("Cannot read API keys file {}: {}", path, e)));
+		for (key, spec) in try!(AccessControl::parse_keys(&json)) {
+			let apis = match spec.apis {
+				Some(apis) => try!(apis.join(",").parse::<ApiSet>()),
+				None => ApiSet::List(conf.apis.list_apis()),
+			};
+			let server = try!(setup_rpc_server(apis, deps));
+			keys.insert(key, ApiKey {
+				handler: server.handler(),
+				methods: spec.methods,
+				rate_limit: spec.rate_limit,
+			});
+		}
+	}
+
+	let access = AccessControl::new(keys, conf.max_batch_size, conf.max_payload);
+	match access.is_enabled() {
+		true => Ok(Some(Arc::new(access))),
+		false => Ok(None),
+	}
 }
 
 fn setup_rpc_server(apis: ApiSet, deps: &Dependencies) -> Result<Server, String> {
@@ -102,15 +146,22 @@ pub fn setup_http_rpc_server(
 	url: &SocketAddr,
 	cors_domains: Option<Vec<String>>,
 	allowed_hosts: Option<Vec<String>>,
-	apis: ApiSet
+	apis: ApiSet,
+	access: Option<Arc<AccessControl>>,
 ) -> Result<HttpServer, String> {
 	let server = try!(setup_rpc_server(apis, dependencies));
 	let ph = dependencies.panic_handler.clone();
+	if let Some(access) = access {
+		return server.start_http_with_access(url, cors_domains, allowed_hosts, access, ph)
+			.map(HttpServer::AccessControlled)
+			.map_err(|e| format!("RPC error: {:?}", e));
+	}
+
 	let start_result = server.start_http(url, cors_domains, allowed_hosts, ph);
 	match start_result {
 		Err(RpcServerError::IoError(err)) => Err(format!("RPC io error: {}", err)),
 		Err(e) => Err(format!("RPC error: {:?}", e)),
-		Ok(server) => Ok(server),
+		Ok(server) => Ok(HttpServer::Open(server)),
 	}
 }
 
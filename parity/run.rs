@@ -22,11 +22,13 @@ use ethcore_logger::{Config as LogConfig, setup_log};
 use ethcore_rpc::NetworkSettings;
 use ethsync::NetworkConfiguration;
 use util::{Colour, version, U256};
+use util::journaldb::Algorithm;
+use util::trie::TrieSpec;
 use io::{MayPanic, ForwardPanic, PanicHandler};
-use ethcore::client::{Mode, Switch, DatabaseCompactionProfile, VMType, ChainNotify};
+use ethcore::client::{Client, Mode, Switch, DatabaseCompactionProfile, VMType, ChainNotify};
 use ethcore::service::ClientService;
 use ethcore::account_provider::AccountProvider;
-use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions};
+use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions, StratumOptions};
 use ethcore::snapshot;
 use ethsync::{SyncConfig, SyncProvider};
 use informant::Informant;
@@ -84,6 +86,31 @@ pub struct RunCmd {
 	pub name: String,
 	pub custom_bootnodes: bool,
 	pub no_periodic_snapshot: bool,
+	pub incremental_snapshots: bool,
+	pub state_replay_limit: u64,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub fat_db: bool,
+	pub stratum: Option<StratumOptions>,
+	pub snapshot_serving: SnapshotServing,
+}
+
+/// Snapshot serving limits.
+#[derive(Debug, PartialEq)]
+pub struct SnapshotServing {
+	pub enabled: bool,
+	pub max_requests: usize,
+	pub max_peer_requests: usize,
+}
+
+impl Default for SnapshotServing {
+	fn default() -> Self {
+		SnapshotServing {
+			enabled: true,
+			max_requests: 8,
+			max_peer_requests: 2,
+		}
+	}
 }
 
 pub fn execute(cmd: RunCmd) -> Result<(), String> {
@@ -109,6 +136,16 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	// select pruning algorithm
 	let algorithm = cmd.pruning.to_algorithm(&cmd.dirs, genesis_hash, fork_name.as_ref());
 
+	// fat DB keeps key preimages as auxiliary data, which only the archive journal stores.
+	if cmd.fat_db && algorithm != Algorithm::Archive {
+		return Err("Fat DB is not supported with the chosen pruning option. Please rerun with `--pruning=archive`".into());
+	}
+
+	// only the overlay journal keeps recent states in memory.
+	if cmd.pruning_memory > 0 && algorithm != Algorithm::OverlayRecent {
+		return Err("Journal memory limit is only supported with fast pruning. Please rerun with `--pruning=fast` or without `--pruning-memory`".into());
+	}
+
 	// prepare client and snapshot paths.
 	let client_path = cmd.dirs.client_path(genesis_hash, fork_name.as_ref(), algorithm);
 	let snapshot_path = cmd.dirs.snapshot_path(genesis_hash, fork_name.as_ref());
@@ -137,6 +174,9 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 		None => spec.network_id(),
 	};
 	sync_config.fork_block = spec.fork_block();
+	sync_config.serve_snapshots = cmd.snapshot_serving.enabled;
+	sync_config.max_snapshot_requests = cmd.snapshot_serving.max_requests;
+	sync_config.max_peer_snapshot_requests = cmd.snapshot_serving.max_peer_requests;
 
 	// prepare account provider
 	let account_provider = Arc::new(try!(prepare_account_provider(&cmd.dirs, cmd.acc_conf)));
@@ -150,7 +190,7 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	miner.set_transactions_limit(cmd.miner_extras.transactions_limit);
 
 	// create client config
-	let client_config = to_client_config(
+	let mut client_config = to_client_config(
 		&cmd.cache_config,
 		&cmd.dirs,
 		genesis_hash,
@@ -164,6 +204,13 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 		fork_name.as_ref(),
 	);
 
+	// changes only need to be recorded if periodic snapshots are taken.
+	client_config.incremental_snapshots = cmd.incremental_snapshots && !cmd.no_periodic_snapshot;
+	client_config.state_replay_limit = cmd.state_replay_limit;
+	client_config.history = cmd.pruning_history;
+	client_config.history_mem = cmd.pruning_memory * 1024 * 1024;
+	client_config.trie_spec = if cmd.fat_db { TrieSpec::Fat } else { TrieSpec::Secure };
+
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
 	if !cmd.custom_bootnodes {
@@ -190,6 +237,11 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	let client = service.client();
 	let snapshot_service = service.snapshot_service();
 
+	// start stratum server for the miner
+	if let Some(ref stratum_options) = cmd.stratum {
+		try!(start_stratum(stratum_options, &miner, &client));
+	}
+
 	// create external miner
 	let external_miner = Arc::new(ExternalMiner::default());
 
@@ -227,7 +279,8 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 	};
 
 	// start rpc servers
-	let http_server = try!(rpc::new_http(cmd.http_conf, &dependencies));
+	let access = try!(rpc::access_control(&cmd.http_conf, &dependencies));
+	let http_server = try!(rpc::new_http(cmd.http_conf, &dependencies, access.clone()));
 	let ipc_server = try!(rpc::new_ipc(cmd.ipc_conf, &dependencies));
 
 	let dapps_deps = dapps::Dependencies {
@@ -235,6 +288,7 @@ pub fn execute(cmd: RunCmd) -> Result<(), String> {
 		apis: deps_for_rpc_apis.clone(),
 		client: client.clone(),
 		sync: sync_provider.clone(),
+		access: access,
 	};
 
 	// start dapps server
@@ -319,6 +373,21 @@ fn daemonize(_pid_file: String) -> Result<(), String> {
 	Err("daemon is no supported on windows".into())
 }
 
+#[cfg(feature="stratum")]
+fn start_stratum(options: &StratumOptions, miner: &Arc<Miner>, client: &Arc<Client>) -> Result<(), String> {
+	use ethcore::miner::Stratum;
+
+	Stratum::register(options, miner.clone(), Arc::downgrade(client))
+		.map(|_| ())
+		.map_err(|e| format!("Stratum start error: {}", e))
+}
+
+#[cfg(not(feature="stratum"))]
+fn start_stratum(_options: &StratumOptions, _miner: &Arc<Miner>, _client: &Arc<Client>) -> Result<(), String> {
+	// rejected when parsing the configuration
+	Err("Stratum is not supported by this build; compile with the `stratum` feature".into())
+}
+
 fn prepare_account_provider(dirs: &Directories, cfg: AccountsConfig) -> Result<AccountProvider, String> {
 	use ethcore::ethstore::{import_accounts, EthStore};
 	use ethcore::ethstore::dir::{GethDirectory, DirectoryType, DiskDirectory};
@@ -63,18 +63,24 @@ pub struct SnapshotCommand {
 	pub block_at: BlockID,
 }
 
-// helper for reading chunks from arbitrary reader and feeding them into the
-// service.
-fn restore_using<R: SnapshotReader>(snapshot: Arc<SnapshotService>, reader: &R, recover: bool) -> Result<(), String> {
-	let manifest = reader.manifest();
+// helper for reading chunks from arbitrary readers and feeding them into the
+// service. the first reader must hold a full snapshot, any others incremental
+// snapshots on top of it.
+fn restore_using(snapshot: Arc<SnapshotService>, readers: &[&SnapshotReader], recover: bool) -> Result<(), String> {
+	let manifests: Vec<_> = readers.iter().map(|r| r.manifest().clone()).collect();
+	let manifest = try!(manifests.last().cloned().ok_or("No snapshot to restore from.".to_owned()));
 
 	info!("Restoring to block #{} (0x{:?})", manifest.block_number, manifest.block_hash);
+	if manifests.len() > 1 {
+		info!("Applying {} incremental snapshots", manifests.len() - 1);
+	}
 
-	try!(snapshot.init_restore(manifest.clone(), recover).map_err(|e| {
+	try!(snapshot.init_restore_incremental(manifests.clone(), recover).map_err(|e| {
 		format!("Failed to begin restoration: {}", e)
 	}));
 
-	let (num_state, num_blocks) = (manifest.state_hashes.len(), manifest.block_hashes.len());
+	let num_state = manifests.iter().map(|m| m.state_hashes.len()).sum::<usize>();
+	let num_blocks = manifest.block_hashes.len();
 
 	let informant_handle = snapshot.clone();
 	::std::thread::spawn(move || {
@@ -86,17 +92,20 @@ fn restore_using<R: SnapshotReader>(snapshot: Arc<SnapshotService>, reader: &R,
  	});
 
  	info!("Restoring state");
- 	for &state_hash in &manifest.state_hashes {
- 		if snapshot.status() == RestorationStatus::Failed {
- 			return Err("Restoration failed".into());
- 		}
+	for reader in readers {
+		for &state_hash in &reader.manifest().state_hashes {
+			if snapshot.status() == RestorationStatus::Failed {
+				return Err("Restoration failed".into());
+			}
 
- 		let chunk = try!(reader.chunk(state_hash)
-			.map_err(|e| format!("Encountered error while reading chunk {:?}: {}", state_hash, e)));
- 		snapshot.feed_state_chunk(state_hash, &chunk);
- 	}
+			let chunk = try!(reader.chunk(state_hash)
+				.map_err(|e| format!("Encountered error while reading chunk {:?}: {}", state_hash, e)));
+			snapshot.feed_state_chunk(state_hash, &chunk);
+		}
+	}
 
 	info!("Restoring blocks");
+	let reader = readers.last().expect("manifest of the last reader exists; qed");
 	for &block_hash in &manifest.block_hashes {
 		if snapshot.status() == RestorationStatus::Failed {
 			return Err("Restoration failed".into());
@@ -177,14 +186,19 @@ impl SnapshotCommand {
 				.and_then(|x| x.ok_or("Snapshot file has invalid format.".into()));
 
 			let reader = try!(reader);
-			try!(restore_using(snapshot, &reader, true));
+			try!(restore_using(snapshot, &[&reader], true));
 		} else {
 			info!("Attempting to restore from local snapshot.");
 
 			// attempting restoration with recovery will lead to deadlock
 			// as we currently hold a read lock on the service's reader.
+			let incremental = snapshot.incremental_readers();
 			match *snapshot.reader() {
-				Some(ref reader) => try!(restore_using(snapshot.clone(), reader, false)),
+				Some(ref reader) => {
+					let mut readers = vec![reader as &SnapshotReader];
+					readers.extend(incremental.iter().map(|r| r as &SnapshotReader));
+					try!(restore_using(snapshot.clone(), &readers, false))
+				}
 				None => return Err("No local snapshot found.".into()),
 			}
 		}
@@ -0,0 +1,464 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Access control filter for `jsonrpc_http_server`.
+//!
+//! `AccessFilter` checks the API key of a request, taken either from the `X-Api-Key` header or from
+//! the request path (`http://host:8545/<key>`), reads the request and checks its calls against the
+//! methods, rate limit and batch size allowed for that key before dispatching them to the handler
+//! serving the APIs of the key. Rejected requests are answered with a JSON-RPC error.
+
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use hyper::{self, header, server, Control, Decoder, Encoder, Next, StatusCode};
+use hyper::method::Method;
+use hyper::net::HttpStream;
+use hyper::uri::RequestUri;
+use jsonrpc_core::{IoHandler, Error, Value};
+use jsonrpc_http_server::{ServerHandler, PanicHandler, AccessControlAllowOrigin, is_host_header_valid};
+use util;
+use v1::AccessControl;
+use v1::helpers::errors;
+
+const API_KEY_HEADER: &'static str = "x-api-key";
+
+/// Request filter enforcing `AccessControl` policy in front of `jsonrpc_http_server::ServerHandler`.
+pub struct AccessFilter {
+	access: Arc<AccessControl>,
+	handler: Arc<IoHandler>,
+	cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+	allowed_hosts: Option<Vec<String>>,
+	path_prefix: &'static str,
+	panic_handler: Arc<Mutex<Option<Box<Fn() -> () + Send>>>>,
+	control: Option<Control>,
+	inner: Option<Box<server::Handler<HttpStream> + Send>>,
+}
+
+impl AccessFilter {
+	/// Creates filter for a single request. `handler` serves requests when no API keys are configured.
+	/// API key in the path may be preceded by `path_prefix` segment.
+	pub fn new(
+		access: Arc<AccessControl>,
+		handler: Arc<IoHandler>,
+		cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+		allowed_hosts: Option<Vec<String>>,
+		path_prefix: &'static str,
+		panic_handler: Arc<Mutex<Option<Box<Fn() -> () + Send>>>>,
+		control: Control,
+	) -> Self {
+		AccessFilter {
+			access: access,
+			handler: handler,
+			cors_domains: cors_domains,
+			allowed_hosts: allowed_hosts,
+			path_prefix: path_prefix,
+			panic_handler: panic_handler,
+			control: Some(control),
+			inner: None,
+		}
+	}
+
+	fn inner(&mut self) -> &mut Box<server::Handler<HttpStream> + Send> {
+		self.inner.as_mut().expect("inner handler is set in on_request; on_request is called first; qed")
+	}
+
+	fn control(&mut self) -> Control {
+		self.control.take().expect("on_request is called only once; control is always defined at start; qed")
+	}
+
+	fn filter(&mut self, request: &server::Request<HttpStream>) -> Box<server::Handler<HttpStream> + Send> {
+		// invalid hosts and CORS preflight requests are handled by the JSON-RPC server itself.
+		let valid_host = self.allowed_hosts.as_ref().map_or(true, |hosts| is_host_header_valid(request, hosts));
+		if !valid_host || *request.method() == Method::Options {
+			return Box::new(ServerHandler::new(
+				self.handler.clone(),
+				self.cors_domains.clone(),
+				self.allowed_hosts.clone(),
+				PanicHandler { handler: self.panic_handler.clone() },
+				self.control(),
+			));
+		}
+
+		let key = raw_header(request, API_KEY_HEADER).or_else(|| api_key_from_path(request.uri(), self.path_prefix));
+		let length = request.headers().get::<header::ContentLength>().map(|&header::ContentLength(length)| length);
+		match self.access.check(key.as_ref().map(String::as_str), length) {
+			Ok(handler) => Box::new(Dispatcher {
+				access: self.access.clone(),
+				handler: handler.unwrap_or_else(|| self.handler.clone()),
+				api_key: key,
+				cors_header: cors_header(raw_header(request, "origin"), &self.cors_domains),
+				control: Some(self.control()),
+				request: Vec::new(),
+				response: Arc::new(util::Mutex::new(None)),
+				written: 0,
+			}),
+			Err(error) => Box::new(Rejection::new(error, Value::Null)),
+		}
+	}
+}
+
+fn raw_header(request: &server::Request<HttpStream>, name: &str) -> Option<String> {
+	request.headers().get_raw(name)
+		.and_then(|list| list.get(0))
+		.and_then(|value| String::from_utf8(value.clone()).ok())
+}
+
+fn api_key_from_path(uri: &RequestUri, prefix: &str) -> Option<String> {
+	let path = match *uri {
+		RequestUri::AbsolutePath(ref path) => path,
+		_ => return None,
+	};
+
+	let mut segments = path.split('?').next().unwrap_or("").split('/').filter(|s| !s.is_empty()).peekable();
+	if segments.peek() == Some(&prefix) {
+		segments.next();
+	}
+	segments.next().map(Into::into)
+}
+
+fn cors_header(origin: Option<String>, domains: &Option<Vec<AccessControlAllowOrigin>>) -> Option<header::AccessControlAllowOrigin> {
+	let (origin, domains) = match (origin, domains.as_ref()) {
+		(Some(origin), Some(domains)) => (origin, domains),
+		_ => return None,
+	};
+
+	domains.iter().filter_map(|domain| match *domain {
+		AccessControlAllowOrigin::Any => Some(header::AccessControlAllowOrigin::Any),
+		AccessControlAllowOrigin::Null if origin == "null" => Some(header::AccessControlAllowOrigin::Null),
+		AccessControlAllowOrigin::Value(ref value) if *value == origin => Some(header::AccessControlAllowOrigin::Value(value.clone())),
+		_ => None,
+	}).next()
+}
+
+impl server::Handler<HttpStream> for AccessFilter {
+	fn on_request(&mut self, request: server::Request<HttpStream>) -> Next {
+		let inner = self.filter(&request);
+		self.inner = Some(inner);
+		self.inner().on_request(request)
+	}
+
+	fn on_request_readable(&mut self, decoder: &mut Decoder<HttpStream>) -> Next {
+		self.inner().on_request_readable(decoder)
+	}
+
+	fn on_response(&mut self, response: &mut server::Response) -> Next {
+		self.inner().on_response(response)
+	}
+
+	fn on_response_writable(&mut self, encoder: &mut Encoder<HttpStream>) -> Next {
+		self.inner().on_response_writable(encoder)
+	}
+}
+
+/// Reads request with an accepted API key, checks its calls and dispatches them to the handler.
+struct Dispatcher {
+	access: Arc<AccessControl>,
+	handler: Arc<IoHandler>,
+	api_key: Option<String>,
+	cors_header: Option<header::AccessControlAllowOrigin>,
+	control: Option<Control>,
+	request: Vec<u8>,
+	response: Arc<util::Mutex<Option<String>>>,
+	written: usize,
+}
+
+impl Dispatcher {
+	fn respond(&mut self, response: String) -> Next {
+		*self.response.lock() = Some(response);
+		Next::write()
+	}
+
+	fn process_request(&mut self) -> Next {
+		let request = String::from_utf8_lossy(&self.request).into_owned();
+		if let Err((error, id)) = self.access.check_calls(self.api_key.as_ref().map(String::as_str), &request) {
+			return self.respond(AccessControl::error_response(error, id));
+		}
+
+		match self.handler.handle_request(&request) {
+			// notifications have no response
+			None => self.respond(String::new()),
+			Some(result) => {
+				let response = self.response.clone();
+				let control = self.control.take().expect("request is processed only once; control is defined at start; qed");
+				result.on_result(move |result| {
+					*response.lock() = Some(result);
+					let _ = control.ready(Next::write());
+				});
+				Next::wait()
+			},
+		}
+	}
+}
+
+impl server::Handler<HttpStream> for Dispatcher {
+	fn on_request(&mut self, _request: server::Request<HttpStream>) -> Next {
+		Next::read()
+	}
+
+	fn on_request_readable(&mut self, decoder: &mut Decoder<HttpStream>) -> Next {
+		let mut chunk = [0u8; 4096];
+		match decoder.read(&mut chunk) {
+			Ok(0) => self.process_request(),
+			Ok(read) => {
+				self.request.extend_from_slice(&chunk[..read]);
+				match self.access.max_payload() {
+					Some(max) if self.request.len() > max => {
+						self.respond(AccessControl::error_response(errors::request_too_large(max), Value::Null))
+					},
+					_ => Next::read(),
+				}
+			},
+			Err(e) => match e.kind() {
+				::std::io::ErrorKind::WouldBlock => Next::read(),
+				_ => Next::end(),
+			},
+		}
+	}
+
+	fn on_response(&mut self, response: &mut server::Response) -> Next {
+		response.set_status(StatusCode::Ok);
+		response.headers_mut().set(header::ContentType::json());
+		if let Some(cors) = self.cors_header.clone() {
+			response.headers_mut().set(cors);
+		}
+		Next::write()
+	}
+
+	fn on_response_writable(&mut self, encoder: &mut Encoder<HttpStream>) -> Next {
+		let response = self.response.lock();
+		let bytes = response.as_ref().map_or(&[][..], |r| r.as_bytes());
+		write_response(encoder, bytes, &mut self.written)
+	}
+}
+
+fn write_response(encoder: &mut Encoder<HttpStream>, bytes: &[u8], written: &mut usize) -> Next {
+	if *written == bytes.len() {
+		return Next::end();
+	}
+
+	match encoder.write(&bytes[*written..]) {
+		Ok(count) => {
+			*written += count;
+			Next::write()
+		},
+		Err(e) => match e.kind() {
+			::std::io::ErrorKind::WouldBlock => Next::write(),
+			_ => Next::end(),
+		},
+	}
+}
+
+/// Answers rejected request with JSON-RPC error without reading its body.
+struct Rejection {
+	response: Vec<u8>,
+	written: usize,
+}
+
+impl Rejection {
+	fn new(error: Error, id: Value) -> Self {
+		Rejection {
+			response: AccessControl::error_response(error, id).into_bytes(),
+			written: 0,
+		}
+	}
+}
+
+impl server::Handler<HttpStream> for Rejection {
+	fn on_request(&mut self, _request: server::Request<HttpStream>) -> Next {
+		Next::write()
+	}
+
+	fn on_request_readable(&mut self, _decoder: &mut Decoder<HttpStream>) -> Next {
+		Next::write()
+	}
+
+	fn on_response(&mut self, response: &mut server::Response) -> Next {
+		response.set_status(StatusCode::Ok);
+		response.headers_mut().set(header::ContentType::json());
+		response.headers_mut().set(header::ContentLength(self.response.len() as u64));
+		Next::write()
+	}
+
+	fn on_response_writable(&mut self, encoder: &mut Encoder<HttpStream>) -> Next {
+		write_response(encoder, &self.response, &mut self.written)
+	}
+}
+
+/// HTTP JSON-RPC server with `AccessFilter` in front of every request. Closes the server when dropped.
+pub struct Server {
+	server: Option<server::Listening>,
+}
+
+impl Server {
+	/// Start HTTP server on given address.
+	pub fn start(
+		addr: &SocketAddr,
+		handler: Arc<IoHandler>,
+		access: Arc<AccessControl>,
+		cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+		allowed_hosts: Option<Vec<String>>,
+		panic_handler: Arc<::io::PanicHandler>,
+	) -> Result<Server, hyper::error::Error> {
+		let notify = panic_handler.clone();
+		let on_panic: Arc<Mutex<Option<Box<Fn() -> () + Send>>>> = Arc::new(Mutex::new(Some(Box::new(move || {
+			notify.notify_all("Panic in RPC thread.".to_owned());
+		}))));
+
+		let (listening, srv) = try!(try!(hyper::Server::http(addr))
+			.handle(move |control| AccessFilter::new(
+				access.clone(),
+				handler.clone(),
+				cors_domains.clone(),
+				allowed_hosts.clone(),
+				"",
+				on_panic.clone(),
+				control,
+			)));
+
+		thread::spawn(move || {
+			if panic_handler.catch_panic(move || srv.run()).is_err() {
+				panic_handler.notify_all("Panic in RPC thread.".to_owned());
+			}
+		});
+
+		Ok(Server {
+			server: Some(listening),
+		})
+	}
+
+	#[cfg(test)]
+	/// Returns address that this server is bound to.
+	pub fn addr(&self) -> &SocketAddr {
+		self.server.as_ref().expect("server is always Some at the start; it's consumed only when object is dropped; qed").addr()
+	}
+}
+
+impl Drop for Server {
+	fn drop(&mut self) {
+		self.server.take().expect("server is always Some at the start; it's consumed only when object is dropped; qed").close()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::collections::HashMap;
+	use jsonrpc_core::IoHandler;
+	use devtools::http_client;
+	use io::PanicHandler;
+	use v1::{AccessControl, ApiKey, Web3, Web3Client};
+	use super::Server;
+
+	const SHA3_REQUEST: &'static str = r#"{"jsonrpc":"2.0","method":"web3_sha3","params":["0x00"],"id":1}"#;
+	const SHA3_RESULT: &'static str = r#""result":"0xbc36789e7a1e281436464229828f817d6612f7b477d66591ff96a9e064bcc98a""#;
+
+	fn serve(max_batch_size: Option<usize>, max_payload: Option<usize>) -> Server {
+		let web3 = IoHandler::new();
+		web3.add_delegate(Web3Client::new().to_delegate());
+
+		let mut keys = HashMap::new();
+		keys.insert("web3key".to_owned(), ApiKey {
+			handler: Arc::new(web3),
+			methods: Some(vec!["web3_sha3".into()]),
+			rate_limit: Some(2),
+		});
+		let access = Arc::new(AccessControl::new(keys, max_batch_size, max_payload));
+
+		// anonymous requests would be served by a handler without any methods.
+		Server::start(
+			&"127.0.0.1:0".parse().unwrap(),
+			Arc::new(IoHandler::new()),
+			access,
+			None,
+			None,
+			PanicHandler::new_in_arc(),
+		).unwrap()
+	}
+
+	fn post(server: &Server, path: &str, headers: &str, body: &str) -> http_client::Response {
+		http_client::request(server.addr(), &format!(
+			"POST {} HTTP/1.1\r\nHost: 127.0.0.1:8545\r\nConnection: close\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\n\r\n{}",
+			path, headers, body.len(), body
+		))
+	}
+
+	#[test]
+	fn should_reject_requests_without_api_key() {
+		let server = serve(None, None);
+
+		let response = post(&server, "/", "", SHA3_REQUEST);
+
+		assert_eq!(response.status, "HTTP/1.1 200 OK".to_owned());
+		assert!(response.body.contains(r#""code":-32060"#), response.body);
+	}
+
+	#[test]
+	fn should_serve_key_apis_with_key_in_header_or_path() {
+		let server = serve(None, None);
+
+		let header = post(&server, "/", "X-Api-Key: web3key\r\n", SHA3_REQUEST);
+		let path = post(&server, "/web3key", "", SHA3_REQUEST);
+		let limited = post(&server, "/web3key", "", SHA3_REQUEST);
+
+		assert!(header.body.contains(SHA3_RESULT), header.body);
+		assert!(path.body.contains(SHA3_RESULT), path.body);
+		// third request exceeds the rate limit of the key.
+		assert!(limited.body.contains(r#""code":-32061"#), limited.body);
+	}
+
+	#[test]
+	fn should_reject_methods_not_allowed_for_key() {
+		let server = serve(None, None);
+
+		let response = post(&server, "/web3key", "", r#"{"jsonrpc":"2.0","method":"web3_clientVersion","params":[],"id":5}"#);
+
+		assert!(response.body.contains(r#""code":-32060"#), response.body);
+		assert!(response.body.contains(r#""id":5"#), response.body);
+	}
+
+	#[test]
+	fn should_charge_rate_limit_for_every_call_of_a_batch() {
+		let server = serve(None, None);
+		let batch = format!("[{},{},{}]", SHA3_REQUEST, SHA3_REQUEST, SHA3_REQUEST);
+
+		let response = post(&server, "/web3key", "", &batch);
+
+		assert!(response.body.contains(r#""code":-32061"#), response.body);
+	}
+
+	#[test]
+	fn should_reject_too_large_batches() {
+		let server = serve(Some(1), None);
+		let batch = format!("[{},{}]", SHA3_REQUEST, SHA3_REQUEST);
+
+		let response = post(&server, "/web3key", "", &batch);
+
+		assert!(response.body.contains(r#""code":-32062"#), response.body);
+		assert!(response.body.contains("Maximum batch size is 1"), response.body);
+	}
+
+	#[test]
+	fn should_reject_too_large_requests() {
+		let server = serve(None, Some(16));
+
+		let response = post(&server, "/web3key", "", SHA3_REQUEST);
+
+		assert!(response.body.contains(r#""code":-32062"#), response.body);
+	}
+}
@@ -24,6 +24,7 @@ extern crate serde;
 extern crate serde_json;
 extern crate jsonrpc_core;
 extern crate jsonrpc_http_server;
+extern crate hyper;
 
 extern crate ethcore_io as io;
 extern crate ethcore;
@@ -53,7 +54,9 @@ use self::jsonrpc_core::{IoHandler, IoDelegate};
 
 pub use jsonrpc_http_server::{ServerBuilder, Server, RpcServerError};
 pub mod v1;
-pub use v1::{SigningQueue, ConfirmationsQueue, NetworkSettings};
+mod http_access;
+pub use v1::{SigningQueue, ConfirmationsQueue, NetworkSettings, AccessControl, ApiKey, ApiKeySpec};
+pub use http_access::{AccessFilter, Server as AccessControlledServer};
 
 /// An object that can be extended with `IoDelegates`
 pub trait Extendable {
@@ -61,6 +64,18 @@ pub trait Extendable {
 	fn add_delegate<D: Send + Sync + 'static>(&self, delegate: IoDelegate<D>);
 }
 
+fn cors(domains: Option<Vec<String>>) -> Option<Vec<jsonrpc_http_server::AccessControlAllowOrigin>> {
+	domains.map(|domains| {
+		domains.into_iter()
+			.map(|v| match v.as_str() {
+				"*" => jsonrpc_http_server::AccessControlAllowOrigin::Any,
+				"null" => jsonrpc_http_server::AccessControlAllowOrigin::Null,
+				v => jsonrpc_http_server::AccessControlAllowOrigin::Value(v.into()),
+			})
+			.collect()
+	})
+}
+
 /// Http server.
 pub struct RpcServer {
 	handler: Arc<jsonrpc_core::io::IoHandler>,
@@ -90,18 +105,8 @@ impl RpcServer {
 		panic_handler: Arc<PanicHandler>,
 		) -> Result<Server, RpcServerError> {
 
-		let cors_domains = cors_domains.map(|domains| {
-			domains.into_iter()
-				.map(|v| match v.as_str() {
-					"*" => jsonrpc_http_server::AccessControlAllowOrigin::Any,
-					"null" => jsonrpc_http_server::AccessControlAllowOrigin::Null,
-					v => jsonrpc_http_server::AccessControlAllowOrigin::Value(v.into()),
-				})
-				.collect()
-		});
-
 		ServerBuilder::new(self.handler.clone())
-			.cors(cors_domains.into())
+			.cors(cors(cors_domains).into())
 			.allowed_hosts(allowed_hosts.into())
 			.panic_handler(move || {
 				panic_handler.notify_all("Panic in RPC thread.".to_owned());
@@ -109,6 +114,24 @@ impl RpcServer {
 			.start_http(addr)
 	}
 
+	/// Start http server enforcing given access control policy asynchronously
+	/// and returns result with `AccessControlledServer` handle on success or an error.
+	pub fn start_http_with_access(
+		&self,
+		addr: &SocketAddr,
+		cors_domains: Option<Vec<String>>,
+		allowed_hosts: Option<Vec<String>>,
+		access: Arc<AccessControl>,
+		panic_handler: Arc<PanicHandler>,
+		) -> Result<AccessControlledServer, hyper::error::Error> {
+		AccessControlledServer::start(addr, self.handler.clone(), access, cors(cors_domains), allowed_hosts, panic_handler)
+	}
+
+	/// Returns handler processing requests of this server.
+	pub fn handler(&self) -> Arc<IoHandler> {
+		self.handler.clone()
+	}
+
 	/// Start ipc server asynchronously and returns result with `Server` handle on success or an error.
 	pub fn start_ipc(&self, addr: &str) -> Result<ipc::Server, ipc::Error> {
 		let server = try!(ipc::Server::new(addr, &self.handler));
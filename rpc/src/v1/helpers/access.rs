@@ -0,0 +1,339 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Access control for the HTTP JSON-RPC endpoints: API keys, their APIs and methods and request limits.
+
+use std::sync::Arc;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use serde_json::{self, Value};
+use jsonrpc_core::{Error, IoHandler};
+use util::Mutex;
+use v1::helpers::errors;
+
+/// Length of the rate limiting window.
+const RATE_LIMIT_PERIOD_SECS: u64 = 60;
+
+/// API key as described in the keys file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApiKeySpec {
+	/// Names of APIs this key may use; `None` means APIs enabled for HTTP.
+	pub apis: Option<Vec<String>>,
+	/// Methods of these APIs this key may call; `None` allows all of them.
+	/// A pattern ending with `*` matches every method with the given prefix, e.g. `eth_*`.
+	pub methods: Option<Vec<String>>,
+	/// Maximal number of calls per minute; `None` means unlimited.
+	pub rate_limit: Option<u32>,
+}
+
+/// Permissions of a single API key.
+pub struct ApiKey {
+	/// Handler exposing only the APIs this key may use.
+	pub handler: Arc<IoHandler>,
+	/// Methods this key may call; `None` allows all methods of the handler.
+	pub methods: Option<Vec<String>>,
+	/// Maximal number of calls per minute; `None` means unlimited.
+	pub rate_limit: Option<u32>,
+}
+
+impl ApiKey {
+	fn allows(&self, method: &str) -> bool {
+		self.methods.as_ref().map_or(true, |methods| methods.iter().any(|pattern| match pattern.ends_with('*') {
+			true => method.starts_with(&pattern[..pattern.len() - 1]),
+			false => pattern == method,
+		}))
+	}
+}
+
+/// Access control policy for HTTP JSON-RPC requests.
+pub struct AccessControl {
+	keys: HashMap<String, ApiKey>,
+	max_batch_size: Option<usize>,
+	max_payload: Option<usize>,
+	usage: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl AccessControl {
+	/// Creates new policy. If any `keys` are given, every request has to provide one of them.
+	pub fn new(keys: HashMap<String, ApiKey>, max_batch_size: Option<usize>, max_payload: Option<usize>) -> Self {
+		AccessControl {
+			keys: keys,
+			max_batch_size: max_batch_size,
+			max_payload: max_payload,
+			usage: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Parses API keys from JSON of the form
+	/// `{ "<key>": { "apis": ["web3", "eth", "net"], "methods": ["eth_*", "net_version"], "rateLimit": 60 } }`.
+	pub fn parse_keys(json: &str) -> Result<HashMap<String, ApiKeySpec>, String> {
+		let value: Value = try!(serde_json::from_str(json).map_err(|e| format!("Invalid API keys JSON: {}", e)));
+		let keys = try!(value.as_object().ok_or_else(|| "API keys JSON should be an object".to_owned()));
+
+		let mut parsed = HashMap::new();
+		for (key, permissions) in keys {
+			let apis = try!(string_list(key, "APIs", permissions.find("apis")));
+			let methods = try!(string_list(key, "methods", permissions.find("methods")));
+			let rate_limit = match permissions.find("rateLimit") {
+				None | Some(&Value::Null) => None,
+				Some(&Value::U64(limit)) if limit <= u32::max_value() as u64 => Some(limit as u32),
+				Some(other) => return Err(format!("Invalid rate limit for key {}: {}", key, other)),
+			};
+			parsed.insert(key.clone(), ApiKeySpec {
+				apis: apis,
+				methods: methods,
+				rate_limit: rate_limit,
+			});
+		}
+		Ok(parsed)
+	}
+
+	/// Returns `true` if the policy restricts anything.
+	pub fn is_enabled(&self) -> bool {
+		!self.keys.is_empty() || self.max_batch_size.is_some() || self.max_payload.is_some()
+	}
+
+	/// Maximal size of request body in bytes.
+	pub fn max_payload(&self) -> Option<usize> {
+		self.max_payload
+	}
+
+	/// Checks if a request made with given API key and declared body length may be read.
+	/// Returns the handler of the key if it has one, `None` if the default handler should serve the request.
+	pub fn check(&self, key: Option<&str>, content_length: Option<u64>) -> Result<Option<Arc<IoHandler>>, Error> {
+		if let Some(max) = self.max_payload {
+			match content_length {
+				// body is read only up to the declared length.
+				None => return Err(errors::content_length_required(max)),
+				Some(length) if length > max as u64 => return Err(errors::request_too_large(max)),
+				_ => {},
+			}
+		}
+
+		match self.api_key(key) {
+			Ok(Some((_, api_key))) => Ok(Some(api_key.handler.clone())),
+			Ok(None) => Ok(None),
+			Err(error) => Err(error),
+		}
+	}
+
+	/// Checks calls of a request body sent with given API key: the batch size, methods allowed for the key
+	/// and its rate limit, which is charged with every call of a batch.
+	/// Returns the error together with id of the request to respond with otherwise.
+	pub fn check_calls(&self, key: Option<&str>, request: &str) -> Result<(), (Error, Value)> {
+		let api_key = try!(self.api_key(key).map_err(|error| (error, Value::Null)));
+
+		// malformed requests are reported by the handler itself.
+		let (calls, id) = match serde_json::from_str::<Value>(request) {
+			Ok(Value::Array(calls)) => {
+				if let Some(max) = self.max_batch_size {
+					if calls.len() > max {
+						return Err((errors::batch_too_large(max), Value::Null));
+					}
+				}
+				(calls, Value::Null)
+			},
+			Ok(call @ Value::Object(_)) => {
+				let id = call.find("id").cloned().unwrap_or(Value::Null);
+				(vec![call], id)
+			},
+			_ => return Ok(()),
+		};
+
+		let (key, api_key) = match api_key {
+			Some(api_key) => api_key,
+			None => return Ok(()),
+		};
+
+		for call in &calls {
+			if let Some(method) = call.find("method").and_then(Value::as_str) {
+				if !api_key.allows(method) {
+					return Err((errors::method_not_allowed(method), id));
+				}
+			}
+		}
+
+		if let Some(limit) = api_key.rate_limit {
+			let now = Instant::now();
+			let mut usage = self.usage.lock();
+			let entry = usage.entry(key.to_owned()).or_insert((now, 0));
+			if now.duration_since(entry.0) >= Duration::from_secs(RATE_LIMIT_PERIOD_SECS) {
+				*entry = (now, 0);
+			}
+			if entry.1 as usize + calls.len() > limit as usize {
+				return Err((errors::rate_limit_exceeded(limit), id));
+			}
+			entry.1 += calls.len() as u32;
+		}
+
+		Ok(())
+	}
+
+	fn api_key<'a, 'b>(&'a self, key: Option<&'b str>) -> Result<Option<(&'b str, &'a ApiKey)>, Error> {
+		if self.keys.is_empty() {
+			return Ok(None);
+		}
+
+		let key = try!(key.ok_or_else(errors::api_key_required));
+		let api_key = try!(self.keys.get(key).ok_or_else(errors::invalid_api_key));
+		Ok(Some((key, api_key)))
+	}
+
+	/// Serializes JSON-RPC error response to a rejected request.
+	pub fn error_response(error: Error, id: Value) -> String {
+		let proof = "serialization of an error and a JSON value cannot fail; qed";
+		format!(
+			r#"{{"jsonrpc":"2.0","error":{},"id":{}}}"#,
+			serde_json::to_string(&error).expect(proof),
+			serde_json::to_string(&id).expect(proof)
+		)
+	}
+}
+
+fn string_list(key: &str, name: &str, value: Option<&Value>) -> Result<Option<Vec<String>>, String> {
+	match value {
+		None | Some(&Value::Null) => Ok(None),
+		Some(&Value::Array(ref items)) => items.iter()
+			.map(|item| item.as_str().map(Into::into).ok_or_else(|| format!("Invalid {} for key {}: {}", name, key, item)))
+			.collect::<Result<Vec<String>, String>>()
+			.map(Some),
+		Some(other) => Err(format!("Invalid {} for key {}: {}", name, key, other)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::collections::HashMap;
+	use serde_json::Value;
+	use jsonrpc_core::{Error, IoHandler};
+	use v1::helpers::errors;
+	use super::{AccessControl, ApiKey, ApiKeySpec};
+
+	fn rejection(result: Result<Option<Arc<IoHandler>>, Error>) -> Error {
+		match result {
+			Err(error) => error,
+			Ok(_) => panic!("request should be rejected"),
+		}
+	}
+
+	fn access(max_batch_size: Option<usize>, max_payload: Option<usize>) -> AccessControl {
+		let mut keys = HashMap::new();
+		keys.insert("public".to_owned(), ApiKey {
+			handler: Arc::new(IoHandler::new()),
+			methods: Some(vec!["eth_*".into(), "net_version".into()]),
+			rate_limit: Some(3),
+		});
+		keys.insert("admin".to_owned(), ApiKey {
+			handler: Arc::new(IoHandler::new()),
+			methods: None,
+			rate_limit: None,
+		});
+		AccessControl::new(keys, max_batch_size, max_payload)
+	}
+
+	#[test]
+	fn should_parse_keys() {
+		let keys = AccessControl::parse_keys(r#"{
+			"public": { "apis": ["web3", "eth"], "methods": ["eth_*", "web3_sha3"], "rateLimit": 60 },
+			"admin": {}
+		}"#).unwrap();
+
+		assert_eq!(keys.len(), 2);
+		assert_eq!(keys["public"], ApiKeySpec {
+			apis: Some(vec!["web3".into(), "eth".into()]),
+			methods: Some(vec!["eth_*".into(), "web3_sha3".into()]),
+			rate_limit: Some(60),
+		});
+		assert_eq!(keys["admin"], ApiKeySpec::default());
+		assert!(AccessControl::parse_keys(r#"{ "public": { "rateLimit": "fast" } }"#).is_err());
+		assert!(AccessControl::parse_keys(r#"{ "public": { "methods": "eth_*" } }"#).is_err());
+	}
+
+	#[test]
+	fn should_require_valid_api_key() {
+		let access = access(None, None);
+		let request = r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#;
+
+		assert_eq!(rejection(access.check(None, Some(10))), errors::api_key_required());
+		assert_eq!(rejection(access.check(Some("unknown"), Some(10))), errors::invalid_api_key());
+		assert!(access.check(Some("admin"), Some(10)).unwrap().is_some());
+		assert_eq!(access.check_calls(None, request), Err((errors::api_key_required(), Value::Null)));
+		assert_eq!(access.check_calls(Some("admin"), request), Ok(()));
+	}
+
+	#[test]
+	fn should_use_default_handler_without_keys() {
+		let access = AccessControl::new(HashMap::new(), None, Some(100));
+
+		assert!(access.check(None, Some(10)).unwrap().is_none());
+		assert!(access.is_enabled());
+		assert!(AccessControl::new(HashMap::new(), Some(10), None).is_enabled());
+		assert!(!AccessControl::new(HashMap::new(), None, None).is_enabled());
+	}
+
+	#[test]
+	fn should_limit_payload() {
+		let access = access(None, Some(100));
+
+		assert_eq!(rejection(access.check(Some("admin"), Some(101))), errors::request_too_large(100));
+		assert_eq!(rejection(access.check(Some("admin"), None)), errors::content_length_required(100));
+		assert!(access.check(Some("admin"), Some(100)).is_ok());
+	}
+
+	#[test]
+	fn should_restrict_methods() {
+		let access = access(None, None);
+		let request = r#"{"jsonrpc":"2.0","method":"trace_filter","params":[],"id":7}"#;
+		let batch = r#"[{"jsonrpc":"2.0","method":"eth_getLogs","params":[],"id":1},{"jsonrpc":"2.0","method":"ethcore_netPeers","params":[],"id":2}]"#;
+
+		assert_eq!(access.check_calls(Some("public"), request), Err((errors::method_not_allowed("trace_filter"), Value::U64(7))));
+		assert_eq!(access.check_calls(Some("public"), batch), Err((errors::method_not_allowed("ethcore_netPeers"), Value::Null)));
+		assert_eq!(access.check_calls(Some("admin"), request), Ok(()));
+	}
+
+	#[test]
+	fn should_limit_batch_size() {
+		let access = AccessControl::new(HashMap::new(), Some(2), None);
+		let batch = r#"[{"jsonrpc":"2.0","method":"net_version","params":[],"id":1},{"jsonrpc":"2.0","method":"net_version","params":[],"id":2}]"#;
+		let big_batch = r#"[{"jsonrpc":"2.0","method":"net_version","params":[],"id":1},{"jsonrpc":"2.0","method":"net_version","params":[],"id":2},{"jsonrpc":"2.0","method":"net_version","params":[],"id":3}]"#;
+
+		assert_eq!(access.check_calls(None, big_batch), Err((errors::batch_too_large(2), Value::Null)));
+		assert_eq!(access.check_calls(None, batch), Ok(()));
+	}
+
+	#[test]
+	fn should_charge_every_call_of_a_batch() {
+		let access = access(None, None);
+		let call = r#"{"jsonrpc":"2.0","method":"net_version","params":[],"id":1}"#;
+		let batch = r#"[{"jsonrpc":"2.0","method":"net_version","params":[],"id":1},{"jsonrpc":"2.0","method":"net_version","params":[],"id":2}]"#;
+
+		assert_eq!(access.check_calls(Some("public"), batch), Ok(()));
+		// a second batch would exceed the limit of 3 calls.
+		assert_eq!(access.check_calls(Some("public"), batch), Err((errors::rate_limit_exceeded(3), Value::Null)));
+		assert_eq!(access.check_calls(Some("public"), call), Ok(()));
+		assert_eq!(access.check_calls(Some("public"), call), Err((errors::rate_limit_exceeded(3), Value::U64(1))));
+		// other keys are not affected
+		assert_eq!(access.check_calls(Some("admin"), call), Ok(()));
+	}
+
+	#[test]
+	fn should_serialize_error_response() {
+		let response = AccessControl::error_response(errors::invalid_api_key(), Value::U64(1));
+		assert_eq!(response, r#"{"jsonrpc":"2.0","error":{"code":-32060,"message":"Invalid API key."},"id":1}"#);
+	}
+}
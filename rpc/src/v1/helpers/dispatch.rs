@@ -87,9 +87,8 @@ pub fn sign_and_dispatch<C, M>(client: &C, miner: &M, request: TransactionReques
 	dispatch_transaction(&*client, &*miner, signed_transaction)
 }
 
+/// Gas price filled in for transactions which don't specify one. Never below the price
+/// our own queue accepts.
 pub fn default_gas_price<C, M>(client: &C, miner: &M) -> U256 where C: MiningBlockChainClient, M: MinerService {
-	client
-		.gas_price_statistics(100, 8)
-		.map(|x| x[4])
-		.unwrap_or_else(|_| miner.sensible_gas_price())
+	::std::cmp::max(miner.gas_price_suggestions(client).standard, miner.minimal_gas_price())
 }
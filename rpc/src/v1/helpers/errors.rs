@@ -21,7 +21,7 @@ macro_rules! rpc_unimplemented {
 }
 
 use std::fmt;
-use ethcore::error::Error as EthcoreError;
+use ethcore::error::{Error as EthcoreError, CallError};
 use ethcore::account_provider::{Error as AccountError};
 use jsonrpc_core::{Error, ErrorCode, Value};
 
@@ -31,8 +31,11 @@ mod codes {
 	pub const NO_WORK: i64 = -32001;
 	pub const NO_AUTHOR: i64 = -32002;
 	pub const NO_NEW_WORK: i64 = -32003;
+	pub const STATE_REPLAY_LIMIT: i64 = -32004;
+	pub const STATE_CHECKPOINT_PENDING: i64 = -32005;
 	pub const UNKNOWN_ERROR: i64 = -32009;
 	pub const TRANSACTION_ERROR: i64 = -32010;
+	pub const EXECUTION_ERROR: i64 = -32015;
 	pub const ACCOUNT_LOCKED: i64 = -32020;
 	pub const PASSWORD_INVALID: i64 = -32021;
 	pub const ACCOUNT_ERROR: i64 = -32023;
@@ -41,6 +44,9 @@ mod codes {
 	pub const REQUEST_REJECTED_LIMIT: i64 = -32041;
 	pub const REQUEST_NOT_FOUND: i64 = -32042;
 	pub const COMPILATION_ERROR: i64 = -32050;
+	pub const ACCESS_DENIED: i64 = -32060;
+	pub const RATE_LIMITED: i64 = -32061;
+	pub const REQUEST_TOO_LARGE: i64 = -32062;
 }
 
 pub fn unimplemented() -> Error {
@@ -75,6 +81,62 @@ pub fn request_rejected_limit() -> Error {
 	}
 }
 
+pub fn api_key_required() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::ACCESS_DENIED),
+		message: "API key is required to access this endpoint.".into(),
+		data: None,
+	}
+}
+
+pub fn invalid_api_key() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::ACCESS_DENIED),
+		message: "Invalid API key.".into(),
+		data: None,
+	}
+}
+
+pub fn method_not_allowed(method: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::ACCESS_DENIED),
+		message: "Method is not allowed for this API key.".into(),
+		data: Some(Value::String(method.into())),
+	}
+}
+
+pub fn rate_limit_exceeded(limit: u32) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::RATE_LIMITED),
+		message: "Request rate limit exceeded. Try again later.".into(),
+		data: Some(Value::String(format!("{} calls per minute", limit))),
+	}
+}
+
+pub fn batch_too_large(max: usize) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_TOO_LARGE),
+		message: "Batch contains too many requests.".into(),
+		data: Some(Value::String(format!("Maximum batch size is {}", max))),
+	}
+}
+
+pub fn content_length_required(max: usize) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_TOO_LARGE),
+		message: "Request has to specify Content-Length.".into(),
+		data: Some(Value::String(format!("Maximum request size is {} bytes", max))),
+	}
+}
+
+pub fn request_too_large(max: usize) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_TOO_LARGE),
+		message: "Request body is too large.".into(),
+		data: Some(Value::String(format!("Maximum request size is {} bytes", max))),
+	}
+}
+
 pub fn account<T: fmt::Debug>(error: &str, details: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::ACCOUNT_ERROR),
@@ -115,6 +177,30 @@ pub fn state_pruned() -> Error {
 	}
 }
 
+pub fn state_replay_limit_exceeded(required: u64, limit: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::STATE_REPLAY_LIMIT),
+		message: "Reconstructing the pruned state of this block takes too long. Run with a higher --state-replay-limit or --pruning=archive.".into(),
+		data: Some(Value::String(format!("{} blocks would have to be re-executed, limit is {}", required, limit))),
+	}
+}
+
+pub fn state_checkpoint_pending() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::STATE_CHECKPOINT_PENDING),
+		message: "The pruned state of this block is being reconstructed. Try again later.".into(),
+		data: None
+	}
+}
+
+pub fn fat_db_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "This request is not supported because your node is not running with fat DB. Run with --fat-db and --pruning=archive.".into(),
+		data: None
+	}
+}
+
 pub fn no_work() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::NO_WORK),
@@ -164,6 +250,25 @@ pub fn from_password_error(error: AccountError) -> Error {
 	}
 }
 
+pub fn from_state_error(error: CallError) -> Error {
+	match error {
+		CallError::ReplayLimitExceeded { required, limit } => state_replay_limit_exceeded(required, limit),
+		CallError::CheckpointPending => state_checkpoint_pending(),
+		_ => state_pruned(),
+	}
+}
+
+pub fn from_call_error(error: CallError) -> Error {
+	match error {
+		CallError::StatePruned | CallError::ReplayLimitExceeded { .. } | CallError::CheckpointPending => from_state_error(error),
+		e => Error {
+			code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
+			message: format!("{}", e),
+			data: None,
+		}
+	}
+}
+
 pub fn from_transaction_error(error: EthcoreError) -> Error {
 	use ethcore::error::TransactionError::*;
 
@@ -187,6 +292,7 @@ pub fn from_transaction_error(error: EthcoreError) -> Error {
 				format!("Transaction cost exceeds current gas limit. Limit: {}, got: {}. Try decreasing supplied gas.", limit, got)
 			},
 			InvalidGasLimit(_) => "Supplied gas is beyond limit.".into(),
+			NotPermitted => "Sender is not permitted to send this type of transaction on this chain.".into(),
 		};
 		Error {
 			code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
@@ -18,14 +18,18 @@
 pub mod errors;
 pub mod dispatch;
 pub mod params;
+mod access;
 mod poll_manager;
 mod poll_filter;
 mod requests;
 mod signing_queue;
 mod network_settings;
+mod typed_data;
 
+pub use self::access::{AccessControl, ApiKey, ApiKeySpec};
 pub use self::poll_manager::PollManager;
 pub use self::poll_filter::PollFilter;
 pub use self::requests::{TransactionRequest, FilledTransactionRequest, ConfirmationRequest, ConfirmationPayload, CallRequest};
 pub use self::signing_queue::{ConfirmationsQueue, ConfirmationPromise, ConfirmationResult, SigningQueue, QueueEvent};
 pub use self::network_settings::NetworkSettings;
+pub use self::typed_data::{TypedData, TypedDataMember};
@@ -52,3 +52,12 @@ pub fn from_params_default_third<F1, F2>(params: Params) -> Result<(F1, F2, Bloc
 	}
 }
 
+
+/// Deserialize request parameters with optional fourth parameter `BlockNumber` defaulting to `BlockNumber::Latest`.
+pub fn from_params_default_fourth<F1, F2, F3>(params: Params) -> Result<(F1, F2, F3, BlockNumber, ), Error>
+	where F1: serde::de::Deserialize, F2: serde::de::Deserialize, F3: serde::de::Deserialize {
+	match params_len(&params) {
+		3 => from_params::<(F1, F2, F3, )>(params).map(|(f1, f2, f3)| (f1, f2, f3, BlockNumber::Latest)),
+		_ => from_params::<(F1, F2, F3, BlockNumber)>(params)
+	}
+}
@@ -15,6 +15,8 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use util::{Address, U256, Bytes, H256};
+use ethcore::client::StateOverride;
+use v1::helpers::TypedData;
 
 /// Transaction request coming from RPC
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
@@ -85,10 +87,12 @@ pub struct CallRequest {
 	pub data: Option<Vec<u8>>,
 	/// Nonce
 	pub nonce: Option<U256>,
+	/// State overrides
+	pub state_override: StateOverride,
 }
 
 /// Confirmation object
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ConfirmationRequest {
 	/// Id of this confirmation
 	pub id: U256,
@@ -97,10 +101,12 @@ pub struct ConfirmationRequest {
 }
 
 /// Payload to confirm in Trusted Signer
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ConfirmationPayload {
 	/// Transaction
 	Transaction(FilledTransactionRequest),
 	/// Sign request
 	Sign(Address, H256),
+	/// Typed data sign request, together with its hash
+	SignTypedData(Address, H256, TypedData),
 }
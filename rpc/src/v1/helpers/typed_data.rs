@@ -0,0 +1,239 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Typed structured data hashing (EIP-712).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use serde_json::Value;
+use util::{Address, H256, U256, Uint, FixedHash, Hashable};
+use util::common::FromHex;
+
+const DOMAIN_TYPE: &'static str = "EIP712Domain";
+
+/// Member of a struct type
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedDataMember {
+	/// Member name
+	pub name: String,
+	/// Member type, e.g. `address`, `uint256`, `Person[]`
+	pub kind: String,
+}
+
+/// Typed structured data to be signed
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedData {
+	/// Struct type definitions, including `EIP712Domain`
+	pub types: BTreeMap<String, Vec<TypedDataMember>>,
+	/// Type of the message
+	pub primary_type: String,
+	/// Domain separator values
+	pub domain: Value,
+	/// The message
+	pub message: Value,
+}
+
+impl TypedData {
+	/// Hash to be signed: `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))`.
+	pub fn hash(&self) -> Result<H256, String> {
+		let domain_separator = try!(self.hash_struct(DOMAIN_TYPE, &self.domain));
+		let message = try!(self.hash_struct(&self.primary_type, &self.message));
+		let mut encoded = vec![0x19, 0x01];
+		encoded.extend_from_slice(&*domain_separator);
+		encoded.extend_from_slice(&*message);
+		Ok(encoded.sha3())
+	}
+
+	fn members(&self, name: &str) -> Result<&Vec<TypedDataMember>, String> {
+		self.types.get(name).ok_or_else(|| format!("Unknown type: {}", name))
+	}
+
+	/// Collects all struct types referenced (transitively) by given type.
+	fn dependencies(&self, name: &str, found: &mut BTreeSet<String>) -> Result<(), String> {
+		for member in try!(self.members(name)) {
+			let base = base_type(&member.kind);
+			if self.types.contains_key(base) && !found.contains(base) {
+				found.insert(base.to_owned());
+				try!(self.dependencies(base, found));
+			}
+		}
+		Ok(())
+	}
+
+	/// Encodes type as `Name(type1 name1,...)` followed by its dependencies sorted by name.
+	fn encode_type(&self, name: &str) -> Result<String, String> {
+		let mut dependencies = BTreeSet::new();
+		try!(self.dependencies(name, &mut dependencies));
+		dependencies.remove(name);
+
+		let mut encoded = String::new();
+		for t in Some(name.to_owned()).into_iter().chain(dependencies) {
+			let members = try!(self.members(&t)).iter()
+				.map(|m| format!("{} {}", m.kind, m.name))
+				.collect::<Vec<_>>();
+			encoded.push_str(&format!("{}({})", t, members.join(",")));
+		}
+		Ok(encoded)
+	}
+
+	fn hash_struct(&self, name: &str, value: &Value) -> Result<H256, String> {
+		let members = try!(self.members(name));
+		let object = try!(value.as_object().ok_or_else(|| format!("Expected {} object, got {}", name, value)));
+
+		let mut encoded = try!(self.encode_type(name)).sha3().to_vec();
+		for member in members {
+			let field = try!(object.get(&member.name).ok_or_else(|| format!("Missing field {}.{}", name, member.name)));
+			encoded.extend_from_slice(&*try!(self.encode_value(&member.kind, field)));
+		}
+		Ok(encoded.sha3())
+	}
+
+	fn encode_value(&self, kind: &str, value: &Value) -> Result<H256, String> {
+		if kind.ends_with(']') {
+			let open = try!(kind.rfind('[').ok_or_else(|| format!("Invalid type: {}", kind)));
+			let (item, length) = (&kind[..open], &kind[open + 1..kind.len() - 1]);
+			let items = try!(value.as_array().ok_or_else(|| format!("Expected array of {}, got {}", item, value)));
+			if !length.is_empty() && length.parse::<usize>() != Ok(items.len()) {
+				return Err(format!("Expected {} items of {}, got {}", length, item, items.len()));
+			}
+
+			let mut encoded = Vec::new();
+			for item_value in items {
+				encoded.extend_from_slice(&*try!(self.encode_value(item, item_value)));
+			}
+			return Ok(encoded.sha3());
+		}
+
+		if self.types.contains_key(kind) {
+			return self.hash_struct(kind, value);
+		}
+
+		match kind {
+			"string" => value.as_str().map(|s| s.sha3()).ok_or_else(|| format!("Expected string, got {}", value)),
+			"bytes" => parse_bytes(value).map(|b| b.sha3()),
+			"bool" => value.as_bool().map(|b| H256::from(U256::from(b as u64))).ok_or_else(|| format!("Expected bool, got {}", value)),
+			"address" => parse_bytes(value)
+				.and_then(|b| if b.len() == 20 { Ok(H256::from(Address::from_slice(&b))) } else { Err(format!("Invalid address: {}", value)) }),
+			_ if kind.starts_with("uint") => parse_uint(value).map(H256::from),
+			_ if kind.starts_with("int") => parse_int(value).map(H256::from),
+			_ if kind.starts_with("bytes") => parse_bytes(value).and_then(|b| {
+				if b.len() > 32 {
+					return Err(format!("Too many bytes for {}: {}", kind, value));
+				}
+				let mut encoded = H256::zero();
+				encoded[..b.len()].copy_from_slice(&b);
+				Ok(encoded)
+			}),
+			_ => Err(format!("Unknown type: {}", kind)),
+		}
+	}
+}
+
+/// Strips array suffixes, e.g. `Person[][2]` -> `Person`.
+fn base_type(kind: &str) -> &str {
+	kind.find('[').map_or(kind, |i| &kind[..i])
+}
+
+fn parse_bytes(value: &Value) -> Result<Vec<u8>, String> {
+	match value.as_str() {
+		Some(s) if s.starts_with("0x") => FromHex::from_hex(&s[2..]).map_err(|_| format!("Invalid hex: {}", s)),
+		_ => Err(format!("Expected 0x-prefixed hex, got {}", value)),
+	}
+}
+
+fn parse_uint(value: &Value) -> Result<U256, String> {
+	match *value {
+		Value::U64(n) => Ok(n.into()),
+		Value::String(ref s) if s.starts_with("0x") && s.len() <= 66 => U256::from_str(&s[2..]).map_err(|_| format!("Invalid number: {}", s)),
+		Value::String(ref s) => U256::from_dec_str(s).map_err(|_| format!("Invalid number: {}", s)),
+		_ => Err(format!("Expected unsigned integer, got {}", value)),
+	}
+}
+
+/// Parses a signed integer into its 256-bit two's complement representation.
+fn parse_int(value: &Value) -> Result<U256, String> {
+	let negate = |abs: U256| if abs.is_zero() { abs } else { !(abs - U256::one()) };
+	match *value {
+		Value::I64(n) if n < 0 => Ok(!U256::from((-(n + 1)) as u64)),
+		Value::String(ref s) if s.starts_with('-') => parse_uint(&Value::String(s[1..].to_owned())).map(negate),
+		_ => parse_uint(value),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use serde_json;
+	use util::{H256, U256};
+	use super::{TypedData, TypedDataMember, parse_int};
+
+	fn members(m: &[(&str, &str)]) -> Vec<TypedDataMember> {
+		m.iter().map(|&(name, kind)| TypedDataMember { name: name.into(), kind: kind.into() }).collect()
+	}
+
+	fn mail() -> TypedData {
+		let mut types = BTreeMap::new();
+		types.insert("EIP712Domain".to_owned(), members(&[("name", "string"), ("version", "string"), ("chainId", "uint256"), ("verifyingContract", "address")]));
+		types.insert("Person".to_owned(), members(&[("name", "string"), ("wallet", "address")]));
+		types.insert("Mail".to_owned(), members(&[("from", "Person"), ("to", "Person"), ("contents", "string")]));
+
+		TypedData {
+			types: types,
+			primary_type: "Mail".into(),
+			domain: serde_json::from_str(r#"{
+				"name": "Ether Mail",
+				"version": "1",
+				"chainId": 1,
+				"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+			}"#).unwrap(),
+			message: serde_json::from_str(r#"{
+				"from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+				"to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+				"contents": "Hello, Bob!"
+			}"#).unwrap(),
+		}
+	}
+
+	#[test]
+	fn should_encode_type_with_dependencies() {
+		assert_eq!(mail().encode_type("Mail").unwrap(), "Mail(Person from,Person to,string contents)Person(string name,address wallet)");
+	}
+
+	#[test]
+	fn should_hash_typed_data() {
+		let data = mail();
+		assert_eq!(data.hash_struct("EIP712Domain", &data.domain).unwrap(), H256::from("0xf2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"));
+		assert_eq!(data.hash().unwrap(), H256::from("0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"));
+	}
+
+	#[test]
+	fn should_reject_missing_fields_and_unknown_types() {
+		let mut data = mail();
+		data.message = serde_json::from_str(r#"{"contents": "Hello, Bob!"}"#).unwrap();
+		assert_eq!(data.hash(), Err("Missing field Mail.from".into()));
+
+		let mut data = mail();
+		data.primary_type = "Letter".into();
+		assert_eq!(data.hash(), Err("Unknown type: Letter".into()));
+	}
+
+	#[test]
+	fn should_encode_negative_integers() {
+		assert_eq!(parse_int(&serde_json::from_str("-1").unwrap()), Ok(!U256::zero()));
+		assert_eq!(parse_int(&serde_json::from_str(r#""-2""#).unwrap()), Ok(!U256::one()));
+		assert_eq!(parse_int(&serde_json::from_str("5").unwrap()), Ok(U256::from(5)));
+	}
+}
@@ -326,10 +326,9 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 				let address: Address = RpcH160::into(address);
 				match block_number {
 					BlockNumber::Pending => Ok(to_value(&RpcU256::from(take_weak!(self.miner).balance(&*take_weak!(self.client), &address)))),
-					id => match take_weak!(self.client).balance(&address, id.into()) {
-						Some(balance) => Ok(to_value(&RpcU256::from(balance))),
-						None => Err(errors::state_pruned()),
-					}
+					id => take_weak!(self.client).try_balance(&address, id.into())
+						.map(|balance| to_value(&RpcU256::from(balance)))
+						.map_err(errors::from_state_error),
 				}
 			})
 	}
@@ -342,10 +341,9 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 				let position: U256 = RpcU256::into(position);
 				match block_number {
 					BlockNumber::Pending => Ok(to_value(&RpcU256::from(take_weak!(self.miner).storage_at(&*take_weak!(self.client), &address, &H256::from(position))))),
-					id => match take_weak!(self.client).storage_at(&address, &H256::from(position), id.into()) {
-						Some(s) => Ok(to_value(&RpcH256::from(s))),
-						None => Err(errors::state_pruned()),
-					}
+					id => take_weak!(self.client).try_storage_at(&address, &H256::from(position), id.into())
+						.map(|s| to_value(&RpcH256::from(s)))
+						.map_err(errors::from_state_error),
 				}
 			})
 
@@ -358,10 +356,9 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 				let address: Address = RpcH160::into(address);
 				match block_number {
 					BlockNumber::Pending => Ok(to_value(&RpcU256::from(take_weak!(self.miner).nonce(&*take_weak!(self.client), &address)))),
-					id => match take_weak!(self.client).nonce(&address, id.into()) {
-						Some(nonce) => Ok(to_value(&RpcU256::from(nonce))),
-						None => Err(errors::state_pruned()),
-					}
+					id => take_weak!(self.client).try_nonce(&address, id.into())
+						.map(|nonce| to_value(&RpcU256::from(nonce)))
+						.map_err(errors::from_state_error),
 				}
 			})
 	}
@@ -411,10 +408,9 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 				let address: Address = RpcH160::into(address);
 				match block_number {
 					BlockNumber::Pending => Ok(to_value(&take_weak!(self.miner).code(&*take_weak!(self.client), &address).map_or_else(Bytes::default, Bytes::new))),
-					_ => match take_weak!(self.client).code(&address, block_number.into()) {
-						Some(code) => Ok(to_value(&code.map_or_else(Bytes::default, Bytes::new))),
-						None => Err(errors::state_pruned()),
-					},
+					_ => take_weak!(self.client).try_code(&address, block_number.into())
+						.map(|code| to_value(&code.map_or_else(Bytes::default, Bytes::new)))
+						.map_err(errors::from_state_error),
 				}
 			})
 	}
@@ -609,12 +605,13 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 		from_params_default_second(params)
 			.and_then(|(request, block_number,)| {
 				let request = CallRequest::into(request);
+				let state_override = request.state_override.clone();
 				let signed = try!(self.sign_call(request));
 				let r = match block_number {
-					BlockNumber::Pending => take_weak!(self.miner).call(&*take_weak!(self.client), &signed, Default::default()),
-					block_number => take_weak!(self.client).call(&signed, block_number.into(), Default::default()),
+					BlockNumber::Pending => take_weak!(self.miner).call(&*take_weak!(self.client), &signed, Default::default(), &state_override),
+					block_number => take_weak!(self.client).call(&signed, block_number.into(), Default::default(), &state_override),
 				};
-				Ok(to_value(&r.map(|e| Bytes(e.output)).unwrap_or(Bytes::new(vec![]))))
+				r.map(|e| to_value(&Bytes(e.output))).map_err(errors::from_call_error)
 			})
 	}
 
@@ -623,10 +620,11 @@ impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM> where
 		from_params_default_second(params)
 			.and_then(|(request, block_number,)| {
 				let request = CallRequest::into(request);
+				let state_override = request.state_override.clone();
 				let signed = try!(self.sign_call(request));
 				let r = match block_number {
-					BlockNumber::Pending => take_weak!(self.miner).call(&*take_weak!(self.client), &signed, Default::default()),
-					block => take_weak!(self.client).call(&signed, block.into(), Default::default()),
+					BlockNumber::Pending => take_weak!(self.miner).call(&*take_weak!(self.client), &signed, Default::default(), &state_override),
+					block => take_weak!(self.client).call(&signed, block.into(), Default::default(), &state_override),
 				};
 				Ok(to_value(&RpcU256::from(r.map(|res| res.gas_used + res.refunded).unwrap_or(From::from(0)))))
 			})
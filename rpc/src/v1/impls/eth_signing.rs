@@ -23,10 +23,10 @@ use ethcore::client::MiningBlockChainClient;
 use util::{U256, Address, H256, Mutex};
 use transient_hashmap::TransientHashMap;
 use ethcore::account_provider::AccountProvider;
-use v1::helpers::{errors, SigningQueue, ConfirmationPromise, ConfirmationResult, ConfirmationsQueue, ConfirmationPayload, TransactionRequest as TRequest, FilledTransactionRequest as FilledRequest};
+use v1::helpers::{errors, SigningQueue, ConfirmationPromise, ConfirmationResult, ConfirmationsQueue, ConfirmationPayload, TransactionRequest as TRequest, FilledTransactionRequest as FilledRequest, TypedData as HTypedData};
 use v1::helpers::dispatch::{default_gas_price, sign_and_dispatch};
 use v1::traits::EthSigning;
-use v1::types::{TransactionRequest, H160 as RpcH160, H256 as RpcH256, H520 as RpcH520, U256 as RpcU256};
+use v1::types::{TransactionRequest, TypedData, H160 as RpcH160, H256 as RpcH256, H520 as RpcH520, U256 as RpcU256};
 
 fn fill_optional_fields<C, M>(request: TRequest, client: &C, miner: &M) -> FilledRequest
 	where C: MiningBlockChainClient, M: MinerService {
@@ -80,19 +80,32 @@ impl<C, M> EthSigningQueueClient<C, M> where C: MiningBlockChainClient, M: Miner
 		from_params::<(RpcH160, RpcH256)>(params).and_then(|(address, msg)| {
 			let address: Address = address.into();
 			let msg: H256 = msg.into();
+			self.sign_or_enqueue(address, msg, ConfirmationPayload::Sign(address, msg))
+		})
+	}
 
-			let accounts = take_weak!(self.accounts);
-			if accounts.is_unlocked(address) {
-				return Ok(DispatchResult::Value(to_value(&accounts.sign(address, msg).ok().map_or_else(RpcH520::default, Into::into))))
-			}
-
-			let queue = take_weak!(self.queue);
-			queue.add_request(ConfirmationPayload::Sign(address, msg))
-				.map(DispatchResult::Promise)
-				.map_err(|_| errors::request_rejected_limit())
+	fn dispatch_sign_typed_data(&self, params: Params) -> Result<DispatchResult, Error> {
+		from_params::<(RpcH160, TypedData)>(params).and_then(|(address, data)| {
+			let address: Address = address.into();
+			let data: HTypedData = data.into();
+			let hash = try!(data.hash().map_err(|e| errors::invalid_params("Invalid typed data", e)));
+			self.sign_or_enqueue(address, hash, ConfirmationPayload::SignTypedData(address, hash, data))
 		})
 	}
 
+	/// Signs the hash right away if the account is unlocked, otherwise adds the payload to the confirmation queue.
+	fn sign_or_enqueue(&self, address: Address, hash: H256, payload: ConfirmationPayload) -> Result<DispatchResult, Error> {
+		let accounts = take_weak!(self.accounts);
+		if accounts.is_unlocked(address) {
+			return Ok(DispatchResult::Value(to_value(&accounts.sign(address, hash).ok().map_or_else(RpcH520::default, Into::into))))
+		}
+
+		let queue = take_weak!(self.queue);
+		queue.add_request(payload)
+			.map(DispatchResult::Promise)
+			.map_err(|_| errors::request_rejected_limit())
+	}
+
 	fn dispatch_transaction(&self, params: Params) -> Result<DispatchResult, Error> {
 		from_params::<(TransactionRequest, )>(params)
 			.and_then(|(request, )| {
@@ -131,6 +144,19 @@ impl<C, M> EthSigning for EthSigningQueueClient<C, M>
 		}
 	}
 
+	fn sign_typed_data(&self, params: Params, ready: Ready) {
+		let res = self.active().and_then(|_| self.dispatch_sign_typed_data(params));
+		match res {
+			Ok(DispatchResult::Promise(promise)) => {
+				promise.wait_for_result(move |result| {
+					ready.ready(result.unwrap_or_else(|| Err(errors::request_rejected())))
+				})
+			},
+			Ok(DispatchResult::Value(v)) => ready.ready(Ok(v)),
+			Err(e) => ready.ready(Err(e)),
+		}
+	}
+
 	fn post_sign(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		self.dispatch_sign(params).map(|result| match result {
@@ -231,6 +257,17 @@ impl<C, M> EthSigning for EthSigningUnsafeClient<C, M> where
 			}))
 	}
 
+	fn sign_typed_data(&self, params: Params, ready: Ready) {
+		ready.ready(self.active()
+			.and_then(|_| from_params::<(RpcH160, TypedData)>(params))
+			.and_then(|(address, data)| {
+				let address: Address = address.into();
+				let data: HTypedData = data.into();
+				let hash = try!(data.hash().map_err(|e| errors::invalid_params("Invalid typed data", e)));
+				Ok(to_value(&take_weak!(self.accounts).sign(address, hash).ok().map_or_else(RpcH520::default, Into::into)))
+			}))
+	}
+
 	fn send_transaction(&self, params: Params, ready: Ready) {
 		ready.ready(self.active()
 			.and_then(|_| from_params::<(TransactionRequest, )>(params))
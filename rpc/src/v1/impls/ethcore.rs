@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Ethcore-specific rpc implementation.
+use std::cmp;
 use std::sync::{Arc, Weak};
 use std::str::FromStr;
 use std::collections::{BTreeMap};
@@ -29,9 +30,13 @@ use ethcore::client::{MiningBlockChainClient};
 
 use jsonrpc_core::*;
 use v1::traits::Ethcore;
-use v1::types::{Bytes, U256, H160, Peers};
+use v1::types::{Bytes, U256, H160, H256, Peers, NetTraffic, TransactionQueue, GasPriceSuggestions, StateHistory, BadBlock};
 use v1::helpers::{errors, SigningQueue, ConfirmationsQueue, NetworkSettings};
-use v1::helpers::params::expect_no_params;
+use v1::helpers::params::{expect_no_params, from_params_default_third, from_params_default_fourth};
+
+/// Maximum number of accounts or storage keys returned by a single listing request.
+/// Larger requested counts are silently reduced to this.
+const MAX_LIST_COUNT: u64 = 1000;
 
 /// Ethcore implementation.
 pub struct EthcoreClient<C, M, S: ?Sized> where
@@ -142,6 +147,14 @@ impl<C, M, S: ?Sized> Ethcore for EthcoreClient<C, M, S> where M: MinerService +
 		}))
 	}
 
+	fn net_traffic(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let traffic = take_weak!(self.net).network_traffic();
+		Ok(to_value(&NetTraffic::from(traffic)))
+	}
+
 	fn net_port(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		try!(expect_no_params(params));
@@ -165,6 +178,73 @@ impl<C, M, S: ?Sized> Ethcore for EthcoreClient<C, M, S> where M: MinerService +
 		Ok(to_value(&r))
 	}
 
+	fn transaction_queue(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let miner = take_weak!(self.miner);
+		let (current, future) = miner.queued_transactions();
+		Ok(to_value(&TransactionQueue {
+			current: current.into_iter().map(Into::into).collect(),
+			future: future.into_iter().map(Into::into).collect(),
+			nonce_gaps: miner.queue_nonce_gaps().into_iter().map(|(sender, gap)| (sender.into(), gap.into())).collect(),
+		}))
+	}
+
+	fn list_accounts(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params_default_third::<u64, Option<H160>>(params)
+			.and_then(|(count, after, block_number,)| {
+				let client = take_weak!(self.client);
+				if !client.is_fat_db() {
+					return Err(errors::fat_db_disabled());
+				}
+
+				match client.list_accounts(block_number.into(), after.map(Into::into), cmp::min(count, MAX_LIST_COUNT)) {
+					Some(accounts) => Ok(to_value(&accounts.into_iter().map(H160::from).collect::<Vec<_>>())),
+					None => Err(errors::state_pruned()),
+				}
+			})
+	}
+
+	fn list_storage_keys(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params_default_fourth::<H160, u64, Option<H256>>(params)
+			.and_then(|(address, count, after, block_number,)| {
+				let client = take_weak!(self.client);
+				if !client.is_fat_db() {
+					return Err(errors::fat_db_disabled());
+				}
+
+				let address: Address = address.into();
+				match client.list_storage(block_number.into(), &address, after.map(Into::into), cmp::min(count, MAX_LIST_COUNT)) {
+					Some(keys) => Ok(to_value(&keys.into_iter().map(H256::from).collect::<Vec<_>>())),
+					None => Err(errors::state_pruned()),
+				}
+			})
+	}
+
+	fn state_history(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let client = take_weak!(self.client);
+		let pruning = client.pruning_info();
+		Ok(to_value(&StateHistory {
+			earliest_state: pruning.earliest_state.into(),
+			latest_state: client.chain_info().best_block_number.into(),
+			history_size: pruning.history_size.into(),
+		}))
+	}
+
+	fn bad_blocks(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let blocks = take_weak!(self.client).bad_blocks();
+		Ok(to_value(&blocks.into_iter().map(BadBlock::from).collect::<Vec<_>>()))
+	}
+
 	fn rpc_settings(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		try!(expect_no_params(params));
@@ -194,6 +274,15 @@ impl<C, M, S: ?Sized> Ethcore for EthcoreClient<C, M, S> where M: MinerService +
 		}
 	}
 
+	fn gas_price_suggestions(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		try!(expect_no_params(params));
+
+		let client = take_weak!(self.client);
+		let miner = take_weak!(self.miner);
+		Ok(to_value(&GasPriceSuggestions::from(miner.gas_price_suggestions(&*client))))
+	}
+
 	fn unsigned_transactions_count(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		try!(expect_no_params(params));
@@ -23,7 +23,7 @@ use ethsync::ManageNetwork;
 use v1::helpers::errors;
 use v1::helpers::params::expect_no_params;
 use v1::traits::EthcoreSet;
-use v1::types::{Bytes, H160, U256};
+use v1::types::{Bytes, H160, H256, U256, Transaction};
 
 /// Ethcore-specific rpc interface for operations altering the settings.
 pub struct EthcoreSetClient<C, M> where
@@ -114,6 +114,22 @@ impl<C, M> EthcoreSet for EthcoreSetClient<C, M> where
 		})
 	}
 
+	fn remove_transaction(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(H256,)>(params).and_then(|(hash,)| {
+			let removed = take_weak!(self.miner).remove_transaction(&*take_weak!(self.client), &hash.into());
+			Ok(to_value(&removed.map(Transaction::from)))
+		})
+	}
+
+	fn remove_sender_transactions(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(H160,)>(params).and_then(|(sender,)| {
+			let removed = take_weak!(self.miner).remove_sender_transactions(&sender.into());
+			Ok(to_value(&removed))
+		})
+	}
+
 	fn add_reserved_peer(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		from_params::<(String,)>(params).and_then(|(peer,)| {
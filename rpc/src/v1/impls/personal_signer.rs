@@ -85,7 +85,7 @@ impl<C: 'static, M: 'static> PersonalSigner for SignerClient<C, M> where C: Mini
 
 							unlock_sign_and_dispatch(&*client, &*miner, request.into(), &*accounts, pass)
 						},
-						ConfirmationPayload::Sign(address, hash) => {
+						ConfirmationPayload::Sign(address, hash) | ConfirmationPayload::SignTypedData(address, hash, _) => {
 							signature_with_password(&*accounts, address, hash, pass)
 						}
 					};
@@ -19,13 +19,18 @@
 use std::sync::{Weak, Arc};
 use jsonrpc_core::*;
 use rlp::{UntrustedRlp, View};
-use ethcore::client::{BlockChainClient, CallAnalytics, TransactionID, TraceId};
+use util::H256 as EthH256;
+use ethcore::client::{BlockChainClient, CallAnalytics, TransactionID, TraceId, BlockID};
 use ethcore::miner::MinerService;
 use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
+use ethcore::views::{BodyView, BlockView};
 use v1::traits::Traces;
 use v1::helpers::{errors, CallRequest as CRequest};
 use v1::helpers::params::from_params_default_third;
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, H256};
+use v1::types::{
+	TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults,
+	TraceResultsWithTransactionHash, H256,
+};
 
 fn to_call_analytics(flags: Vec<String>) -> CallAnalytics {
 	CallAnalytics {
@@ -126,21 +131,40 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 		from_params_default_third(params)
 			.and_then(|(request, flags, block)| {
 				let request = CallRequest::into(request);
+				let state_override = request.state_override.clone();
 				let signed = try!(self.sign_call(request));
-				match take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags)) {
+				match take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags), &state_override) {
 					Ok(e) => Ok(to_value(&TraceResults::from(e))),
 					_ => Ok(Value::Null),
 				}
 			})
 	}
 
+	fn call_many(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params_default_third::<Vec<CallRequest>, _>(params)
+			.and_then(|(requests, flags, block)| {
+				let mut transactions = Vec::with_capacity(requests.len());
+				let mut state_overrides = Vec::with_capacity(requests.len());
+				for request in requests {
+					let request = CallRequest::into(request);
+					state_overrides.push(request.state_override.clone());
+					transactions.push(try!(self.sign_call(request)));
+				}
+				match take_weak!(self.client).call_many(transactions, block.into(), to_call_analytics(flags), state_overrides) {
+					Ok(results) => Ok(to_value(&results.into_iter().map(TraceResults::from).collect::<Vec<_>>())),
+					_ => Ok(Value::Null),
+				}
+			})
+	}
+
 	fn raw_transaction(&self, params: Params) -> Result<Value, Error> {
 		try!(self.active());
 		from_params_default_third(params)
 			.and_then(|(raw_transaction, flags, block)| {
 				let raw_transaction = Bytes::to_vec(raw_transaction);
 				match UntrustedRlp::new(&raw_transaction).as_val() {
-					Ok(signed) => match take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags)) {
+					Ok(signed) => match take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags), &Default::default()) {
 						Ok(e) => Ok(to_value(&TraceResults::from(e))),
 						_ => Ok(Value::Null),
 					},
@@ -159,4 +183,46 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 				}
 			})
 	}
+
+	fn replay_block_transactions(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(BlockNumber, _)>(params)
+			.and_then(|(block_number, flags)| {
+				let client = take_weak!(self.client);
+				let block: BlockID = block_number.into();
+				let hashes = match client.block_body(block.clone()) {
+					Some(body) => BodyView::new(&body).transaction_hashes(),
+					None => return Ok(Value::Null),
+				};
+				match client.replay_block_transactions(block, to_call_analytics(flags)) {
+					Ok(results) => Ok(to_value(&hashes.into_iter()
+						.zip(results)
+						.map(|(hash, result)| TraceResultsWithTransactionHash::from((H256::from(hash), result)))
+						.collect::<Vec<_>>()
+					)),
+					_ => Ok(Value::Null),
+				}
+			})
+	}
+
+	fn replay_bad_block(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(H256, _)>(params)
+			.and_then(|(block_hash, flags)| {
+				let client = take_weak!(self.client);
+				let block_hash: EthH256 = block_hash.into();
+				let hashes = match client.bad_blocks().into_iter().find(|block| block.hash == block_hash) {
+					Some(block) => BlockView::new(&block.bytes).transaction_hashes(),
+					None => return Ok(Value::Null),
+				};
+				match client.replay_bad_block(block_hash, to_call_analytics(flags)) {
+					Ok(results) => Ok(to_value(&hashes.into_iter()
+						.zip(results)
+						.map(|(hash, result)| TraceResultsWithTransactionHash::from((H256::from(hash), result)))
+						.collect::<Vec<_>>()
+					)),
+					_ => Ok(Value::Null),
+				}
+			})
+	}
 }
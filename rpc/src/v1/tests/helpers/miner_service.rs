@@ -19,11 +19,11 @@
 use util::{Address, H256, Bytes, U256, FixedHash, Uint};
 use util::standard::*;
 use ethcore::error::{Error, CallError};
-use ethcore::client::{MiningBlockChainClient, Executed, CallAnalytics};
+use ethcore::client::{MiningBlockChainClient, Executed, CallAnalytics, StateOverride};
 use ethcore::block::{ClosedBlock, IsBlock};
 use ethcore::transaction::SignedTransaction;
 use ethcore::receipt::{Receipt, RichReceipt};
-use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult};
+use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult, TransactionOrigin, QueuedTransaction, NonceGap};
 
 /// Test miner service.
 pub struct TestMinerService {
@@ -35,6 +35,10 @@ pub struct TestMinerService {
 	pub pending_transactions: Mutex<HashMap<H256, SignedTransaction>>,
 	/// Pre-existed pending receipts
 	pub pending_receipts: Mutex<BTreeMap<H256, Receipt>>,
+	/// Pre-existed future transactions
+	pub future_transactions: Mutex<Vec<QueuedTransaction>>,
+	/// Pre-existed nonce gaps
+	pub nonce_gaps: Mutex<BTreeMap<Address, NonceGap>>,
 	/// Last nonces.
 	pub last_nonces: RwLock<HashMap<Address, U256>>,
 
@@ -53,6 +57,8 @@ impl Default for TestMinerService {
 			latest_closed_block: Mutex::new(None),
 			pending_transactions: Mutex::new(HashMap::new()),
 			pending_receipts: Mutex::new(BTreeMap::new()),
+			future_transactions: Mutex::new(Vec::new()),
+			nonce_gaps: Mutex::new(BTreeMap::new()),
 			last_nonces: RwLock::new(HashMap::new()),
 			min_gas_price: RwLock::new(U256::from(20_000_000)),
 			gas_range_target: RwLock::new((U256::from(12345), U256::from(54321))),
@@ -194,6 +200,36 @@ impl MinerService for TestMinerService {
 		self.pending_transactions.lock().values().cloned().collect()
 	}
 
+	fn queued_transactions(&self) -> (Vec<QueuedTransaction>, Vec<QueuedTransaction>) {
+		let current = self.pending_transactions.lock().values().map(|tx| QueuedTransaction {
+			transaction: tx.clone(),
+			sender: tx.sender().unwrap(),
+			origin: TransactionOrigin::Local,
+			insertion_time: 0,
+		}).collect();
+		(current, self.future_transactions.lock().clone())
+	}
+
+	fn queue_nonce_gaps(&self) -> BTreeMap<Address, NonceGap> {
+		self.nonce_gaps.lock().clone()
+	}
+
+	fn remove_transaction(&self, _chain: &MiningBlockChainClient, hash: &H256) -> Option<SignedTransaction> {
+		self.pending_transactions.lock().remove(hash)
+	}
+
+	fn remove_sender_transactions(&self, sender: &Address) -> usize {
+		let mut pending = self.pending_transactions.lock();
+		let hashes: Vec<H256> = pending.iter()
+			.filter(|&(_, tx)| tx.sender().ok().as_ref() == Some(sender))
+			.map(|(hash, _)| *hash)
+			.collect();
+		for hash in &hashes {
+			pending.remove(hash);
+		}
+		hashes.len()
+	}
+
 	fn pending_transactions(&self) -> Vec<SignedTransaction> {
 		self.pending_transactions.lock().values().cloned().collect()
 	}
@@ -234,7 +270,7 @@ impl MinerService for TestMinerService {
 		self.latest_closed_block.lock().as_ref().map_or_else(U256::zero, |b| b.block().fields().state.balance(address).clone())
 	}
 
-	fn call(&self, _chain: &MiningBlockChainClient, _t: &SignedTransaction, _analytics: CallAnalytics) -> Result<Executed, CallError> {
+	fn call(&self, _chain: &MiningBlockChainClient, _t: &SignedTransaction, _analytics: CallAnalytics, _state_override: &StateOverride) -> Result<Executed, CallError> {
 		unimplemented!();
 	}
 
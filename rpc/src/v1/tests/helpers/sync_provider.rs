@@ -51,6 +51,8 @@ impl TestSyncProvider {
 				mem_used: 0,
 				num_snapshot_chunks: 0,
 				snapshot_chunks_done: 0,
+				snapshot_chunks_served: 0,
+				snapshot_requests_refused: 0,
 			}),
 		}
 	}
@@ -210,6 +210,21 @@ fn rpc_ethcore_net_peers() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_ethcore_net_traffic() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_netTraffic", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"bytesSent":2048,"bytesReceived":4096,"packets":[{"protocol":"eth","packetId":3,"packetsSent":1,"bytesSent":10,"packetsReceived":2,"bytesReceived":300}]},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_ethcore_net_port() {
 	let miner = miner_service();
@@ -286,3 +301,106 @@ fn rpc_ethcore_unsigned_transactions_count_when_signer_disabled() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_ethcore_transaction_queue() {
+	use util::Address;
+	use ethcore::miner::NonceGap;
+
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+	miner.nonce_gaps.lock().insert(Address::from(1u64), NonceGap { expected: 5.into(), first_future: 7.into() });
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_transactionQueue", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"current":[],"future":[],"nonceGaps":{"0x0000000000000000000000000000000000000001":{"expected":"0x5","firstFuture":"0x7"}}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_gas_price_suggestions() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_gasPriceSuggestions", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"safe":"0x4a817c800","standard":"0x4a817c800","fast":"0x4a817c800"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_list_accounts_without_fat_db() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_listAccounts", "params":[10, null], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is not running with fat DB. Run with --fat-db and --pruning=archive.","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_list_storage_keys_without_fat_db() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_listStorageKeys", "params":["0x0000000000000000000000000000000000000001", 10, null, "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is not running with fat DB. Run with --fat-db and --pruning=archive.","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_state_history() {
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_stateHistory", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"earliestState":"0x0","latestState":"0x0","historySize":"0x4b0"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_bad_blocks() {
+	use ethcore::client::BadBlock;
+
+	let miner = miner_service();
+	let client = client_service();
+	let sync = sync_provider();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner, &sync, &net).to_delegate());
+
+	*client.bad_blocks.write() = vec![BadBlock {
+		hash: 1.into(),
+		bytes: vec![0xc0],
+		origin: Some("peer".into()),
+		reason: "InvalidSeal".into(),
+	}];
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_badBlocks", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","rlp":"0xc0","origin":"peer","reason":"InvalidSeal"}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
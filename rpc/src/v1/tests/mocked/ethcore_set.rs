@@ -116,3 +116,26 @@ fn rpc_ethcore_set_transactions_limit() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 	assert_eq!(miner.transactions_limit(), 10_240_240);
 }
+
+#[test]
+fn rpc_ethcore_remove_sender_transactions() {
+	use util::H256;
+	use ethcore::transaction::SignedTransaction;
+
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&client, &miner, &network).to_delegate());
+	let tx: SignedTransaction = ::rlp::decode(&FromHex::from_hex("f85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804").unwrap());
+	miner.pending_transactions.lock().insert(H256::zero(), tx);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_removeTransaction", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_removeSenderTransactions", "params":["0x0f65fe9276bc9a24ae7083ae28e2660ef72df99e"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":1,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(miner.pending_transactions.lock().is_empty());
+}
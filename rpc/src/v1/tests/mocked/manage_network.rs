@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethsync::{ManageNetwork, NetworkConfiguration};
+use ethsync::{ManageNetwork, NetworkConfiguration, NetworkTraffic, PacketTraffic};
 
 pub struct TestManageNetwork;
 
@@ -27,4 +27,18 @@ impl ManageNetwork for TestManageNetwork {
 	fn start_network(&self) {}
 	fn stop_network(&self) {}
 	fn network_config(&self) -> NetworkConfiguration { NetworkConfiguration::new_local() }
+	fn network_traffic(&self) -> NetworkTraffic {
+		NetworkTraffic {
+			bytes_sent: 2048,
+			bytes_received: 4096,
+			packets: vec![PacketTraffic {
+				protocol: "eth".into(),
+				packet_id: 3,
+				packets_sent: 1,
+				bytes_sent: 10,
+				packets_received: 2,
+				bytes_received: 300,
+			}],
+		}
+	}
 }
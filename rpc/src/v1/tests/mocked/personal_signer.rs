@@ -23,7 +23,9 @@ use ethcore::client::TestBlockChainClient;
 use ethcore::transaction::{Transaction, Action};
 use v1::{SignerClient, PersonalSigner};
 use v1::tests::helpers::TestMinerService;
-use v1::helpers::{SigningQueue, ConfirmationsQueue, FilledTransactionRequest, ConfirmationPayload};
+use v1::helpers::{SigningQueue, ConfirmationsQueue, FilledTransactionRequest, ConfirmationPayload, TypedData as HTypedData};
+use v1::types::TypedData;
+use serde_json;
 
 struct PersonalSignerTester {
 	queue: Arc<ConfirmationsQueue>,
@@ -206,3 +208,30 @@ fn should_confirm_transaction_and_dispatch() {
 	assert_eq!(tester.miner.imported_transactions.lock().len(), 1);
 }
 
+
+#[test]
+fn should_confirm_sign_typed_data() {
+	// given
+	let tester = signer_tester();
+	let address = tester.accounts.new_account("test").unwrap();
+	let data: TypedData = serde_json::from_str(r#"{
+		"types": { "EIP712Domain": [{ "name": "name", "type": "string" }] },
+		"primaryType": "EIP712Domain",
+		"domain": { "name": "Test" },
+		"message": { "name": "Test" }
+	}"#).unwrap();
+	let data: HTypedData = data.into();
+	let hash = data.hash().unwrap();
+	tester.queue.add_request(ConfirmationPayload::SignTypedData(address, hash, data)).unwrap();
+
+	tester.accounts.unlock_account_temporarily(address, "test".into()).unwrap();
+	let signature = tester.accounts.sign(address, hash).unwrap();
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"personal_confirmRequest","params":["0x1",{},"test"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:?}", signature).as_ref() + r#"","id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(tester.queue.requests().len(), 0);
+}
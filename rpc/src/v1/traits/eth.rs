@@ -210,6 +210,9 @@ pub trait EthSigning: Sized + Send + Sync + 'static {
 	/// Will return a confirmation ID for later use with check_transaction.
 	fn post_sign(&self, _: Params) -> Result<Value, Error>;
 
+	/// Signs typed structured data (EIP-712) with given address signature.
+	fn sign_typed_data(&self, _: Params, _: Ready);
+
 	/// Sends transaction; will block for 20s to try to return the
 	/// transaction hash.
 	/// If it cannot yet be signed, it will return a transaction ID for
@@ -231,6 +234,7 @@ pub trait EthSigning: Sized + Send + Sync + 'static {
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
 		delegate.add_async_method("eth_sign", EthSigning::sign);
+		delegate.add_async_method("eth_signTypedData", EthSigning::sign_typed_data);
 		delegate.add_async_method("eth_sendTransaction", EthSigning::send_transaction);
 		delegate.add_method("eth_postSign", EthSigning::post_sign);
 		delegate.add_method("eth_postTransaction", EthSigning::post_transaction);
@@ -48,6 +48,9 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns peers details
 	fn net_peers(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns network traffic statistics
+	fn net_traffic(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns network port
 	fn net_port(&self, _: Params) -> Result<Value, Error>;
 
@@ -63,6 +66,9 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns distribution of gas price in latest blocks.
 	fn gas_price_statistics(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns safe, standard and fast gas price suggestions based on recent blocks and the queue.
+	fn gas_price_suggestions(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns number of unsigned transactions waiting in the signer queue (if signer enabled)
 	/// Returns error when signer is disabled
 	fn unsigned_transactions_count(&self, _: Params) -> Result<Value, Error>;
@@ -76,6 +82,23 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns the value of the registrar for this network.
 	fn registry_address(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns current and future transactions in the queue along with nonce gaps blocking the future ones.
+	fn transaction_queue(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns up to the given number of accounts in the state, starting after the given address.
+	/// At most 1000 accounts are returned per request. Requires fat DB.
+	fn list_accounts(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns up to the given number of storage keys of an account, starting after the given key.
+	/// At most 1000 keys are returned per request. Requires fat DB.
+	fn list_storage_keys(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the range of blocks whose state can be queried without re-executing blocks.
+	fn state_history(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the most recent blocks rejected during import, oldest first.
+	fn bad_blocks(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -89,15 +112,22 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 		delegate.add_method("ethcore_devLogsLevels", Ethcore::dev_logs_levels);
 		delegate.add_method("ethcore_netChain", Ethcore::net_chain);
 		delegate.add_method("ethcore_netPeers", Ethcore::net_peers);
+		delegate.add_method("ethcore_netTraffic", Ethcore::net_traffic);
 		delegate.add_method("ethcore_netPort", Ethcore::net_port);
 		delegate.add_method("ethcore_rpcSettings", Ethcore::rpc_settings);
 		delegate.add_method("ethcore_nodeName", Ethcore::node_name);
 		delegate.add_method("ethcore_defaultExtraData", Ethcore::default_extra_data);
 		delegate.add_method("ethcore_gasPriceStatistics", Ethcore::gas_price_statistics);
+		delegate.add_method("ethcore_gasPriceSuggestions", Ethcore::gas_price_suggestions);
 		delegate.add_method("ethcore_unsignedTransactionsCount", Ethcore::unsigned_transactions_count);
 		delegate.add_method("ethcore_generateSecretPhrase", Ethcore::generate_secret_phrase);
 		delegate.add_method("ethcore_phraseToAddress", Ethcore::phrase_to_address);
 		delegate.add_method("ethcore_registryAddress", Ethcore::registry_address);
+		delegate.add_method("ethcore_transactionQueue", Ethcore::transaction_queue);
+		delegate.add_method("ethcore_listAccounts", Ethcore::list_accounts);
+		delegate.add_method("ethcore_listStorageKeys", Ethcore::list_storage_keys);
+		delegate.add_method("ethcore_stateHistory", Ethcore::state_history);
+		delegate.add_method("ethcore_badBlocks", Ethcore::bad_blocks);
 
 		delegate
 	}
@@ -43,6 +43,12 @@ pub trait EthcoreSet: Sized + Send + Sync + 'static {
 	/// Sets the maximum amount of gas a single transaction may consume.
 	fn set_tx_gas_limit(&self, _: Params) -> Result<Value, Error>;
 
+	/// Removes transaction with given hash from the queue.
+	fn remove_transaction(&self, _: Params) -> Result<Value, Error>;
+
+	/// Removes all transactions of given sender from the queue.
+	fn remove_sender_transactions(&self, _: Params) -> Result<Value, Error>;
+
 	/// Add a reserved peer.
 	fn add_reserved_peer(&self, _: Params) -> Result<Value, Error>;
 
@@ -71,6 +77,8 @@ pub trait EthcoreSet: Sized + Send + Sync + 'static {
 		delegate.add_method("ethcore_setAuthor", EthcoreSet::set_author);
 		delegate.add_method("ethcore_setMaxTransactionGas", EthcoreSet::set_tx_gas_limit);
 		delegate.add_method("ethcore_setTransactionsLimit", EthcoreSet::set_transactions_limit);
+		delegate.add_method("ethcore_removeTransaction", EthcoreSet::remove_transaction);
+		delegate.add_method("ethcore_removeSenderTransactions", EthcoreSet::remove_sender_transactions);
 		delegate.add_method("ethcore_addReservedPeer", EthcoreSet::add_reserved_peer);
 		delegate.add_method("ethcore_removeReservedPeer", EthcoreSet::remove_reserved_peer);
 		delegate.add_method("ethcore_dropNonReservedPeers", EthcoreSet::drop_non_reserved_peers);
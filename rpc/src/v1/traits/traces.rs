@@ -35,12 +35,21 @@ pub trait Traces: Sized + Send + Sync + 'static {
 	/// Executes the given call and returns a number of possible traces for it.
 	fn call(&self, _: Params) -> Result<Value, Error>;
 
+	/// Executes the given calls one after another on the same state and returns a number of possible traces for each.
+	fn call_many(&self, _: Params) -> Result<Value, Error>;
+
 	/// Executes the given raw transaction and returns a number of possible traces for it.
 	fn raw_transaction(&self, _: Params) -> Result<Value, Error>;
 
 	/// Executes the transaction with the given hash and returns a number of possible traces for it.
 	fn replay_transaction(&self, _: Params) -> Result<Value, Error>;
 
+	/// Executes all transactions of the given block and returns a number of possible traces for each.
+	fn replay_block_transactions(&self, _: Params) -> Result<Value, Error>;
+
+	/// Executes all transactions of the given bad block on its parent state and returns a number of possible traces for each.
+	fn replay_bad_block(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -49,8 +58,11 @@ pub trait Traces: Sized + Send + Sync + 'static {
 		delegate.add_method("trace_transaction", Traces::transaction_traces);
 		delegate.add_method("trace_block", Traces::block_traces);
 		delegate.add_method("trace_call", Traces::call);
+		delegate.add_method("trace_callMany", Traces::call_many);
 		delegate.add_method("trace_rawTransaction", Traces::raw_transaction);
 		delegate.add_method("trace_replayTransaction", Traces::replay_transaction);
+		delegate.add_method("trace_replayBlockTransactions", Traces::replay_block_transactions);
+		delegate.add_method("trace_replayBadBlock", Traces::replay_bad_block);
 
 		delegate
 	}
@@ -0,0 +1,61 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::client::BadBlock as EthBadBlock;
+use v1::types::{Bytes, H256};
+
+/// Block rejected during import
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BadBlock {
+	/// Block hash
+	pub hash: H256,
+	/// RLP-encoded block
+	pub rlp: Bytes,
+	/// Peer the block was received from, if any
+	pub origin: Option<String>,
+	/// Reason the block was rejected
+	pub reason: String,
+}
+
+impl From<EthBadBlock> for BadBlock {
+	fn from(block: EthBadBlock) -> Self {
+		BadBlock {
+			hash: block.hash.into(),
+			rlp: block.bytes.into(),
+			origin: block.origin,
+			reason: block.reason,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::BadBlock;
+
+	#[test]
+	fn should_serialize_bad_block() {
+		let block = BadBlock {
+			hash: 5.into(),
+			rlp: vec![0xc0].into(),
+			origin: None,
+			reason: "InvalidSeal".into(),
+		};
+
+		let serialized = serde_json::to_string(&block).unwrap();
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000005","rlp":"0xc0","origin":null,"reason":"InvalidSeal"}"#);
+	}
+}
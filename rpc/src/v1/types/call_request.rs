@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use v1::helpers::CallRequest as Request;
-use v1::types::{Bytes, H160, U256};
+use v1::types::{Bytes, H160, U256, StateOverride};
 
 /// Call request
 #[derive(Debug, Default, PartialEq, Deserialize)]
@@ -35,6 +35,9 @@ pub struct CallRequest {
 	pub data: Option<Bytes>,
 	/// Nonce
 	pub nonce: Option<U256>,
+	/// State overrides applied before making the call
+	#[serde(rename="stateOverride")]
+	pub state_override: Option<StateOverride>,
 }
 
 impl Into<Request> for CallRequest {
@@ -47,6 +50,7 @@ impl Into<Request> for CallRequest {
 			value: self.value.map(Into::into),
 			data: self.data.map(Into::into),
 			nonce: self.nonce.map(Into::into),
+			state_override: self.state_override.map_or_else(Default::default, Into::into),
 		}
 	}
 }
@@ -80,6 +84,7 @@ mod tests {
 			value: Some(U256::from(3)),
 			data: Some(vec![0x12, 0x34, 0x56].into()),
 			nonce: Some(U256::from(4)),
+			state_override: None,
 		});
 	}
 
@@ -102,7 +107,8 @@ mod tests {
 			gas: Some(U256::from_str("76c0").unwrap()),
 			value: Some(U256::from_str("9184e72a").unwrap()),
 			data: Some("d46e8dd67c5d32be8d46e8dd67c5d32be8058bb8eb970870f072445675058bb8eb970870f072445675".from_hex().unwrap().into()),
-			nonce: None
+			nonce: None,
+			state_override: None,
 		});
 	}
 
@@ -119,6 +125,7 @@ mod tests {
 			value: None,
 			data: None,
 			nonce: None,
+			state_override: None,
 		});
 	}
 }
@@ -16,12 +16,12 @@
 
 //! Types used in Confirmations queue (Trusted Signer)
 
-use v1::types::{U256, TransactionRequest, H160, H256};
+use v1::types::{U256, TransactionRequest, H160, H256, TypedData};
 use v1::helpers;
 
 
 /// Confirmation waiting in a queue
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ConfirmationRequest {
 	/// Id of this confirmation
 	pub id: U256,
@@ -47,8 +47,19 @@ pub struct SignRequest {
 	pub hash: H256,
 }
 
+/// Typed data sign request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignTypedDataRequest {
+	/// Address
+	pub address: H160,
+	/// Structured data to display and sign
+	pub data: TypedData,
+	/// Hash of the structured data that will be signed
+	pub hash: H256,
+}
+
 /// Confirmation payload, i.e. the thing to be confirmed
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum ConfirmationPayload {
 	/// Transaction
 	#[serde(rename="transaction")]
@@ -56,6 +67,9 @@ pub enum ConfirmationPayload {
 	/// Signature
 	#[serde(rename="sign")]
 	Sign(SignRequest),
+	/// Typed data signature
+	#[serde(rename="signTypedData")]
+	SignTypedData(SignTypedDataRequest),
 }
 
 impl From<helpers::ConfirmationPayload> for ConfirmationPayload {
@@ -66,6 +80,11 @@ impl From<helpers::ConfirmationPayload> for ConfirmationPayload {
 				address: address.into(),
 				hash: hash.into(),
 			}),
+			helpers::ConfirmationPayload::SignTypedData(address, hash, data) => ConfirmationPayload::SignTypedData(SignTypedDataRequest {
+				address: address.into(),
+				data: data.into(),
+				hash: hash.into(),
+			}),
 		}
 	}
 }
@@ -81,6 +100,7 @@ pub struct TransactionModification {
 #[cfg(test)]
 mod tests {
 	use std::str::FromStr;
+	use std::collections::BTreeMap;
 	use serde_json;
 	use v1::types::U256;
 	use v1::helpers;
@@ -102,6 +122,29 @@ mod tests {
 		assert_eq!(res.unwrap(), expected.to_owned());
 	}
 
+	#[test]
+	fn should_serialize_sign_typed_data_confirmation() {
+		// given
+		let mut types = BTreeMap::new();
+		types.insert("EIP712Domain".to_owned(), vec![helpers::TypedDataMember { name: "name".into(), kind: "string".into() }]);
+		let request = helpers::ConfirmationRequest {
+			id: 15.into(),
+			payload: helpers::ConfirmationPayload::SignTypedData(1.into(), 5.into(), helpers::TypedData {
+				types: types,
+				primary_type: "EIP712Domain".into(),
+				domain: serde_json::from_str(r#"{"name":"Test"}"#).unwrap(),
+				message: serde_json::from_str(r#"{"name":"Test"}"#).unwrap(),
+			}),
+		};
+
+		// when
+		let res = serde_json::to_string(&ConfirmationRequest::from(request));
+		let expected = r#"{"id":"0xf","payload":{"signTypedData":{"address":"0x0000000000000000000000000000000000000001","data":{"types":{"EIP712Domain":[{"name":"name","type":"string"}]},"primaryType":"EIP712Domain","domain":{"name":"Test"},"message":{"name":"Test"}},"hash":"0x0000000000000000000000000000000000000000000000000000000000000005"}}}"#;
+
+		// then
+		assert_eq!(res.unwrap(), expected.to_owned());
+	}
+
 	#[test]
 	fn should_serialize_transaction_confirmation() {
 		// given
@@ -0,0 +1,56 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::miner;
+use v1::types::U256;
+
+/// Gas price suggestions of the gas price oracle
+#[derive(Debug, Serialize, PartialEq)]
+pub struct GasPriceSuggestions {
+	/// Price enough to be included in a quarter of recent blocks
+	pub safe: U256,
+	/// Price enough to be included in half of recent blocks
+	pub standard: U256,
+	/// Price expected to be included in the next block
+	pub fast: U256,
+}
+
+impl From<miner::GasPriceSuggestions> for GasPriceSuggestions {
+	fn from(s: miner::GasPriceSuggestions) -> Self {
+		GasPriceSuggestions {
+			safe: s.safe.into(),
+			standard: s.standard.into(),
+			fast: s.fast.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::GasPriceSuggestions;
+
+	#[test]
+	fn should_serialize_gas_price_suggestions() {
+		let suggestions = GasPriceSuggestions {
+			safe: 1u64.into(),
+			standard: 2u64.into(),
+			fast: 3u64.into(),
+		};
+		let serialized = serde_json::to_string(&suggestions).unwrap();
+		assert_eq!(serialized, r#"{"safe":"0x1","standard":"0x2","fast":"0x3"}"#);
+	}
+}
@@ -0,0 +1,49 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use v1::types::U256;
+
+/// Range of blocks whose state can be queried without re-executing blocks
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StateHistory {
+	/// Earliest block with state available
+	#[serde(rename="earliestState")]
+	pub earliest_state: U256,
+	/// Latest block with state available
+	#[serde(rename="latestState")]
+	pub latest_state: U256,
+	/// Maximum number of recent states kept
+	#[serde(rename="historySize")]
+	pub history_size: U256,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::StateHistory;
+
+	#[test]
+	fn should_serialize_state_history() {
+		let history = StateHistory {
+			earliest_state: 10.into(),
+			latest_state: 1209.into(),
+			history_size: 1200.into(),
+		};
+
+		let serialized = serde_json::to_string(&history).unwrap();
+		assert_eq!(serialized, r#"{"earliestState":"0xa","latestState":"0x4b9","historySize":"0x4b0"}"#);
+	}
+}
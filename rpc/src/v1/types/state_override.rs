@@ -0,0 +1,100 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use ethcore::client::{StateOverride as EthStateOverride, AccountOverride as EthAccountOverride};
+use v1::types::{Bytes, H160, H256, U256};
+
+/// Account fields replaced for the duration of a call
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct AccountOverride {
+	/// Balance
+	pub balance: Option<U256>,
+	/// Nonce
+	pub nonce: Option<U256>,
+	/// Code
+	pub code: Option<Bytes>,
+	/// Storage slots to replace
+	#[serde(rename="stateDiff")]
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+impl Into<EthAccountOverride> for AccountOverride {
+	fn into(self) -> EthAccountOverride {
+		EthAccountOverride {
+			balance: self.balance.map(Into::into),
+			nonce: self.nonce.map(Into::into),
+			code: self.code.map(Into::into),
+			storage: self.state_diff.unwrap_or_else(BTreeMap::new).into_iter()
+				.map(|(key, value)| (key.into(), value.into()))
+				.collect(),
+		}
+	}
+}
+
+/// State overrides applied to a copy of the state before a call (address - account override)
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct StateOverride(BTreeMap<H160, AccountOverride>);
+
+impl Into<EthStateOverride> for StateOverride {
+	fn into(self) -> EthStateOverride {
+		EthStateOverride {
+			accounts: self.0.into_iter().map(|(address, account)| (address.into(), account.into())).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use serde_json;
+	use ethcore::client::{StateOverride as EthStateOverride, AccountOverride as EthAccountOverride};
+	use util::{Address, H256 as EthH256, U256 as EthU256};
+	use super::StateOverride;
+
+	#[test]
+	fn state_override_deserialize() {
+		let s = r#"{
+			"0x0000000000000000000000000000000000000001": {
+				"balance": "0x10",
+				"code": "0x6000",
+				"stateDiff": {
+					"0x0000000000000000000000000000000000000000000000000000000000000002": "0x0000000000000000000000000000000000000000000000000000000000000003"
+				}
+			},
+			"0x0000000000000000000000000000000000000002": {
+				"nonce": "0x5"
+			}
+		}"#;
+		let deserialized: StateOverride = serde_json::from_str(s).unwrap();
+		let state_override: EthStateOverride = deserialized.into();
+
+		let mut storage = BTreeMap::new();
+		storage.insert(EthH256::from(2), EthH256::from(3));
+		let mut accounts = BTreeMap::new();
+		accounts.insert(Address::from(1), EthAccountOverride {
+			balance: Some(EthU256::from(0x10)),
+			nonce: None,
+			code: Some(vec![0x60, 0x00]),
+			storage: storage,
+		});
+		accounts.insert(Address::from(2), EthAccountOverride {
+			nonce: Some(EthU256::from(5)),
+			..Default::default()
+		});
+		assert_eq!(state_override, EthStateOverride { accounts: accounts });
+	}
+}
@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use serde::{Serialize, Serializer};
+use ethsync;
 use v1::types::U256;
 
 /// Sync info
@@ -42,6 +43,64 @@ pub struct Peers {
 	pub max: u32,
 }
 
+/// Traffic of a single protocol packet
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct PacketTraffic {
+	/// Protocol name
+	pub protocol: String,
+	/// Protocol level packet id
+	#[serde(rename="packetId")]
+	pub packet_id: u16,
+	/// Number of packets sent
+	#[serde(rename="packetsSent")]
+	pub packets_sent: u64,
+	/// Payload bytes sent
+	#[serde(rename="bytesSent")]
+	pub bytes_sent: u64,
+	/// Number of packets received
+	#[serde(rename="packetsReceived")]
+	pub packets_received: u64,
+	/// Payload bytes received
+	#[serde(rename="bytesReceived")]
+	pub bytes_received: u64,
+}
+
+impl From<ethsync::PacketTraffic> for PacketTraffic {
+	fn from(t: ethsync::PacketTraffic) -> Self {
+		PacketTraffic {
+			protocol: t.protocol,
+			packet_id: t.packet_id,
+			packets_sent: t.packets_sent,
+			bytes_sent: t.bytes_sent,
+			packets_received: t.packets_received,
+			bytes_received: t.bytes_received,
+		}
+	}
+}
+
+/// Network traffic info
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct NetTraffic {
+	/// Total bytes sent
+	#[serde(rename="bytesSent")]
+	pub bytes_sent: u64,
+	/// Total bytes received
+	#[serde(rename="bytesReceived")]
+	pub bytes_received: u64,
+	/// Per packet traffic
+	pub packets: Vec<PacketTraffic>,
+}
+
+impl From<ethsync::NetworkTraffic> for NetTraffic {
+	fn from(t: ethsync::NetworkTraffic) -> Self {
+		NetTraffic {
+			bytes_sent: t.bytes_sent,
+			bytes_received: t.bytes_received,
+			packets: t.packets.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
 /// Sync status
 #[derive(Debug, PartialEq)]
 pub enum SyncStatus {
@@ -64,7 +123,7 @@ impl Serialize for SyncStatus {
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use super::{SyncInfo, SyncStatus, Peers};
+	use super::{SyncInfo, SyncStatus, Peers, NetTraffic};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -80,6 +139,13 @@ mod tests {
 		assert_eq!(serialized, r#"{"active":0,"connected":0,"max":0}"#);
 	}
 
+	#[test]
+	fn test_serialize_net_traffic() {
+		let t = NetTraffic::default();
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"bytesSent":0,"bytesReceived":0,"packets":[]}"#);
+	}
+
 	#[test]
 	fn test_serialize_sync_status() {
 		let t = SyncStatus::None;
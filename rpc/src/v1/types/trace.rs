@@ -566,6 +566,36 @@ impl From<Executed> for TraceResults {
 	}
 }
 
+#[derive(Debug, Serialize)]
+/// Trace results of a transaction replayed as part of its block.
+pub struct TraceResultsWithTransactionHash {
+	/// The output of the call/create
+	pub output: Bytes,
+	/// The transaction trace.
+	pub trace: Vec<Trace>,
+	/// The transaction trace.
+	#[serde(rename="vmTrace")]
+	pub vm_trace: Option<VMTrace>,
+	/// The transaction trace.
+	#[serde(rename="stateDiff")]
+	pub state_diff: Option<StateDiff>,
+	/// The transaction hash.
+	#[serde(rename="transactionHash")]
+	pub transaction_hash: H256,
+}
+
+impl From<(H256, Executed)> for TraceResultsWithTransactionHash {
+	fn from(t: (H256, Executed)) -> Self {
+		TraceResultsWithTransactionHash {
+			output: t.1.output.into(),
+			trace: t.1.trace.into_iter().map(Into::into).collect(),
+			vm_trace: t.1.vm_trace.map(Into::into),
+			state_diff: t.1.state_diff.map(Into::into),
+			transaction_hash: t.0,
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
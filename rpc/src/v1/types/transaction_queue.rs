@@ -0,0 +1,128 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use serde::{Serialize, Serializer};
+use ethcore::miner;
+use v1::types::{Transaction, H160, U256};
+
+/// Origin of a queued transaction
+#[derive(Debug, PartialEq)]
+pub enum TransactionOrigin {
+	/// Transaction submitted through local RPC
+	Local,
+	/// Transaction received from the network
+	External,
+}
+
+impl Serialize for TransactionOrigin {
+	fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+	where S: Serializer {
+		match *self {
+			TransactionOrigin::Local => serializer.serialize_str("local"),
+			TransactionOrigin::External => serializer.serialize_str("external"),
+		}
+	}
+}
+
+impl From<miner::TransactionOrigin> for TransactionOrigin {
+	fn from(origin: miner::TransactionOrigin) -> Self {
+		match origin {
+			miner::TransactionOrigin::Local => TransactionOrigin::Local,
+			miner::TransactionOrigin::External => TransactionOrigin::External,
+		}
+	}
+}
+
+/// Transaction waiting in the queue
+#[derive(Debug, Serialize)]
+pub struct QueuedTransaction {
+	/// The transaction
+	pub transaction: Transaction,
+	/// Origin of the transaction
+	pub origin: TransactionOrigin,
+	/// UNIX timestamp of insertion to the queue
+	#[serde(rename="insertionTime")]
+	pub insertion_time: u64,
+}
+
+impl From<miner::QueuedTransaction> for QueuedTransaction {
+	fn from(t: miner::QueuedTransaction) -> Self {
+		QueuedTransaction {
+			transaction: t.transaction.into(),
+			origin: t.origin.into(),
+			insertion_time: t.insertion_time,
+		}
+	}
+}
+
+/// Gap in nonces keeping future transactions of a sender from being mined
+#[derive(Debug, Serialize, PartialEq)]
+pub struct NonceGap {
+	/// Nonce of the transaction the future ones are waiting for
+	pub expected: U256,
+	/// Lowest nonce of the future transactions
+	#[serde(rename="firstFuture")]
+	pub first_future: U256,
+}
+
+impl From<miner::NonceGap> for NonceGap {
+	fn from(gap: miner::NonceGap) -> Self {
+		NonceGap {
+			expected: gap.expected.into(),
+			first_future: gap.first_future.into(),
+		}
+	}
+}
+
+/// Contents of the transaction queue
+#[derive(Debug, Serialize)]
+pub struct TransactionQueue {
+	/// Transactions ready to go to block, ordered by priority
+	pub current: Vec<QueuedTransaction>,
+	/// Transactions waiting for transactions with lower nonces, ordered by priority
+	pub future: Vec<QueuedTransaction>,
+	/// Nonce gaps of senders with future transactions
+	#[serde(rename="nonceGaps")]
+	pub nonce_gaps: BTreeMap<H160, NonceGap>,
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use serde_json;
+	use v1::types::{Transaction, H160};
+	use super::{TransactionQueue, QueuedTransaction, TransactionOrigin, NonceGap};
+
+	#[test]
+	fn test_serialize_transaction_queue() {
+		let mut nonce_gaps = BTreeMap::new();
+		nonce_gaps.insert(H160::from(1u64), NonceGap { expected: 1u64.into(), first_future: 3u64.into() });
+		let queue = TransactionQueue {
+			current: vec![],
+			future: vec![QueuedTransaction {
+				transaction: Transaction::default(),
+				origin: TransactionOrigin::External,
+				insertion_time: 1473159600,
+			}],
+			nonce_gaps: nonce_gaps,
+		};
+
+		let serialized = serde_json::to_string(&queue).unwrap();
+		let transaction = serde_json::to_string(&Transaction::default()).unwrap();
+		assert_eq!(serialized, format!(r#"{{"current":[],"future":[{{"transaction":{},"origin":"external","insertionTime":1473159600}}],"nonceGaps":{{"0x0000000000000000000000000000000000000001":{{"expected":"0x1","firstFuture":"0x3"}}}}}}"#, transaction));
+	}
+}
@@ -0,0 +1,100 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Typed structured data (EIP-712).
+
+use std::collections::BTreeMap;
+use serde_json::Value;
+use v1::helpers;
+
+/// Member of a struct type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypedDataMember {
+	/// Member name
+	pub name: String,
+	/// Member type
+	#[serde(rename="type")]
+	pub kind: String,
+}
+
+/// Typed structured data to be signed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypedData {
+	/// Struct type definitions, including `EIP712Domain`
+	pub types: BTreeMap<String, Vec<TypedDataMember>>,
+	/// Type of the message
+	#[serde(rename="primaryType")]
+	pub primary_type: String,
+	/// Domain separator values
+	pub domain: Value,
+	/// The message
+	pub message: Value,
+}
+
+impl From<TypedData> for helpers::TypedData {
+	fn from(d: TypedData) -> Self {
+		helpers::TypedData {
+			types: d.types.into_iter().map(|(name, members)| (name, members.into_iter().map(|m| helpers::TypedDataMember {
+				name: m.name,
+				kind: m.kind,
+			}).collect())).collect(),
+			primary_type: d.primary_type,
+			domain: d.domain,
+			message: d.message,
+		}
+	}
+}
+
+impl From<helpers::TypedData> for TypedData {
+	fn from(d: helpers::TypedData) -> Self {
+		TypedData {
+			types: d.types.into_iter().map(|(name, members)| (name, members.into_iter().map(|m| TypedDataMember {
+				name: m.name,
+				kind: m.kind,
+			}).collect())).collect(),
+			primary_type: d.primary_type,
+			domain: d.domain,
+			message: d.message,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn should_deserialize_typed_data() {
+		let s = r#"{
+			"types": {
+				"EIP712Domain": [{"name": "name", "type": "string"}],
+				"Person": [{"name": "name", "type": "string"}, {"name": "wallet", "type": "address"}]
+			},
+			"primaryType": "Person",
+			"domain": {"name": "Ether Mail"},
+			"message": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"}
+		}"#;
+		let deserialized: TypedData = serde_json::from_str(s).unwrap();
+
+		assert_eq!(deserialized.primary_type, "Person".to_owned());
+		assert_eq!(deserialized.types["Person"], vec![
+			TypedDataMember { name: "name".into(), kind: "string".into() },
+			TypedDataMember { name: "wallet".into(), kind: "address".into() },
+		]);
+		assert_eq!(deserialized.message, serde_json::from_str(r#"{"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"}"#).unwrap());
+	}
+}
@@ -50,7 +50,18 @@ use std::sync::Arc;
 
 use std::net::SocketAddr;
 use std::collections::{HashSet, HashMap};
-use util::{H256, Hashable, RwLock, RwLockReadGuard};
+use util::{H256, U256, Hashable, RwLock, RwLockReadGuard};
+
+/// Solution statistics of an authorized worker
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WorkerStats {
+	/// Difficulty of the work solved by the last accepted solution
+	pub difficulty: U256,
+	/// Number of accepted solutions
+	pub accepted: u64,
+	/// Number of rejected solutions
+	pub rejected: u64,
+}
 
 pub struct Stratum {
 	rpc_server: JsonRpcServer,
@@ -63,6 +74,8 @@ pub struct Stratum {
 	dispatcher: Arc<JobDispatcher>,
 	/// Authorized workers (socket - worker_id)
 	workers: Arc<RwLock<HashMap<SocketAddr, String>>>,
+	/// Solution statistics (worker_id - stats)
+	stats: RwLock<HashMap<String, WorkerStats>>,
 	/// Secret if any
 	secret: Option<H256>,
 }
@@ -82,12 +95,14 @@ impl Stratum {
 			job_que: RwLock::new(HashSet::new()),
 			dispatcher: dispatcher,
 			workers: Arc::new(RwLock::new(HashMap::new())),
+			stats: RwLock::new(HashMap::new()),
 			secret: secret,
 		});
 
 		let mut delegate = IoDelegate::<Stratum>::new(stratum.clone());
 		delegate.add_method("miner.subscribe", Stratum::subscribe);
 		delegate.add_method("miner.authorize", Stratum::authorize);
+		delegate.add_method("mining.submit", Stratum::submit);
 		stratum.handler.add_delegate(delegate);
 
 		try!(stratum.rpc_server.run_async());
@@ -134,10 +149,49 @@ impl Stratum {
 		})
 	}
 
+	fn submit(&self, params: Params) -> std::result::Result<jsonrpc_core::Value, jsonrpc_core::Error> {
+		from_params::<Vec<String>>(params).map(|payload| {
+			let worker_id = self.rpc_server.request_context()
+				.and_then(|context| self.workers.read().get(&context.socket_addr).cloned());
+			match worker_id {
+				Some(worker_id) => to_value(&self.submit_solution(&worker_id, payload)),
+				None => {
+					warn!(target: "stratum", "Solution submitted by unauthorized worker, ignoring");
+					to_value(&false)
+				}
+			}
+		})
+	}
+
+	/// Dispatch solution submitted by authorized worker and account for it in worker stats
+	fn submit_solution(&self, worker_id: &str, payload: Vec<String>) -> bool {
+		let result = self.dispatcher.submit(payload);
+		let mut stats = self.stats.write();
+		let worker_stats = stats.entry(worker_id.to_owned()).or_insert_with(WorkerStats::default);
+		match result {
+			Ok(difficulty) => {
+				trace!(target: "stratum", "Solution from '{}' accepted", worker_id);
+				worker_stats.accepted += 1;
+				worker_stats.difficulty = difficulty;
+				true
+			},
+			Err(e) => {
+				warn!(target: "stratum", "Solution from '{}' rejected: {:?}", worker_id, e);
+				worker_stats.rejected += 1;
+				false
+			},
+		}
+	}
+
 	pub fn subscribers(&self) -> RwLockReadGuard<Vec<SocketAddr>> {
 		self.subscribers.read()
 	}
 
+	/// Solution statistics of all workers that have submitted anything so far
+	pub fn worker_stats(&self) -> HashMap<String, WorkerStats> {
+		self.stats.read().clone()
+	}
+
 	pub fn maintain(&self) {
 		let mut job_que = self.job_que.write();
 		let workers = self.workers.read();
@@ -161,7 +215,7 @@ impl Stratum {
 impl PushWorkHandler for Stratum {
 	fn push_work_all(&self, payload: String) -> Result<(), Error> {
 		let workers = self.workers.read();
-		println!("pushing work for {} workers", workers.len());
+		trace!(target: "stratum", "pushing work for {} workers", workers.len());
 		for (ref addr, _) in workers.iter() {
 			try!(self.rpc_server.push_message(addr, payload.as_bytes()));
 		}
@@ -201,6 +255,7 @@ mod tests {
 	use std::net::SocketAddr;
 	use std::sync::{Arc, RwLock};
 	use std::thread;
+	use util::U256;
 
 	pub struct VoidManager;
 
@@ -386,4 +441,39 @@ mod tests {
 
 		assert_eq!(2, result.read().unwrap().len());
 	}
+
+	struct SubmitManager;
+
+	impl JobDispatcher for SubmitManager {
+		fn submit(&self, payload: Vec<String>) -> Result<U256, Error> {
+			match payload.get(0).map(|s| s.as_str()) {
+				Some("0x0000000000000001") => Ok(U256::from(131072)),
+				_ => Err(Error::InvalidSolution("dummy rejection".to_owned())),
+			}
+		}
+	}
+
+	fn solution(nonce: &str) -> Vec<String> {
+		vec![nonce.to_owned(), "0x00".to_owned(), "0x00".to_owned()]
+	}
+
+	#[test]
+	fn records_submitted_solutions() {
+		init_log();
+
+		// any free port, no worker connects to it
+		let addr = SocketAddr::from_str("127.0.0.1:0").unwrap();
+		let stratum = Stratum::start(&addr, Arc::new(SubmitManager), None).unwrap();
+
+		assert!(stratum.submit_solution("miner1", solution("0x0000000000000001")));
+		assert!(!stratum.submit_solution("miner1", solution("0x0000000000000002")));
+		assert!(stratum.submit_solution("miner1", solution("0x0000000000000001")));
+
+		let stats = stratum.worker_stats();
+		let miner_stats = stats.get("miner1").expect("miner1 submitted solutions");
+		assert_eq!(2, miner_stats.accepted);
+		assert_eq!(1, miner_stats.rejected);
+		assert_eq!(U256::from(131072), miner_stats.difficulty);
+		assert!(stats.get("miner2").is_none());
+	}
 }
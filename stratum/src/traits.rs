@@ -16,7 +16,7 @@
 
 use std;
 use std::error::Error as StdError;
-use util::H256;
+use util::{H256, U256};
 use ipc::IpcConfig;
 
 #[derive(Debug, Clone, Binary)]
@@ -24,6 +24,10 @@ pub enum Error {
 	NoWork,
 	NoWorkers,
 	Io(String),
+	/// Submitted solution is malformed or does not solve the work it refers to
+	InvalidSolution(String),
+	/// Solution was valid but could not be dispatched to the chain
+	Dispatch(String),
 }
 
 impl From<std::io::Error> for Error {
@@ -42,6 +46,8 @@ pub trait JobDispatcher: Send + Sync {
 	fn difficulty(&self) -> Option<String> { None }
 	// json for job update given worker_id (payload manager should split job!)
 	fn job(&self, _worker_id: String) -> Option<String> { None }
+	// submit solution (`payload`: pow-specific set of solution params); returns difficulty of the solved work
+	fn submit(&self, _payload: Vec<String>) -> Result<U256, Error> { Err(Error::NoWork) }
 }
 
 #[derive(Ipc)]
@@ -15,15 +15,16 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::collections::HashMap;
 use network::{NetworkProtocolHandler, NetworkService, NetworkContext, PeerId,
 	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, NetworkError};
-use util::{U256, H256};
+use util::{U256, H256, Bytes};
 use io::{TimerToken};
 use ethcore::client::{BlockChainClient, ChainNotify};
 use ethcore::snapshot::SnapshotService;
 use ethcore::header::BlockNumber;
 use sync_io::NetSyncIo;
-use chain::{ChainSync, SyncStatus};
+use chain::{ChainSync, SyncStatus, VERSIONED_MANIFEST_PROTOCOL};
 use std::net::{SocketAddr, AddrParseError};
 use ipc::{BinaryConvertable, BinaryConvertError, IpcConfig};
 use std::str::FromStr;
@@ -31,6 +32,9 @@ use parking_lot::RwLock;
 
 /// Ethereum sync protocol
 pub const ETH_PROTOCOL: &'static str = "eth";
+/// Parity capability. Carries no packets of its own, it only advertises
+/// extensions to the `eth` snapshot sync such as versioned manifests.
+pub const PAR_PROTOCOL: &'static str = "par";
 
 /// Sync configuration
 #[derive(Debug, Clone, Copy)]
@@ -41,6 +45,14 @@ pub struct SyncConfig {
 	pub network_id: U256,
 	/// Fork block to check
 	pub fork_block: Option<(BlockNumber, H256)>,
+	/// Serve snapshot data to other peers
+	pub serve_snapshots: bool,
+	/// Max number of snapshot chunk requests served concurrently
+	pub max_snapshot_requests: usize,
+	/// Max number of snapshot chunk requests served concurrently to a single peer
+	pub max_peer_snapshot_requests: usize,
+	/// Download and import new blocks. Disabled when only state nodes should be fetched.
+	pub import_blocks: bool,
 }
 
 impl Default for SyncConfig {
@@ -49,6 +61,10 @@ impl Default for SyncConfig {
 			max_download_ahead_blocks: 20000,
 			network_id: U256::from(1),
 			fork_block: None,
+			serve_snapshots: true,
+			max_snapshot_requests: 8,
+			max_peer_snapshot_requests: 2,
+			import_blocks: true,
 		}
 	}
 }
@@ -82,6 +98,17 @@ impl EthSync {
 
 		Ok(sync)
 	}
+
+	/// Download state nodes with given hashes from peers.
+	/// Block download is held back until all of them are retrieved.
+	pub fn request_state_nodes(&self, hashes: Vec<H256>) {
+		self.handler.sync.write().request_state_nodes(hashes);
+	}
+
+	/// Take state nodes downloaded so far, keyed by hash.
+	pub fn take_state_nodes(&self) -> HashMap<H256, Bytes> {
+		self.handler.sync.write().take_state_nodes()
+	}
 }
 
 #[derive(Ipc)]
@@ -126,6 +153,19 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
 	}
 }
 
+/// Handler for the `par` capability. All the traffic goes over `eth`.
+struct ParProtocolHandler;
+
+impl NetworkProtocolHandler for ParProtocolHandler {
+	fn read(&self, _io: &NetworkContext, peer: &PeerId, packet_id: u8, _data: &[u8]) {
+		debug!(target: "sync", "{}: Unexpected packet {} on par protocol", peer, packet_id);
+	}
+
+	fn connected(&self, _io: &NetworkContext, _peer: &PeerId) {}
+
+	fn disconnected(&self, _io: &NetworkContext, _peer: &PeerId) {}
+}
+
 impl ChainNotify for EthSync {
 	fn new_blocks(&self,
 		imported: Vec<H256>,
@@ -151,6 +191,9 @@ impl ChainNotify for EthSync {
 		self.network.start().unwrap_or_else(|e| warn!("Error starting network: {:?}", e));
 		self.network.register_protocol(self.handler.clone(), ETH_PROTOCOL, &[62u8, 63u8, 64u8])
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
+		// must be registered after `eth`, packets are routed to the first shared capability
+		self.network.register_protocol(Arc::new(ParProtocolHandler), PAR_PROTOCOL, &[VERSIONED_MANIFEST_PROTOCOL])
+			.unwrap_or_else(|e| warn!("Error registering parity protocol: {:?}", e));
 	}
 
 	fn stop(&self) {
@@ -177,6 +220,8 @@ pub trait ManageNetwork : Send + Sync {
 	fn stop_network(&self);
 	/// Query the current configuration of the network
 	fn network_config(&self) -> NetworkConfiguration;
+	/// Query network traffic counters
+	fn network_traffic(&self) -> NetworkTraffic;
 }
 
 
@@ -214,6 +259,50 @@ impl ManageNetwork for EthSync {
 	fn network_config(&self) -> NetworkConfiguration {
 		NetworkConfiguration::from(self.network.config().clone())
 	}
+
+	fn network_traffic(&self) -> NetworkTraffic {
+		let stats = self.network.stats();
+		NetworkTraffic {
+			bytes_sent: stats.send() as u64,
+			bytes_received: stats.recv() as u64,
+			packets: stats.packet_stats().into_iter().map(|(protocol, packet_id, s)| PacketTraffic {
+				protocol: protocol.to_owned(),
+				packet_id: packet_id as u16,
+				packets_sent: s.packets_sent as u64,
+				bytes_sent: s.bytes_sent as u64,
+				packets_received: s.packets_received as u64,
+				bytes_received: s.bytes_received as u64,
+			}).collect(),
+		}
+	}
+}
+
+#[derive(Binary, Debug, Clone, PartialEq, Eq)]
+/// Traffic counters for a single protocol packet
+pub struct PacketTraffic {
+	/// Protocol name
+	pub protocol: String,
+	/// Protocol level packet id
+	pub packet_id: u16,
+	/// Number of packets sent
+	pub packets_sent: u64,
+	/// Payload bytes sent
+	pub bytes_sent: u64,
+	/// Number of packets received
+	pub packets_received: u64,
+	/// Payload bytes received
+	pub bytes_received: u64,
+}
+
+#[derive(Binary, Debug, Clone, PartialEq, Eq)]
+/// Network traffic summary
+pub struct NetworkTraffic {
+	/// Total bytes sent, including protocol overhead
+	pub bytes_sent: u64,
+	/// Total bytes received, including protocol overhead
+	pub bytes_received: u64,
+	/// Per packet traffic counters
+	pub packets: Vec<PacketTraffic>,
 }
 
 #[derive(Binary, Debug, Clone, PartialEq, Eq)]
@@ -235,6 +324,8 @@ pub struct NetworkConfiguration {
 	pub discovery_enabled: bool,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
+	/// List of `enrtree://` URLs of signed node lists published over DNS
+	pub dns_trees: Vec<String>,
 	/// Use provided node key instead of default
 	pub use_secret: Option<H256>,
 	/// Max number of connected peers to maintain
@@ -245,6 +336,14 @@ pub struct NetworkConfiguration {
 	pub reserved_nodes: Vec<String>,
 	/// The non-reserved peer mode.
 	pub allow_non_reserved: bool,
+	/// Total upload limit in bytes per second. Unlimited if none.
+	pub max_upload: Option<u64>,
+	/// Total download limit in bytes per second. Unlimited if none.
+	pub max_download: Option<u64>,
+	/// Per peer upload limit in bytes per second. Unlimited if none.
+	pub max_peer_upload: Option<u64>,
+	/// Per peer download limit in bytes per second. Unlimited if none.
+	pub max_peer_download: Option<u64>,
 }
 
 impl NetworkConfiguration {
@@ -277,11 +376,16 @@ impl NetworkConfiguration {
 			nat_enabled: self.nat_enabled,
 			discovery_enabled: self.discovery_enabled,
 			boot_nodes: self.boot_nodes,
+			dns_trees: self.dns_trees,
 			use_secret: self.use_secret,
 			max_peers: self.max_peers,
 			min_peers: self.min_peers,
 			reserved_nodes: self.reserved_nodes,
 			non_reserved_mode: if self.allow_non_reserved { NonReservedPeerMode::Accept } else { NonReservedPeerMode::Deny },
+			max_upload: self.max_upload,
+			max_download: self.max_download,
+			max_peer_upload: self.max_peer_upload,
+			max_peer_download: self.max_peer_download,
 		})
 	}
 }
@@ -297,11 +401,16 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			nat_enabled: other.nat_enabled,
 			discovery_enabled: other.discovery_enabled,
 			boot_nodes: other.boot_nodes,
+			dns_trees: other.dns_trees,
 			use_secret: other.use_secret,
 			max_peers: other.max_peers,
 			min_peers: other.min_peers,
 			reserved_nodes: other.reserved_nodes,
 			allow_non_reserved: match other.non_reserved_mode { NonReservedPeerMode::Accept => true, _ => false } ,
+			max_upload: other.max_upload,
+			max_download: other.max_download,
+			max_peer_upload: other.max_peer_upload,
+			max_peer_download: other.max_peer_download,
 		}
 	}
 }
@@ -25,6 +25,8 @@ known_heap_size!(0, HeaderId, SyncBlock);
 struct SyncBlock {
 	header: Bytes,
 	body: Option<Bytes>,
+	/// Peer which delivered the last part of the block.
+	peer: String,
 }
 
 /// Used to identify header by transactions and uncles hashes
@@ -86,21 +88,21 @@ impl BlockCollection {
 		self.heads = hashes;
 	}
 
-	/// Insert a set of headers into collection and advance subchain head pointers.
-	pub fn insert_headers(&mut self, headers: Vec<Bytes>) {
+	/// Insert a set of headers delivered by `peer` into collection and advance subchain head pointers.
+	pub fn insert_headers(&mut self, headers: Vec<Bytes>, peer: &str) {
 		for h in headers.into_iter() {
-			if let Err(e) =  self.insert_header(h) {
+			if let Err(e) =  self.insert_header(h, peer) {
 				trace!(target: "sync", "Ignored invalid header: {:?}", e);
 			}
 		}
 		self.update_heads();
 	}
 
-	/// Insert a collection of block bodies for previously downloaded headers.
-	pub fn insert_bodies(&mut self, bodies: Vec<Bytes>) -> usize {
+	/// Insert a collection of block bodies delivered by `peer` for previously downloaded headers.
+	pub fn insert_bodies(&mut self, bodies: Vec<Bytes>, peer: &str) -> usize {
 		let mut inserted = 0;
 		for b in bodies.into_iter() {
-			if let Err(e) =  self.insert_body(b) {
+			if let Err(e) =  self.insert_body(b, peer) {
 				trace!(target: "sync", "Ignored invalid body: {:?}", e);
 			}
 			else {
@@ -167,8 +169,9 @@ impl BlockCollection {
 		self.downloading_bodies.remove(hash);
 	}
 
-	/// Get a valid chain of blocks ordered in descending order and ready for importing into blockchain.
-	pub fn drain(&mut self) -> Vec<Bytes> {
+	/// Get a valid chain of blocks ordered in descending order and ready for importing into blockchain,
+	/// together with the peer each block was received from.
+	pub fn drain(&mut self) -> Vec<(Bytes, String)> {
 		if self.blocks.is_empty() || self.head.is_none() {
 			return Vec::new();
 		}
@@ -198,7 +201,7 @@ impl BlockCollection {
 				let body = Rlp::new(block.body.as_ref().unwrap()); // incomplete blocks are filtered out in the loop above
 				block_rlp.append_raw(body.at(0).as_raw(), 1);
 				block_rlp.append_raw(body.at(1).as_raw(), 1);
-				drained.push(block_rlp.out());
+				drained.push((block_rlp.out(), block.peer.clone()));
 			}
 		}
 		for h in hashes {
@@ -230,7 +233,7 @@ impl BlockCollection {
 		self.downloading_headers.contains(hash) || self.downloading_bodies.contains(hash)
 	}
 
-	fn insert_body(&mut self, b: Bytes) -> Result<(), NetworkError> {
+	fn insert_body(&mut self, b: Bytes, peer: &str) -> Result<(), NetworkError> {
 		let body = UntrustedRlp::new(&b);
 		let tx = try!(body.at(0));
 		let tx_root = ordered_trie_root(tx.iter().map(|r| r.as_raw().to_vec()).collect()); //TODO: get rid of vectors here
@@ -247,6 +250,7 @@ impl BlockCollection {
 					Some(ref mut block) => {
 						trace!(target: "sync", "Got body {}", h);
 						block.body = Some(body.as_raw().to_vec());
+						block.peer = peer.to_owned();
 						Ok(())
 					},
 					None => {
@@ -262,7 +266,7 @@ impl BlockCollection {
 		}
 	}
 
-	fn insert_header(&mut self, header: Bytes) -> Result<H256, UtilError> {
+	fn insert_header(&mut self, header: Bytes, peer: &str) -> Result<H256, UtilError> {
 		let info: BlockHeader = try!(UntrustedRlp::new(&header).as_val());
 		let hash = info.hash();
 		if self.blocks.contains_key(&hash) {
@@ -279,6 +283,7 @@ impl BlockCollection {
 		let mut block = SyncBlock {
 			header: header,
 			body: None,
+			peer: peer.to_owned(),
 		};
 		let header_id = HeaderId {
 			transactions_root: info.transactions_root().clone(),
@@ -340,6 +345,10 @@ mod test {
 	use util::*;
 	use rlp::*;
 
+	fn drain_blocks(bc: &mut BlockCollection) -> Vec<Bytes> {
+		bc.drain().into_iter().map(|(block, _)| block).collect()
+	}
+
 	fn is_empty(bc: &BlockCollection) -> bool {
 		bc.heads.is_empty() &&
 		bc.blocks.is_empty() &&
@@ -388,7 +397,7 @@ mod test {
 		assert_eq!(bc.downloading_headers.len(), 1);
 		assert!(bc.drain().is_empty());
 
-		bc.insert_headers(headers[0..6].to_vec());
+		bc.insert_headers(headers[0..6].to_vec(), "peer");
 		assert_eq!(hashes[5], bc.heads[0]);
 		for h in &hashes[0..6] {
 			bc.clear_header_download(h)
@@ -397,7 +406,9 @@ mod test {
 		assert!(!bc.is_downloading(&hashes[0]));
 		assert!(bc.contains(&hashes[0]));
 
-		assert_eq!(&bc.drain()[..], &blocks[0..6]);
+		let drained = bc.drain();
+		assert!(drained.iter().all(|&(_, ref peer)| peer == "peer"));
+		assert_eq!(&drained.into_iter().map(|(block, _)| block).collect::<Vec<_>>()[..], &blocks[0..6]);
 		assert!(!bc.contains(&hashes[0]));
 		assert_eq!(hashes[5], bc.head.unwrap());
 
@@ -405,13 +416,13 @@ mod test {
 		assert_eq!(hashes[5], h);
 		let (h, _) = bc.needed_headers(6, false).unwrap();
 		assert_eq!(hashes[20], h);
-		bc.insert_headers(headers[10..16].to_vec());
+		bc.insert_headers(headers[10..16].to_vec(), "peer");
 		assert!(bc.drain().is_empty());
-		bc.insert_headers(headers[5..10].to_vec());
-		assert_eq!(&bc.drain()[..], &blocks[6..16]);
+		bc.insert_headers(headers[5..10].to_vec(), "peer");
+		assert_eq!(&drain_blocks(&mut bc)[..], &blocks[6..16]);
 		assert_eq!(hashes[15], bc.heads[0]);
 
-		bc.insert_headers(headers[15..].to_vec());
+		bc.insert_headers(headers[15..].to_vec(), "peer");
 		bc.drain();
 		assert!(bc.is_empty());
 	}
@@ -429,11 +440,11 @@ mod test {
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(h.clone()) } else { None }).collect();
 		bc.reset_to(heads);
 
-		bc.insert_headers(headers[2..22].to_vec());
+		bc.insert_headers(headers[2..22].to_vec(), "peer");
 		assert_eq!(hashes[0], bc.heads[0]);
 		assert_eq!(hashes[21], bc.heads[1]);
 		assert!(bc.head.is_none());
-		bc.insert_headers(headers[0..2].to_vec());
+		bc.insert_headers(headers[0..2].to_vec(), "peer");
 		assert!(bc.head.is_some());
 		assert_eq!(hashes[21], bc.heads[0]);
 	}
@@ -451,9 +462,9 @@ mod test {
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(h.clone()) } else { None }).collect();
 		bc.reset_to(heads);
 
-		bc.insert_headers(headers[1..2].to_vec());
+		bc.insert_headers(headers[1..2].to_vec(), "peer");
 		assert!(bc.drain().is_empty());
-		bc.insert_headers(headers[0..1].to_vec());
+		bc.insert_headers(headers[0..1].to_vec(), "peer");
 		assert_eq!(bc.drain().len(), 2);
 	}
 }
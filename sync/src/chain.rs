@@ -96,12 +96,15 @@ use ethcore::header::{BlockNumber, Header as BlockHeader};
 use ethcore::client::{BlockChainClient, BlockStatus, BlockID, BlockChainInfo, BlockImportError};
 use ethcore::error::*;
 use ethcore::block::Block;
-use ethcore::snapshot::{ManifestData, RestorationStatus};
+use ethcore::snapshot::{ManifestData, RestorationStatus, decode_manifest};
+use ethcore::snapshot::Error as SnapshotError;
 use sync_io::SyncIo;
 use time;
 use super::SyncConfig;
 use blocks::BlockCollection;
 use snapshot::{Snapshot, ChunkType};
+use snapshot_server::SnapshotServer;
+use std::sync::Arc;
 use rand::{thread_rng, Rng};
 
 known_heap_size!(0, PeerInfo);
@@ -115,6 +118,7 @@ const MAX_RECEIPTS_TO_SEND: usize = 1024;
 const MAX_RECEIPTS_HEADERS_TO_SEND: usize = 256;
 const MAX_HEADERS_TO_REQUEST: usize = 128;
 const MAX_BODIES_TO_REQUEST: usize = 128;
+const MAX_NODE_DATA_TO_REQUEST: usize = 384;
 const MIN_PEERS_PROPAGATION: usize = 4;
 const MAX_PEERS_PROPAGATION: usize = 128;
 const MAX_PEER_LAG_PROPAGATION: BlockNumber = 20;
@@ -142,11 +146,17 @@ const SNAPSHOT_MANIFEST_PACKET: u8 = 0x12;
 const GET_SNAPSHOT_DATA_PACKET: u8 = 0x13;
 const SNAPSHOT_DATA_PACKET: u8 = 0x14;
 
+/// Protocol version which introduced snapshot sync.
+const SNAPSHOT_SYNC_PROTOCOL: u8 = 64;
+/// `par` protocol version which introduced versioned snapshot manifests.
+pub const VERSIONED_MANIFEST_PROTOCOL: u8 = 1;
+
 const HEADERS_TIMEOUT_SEC: f64 = 15f64;
 const BODIES_TIMEOUT_SEC: f64 = 5f64;
 const FORK_HEADER_TIMEOUT_SEC: f64 = 3f64;
 const SNAPSHOT_MANIFEST_TIMEOUT_SEC: f64 = 3f64;
 const SNAPSHOT_DATA_TIMEOUT_SEC: f64 = 10f64;
+const NODE_DATA_TIMEOUT_SEC: f64 = 10f64;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Sync state
@@ -198,6 +208,10 @@ pub struct SyncStatus {
 	pub num_snapshot_chunks: usize,
 	/// Snapshot chunks downloaded
 	pub snapshot_chunks_done: usize,
+	/// Snapshot chunks served to other peers
+	pub snapshot_chunks_served: usize,
+	/// Snapshot chunk requests refused due to serving limits
+	pub snapshot_requests_refused: usize,
 }
 
 impl SyncStatus {
@@ -226,6 +240,7 @@ enum PeerAsking {
 	Heads,
 	SnapshotManifest,
 	SnapshotData,
+	NodeData,
 }
 
 #[derive(Clone, Eq, PartialEq)]
@@ -261,6 +276,8 @@ struct PeerInfo {
 	asking_hash: Option<H256>,
 	/// Holds requested snapshot chunk hash if any.
 	asking_snapshot_data: Option<H256>,
+	/// Holds requested state node hashes if any.
+	asking_nodes: Vec<H256>,
 	/// Request timestamp
 	ask_time: f64,
 	/// Holds a set of transactions recently sent to this peer to avoid spamming.
@@ -320,6 +337,14 @@ pub struct ChainSync {
 	fork_block: Option<(BlockNumber, H256)>,
 	/// Snapshot downloader.
 	snapshot: Snapshot,
+	/// Snapshot serving policy.
+	snapshot_server: Arc<SnapshotServer>,
+	/// State nodes to download, mapped to whether they are being downloaded.
+	wanted_nodes: HashMap<H256, bool>,
+	/// Downloaded state nodes not yet taken.
+	retrieved_nodes: HashMap<H256, Bytes>,
+	/// Download and import new blocks.
+	import_blocks: bool,
 }
 
 type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
@@ -345,11 +370,31 @@ impl ChainSync {
 			network_id: config.network_id,
 			fork_block: config.fork_block,
 			snapshot: Snapshot::new(),
+			snapshot_server: Arc::new(SnapshotServer::new(config.serve_snapshots, config.max_snapshot_requests, config.max_peer_snapshot_requests)),
+			wanted_nodes: HashMap::new(),
+			retrieved_nodes: HashMap::new(),
+			import_blocks: config.import_blocks,
 		}
 	}
 
+	/// Queue state nodes to be downloaded from peers by hash.
+	/// Block download is held back until all of them are retrieved.
+	pub fn request_state_nodes(&mut self, hashes: Vec<H256>) {
+		for hash in hashes {
+			if !self.retrieved_nodes.contains_key(&hash) {
+				self.wanted_nodes.entry(hash).or_insert(false);
+			}
+		}
+	}
+
+	/// Take state nodes downloaded so far, keyed by hash.
+	pub fn take_state_nodes(&mut self) -> HashMap<H256, Bytes> {
+		replace(&mut self.retrieved_nodes, HashMap::new())
+	}
+
 	/// @returns Synchonization status
 	pub fn status(&self) -> SyncStatus {
+		let serving = self.snapshot_server.stats();
 		SyncStatus {
 			state: self.state.clone(),
 			protocol_version: if self.state == SyncState::SnapshotData { 64 } else { 63 },
@@ -363,6 +408,8 @@ impl ChainSync {
 			num_active_peers: self.peers.values().filter(|p| p.is_allowed() && p.asking != PeerAsking::Nothing).count(),
 			num_snapshot_chunks: self.snapshot.total_chunks(),
 			snapshot_chunks_done: self.snapshot.done_chunks(),
+			snapshot_chunks_served: serving.chunks_served,
+			snapshot_requests_refused: serving.requests_refused,
 			mem_used:
 				self.blocks.heap_size()
 				+ self.peers.heap_size_of_children()
@@ -453,8 +500,9 @@ impl ChainSync {
 			expired: false,
 			confirmation: if self.fork_block.is_none() { ForkConfirmation::Confirmed } else { ForkConfirmation::Unconfirmed },
 			asking_snapshot_data: None,
-			snapshot_hash: if protocol_version == 64 { Some(try!(r.val_at(5))) } else { None },
-			snapshot_number: if protocol_version == 64 { Some(try!(r.val_at(6))) } else { None },
+			asking_nodes: Vec::new(),
+			snapshot_hash: if protocol_version >= SNAPSHOT_SYNC_PROTOCOL as u32 { Some(try!(r.val_at(5))) } else { None },
+			snapshot_number: if protocol_version >= SNAPSHOT_SYNC_PROTOCOL as u32 { Some(try!(r.val_at(6))) } else { None },
 		};
 
 		trace!(target: "sync", "New peer {} (protocol: {}, network: {:?}, difficulty: {:?}, latest:{}, genesis:{})", peer_id, peer.protocol_version, peer.network_id, peer.difficulty, peer.latest_hash, peer.genesis);
@@ -614,7 +662,7 @@ impl ChainSync {
 			},
 			SyncState::Blocks | SyncState::NewBlocks | SyncState::Waiting => {
 				trace!(target: "sync", "Inserted {} headers", headers.len());
-				self.blocks.insert_headers(headers);
+				self.blocks.insert_headers(headers, &io.peer_info(peer_id));
 			},
 			_ => trace!(target: "sync", "Unexpected headers({}) from  {} ({}), state = {:?}", headers.len(), peer_id, io.peer_info(peer_id), self.state)
 		}
@@ -648,7 +696,7 @@ impl ChainSync {
 			for i in 0..item_count {
 				bodies.push(try!(r.at(i)).as_raw().to_vec());
 			}
-			if self.blocks.insert_bodies(bodies) != item_count {
+			if self.blocks.insert_bodies(bodies, &io.peer_info(peer_id)) != item_count {
 				trace!(target: "sync", "Deactivating peer for giving invalid block bodies");
 				self.deactivate_peer(io, peer_id);
 			}
@@ -665,6 +713,10 @@ impl ChainSync {
 			trace!(target: "sync", "Ignoring new block from unconfirmed peer {}", peer_id);
 			return Ok(());
 		}
+		if !self.import_blocks {
+			trace!(target: "sync", "Ignoring new block from {}, block import is disabled", peer_id);
+			return Ok(());
+		}
 		let block_rlp = try!(r.at(0));
 		let header_rlp = try!(block_rlp.at(0));
 		let h = header_rlp.as_raw().sha3();
@@ -684,7 +736,7 @@ impl ChainSync {
 			io.disable_peer(peer_id);
 			return Ok(());
 		}
-		match io.chain().import_block(block_rlp.as_raw().to_vec()) {
+		match io.chain().import_block_from_peer(block_rlp.as_raw().to_vec(), io.peer_info(peer_id)) {
 			Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {
 				trace!(target: "sync", "New block already in chain {:?}", h);
 			},
@@ -733,6 +785,10 @@ impl ChainSync {
 			trace!(target: "sync", "Ignoring new hashes from unconfirmed peer {}", peer_id);
 			return Ok(());
 		}
+		if !self.import_blocks {
+			trace!(target: "sync", "Ignoring new hashes from {}, block import is disabled", peer_id);
+			return Ok(());
+		}
 		if self.state != SyncState::Idle {
 			trace!(target: "sync", "Ignoring new hashes since we're already downloading.");
 			let max = r.iter().take(MAX_NEW_HASHES).map(|item| item.val_at::<BlockNumber>(1).unwrap_or(0)).fold(0u64, max);
@@ -806,7 +862,17 @@ impl ChainSync {
 		}
 
 		let manifest_rlp = try!(r.at(0));
-		let manifest = match ManifestData::from_rlp(manifest_rlp.as_raw()) {
+		let manifest = match decode_manifest(manifest_rlp.as_raw()) {
+			Err(SnapshotError::VersionNotSupported(version)) => {
+				trace!(target: "sync", "{}: Ignored manifest with unsupported version {}", peer_id, version);
+				if let Some(peer) = self.peers.get_mut(&peer_id) {
+					peer.snapshot_hash = None;
+					peer.snapshot_number = None;
+				}
+				self.state = SyncState::Idle;
+				self.continue_sync(io);
+				return Ok(());
+			}
 			Err(e) => {
 				trace!(target: "sync", "{}: Ignored bad manifest: {:?}", peer_id, e);
 				io.disconnect_peer(peer_id);
@@ -882,12 +948,45 @@ impl ChainSync {
 		Ok(())
 	}
 
+	/// Called when state nodes are downloaded from a peer.
+	fn on_peer_node_data(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.clear_peer_download(peer_id);
+		if !self.reset_peer_asking(peer_id, PeerAsking::NodeData) {
+			trace!(target: "sync", "{}: Ignored unexpected node data", peer_id);
+			self.continue_sync(io);
+			return Ok(());
+		}
+
+		trace!(target: "sync", "{} -> NodeData ({} entries)", peer_id, r.item_count());
+		for item in r.iter() {
+			// nodes may come either as raw RLP or wrapped into a byte string.
+			let node = match self.wanted_nodes.contains_key(&item.as_raw().sha3()) {
+				true => item.as_raw(),
+				false => match item.data() {
+					Ok(data) => data,
+					Err(_) => continue,
+				},
+			};
+			let hash = node.sha3();
+			if self.wanted_nodes.remove(&hash).is_some() {
+				self.retrieved_nodes.insert(hash, node.to_vec());
+			}
+		}
+
+		// give a task to the same peer first.
+		self.sync_peer(io, peer_id, false);
+		// give tasks to other peers
+		self.continue_sync(io);
+		Ok(())
+	}
+
 	/// Called by peer when it is disconnecting
 	pub fn on_peer_aborting(&mut self, io: &mut SyncIo, peer: PeerId) {
 		trace!(target: "sync", "== Disconnecting {}: {}", peer, io.peer_info(peer));
 		if self.peers.contains_key(&peer) {
 			debug!(target: "sync", "Disconnected {}", peer);
 			self.clear_peer_download(peer);
+			self.snapshot_server.remove_peer(peer);
 			self.peers.remove(&peer);
 			self.active_peers.remove(&peer);
 			self.continue_sync(io);
@@ -941,7 +1040,7 @@ impl ChainSync {
 			trace!(target: "sync", "Skipping deactivated peer");
 			return;
 		}
-		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash) = {
+		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash, peer_protocol_version) = {
 			let peer = self.peers.get_mut(&peer_id).unwrap();
 			if peer.asking != PeerAsking::Nothing || !peer.can_sync() {
 				return;
@@ -954,8 +1053,18 @@ impl ChainSync {
 				trace!(target: "sync", "Waiting for the snapshot restoration");
 				return;
 			}
-			(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned(), peer.snapshot_hash.as_ref().cloned())
+			(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned(), peer.snapshot_hash.as_ref().cloned(), peer.protocol_version)
 		};
+		if !self.wanted_nodes.is_empty() {
+			// state nodes take priority over blocks.
+			if peer_protocol_version >= 63 {
+				self.request_node_data(io, peer_id);
+			}
+			return;
+		}
+		if !self.import_blocks {
+			return;
+		}
 		let chain_info = io.chain().chain_info();
 		let td = chain_info.pending_total_difficulty;
 		let syncing_difficulty = max(self.syncing_difficulty, td);
@@ -1064,6 +1173,28 @@ impl ChainSync {
 		}
 	}
 
+	/// Find some state nodes to download for a peer.
+	fn request_node_data(&mut self, io: &mut SyncIo, peer_id: PeerId) {
+		self.clear_peer_download(peer_id);
+		let hashes: Vec<H256> = self.wanted_nodes.iter()
+			.filter(|&(_, downloading)| !*downloading)
+			.map(|(hash, _)| hash.clone())
+			.take(MAX_NODE_DATA_TO_REQUEST)
+			.collect();
+		if hashes.is_empty() {
+			return;
+		}
+
+		let mut rlp = RlpStream::new_list(hashes.len());
+		trace!(target: "sync", "{} <- GetNodeData: {} entries", peer_id, hashes.len());
+		for hash in &hashes {
+			rlp.append(hash);
+			self.wanted_nodes.insert(hash.clone(), true);
+		}
+		self.peers.get_mut(&peer_id).unwrap().asking_nodes = hashes;
+		self.send_request(io, peer_id, PeerAsking::NodeData, GET_NODE_DATA_PACKET, rlp.out());
+	}
+
 	/// Clear all blocks/headers marked as being downloaded by a peer.
 	fn clear_peer_download(&mut self, peer_id: PeerId) {
 		let peer = self.peers.get_mut(&peer_id).unwrap();
@@ -1083,10 +1214,18 @@ impl ChainSync {
 					self.snapshot.clear_chunk_download(&hash);
 				}
 			},
+			PeerAsking::NodeData => {
+				for hash in &peer.asking_nodes {
+					if let Some(downloading) = self.wanted_nodes.get_mut(hash) {
+						*downloading = false;
+					}
+				}
+			},
 			_ => (),
 		}
 		peer.asking_blocks.clear();
 		peer.asking_snapshot_data = None;
+		peer.asking_nodes.clear();
 	}
 
 	fn block_imported(&mut self, hash: &H256, number: BlockNumber, parent: &H256) {
@@ -1104,7 +1243,7 @@ impl ChainSync {
 		let mut imported = HashSet::new();
 		let blocks = self.blocks.drain();
 		let count = blocks.len();
-		for block in blocks {
+		for (block, peer) in blocks {
 			let (h, number, parent) = {
 				let header = BlockView::new(&block).header_view();
 				(header.sha3(), header.number(), header.parent_hash())
@@ -1117,7 +1256,7 @@ impl ChainSync {
 				break;
 			}
 
-			match io.chain().import_block(block) {
+			match io.chain().import_block_from_peer(block, peer) {
 				Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {
 					trace!(target: "sync", "Block already in chain {:?}", h);
 					self.block_imported(&h, number, &parent);
@@ -1270,18 +1409,23 @@ impl ChainSync {
 
 	/// Send Status message
 	fn send_status(&mut self, io: &mut SyncIo, peer: PeerId) -> Result<(), NetworkError> {
-		let pv64 = io.eth_protocol_version(peer) >= 64;
+		let protocol_version = io.eth_protocol_version(peer);
+		let pv64 = protocol_version >= SNAPSHOT_SYNC_PROTOCOL;
 		let mut packet = RlpStream::new_list(if pv64 { 7 } else { 5 });
 		let chain = io.chain().chain_info();
-		packet.append(&(io.eth_protocol_version(peer) as u32));
+		packet.append(&(protocol_version as u32));
 		packet.append(&self.network_id);
 		packet.append(&chain.total_difficulty);
 		packet.append(&chain.best_block_hash);
 		packet.append(&chain.genesis_hash);
 		if pv64 {
-			let manifest = io.snapshot_service().manifest();
-			let block_number = manifest.as_ref().map_or(0, |m| m.block_number);
-			let manifest_hash = manifest.map_or(H256::new(), |m| m.into_rlp().sha3());
+			// don't advertise a snapshot we are not going to serve
+			let manifest = if self.snapshot_server.is_enabled() { io.snapshot_service().manifest() } else { None };
+			let par_version = io.par_protocol_version(peer);
+			// the hash must match the manifest encoding this peer is going to receive
+			let (manifest_hash, block_number) = manifest
+				.and_then(|m| ChainSync::encode_manifest(m.clone(), par_version).map(|rlp| (rlp.sha3(), m.block_number)))
+				.unwrap_or((H256::new(), 0));
 			packet.append(&manifest_hash);
 			packet.append(&block_number);
 		}
@@ -1421,19 +1565,31 @@ impl ChainSync {
 		Ok(Some((RECEIPTS_PACKET, rlp_result)))
 	}
 
+	/// Encode the manifest in the format understood by a peer using the given `par` protocol version.
+	/// Returns `None` if the peer is not able to use the snapshot.
+	fn encode_manifest(manifest: ManifestData, par_version: u8) -> Option<Bytes> {
+		if par_version >= VERSIONED_MANIFEST_PROTOCOL {
+			Some(manifest.into_rlp())
+		} else {
+			manifest.to_legacy_rlp()
+		}
+	}
+
 	/// Respond to GetSnapshotManifest request
-	fn return_snapshot_manifest(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
+	fn return_snapshot_manifest(server: &SnapshotServer, io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
 		let count = r.item_count();
 		trace!(target: "sync", "{} -> GetSnapshotManifest", peer_id);
 		if count != 0 {
 			debug!(target: "sync", "Invalid GetSnapshotManifest request, ignoring.");
 			return Ok(None);
 		}
-		let rlp = match io.snapshot_service().manifest() {
-			Some(manifest) => {
+		let manifest = if server.is_enabled() { io.snapshot_service().manifest() } else { None };
+		let par_version = io.par_protocol_version(peer_id);
+		let rlp = match manifest.and_then(|m| ChainSync::encode_manifest(m, par_version)) {
+			Some(manifest_rlp) => {
 				trace!(target: "sync", "{} <- SnapshotManifest", peer_id);
 				let mut rlp = RlpStream::new_list(1);
-				rlp.append_raw(&manifest.into_rlp(), 1);
+				rlp.append_raw(&manifest_rlp, 1);
 				rlp
 			},
 			None => {
@@ -1444,21 +1600,29 @@ impl ChainSync {
 		Ok(Some((SNAPSHOT_MANIFEST_PACKET, rlp)))
 	}
 
-	/// Respond to GetSnapshotManifest request
-	fn return_snapshot_data(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
+	/// Respond to GetSnapshotData request.
+	/// The request slot taken from the snapshot server is held until the response has been sent.
+	fn return_snapshot_data(server: &SnapshotServer, io: &mut SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> Result<(), PacketDecodeError> {
 		let hash: H256 = try!(r.val_at(0));
 		trace!(target: "sync", "{} -> GetSnapshotData {:?}", peer_id, hash);
-		let rlp = match io.snapshot_service().chunk(hash) {
-			Some(data) => {
-				let mut rlp = RlpStream::new_list(1);
-				rlp.append(&data);
-				rlp
-			},
-			None => {
-				RlpStream::new_list(0)
-			}
+		let guard = server.begin(peer_id, &hash);
+		let data = match guard {
+			Some(_) => io.snapshot_service().chunk(hash),
+			None => None,
 		};
-		Ok(Some((SNAPSHOT_DATA_PACKET, rlp)))
+		let mut rlp = RlpStream::new_list(if data.is_some() { 1 } else { 0 });
+		if let Some(ref data) = data {
+			rlp.append(data);
+		}
+		match io.respond(SNAPSHOT_DATA_PACKET, rlp.out()) {
+			Ok(()) => {
+				if let (Some(guard), Some(data)) = (guard, data) {
+					guard.served(data.len());
+				}
+			},
+			Err(e) => debug!(target: "sync", "Error sending snapshot data: {:?}", e),
+		}
+		Ok(())
 	}
 
 	fn return_rlp<FRlp, FError>(io: &mut SyncIo, rlp: &UntrustedRlp, peer: PeerId, rlp_func: FRlp, error_func: FError) -> Result<(), PacketDecodeError>
@@ -1497,13 +1661,17 @@ impl ChainSync {
 				ChainSync::return_node_data,
 				|e| format!("Error sending nodes: {:?}", e)),
 
-			GET_SNAPSHOT_MANIFEST_PACKET => ChainSync::return_rlp(io, &rlp, peer,
-				ChainSync::return_snapshot_manifest,
-				|e| format!("Error sending snapshot manifest: {:?}", e)),
+			GET_SNAPSHOT_MANIFEST_PACKET => {
+				let server = sync.read().snapshot_server.clone();
+				ChainSync::return_rlp(io, &rlp, peer,
+					|io, rlp, peer| ChainSync::return_snapshot_manifest(&server, io, rlp, peer),
+					|e| format!("Error sending snapshot manifest: {:?}", e))
+			},
 
-			GET_SNAPSHOT_DATA_PACKET => ChainSync::return_rlp(io, &rlp, peer,
-				ChainSync::return_snapshot_data,
-				|e| format!("Error sending snapshot data: {:?}", e)),
+			GET_SNAPSHOT_DATA_PACKET => {
+				let server = sync.read().snapshot_server.clone();
+				ChainSync::return_snapshot_data(&server, io, &rlp, peer)
+			},
 
 			_ => {
 				sync.write().on_packet(io, peer, packet_id, data);
@@ -1530,6 +1698,7 @@ impl ChainSync {
 			NEW_BLOCK_HASHES_PACKET => self.on_peer_new_hashes(io, peer, &rlp),
 			SNAPSHOT_MANIFEST_PACKET => self.on_snapshot_manifest(io, peer, &rlp),
 			SNAPSHOT_DATA_PACKET => self.on_snapshot_data(io, peer, &rlp),
+			NODE_DATA_PACKET => self.on_peer_node_data(io, peer, &rlp),
 			_ => {
 				debug!(target: "sync", "Unknown packet {}", packet_id);
 				Ok(())
@@ -1552,6 +1721,7 @@ impl ChainSync {
 				PeerAsking::ForkHeader => (tick - peer.ask_time) > FORK_HEADER_TIMEOUT_SEC,
 				PeerAsking::SnapshotManifest => (tick - peer.ask_time) > SNAPSHOT_MANIFEST_TIMEOUT_SEC,
 				PeerAsking::SnapshotData => (tick - peer.ask_time) > SNAPSHOT_DATA_TIMEOUT_SEC,
+				PeerAsking::NodeData => (tick - peer.ask_time) > NODE_DATA_TIMEOUT_SEC,
 			};
 			if timeout {
 				trace!(target:"sync", "Timeout {}", peer_id);
@@ -1786,6 +1956,12 @@ impl ChainSync {
 	/// Maintain other peers. Send out any new blocks and transactions
 	pub fn maintain_sync(&mut self, io: &mut SyncIo) {
 		self.check_resume(io);
+		if !self.wanted_nodes.is_empty() {
+			let peers: Vec<PeerId> = self.peers.keys().cloned().collect();
+			for peer_id in peers {
+				self.sync_peer(io, peer_id, false);
+			}
+		}
 	}
 
 	/// called when block is imported to chain - propagates the blocks and updates transactions sent to peers
@@ -1807,6 +1983,7 @@ impl ChainSync {
 mod tests {
 	use tests::helpers::*;
 	use tests::snapshot::TestSnapshotService;
+	use snapshot_server::SnapshotServer;
 	use super::*;
 	use ::SyncConfig;
 	use util::*;
@@ -1816,6 +1993,7 @@ mod tests {
 	use ethcore::header::*;
 	use ethcore::client::*;
 	use ethcore::miner::MinerService;
+	use ethcore::snapshot::{SnapshotService, ManifestData, Compression};
 
 	fn get_dummy_block(order: u32, parent_hash: H256) -> Bytes {
 		let mut header = Header::new();
@@ -1993,6 +2171,81 @@ mod tests {
 		assert_eq!(1, io.queue.len());
 	}
 
+	#[test]
+	fn requests_state_nodes() {
+		let mut client = TestBlockChainClient::new();
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(H256::new(), &client);
+		sync.peers.get_mut(&0).unwrap().protocol_version = 63;
+		sync.active_peers.insert(0);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		let node = encode(&vec![1u8; 40]).to_vec();
+		sync.request_state_nodes(vec![node.sha3(), H256::from(1)]);
+		sync.maintain_sync(&mut io);
+
+		assert_eq!(1, io.queue.len());
+		assert_eq!(super::GET_NODE_DATA_PACKET, io.queue[0].packet_id);
+		assert_eq!(2, UntrustedRlp::new(&io.queue[0].data).item_count());
+
+		let mut response = RlpStream::new_list(1);
+		response.append_raw(&node, 1);
+		sync.on_packet(&mut io, 0, super::NODE_DATA_PACKET, &response.out());
+
+		let retrieved = sync.take_state_nodes();
+		assert_eq!(retrieved.len(), 1);
+		assert_eq!(retrieved[&node.sha3()], node);
+		// the missing node is asked for again.
+		assert_eq!(2, io.queue.len());
+		assert_eq!(1, UntrustedRlp::new(&io.queue[1].data).item_count());
+	}
+
+	#[test]
+	fn return_snapshot_data_respects_serving_policy() {
+		let mut client = TestBlockChainClient::new();
+		let mut queue = VecDeque::new();
+		let ss = TestSnapshotService::new_with_snapshot(2, H256::new(), 0);
+		let hash = ss.manifest().unwrap().state_hashes[0].clone();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, Some(0));
+		let mut request = RlpStream::new_list(1);
+		request.append(&hash);
+		let request = request.out();
+		let manifest_request = RlpStream::new_list(0).out();
+
+		let disabled = SnapshotServer::new(false, 4, 1);
+		ChainSync::return_snapshot_data(&disabled, &mut io, &UntrustedRlp::new(&request), 0).unwrap();
+		assert_eq!(UntrustedRlp::new(&io.queue.pop_front().unwrap().data).item_count(), 0);
+		let result = ChainSync::return_snapshot_manifest(&disabled, &io, &UntrustedRlp::new(&manifest_request), 0).unwrap().unwrap();
+		assert_eq!(UntrustedRlp::new(&result.1.out()).item_count(), 0);
+
+		let enabled = SnapshotServer::new(true, 4, 1);
+		ChainSync::return_snapshot_data(&enabled, &mut io, &UntrustedRlp::new(&request), 0).unwrap();
+		assert_eq!(UntrustedRlp::new(&io.queue.pop_front().unwrap().data).item_count(), 1);
+		// the request slot is free again once the chunk has been sent
+		ChainSync::return_snapshot_data(&enabled, &mut io, &UntrustedRlp::new(&request), 0).unwrap();
+		assert_eq!(UntrustedRlp::new(&io.queue.pop_front().unwrap().data).item_count(), 1);
+		let result = ChainSync::return_snapshot_manifest(&enabled, &io, &UntrustedRlp::new(&manifest_request), 0).unwrap().unwrap();
+		assert_eq!(UntrustedRlp::new(&result.1.out()).item_count(), 1);
+		assert_eq!(enabled.stats().chunks_served, 2);
+		assert_eq!(enabled.stats().requests_refused, 0);
+	}
+
+	#[test]
+	fn encodes_manifest_for_peer_version() {
+		let ss = TestSnapshotService::new_with_snapshot(2, H256::new(), 0);
+		let manifest = ss.manifest().unwrap();
+
+		let legacy = ChainSync::encode_manifest(manifest.clone(), 0).unwrap();
+		assert_eq!(UntrustedRlp::new(&legacy).item_count(), 5);
+		let versioned = ChainSync::encode_manifest(manifest.clone(), VERSIONED_MANIFEST_PROTOCOL).unwrap();
+		assert_eq!(ManifestData::from_rlp(&versioned).unwrap(), manifest);
+
+		let uncompressed = ManifestData { compression: Compression::None, ..manifest };
+		assert!(ChainSync::encode_manifest(uncompressed.clone(), 0).is_none());
+		assert!(ChainSync::encode_manifest(uncompressed, VERSIONED_MANIFEST_PROTOCOL).is_some());
+	}
+
 	fn dummy_sync_with_peer(peer_latest_hash: H256, client: &BlockChainClient) -> ChainSync {
 		let mut sync = ChainSync::new(SyncConfig::default(), client);
 		sync.peers.insert(0,
@@ -2013,6 +2266,7 @@ mod tests {
 				snapshot_number: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
+				asking_nodes: Vec::new(),
 			});
 		sync
 	}
@@ -2229,6 +2483,27 @@ mod tests {
 		assert!(result.is_ok());
 	}
 
+	#[test]
+	fn does_not_import_blocks_when_disabled() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+
+		let block_data = get_dummy_blocks(11, client.chain_info().best_block_hash);
+
+		let mut queue = VecDeque::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.import_blocks = false;
+		sync.peers.get_mut(&0).unwrap().difficulty = Some(U256::from(1_000_000u64));
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &mut queue, None);
+
+		sync.on_peer_new_block(&mut io, 0, &UntrustedRlp::new(&block_data)).unwrap();
+		sync.sync_peer(&mut io, 0, true);
+
+		assert_eq!(io.chain.chain_info().best_block_number, 10);
+		assert!(io.queue.is_empty());
+	}
+
 	#[test]
 	fn handles_peer_new_block_empty() {
 		let mut client = TestBlockChainClient::new();
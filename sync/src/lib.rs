@@ -50,6 +50,7 @@ mod chain;
 mod blocks;
 mod sync_io;
 mod snapshot;
+mod snapshot_server;
 
 #[cfg(test)]
 mod tests;
@@ -60,6 +61,6 @@ mod api {
 }
 
 pub use api::{EthSync, SyncProvider, SyncClient, NetworkManagerClient, ManageNetwork, SyncConfig,
-	ServiceConfiguration, NetworkConfiguration};
+	ServiceConfiguration, NetworkConfiguration, NetworkTraffic, PacketTraffic};
 pub use chain::{SyncStatus, SyncState};
-pub use network::{is_valid_node_url, NonReservedPeerMode, NetworkError};
+pub use network::{is_valid_node_url, is_valid_dns_tree_url, NonReservedPeerMode, NetworkError};
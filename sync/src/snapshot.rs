@@ -125,7 +125,7 @@ impl Snapshot {
 mod test {
 	use util::*;
 	use super::*;
-	use ethcore::snapshot::ManifestData;
+	use ethcore::snapshot::{ManifestData, Compression, SNAPSHOT_VERSION};
 
 	fn is_empty(snapshot: &Snapshot) -> bool {
 		snapshot.pending_block_chunks.is_empty() &&
@@ -139,11 +139,15 @@ mod test {
 		let state_chunks: Vec<Bytes> = (0..20).map(|_| H256::random().to_vec()).collect();
 		let block_chunks: Vec<Bytes> = (0..20).map(|_| H256::random().to_vec()).collect();
 		let manifest = ManifestData {
+			version: SNAPSHOT_VERSION,
+			compression: Compression::Snappy,
+			engine: String::new(),
 			state_hashes: state_chunks.iter().map(|data| data.sha3()).collect(),
 			block_hashes: block_chunks.iter().map(|data| data.sha3()).collect(),
 			state_root: H256::new(),
 			block_number: 42,
 			block_hash: H256::new(),
+			base: None,
 		};
 		let mhash = manifest.clone().into_rlp().sha3();
 		(manifest, mhash, state_chunks, block_chunks)
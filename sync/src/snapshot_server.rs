@@ -0,0 +1,268 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use util::H256;
+use std::collections::{HashMap, HashSet};
+use network::PeerId;
+use parking_lot::Mutex;
+
+/// Number of repeated chunk requests tolerated before a peer is deprioritised.
+const MAX_REPEATED_REQUESTS: usize = 16;
+
+#[derive(Default)]
+struct PeerServing {
+	/// Requests being served right now.
+	active: usize,
+	/// Peer has disconnected, forget it once its requests are released.
+	disconnected: bool,
+	/// Chunks served to this peer.
+	served: HashSet<H256>,
+	/// Number of requests for chunks that were already served.
+	repeated: usize,
+}
+
+impl PeerServing {
+	/// Peers that keep re-requesting chunks they have already been given are likely
+	/// not using them for restoration.
+	fn is_wasteful(&self) -> bool {
+		self.repeated > MAX_REPEATED_REQUESTS && self.repeated > self.served.len()
+	}
+}
+
+#[derive(Default)]
+struct ServingState {
+	active: usize,
+	peers: HashMap<PeerId, PeerServing>,
+	chunks_served: usize,
+	bytes_served: usize,
+	requests_refused: usize,
+}
+
+impl ServingState {
+	/// Release the request slot of `peer`.
+	fn release(&mut self, peer: PeerId) {
+		self.active -= 1;
+		let forget = match self.peers.get_mut(&peer) {
+			Some(p) => {
+				p.active -= 1;
+				p.active == 0 && p.disconnected
+			},
+			None => false,
+		};
+		if forget {
+			self.peers.remove(&peer);
+		}
+	}
+}
+
+/// Snapshot serving statistics.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ServingStats {
+	/// Total number of chunks served.
+	pub chunks_served: usize,
+	/// Total number of chunk bytes served.
+	pub bytes_served: usize,
+	/// Number of chunk requests refused due to limits.
+	pub requests_refused: usize,
+}
+
+/// Limits access to local snapshot data requested by peers.
+pub struct SnapshotServer {
+	enabled: bool,
+	max_requests: usize,
+	max_peer_requests: usize,
+	state: Mutex<ServingState>,
+}
+
+/// Represents a chunk request being served. The request slot is released when
+/// the guard is dropped, which should happen once the response has been sent.
+pub struct ServingGuard<'a> {
+	server: &'a SnapshotServer,
+	peer: PeerId,
+	hash: H256,
+}
+
+impl<'a> ServingGuard<'a> {
+	/// Record that the chunk of `size` bytes has been sent to the peer and release the request slot.
+	pub fn served(self, size: usize) {
+		let mut state = self.server.state.lock();
+		state.chunks_served += 1;
+		state.bytes_served += size;
+		if let Some(peer) = state.peers.get_mut(&self.peer) {
+			peer.served.insert(self.hash.clone());
+		}
+	}
+}
+
+impl<'a> Drop for ServingGuard<'a> {
+	fn drop(&mut self) {
+		self.server.state.lock().release(self.peer);
+	}
+}
+
+impl SnapshotServer {
+	/// Create a new instance. `max_requests` and `max_peer_requests` limit the number of
+	/// concurrently served chunk requests overall and for a single peer.
+	pub fn new(enabled: bool, max_requests: usize, max_peer_requests: usize) -> SnapshotServer {
+		SnapshotServer {
+			enabled: enabled,
+			max_requests: max_requests,
+			max_peer_requests: max_peer_requests,
+			state: Mutex::new(ServingState::default()),
+		}
+	}
+
+	/// Check if serving snapshots is enabled.
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	/// Try to reserve a slot for serving chunk `hash` to `peer`.
+	/// Returns `None` if the request should be refused.
+	pub fn begin(&self, peer: PeerId, hash: &H256) -> Option<ServingGuard> {
+		if !self.enabled {
+			return None;
+		}
+		let mut state = self.state.lock();
+		let active = state.active;
+		let allowed = {
+			let p = state.peers.entry(peer).or_insert_with(Default::default);
+			if p.served.contains(hash) {
+				p.repeated += 1;
+			}
+			let (max_peer, max_total) = if p.is_wasteful() {
+				// deprioritised peers only get served when we are mostly idle
+				(1, self.max_requests / 2)
+			} else {
+				(self.max_peer_requests, self.max_requests)
+			};
+			if p.active < max_peer && active < max_total {
+				p.active += 1;
+				true
+			} else {
+				false
+			}
+		};
+		if !allowed {
+			trace!(target: "sync", "{}: Refusing snapshot chunk request", peer);
+			state.requests_refused += 1;
+			return None;
+		}
+		state.active += 1;
+		Some(ServingGuard {
+			server: self,
+			peer: peer,
+			hash: hash.clone(),
+		})
+	}
+
+	/// Forget about a disconnected peer. Requests still being served are released
+	/// when they complete.
+	pub fn remove_peer(&self, peer: PeerId) {
+		let mut state = self.state.lock();
+		let idle = match state.peers.get_mut(&peer) {
+			Some(p) => {
+				p.disconnected = true;
+				p.active == 0
+			},
+			None => false,
+		};
+		if idle {
+			state.peers.remove(&peer);
+		}
+	}
+
+	/// Get serving statistics.
+	pub fn stats(&self) -> ServingStats {
+		let state = self.state.lock();
+		ServingStats {
+			chunks_served: state.chunks_served,
+			bytes_served: state.bytes_served,
+			requests_refused: state.requests_refused,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use util::H256;
+
+	#[test]
+	fn disabled_refuses_all() {
+		let server = SnapshotServer::new(false, 10, 10);
+		assert!(server.begin(0, &H256::random()).is_none());
+	}
+
+	#[test]
+	fn limits_concurrent_requests() {
+		let server = SnapshotServer::new(true, 3, 2);
+		let a = server.begin(0, &H256::random()).unwrap();
+		let b = server.begin(0, &H256::random()).unwrap();
+		assert!(server.begin(0, &H256::random()).is_none());
+		let c = server.begin(1, &H256::random()).unwrap();
+		assert!(server.begin(2, &H256::random()).is_none());
+
+		a.served(100);
+		drop(b);
+		assert!(server.begin(0, &H256::random()).is_some());
+		drop(c);
+
+		let stats = server.stats();
+		assert_eq!(stats.chunks_served, 1);
+		assert_eq!(stats.bytes_served, 100);
+		assert_eq!(stats.requests_refused, 2);
+	}
+
+	#[test]
+	fn holds_slots_until_chunks_are_sent() {
+		let server = SnapshotServer::new(true, 2, 1);
+		let sending = server.begin(0, &H256::random()).unwrap();
+		assert!(server.begin(0, &H256::random()).is_none());
+		let _other = server.begin(1, &H256::random()).unwrap();
+		assert!(server.begin(2, &H256::random()).is_none());
+
+		sending.served(100);
+		assert_eq!(server.state.lock().active, 1);
+		assert!(server.begin(0, &H256::random()).is_some());
+		assert!(server.begin(2, &H256::random()).is_some());
+	}
+
+	#[test]
+	fn forgets_disconnected_peers() {
+		let server = SnapshotServer::new(true, 4, 4);
+		let guard = server.begin(0, &H256::random()).unwrap();
+		server.remove_peer(0);
+		assert!(server.state.lock().peers.contains_key(&0));
+		drop(guard);
+		assert!(!server.state.lock().peers.contains_key(&0));
+		assert_eq!(server.state.lock().active, 0);
+	}
+
+	#[test]
+	fn deprioritises_repeated_requests() {
+		let server = SnapshotServer::new(true, 4, 4);
+		let hash = H256::random();
+		for _ in 0..(MAX_REPEATED_REQUESTS + 2) {
+			server.begin(0, &hash).unwrap().served(10);
+		}
+		let _busy1 = server.begin(1, &H256::random()).unwrap();
+		let _busy2 = server.begin(1, &H256::random()).unwrap();
+		// global load is at half capacity
+		assert!(server.begin(0, &hash).is_none());
+		assert!(server.begin(2, &H256::random()).is_some());
+	}
+}
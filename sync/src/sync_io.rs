@@ -17,7 +17,7 @@
 use network::{NetworkContext, PeerId, PacketId, NetworkError};
 use ethcore::client::BlockChainClient;
 use ethcore::snapshot::SnapshotService;
-use api::ETH_PROTOCOL;
+use api::{ETH_PROTOCOL, PAR_PROTOCOL};
 
 /// IO interface for the syning handler.
 /// Provides peer connection management and an interface to the blockchain client.
@@ -41,6 +41,8 @@ pub trait SyncIo {
 	}
 	/// Maximum mutuallt supported ETH protocol version
 	fn eth_protocol_version(&self, peer_id: PeerId) -> u8;
+	/// Maximum mutually supported PAR protocol version, 0 if the peer does not support it
+	fn par_protocol_version(&self, peer_id: PeerId) -> u8;
 	/// Returns if the chain block queue empty
 	fn is_chain_queue_empty(&self) -> bool {
 		self.chain().queue_info().is_empty()
@@ -103,6 +105,10 @@ impl<'s, 'h> SyncIo for NetSyncIo<'s, 'h> {
 	fn eth_protocol_version(&self, peer_id: PeerId) -> u8 {
 		self.network.protocol_version(peer_id, ETH_PROTOCOL).unwrap_or(0)
 	}
+
+	fn par_protocol_version(&self, peer_id: PeerId) -> u8 {
+		self.network.protocol_version(peer_id, PAR_PROTOCOL).unwrap_or(0)
+	}
 }
 
 
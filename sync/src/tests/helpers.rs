@@ -82,6 +82,10 @@ impl<'p> SyncIo for TestIo<'p> {
 	fn eth_protocol_version(&self, _peer: PeerId) -> u8 {
 		64
 	}
+
+	fn par_protocol_version(&self, _peer: PeerId) -> u8 {
+		0
+	}
 }
 
 pub struct TestPacket {
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use util::*;
-use ethcore::snapshot::{SnapshotService, ManifestData, RestorationStatus};
+use ethcore::snapshot::{SnapshotService, ManifestData, RestorationStatus, Compression, SNAPSHOT_VERSION};
 use ethcore::header::BlockNumber;
 use ethcore::client::{EachBlockWith};
 use super::helpers::*;
@@ -46,11 +46,15 @@ impl TestSnapshotService {
 		let state_chunks: Vec<Bytes> = (0..num_state_chunks).map(|_| H256::random().to_vec()).collect();
 		let block_chunks: Vec<Bytes> = (0..num_block_chunks).map(|_| H256::random().to_vec()).collect();
 		let manifest = ManifestData {
+			version: SNAPSHOT_VERSION,
+			compression: Compression::Snappy,
+			engine: String::new(),
 			state_hashes: state_chunks.iter().map(|data| data.sha3()).collect(),
 			block_hashes: block_chunks.iter().map(|data| data.sha3()).collect(),
 			state_root: H256::new(),
 			block_number: block_number,
 			block_hash: block_hash,
+			base: None,
 		};
 		let mut chunks: HashMap<H256, Bytes> = state_chunks.into_iter().map(|data| (data.sha3(), data)).collect();
 		chunks.extend(block_chunks.into_iter().map(|data| (data.sha3(), data)));
@@ -0,0 +1,210 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Upload and download rate limiting.
+
+use std::sync::Arc;
+use std::cmp::min;
+use parking_lot::Mutex;
+use time;
+
+const NS_PER_SEC: u64 = 1_000_000_000;
+
+/// Token bucket limiting throughput to a number of bytes per second.
+/// Allows bursts of up to one second worth of traffic.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+	rate: u64,
+	available: u64,
+	last_refill_ns: u64,
+}
+
+impl RateLimiter {
+	/// Create a new limiter for `rate` bytes per second.
+	pub fn new(rate: u64) -> RateLimiter {
+		RateLimiter {
+			rate: rate,
+			available: rate,
+			last_refill_ns: time::precise_time_ns(),
+		}
+	}
+
+	/// Number of bytes that may be transferred right now.
+	pub fn available(&mut self) -> usize {
+		self.refill(time::precise_time_ns());
+		self.available as usize
+	}
+
+	/// Account for transferred bytes.
+	pub fn consume(&mut self, size: usize) {
+		self.available = self.available.saturating_sub(size as u64);
+	}
+
+	fn refill(&mut self, now_ns: u64) {
+		let elapsed = min(now_ns.saturating_sub(self.last_refill_ns), NS_PER_SEC);
+		let refill = elapsed * self.rate / NS_PER_SEC;
+		if refill > 0 {
+			self.available = min(self.available + refill, self.rate);
+			self.last_refill_ns = now_ns;
+		}
+	}
+}
+
+/// Bandwidth limits shared by all connections of the host.
+pub struct Bandwidth {
+	upload: Option<Mutex<RateLimiter>>,
+	download: Option<Mutex<RateLimiter>>,
+	peer_upload: Option<u64>,
+	peer_download: Option<u64>,
+}
+
+impl Bandwidth {
+	/// Create limits given in bytes per second. `None` means unlimited.
+	pub fn new(upload: Option<u64>, download: Option<u64>, peer_upload: Option<u64>, peer_download: Option<u64>) -> Bandwidth {
+		Bandwidth {
+			upload: upload.map(|r| Mutex::new(RateLimiter::new(r))),
+			download: download.map(|r| Mutex::new(RateLimiter::new(r))),
+			peer_upload: peer_upload,
+			peer_download: peer_download,
+		}
+	}
+
+	/// No limits at all.
+	pub fn unlimited() -> Bandwidth {
+		Bandwidth::new(None, None, None, None)
+	}
+
+	/// Check if any of the limits is set.
+	pub fn is_limited(&self) -> bool {
+		self.upload.is_some() || self.download.is_some() || self.peer_upload.is_some() || self.peer_download.is_some()
+	}
+}
+
+/// Connection's view of the host bandwidth limits. Keeps track of the per-peer allowance.
+#[derive(Clone)]
+pub struct ConnectionLimiter {
+	shared: Arc<Bandwidth>,
+	upload: Option<RateLimiter>,
+	download: Option<RateLimiter>,
+}
+
+impl ConnectionLimiter {
+	/// Create a new limiter for a single connection.
+	pub fn new(shared: Arc<Bandwidth>) -> ConnectionLimiter {
+		ConnectionLimiter {
+			upload: shared.peer_upload.map(RateLimiter::new),
+			download: shared.peer_download.map(RateLimiter::new),
+			shared: shared,
+		}
+	}
+
+	/// Number of bytes that may be read from the socket right now.
+	pub fn read_allowance(&mut self) -> usize {
+		ConnectionLimiter::allowance(&self.shared.download, &mut self.download)
+	}
+
+	/// Number of bytes that may be written to the socket right now.
+	pub fn write_allowance(&mut self) -> usize {
+		ConnectionLimiter::allowance(&self.shared.upload, &mut self.upload)
+	}
+
+	/// Account for bytes read.
+	pub fn on_read(&mut self, size: usize) {
+		ConnectionLimiter::consume(&self.shared.download, &mut self.download, size);
+	}
+
+	/// Account for bytes written.
+	pub fn on_write(&mut self, size: usize) {
+		ConnectionLimiter::consume(&self.shared.upload, &mut self.upload, size);
+	}
+
+	fn allowance(global: &Option<Mutex<RateLimiter>>, peer: &mut Option<RateLimiter>) -> usize {
+		let global = global.as_ref().map_or(usize::max_value(), |l| l.lock().available());
+		let peer = peer.as_mut().map_or(usize::max_value(), |l| l.available());
+		min(global, peer)
+	}
+
+	fn consume(global: &Option<Mutex<RateLimiter>>, peer: &mut Option<RateLimiter>, size: usize) {
+		if let Some(ref global) = *global {
+			global.lock().consume(size);
+		}
+		if let Some(ref mut peer) = *peer {
+			peer.consume(size);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Arc;
+
+	#[test]
+	fn rate_limiter_refills() {
+		let mut limiter = RateLimiter::new(1000);
+		let start = limiter.last_refill_ns;
+		limiter.consume(1000);
+		assert_eq!(limiter.available, 0);
+
+		limiter.refill(start + 500_000_000);
+		assert_eq!(limiter.available, 500);
+
+		// never more than a second worth of data
+		limiter.refill(start + 10_000_000_000);
+		assert_eq!(limiter.available, 1000);
+	}
+
+	#[test]
+	fn rate_limiter_accumulates_small_intervals() {
+		let mut limiter = RateLimiter::new(10);
+		let start = limiter.last_refill_ns;
+		limiter.consume(10);
+		limiter.refill(start + 50_000_000);
+		assert_eq!(limiter.available, 0);
+		limiter.refill(start + 100_000_000);
+		assert_eq!(limiter.available, 1);
+	}
+
+	#[test]
+	fn connection_limiter_uses_lowest_limit() {
+		let shared = Arc::new(Bandwidth::new(Some(100), None, Some(10), None));
+		assert!(shared.is_limited());
+		let mut first = ConnectionLimiter::new(shared.clone());
+		let mut second = ConnectionLimiter::new(shared.clone());
+		assert_eq!(first.read_allowance(), usize::max_value());
+		assert_eq!(first.write_allowance(), 10);
+
+		first.on_write(10);
+		assert_eq!(first.write_allowance(), 0);
+		assert_eq!(second.write_allowance(), 10);
+
+		for _ in 0..9 {
+			second.on_write(10);
+		}
+		// global allowance is exhausted
+		let mut third = ConnectionLimiter::new(shared);
+		assert_eq!(third.write_allowance(), 0);
+	}
+
+	#[test]
+	fn unlimited() {
+		let mut limiter = ConnectionLimiter::new(Arc::new(Bandwidth::unlimited()));
+		limiter.on_read(1 << 20);
+		limiter.on_write(1 << 20);
+		assert_eq!(limiter.read_allowance(), usize::max_value());
+		assert_eq!(limiter.write_allowance(), usize::max_value());
+	}
+}
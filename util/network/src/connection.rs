@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::cmp::min;
 use std::collections::VecDeque;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
@@ -29,6 +30,7 @@ use error::*;
 use io::{IoContext, StreamToken};
 use handshake::Handshake;
 use stats::NetworkStats;
+use bandwidth::{Bandwidth, ConnectionLimiter};
 use rcrypto::blockmodes::*;
 use rcrypto::aessafe::*;
 use rcrypto::symmetriccipher::*;
@@ -62,6 +64,10 @@ pub struct GenericConnection<Socket: GenericSocket> {
 	stats: Arc<NetworkStats>,
 	/// Registered flag
 	registered: AtomicBool,
+	/// Bandwidth limiter
+	limiter: ConnectionLimiter,
+	/// Set when the last read or write was cut short by the bandwidth limit
+	throttled: bool,
 }
 
 impl<Socket: GenericSocket> GenericConnection<Socket> {
@@ -78,12 +84,20 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		if self.rec_size == 0 || self.rec_buf.len() >= self.rec_size {
 			return Ok(None);
 		}
+		self.throttled = false;
 		let sock_ref = <Socket as Read>::by_ref(&mut self.socket);
 		loop {
-			let max = self.rec_size - self.rec_buf.len();
+			let allowance = self.limiter.read_allowance();
+			if allowance == 0 {
+				trace!(target:"network", "{}: Read throttled", self.token);
+				self.throttled = true;
+				return Ok(None);
+			}
+			let max = min(self.rec_size - self.rec_buf.len(), allowance);
 			match sock_ref.take(max as u64).try_read_buf(&mut self.rec_buf) {
 				Ok(Some(size)) if size != 0  => {
 					self.stats.inc_recv(size);
+					self.limiter.on_read(size);
 					trace!(target:"network", "{}: Read {} of {} bytes", self.token, self.rec_buf.len(), self.rec_size);
 					if self.rec_size != 0 && self.rec_buf.len() == self.rec_size {
 						self.rec_size = 0;
@@ -119,25 +133,44 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		self.interest.is_writable()
 	}
 
+	/// Check if the last read or write was limited by the bandwidth settings.
+	pub fn is_throttled(&self) -> bool {
+		self.throttled
+	}
+
 	/// Writable IO handler. Called when the socket is ready to send.
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>) -> Result<WriteStatus, NetworkError> where Message: Send + Clone {
+		self.throttled = false;
 		if self.send_queue.is_empty() {
 			return Ok(WriteStatus::Complete)
 		}
+		let allowance = self.limiter.write_allowance();
+		if allowance == 0 {
+			trace!(target:"network", "{}: Write throttled", self.token);
+			self.throttled = true;
+			return Ok(WriteStatus::Ongoing)
+		}
 		{
 			let buf = self.send_queue.front_mut().unwrap();
 			let send_size = buf.get_ref().len();
-			if (buf.position() as usize) >= send_size {
+			let pos = buf.position() as usize;
+			if pos >= send_size {
 				warn!(target:"net", "Unexpected connection data");
 				return Ok(WriteStatus::Complete)
 			}
-			match self.socket.try_write_buf(buf) {
-				Ok(Some(size)) if (buf.position() as usize) < send_size => {
+			let end = min(send_size, pos.saturating_add(allowance));
+			match self.socket.try_write(&buf.get_ref()[pos..end]) {
+				Ok(Some(size)) if pos + size < send_size => {
+					buf.set_position((pos + size) as u64);
 					self.stats.inc_send(size);
+					self.limiter.on_write(size);
+					self.throttled = end < send_size;
 					Ok(WriteStatus::Ongoing)
 				},
-				Ok(Some(size)) if (buf.position() as usize) == send_size => {
+				Ok(Some(size)) if pos + size == send_size => {
+					buf.set_position(send_size as u64);
 					self.stats.inc_send(size);
+					self.limiter.on_write(size);
 					trace!(target:"network", "{}: Wrote {} bytes", self.token, send_size);
 					Ok(WriteStatus::Complete)
 				},
@@ -163,7 +196,7 @@ pub type Connection = GenericConnection<TcpStream>;
 
 impl Connection {
 	/// Create a new connection with given id and socket.
-	pub fn new(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>) -> Connection {
+	pub fn new(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>, bandwidth: Arc<Bandwidth>) -> Connection {
 		Connection {
 			token: token,
 			socket: socket,
@@ -173,6 +206,8 @@ impl Connection {
 			interest: EventSet::hup() | EventSet::readable(),
 			stats: stats,
 			registered: AtomicBool::new(false),
+			limiter: ConnectionLimiter::new(bandwidth),
+			throttled: false,
 		}
 	}
 
@@ -202,6 +237,8 @@ impl Connection {
 			interest: EventSet::hup(),
 			stats: self.stats.clone(),
 			registered: AtomicBool::new(false),
+			limiter: self.limiter.clone(),
+			throttled: false,
 		})
 	}
 
@@ -492,6 +529,7 @@ mod tests {
 	use std::sync::Arc;
 	use std::sync::atomic::AtomicBool;
 	use super::super::stats::*;
+	use bandwidth::{Bandwidth, ConnectionLimiter};
 	use std::io::{Read, Write, Error, Cursor, ErrorKind};
 	use mio::{EventSet};
 	use std::collections::VecDeque;
@@ -542,6 +580,8 @@ mod tests {
 				interest: EventSet::hup() | EventSet::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
 				registered: AtomicBool::new(false),
+				limiter: ConnectionLimiter::new(Arc::new(Bandwidth::unlimited())),
+				throttled: false,
 			}
 		}
 	}
@@ -565,6 +605,8 @@ mod tests {
 				interest: EventSet::hup() | EventSet::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
 				registered: AtomicBool::new(false),
+				limiter: ConnectionLimiter::new(Arc::new(Bandwidth::unlimited())),
+				throttled: false,
 			}
 		}
 	}
@@ -671,4 +713,31 @@ mod tests {
 		assert!(status.is_ok());
 		assert_eq!(0, connection.socket.cursor);
 	}
+
+	#[test]
+	fn connection_write_is_throttled() {
+		let mut connection = TestConnection::new();
+		connection.limiter = ConnectionLimiter::new(Arc::new(Bandwidth::new(None, None, Some(1000), None)));
+		let data = Cursor::new(vec![0; 10240]);
+		connection.send_queue.push_back(data);
+
+		let status = connection.writable(&test_io());
+		assert!(WriteStatus::Ongoing == status.unwrap());
+		assert!(connection.is_throttled());
+		assert!(connection.socket.write_buffer.len() <= 1000);
+	}
+
+	#[test]
+	fn connection_read_is_throttled() {
+		let mut connection = TestConnection::new();
+		connection.limiter = ConnectionLimiter::new(Arc::new(Bandwidth::new(None, Some(1000), None, None)));
+		connection.rec_size = 2048;
+		connection.socket.read_buffer = vec![99; 2048];
+
+		let status = connection.readable();
+
+		assert!(status.unwrap().is_none());
+		assert!(connection.is_throttled());
+		assert!(connection.socket.cursor < 2048);
+	}
 }
@@ -0,0 +1,708 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! DNS-based node lists (EIP-1459 style).
+//!
+//! A node list is published as a Merkle tree of TXT records under a domain.
+//! The root record at `<domain>` is signed by the publisher:
+//!
+//! `enrtree-root:v1 e=<nodes-root> l=<links-root> seq=<n> sig=<base64 signature>`
+//!
+//! Every other entry lives at `<hash>.<domain>`, where `<hash>` is the base32 encoded
+//! first 16 bytes of the keccak hash of the entry text. Entries are either branches
+//! (`enrtree-branch:<hash>,<hash>,...`), node records (`enode://...`) or links to
+//! other trees (`enrtree://<key>@<domain>`). Trees are referenced by
+//! `enrtree://<base32 compressed public key>@<domain>` URLs.
+
+use std::fmt;
+use std::io;
+use std::fs::File;
+use std::io::Read;
+use std::str::FromStr;
+use std::time::Duration;
+use std::net::{SocketAddr, IpAddr, Ipv4Addr, UdpSocket};
+use std::collections::{HashSet, VecDeque};
+use rand::random;
+use rustc_serialize::base64::FromBase64;
+use util::sha3::Hashable;
+use util::hash::H256;
+use ethkey::{Signature, Public, recover};
+use node_table::{Node, NodeId};
+use discovery::NodeEntry;
+
+const ROOT_PREFIX: &'static str = "enrtree-root:v1";
+const BRANCH_PREFIX: &'static str = "enrtree-branch:";
+const TREE_PREFIX: &'static str = "enrtree://";
+const NODE_PREFIX: &'static str = "enode://";
+const BASE32_ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+const MAX_TREE_ENTRIES: usize = 10_000;
+const MAX_DNS_PACKET_SIZE: usize = 4096;
+const DNS_PORT: u16 = 53;
+const DNS_TIMEOUT_SECS: u64 = 5;
+const DNS_TYPE_TXT: u16 = 16;
+const DNS_CLASS_IN: u16 = 1;
+
+/// DNS node list error.
+#[derive(Debug)]
+pub enum DnsError {
+	/// Malformed tree URL.
+	InvalidUrl(String),
+	/// No matching TXT record for the given name.
+	NotFound(String),
+	/// Socket error while talking to the name server.
+	Io(io::Error),
+	/// Malformed or unexpected name server response.
+	InvalidResponse,
+	/// Tree entry could not be parsed.
+	InvalidEntry(String),
+	/// Tree entry content doesn't match the name it was published under.
+	HashMismatch(String),
+	/// Root record signature is malformed or not made by the tree key.
+	InvalidSignature,
+	/// Tree has more entries than we are willing to fetch.
+	TooManyEntries,
+}
+
+impl fmt::Display for DnsError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		use self::DnsError::*;
+
+		let msg = match *self {
+			InvalidUrl(ref url) => format!("Invalid DNS tree URL: {}", url),
+			NotFound(ref name) => format!("No DNS tree record found at {}", name),
+			Io(ref err) => format!("DNS query failed: {}", err),
+			InvalidResponse => "Invalid DNS response".into(),
+			InvalidEntry(ref entry) => format!("Invalid DNS tree entry: {}", entry),
+			HashMismatch(ref name) => format!("DNS tree entry at {} does not match its hash", name),
+			InvalidSignature => "Invalid DNS tree root signature".into(),
+			TooManyEntries => format!("DNS tree has more than {} entries", MAX_TREE_ENTRIES),
+		};
+
+		f.write_str(&msg)
+	}
+}
+
+impl From<io::Error> for DnsError {
+	fn from(err: io::Error) -> DnsError {
+		DnsError::Io(err)
+	}
+}
+
+/// Source of DNS TXT records.
+pub trait DnsResolver: Send + Sync {
+	/// Look up all TXT records for `name`. Character strings within a record are concatenated.
+	/// Returns an empty list if the name does not exist.
+	fn txt(&self, name: &str) -> Result<Vec<String>, DnsError>;
+}
+
+/// Reference to a signed node tree: `enrtree://<base32 compressed public key>@<domain>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TreeUrl {
+	/// Compressed public key of the tree publisher.
+	pub public: Vec<u8>,
+	/// Domain the root record is published under.
+	pub domain: String,
+}
+
+impl FromStr for TreeUrl {
+	type Err = DnsError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || DnsError::InvalidUrl(s.to_owned());
+		if !s.starts_with(TREE_PREFIX) {
+			return Err(invalid());
+		}
+		let mut parts = s[TREE_PREFIX.len()..].splitn(2, '@');
+		let key = parts.next().and_then(base32_decode).ok_or_else(&invalid);
+		let domain = parts.next().map(|d| d.trim_right_matches('.'));
+		match (key, domain) {
+			(Ok(ref key), Some(domain)) if key.len() == 33 && (key[0] == 2 || key[0] == 3) && !domain.is_empty() => Ok(TreeUrl {
+				public: key.clone(),
+				domain: domain.to_owned(),
+			}),
+			_ => Err(invalid()),
+		}
+	}
+}
+
+impl fmt::Display for TreeUrl {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}{}@{}", TREE_PREFIX, base32_encode(&self.public), self.domain)
+	}
+}
+
+/// Check if DNS tree url is valid
+pub fn is_valid_dns_tree_url(url: &str) -> bool {
+	TreeUrl::from_str(url).is_ok()
+}
+
+/// Verified root record of a tree.
+struct TreeRoot {
+	nodes_root: String,
+	links_root: String,
+	seq: u32,
+}
+
+impl TreeRoot {
+	/// Parse the root record and check it is signed by `public`.
+	fn verify(record: &str, public: &[u8]) -> Result<TreeRoot, DnsError> {
+		let invalid = || DnsError::InvalidEntry(record.to_owned());
+		let sig_pos = try!(record.find(" sig=").ok_or_else(&invalid));
+		let (signed, sig) = (&record[..sig_pos], &record[sig_pos + 5..]);
+
+		let (mut nodes_root, mut links_root, mut seq) = (None, None, None);
+		let mut fields = signed.split(' ');
+		if fields.next() != Some(ROOT_PREFIX) {
+			return Err(invalid());
+		}
+		for field in fields {
+			if field.starts_with("e=") {
+				nodes_root = Some(field[2..].to_owned());
+			} else if field.starts_with("l=") {
+				links_root = Some(field[2..].to_owned());
+			} else if field.starts_with("seq=") {
+				seq = field[4..].parse::<u32>().ok();
+			}
+		}
+
+		let sig = try!(sig.from_base64().map_err(|_| DnsError::InvalidSignature));
+		if sig.len() != 65 {
+			return Err(DnsError::InvalidSignature);
+		}
+		let mut data = [0u8; 65];
+		data.copy_from_slice(&sig);
+		let signer = try!(recover(&Signature::from(data), &signed.sha3()).map_err(|_| DnsError::InvalidSignature));
+		if &compress_public(&signer)[..] != public {
+			return Err(DnsError::InvalidSignature);
+		}
+
+		match (nodes_root, links_root, seq) {
+			(Some(nodes_root), Some(links_root), Some(seq)) => Ok(TreeRoot {
+				nodes_root: nodes_root,
+				links_root: links_root,
+				seq: seq,
+			}),
+			_ => Err(invalid()),
+		}
+	}
+}
+
+/// Non-root tree entry.
+enum TreeEntry {
+	Branch(Vec<String>),
+	Node(NodeEntry),
+	Link(TreeUrl),
+}
+
+impl FromStr for TreeEntry {
+	type Err = DnsError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.starts_with(BRANCH_PREFIX) {
+			Ok(TreeEntry::Branch(s[BRANCH_PREFIX.len()..].split(',').filter(|h| !h.is_empty()).map(|h| h.to_owned()).collect()))
+		} else if s.starts_with(TREE_PREFIX) {
+			Ok(TreeEntry::Link(try!(TreeUrl::from_str(s))))
+		} else if s.starts_with(NODE_PREFIX) {
+			match Node::from_str(s) {
+				Ok(ref node) if node.id != NodeId::new() => Ok(TreeEntry::Node(NodeEntry { id: node.id.clone(), endpoint: node.endpoint.clone() })),
+				_ => Err(DnsError::InvalidEntry(s.to_owned())),
+			}
+		} else {
+			Err(DnsError::InvalidEntry(s.to_owned()))
+		}
+	}
+}
+
+/// A node list published over DNS.
+pub struct DnsTree {
+	url: TreeUrl,
+	seq: Option<u32>,
+	nodes: Vec<NodeEntry>,
+	links: Vec<TreeUrl>,
+}
+
+impl DnsTree {
+	/// Create a new, not yet resolved, tree.
+	pub fn new(url: TreeUrl) -> DnsTree {
+		DnsTree {
+			url: url,
+			seq: None,
+			nodes: Vec::new(),
+			links: Vec::new(),
+		}
+	}
+
+	/// Tree URL.
+	pub fn url(&self) -> &TreeUrl {
+		&self.url
+	}
+
+	/// Nodes listed in the tree as of the last successful sync.
+	pub fn nodes(&self) -> &[NodeEntry] {
+		&self.nodes
+	}
+
+	/// Other trees linked from this one as of the last successful sync.
+	pub fn links(&self) -> &[TreeUrl] {
+		&self.links
+	}
+
+	/// Fetch and verify the tree. The entries are only walked if the root sequence number
+	/// has changed since the last successful sync. Returns `true` if the tree was updated.
+	pub fn sync(&mut self, resolver: &DnsResolver) -> Result<bool, DnsError> {
+		let root = try!(self.resolve_root(resolver));
+		if self.seq == Some(root.seq) {
+			return Ok(false);
+		}
+
+		let mut nodes = Vec::new();
+		let mut links = Vec::new();
+		let mut budget = MAX_TREE_ENTRIES;
+		try!(self.walk(resolver, &root.nodes_root, &mut budget, &mut |entry| match entry {
+			TreeEntry::Node(node) => { nodes.push(node); true },
+			_ => false,
+		}));
+		try!(self.walk(resolver, &root.links_root, &mut budget, &mut |entry| match entry {
+			TreeEntry::Link(url) => { links.push(url); true },
+			_ => false,
+		}));
+
+		trace!(target: "network", "DNS tree {} seq {}: {} nodes, {} links", self.url, root.seq, nodes.len(), links.len());
+		self.seq = Some(root.seq);
+		self.nodes = nodes;
+		self.links = links;
+		Ok(true)
+	}
+
+	fn resolve_root(&self, resolver: &DnsResolver) -> Result<TreeRoot, DnsError> {
+		let records = try!(resolver.txt(&self.url.domain));
+		match records.iter().find(|r| r.starts_with(ROOT_PREFIX)) {
+			Some(record) => TreeRoot::verify(record, &self.url.public),
+			None => Err(DnsError::NotFound(self.url.domain.clone())),
+		}
+	}
+
+	/// Walk the subtree starting at `root`, passing every leaf to `leaf`. A leaf that is not
+	/// accepted by `leaf` makes the whole tree invalid.
+	fn walk<F>(&self, resolver: &DnsResolver, root: &str, budget: &mut usize, leaf: &mut F) -> Result<(), DnsError>
+		where F: FnMut(TreeEntry) -> bool
+	{
+		let mut visited = HashSet::new();
+		let mut queue = VecDeque::new();
+		queue.push_back(root.to_owned());
+
+		while let Some(hash) = queue.pop_front() {
+			if !visited.insert(hash.clone()) {
+				continue;
+			}
+			if *budget == 0 {
+				return Err(DnsError::TooManyEntries);
+			}
+			*budget -= 1;
+
+			let name = format!("{}.{}", hash, self.url.domain);
+			let records = try!(resolver.txt(&name));
+			if records.is_empty() {
+				return Err(DnsError::NotFound(name));
+			}
+			let expected = hash.to_uppercase();
+			let record = try!(records.iter().find(|r| entry_hash(r) == expected).ok_or_else(|| DnsError::HashMismatch(name.clone())));
+			match try!(TreeEntry::from_str(record)) {
+				TreeEntry::Branch(children) => queue.extend(children),
+				entry => if !leaf(entry) {
+					return Err(DnsError::InvalidEntry(record.clone()));
+				},
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Resolver that sends TXT queries over UDP to a name server, by default the first one
+/// listed in `/etc/resolv.conf`.
+pub struct SystemResolver {
+	server: SocketAddr,
+	timeout: Duration,
+}
+
+impl SystemResolver {
+	/// Create a resolver using the system configured name server.
+	pub fn new() -> SystemResolver {
+		SystemResolver::with_server(system_name_server().unwrap_or_else(|| SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), DNS_PORT)))
+	}
+
+	/// Create a resolver using the given name server.
+	pub fn with_server(server: SocketAddr) -> SystemResolver {
+		SystemResolver {
+			server: server,
+			timeout: Duration::from_secs(DNS_TIMEOUT_SECS),
+		}
+	}
+}
+
+impl Default for SystemResolver {
+	fn default() -> Self {
+		SystemResolver::new()
+	}
+}
+
+impl DnsResolver for SystemResolver {
+	fn txt(&self, name: &str) -> Result<Vec<String>, DnsError> {
+		let id = random::<u16>();
+		let query = try!(encode_txt_query(id, name));
+		let bind_address = match self.server {
+			SocketAddr::V4(_) => "0.0.0.0:0",
+			SocketAddr::V6(_) => "[::]:0",
+		};
+		let socket = try!(UdpSocket::bind(bind_address));
+		try!(socket.set_read_timeout(Some(self.timeout)));
+		try!(socket.send_to(&query, &self.server));
+
+		let mut buf = [0u8; MAX_DNS_PACKET_SIZE];
+		loop {
+			let (len, from) = try!(socket.recv_from(&mut buf));
+			if from == self.server {
+				return decode_txt_response(id, &buf[..len]);
+			}
+		}
+	}
+}
+
+fn system_name_server() -> Option<SocketAddr> {
+	let mut conf = String::new();
+	if let Err(e) = File::open("/etc/resolv.conf").and_then(|mut f| f.read_to_string(&mut conf)) {
+		debug!(target: "network", "Error reading resolv.conf: {:?}", e);
+		return None;
+	}
+	conf.lines()
+		.map(|l| l.trim())
+		.filter(|l| l.starts_with("nameserver"))
+		.filter_map(|l| l.split_whitespace().nth(1))
+		.filter_map(|ip| ip.parse::<IpAddr>().ok())
+		.map(|ip| SocketAddr::new(ip, DNS_PORT))
+		.next()
+}
+
+fn encode_txt_query(id: u16, name: &str) -> Result<Vec<u8>, DnsError> {
+	let mut query = Vec::with_capacity(18 + name.len());
+	// header: id, recursion desired, one question
+	query.extend_from_slice(&[(id >> 8) as u8, id as u8, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+	for label in name.trim_right_matches('.').split('.') {
+		if label.is_empty() || label.len() > 63 {
+			return Err(DnsError::InvalidUrl(name.to_owned()));
+		}
+		query.push(label.len() as u8);
+		query.extend_from_slice(label.as_bytes());
+	}
+	query.push(0);
+	query.extend_from_slice(&[(DNS_TYPE_TXT >> 8) as u8, DNS_TYPE_TXT as u8, (DNS_CLASS_IN >> 8) as u8, DNS_CLASS_IN as u8]);
+	Ok(query)
+}
+
+fn decode_txt_response(id: u16, data: &[u8]) -> Result<Vec<String>, DnsError> {
+	let read_u16 = |pos: usize| -> Result<u16, DnsError> {
+		if pos + 2 > data.len() {
+			return Err(DnsError::InvalidResponse);
+		}
+		Ok(((data[pos] as u16) << 8) | data[pos + 1] as u16)
+	};
+
+	let flags = try!(read_u16(2));
+	let is_response = flags & 0x8000 != 0;
+	let truncated = flags & 0x0200 != 0;
+	if try!(read_u16(0)) != id || !is_response || truncated {
+		return Err(DnsError::InvalidResponse);
+	}
+	match flags & 0x000f {
+		0 => {},
+		3 => return Ok(Vec::new()), // NXDOMAIN
+		_ => return Err(DnsError::InvalidResponse),
+	}
+
+	let questions = try!(read_u16(4));
+	let answers = try!(read_u16(6));
+	let mut pos = 12;
+	for _ in 0..questions {
+		pos = try!(skip_name(data, pos)) + 4;
+	}
+
+	let mut records = Vec::new();
+	for _ in 0..answers {
+		pos = try!(skip_name(data, pos));
+		let record_type = try!(read_u16(pos));
+		let len = try!(read_u16(pos + 8)) as usize;
+		pos += 10;
+		if pos + len > data.len() {
+			return Err(DnsError::InvalidResponse);
+		}
+		if record_type == DNS_TYPE_TXT {
+			let rdata = &data[pos..pos + len];
+			let mut text = Vec::with_capacity(len);
+			let mut i = 0;
+			while i < rdata.len() {
+				let end = i + 1 + rdata[i] as usize;
+				if end > rdata.len() {
+					return Err(DnsError::InvalidResponse);
+				}
+				text.extend_from_slice(&rdata[i + 1..end]);
+				i = end;
+			}
+			match String::from_utf8(text) {
+				Ok(text) => records.push(text),
+				Err(_) => trace!(target: "network", "Ignoring non-UTF8 TXT record"),
+			}
+		}
+		pos += len;
+	}
+	Ok(records)
+}
+
+fn skip_name(data: &[u8], mut pos: usize) -> Result<usize, DnsError> {
+	loop {
+		let len = match data.get(pos) {
+			Some(len) => *len as usize,
+			None => return Err(DnsError::InvalidResponse),
+		};
+		if len & 0xc0 == 0xc0 {
+			// compression pointer terminates the name
+			return Ok(pos + 2);
+		}
+		if len == 0 {
+			return Ok(pos + 1);
+		}
+		pos += 1 + len;
+	}
+}
+
+/// Subdomain an entry is published under.
+fn entry_hash(entry: &str) -> String {
+	let hash: H256 = entry.sha3();
+	base32_encode(&hash[0..16])
+}
+
+fn compress_public(public: &Public) -> [u8; 33] {
+	let mut compressed = [0u8; 33];
+	compressed[0] = 2 + (public[63] & 1);
+	compressed[1..].copy_from_slice(&public[0..32]);
+	compressed
+}
+
+/// Unpadded RFC 4648 base32.
+fn base32_encode(data: &[u8]) -> String {
+	let mut out = String::with_capacity((data.len() * 8 + 4) / 5);
+	let (mut buffer, mut bits) = (0u32, 0u32);
+	for b in data {
+		buffer = ((buffer << 8) | *b as u32) & 0xffff;
+		bits += 8;
+		while bits >= 5 {
+			bits -= 5;
+			out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+		}
+	}
+	if bits > 0 {
+		out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+	}
+	out
+}
+
+fn base32_decode(s: &str) -> Option<Vec<u8>> {
+	let mut out = Vec::with_capacity(s.len() * 5 / 8);
+	let (mut buffer, mut bits) = (0u32, 0u32);
+	for c in s.bytes() {
+		let value = match c {
+			b'A'...b'Z' => c - b'A',
+			b'a'...b'z' => c - b'a',
+			b'2'...b'7' => c - b'2' + 26,
+			_ => return None,
+		};
+		buffer = ((buffer << 5) | value as u32) & 0xffff;
+		bits += 5;
+		if bits >= 8 {
+			bits -= 8;
+			out.push((buffer >> bits) as u8);
+		}
+	}
+	Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::{base32_encode, base32_decode, compress_public, entry_hash, encode_txt_query, decode_txt_response};
+	use std::collections::HashMap;
+	use std::str::FromStr;
+	use node_table::NodeId;
+	use rustc_serialize::base64::{ToBase64, URL_SAFE};
+	use util::sha3::Hashable;
+	use ethkey::{KeyPair, Random, Generator, sign};
+
+	const NODE1: &'static str = "enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770";
+	const NODE2: &'static str = "enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.45:7770";
+
+	#[derive(Default)]
+	struct StubResolver {
+		records: HashMap<String, Vec<String>>,
+	}
+
+	impl StubResolver {
+		fn add_entry(&mut self, domain: &str, entry: &str) -> String {
+			let hash = entry_hash(entry);
+			self.records.insert(format!("{}.{}", hash, domain), vec![entry.to_owned()]);
+			hash
+		}
+
+		fn set_root(&mut self, domain: &str, key: &KeyPair, nodes_root: &str, links_root: &str, seq: u32) {
+			let signed = format!("enrtree-root:v1 e={} l={} seq={}", nodes_root, links_root, seq);
+			let sig = sign(key.secret(), &signed.sha3()).unwrap();
+			self.records.insert(domain.to_owned(), vec![format!("{} sig={}", signed, sig[..].to_base64(URL_SAFE))]);
+		}
+	}
+
+	impl DnsResolver for StubResolver {
+		fn txt(&self, name: &str) -> Result<Vec<String>, DnsError> {
+			Ok(self.records.get(name).cloned().unwrap_or_else(Vec::new))
+		}
+	}
+
+	fn tree_url(key: &KeyPair, domain: &str) -> TreeUrl {
+		TreeUrl { public: compress_public(key.public()).to_vec(), domain: domain.to_owned() }
+	}
+
+	fn two_node_tree(key: &KeyPair, domain: &str) -> StubResolver {
+		let mut resolver = StubResolver::default();
+		let h1 = resolver.add_entry(domain, NODE1);
+		let h2 = resolver.add_entry(domain, NODE2);
+		let nodes_root = resolver.add_entry(domain, &format!("enrtree-branch:{},{}", h1, h2));
+		let links_root = resolver.add_entry(domain, "enrtree-branch:");
+		resolver.set_root(domain, key, &nodes_root, &links_root, 1);
+		resolver
+	}
+
+	#[test]
+	fn base32_roundtrip() {
+		assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+		assert_eq!(base32_decode("MZXW6YTBOI").unwrap(), b"foobar".to_vec());
+		assert_eq!(base32_decode("mzxw6ytboi").unwrap(), b"foobar".to_vec());
+		assert!(base32_decode("MZXW6YTBO1").is_none());
+	}
+
+	#[test]
+	fn tree_url_parse() {
+		let key = Random.generate().unwrap();
+		let url = tree_url(&key, "nodes.example.org");
+		let parsed = TreeUrl::from_str(&format!("{}", url)).unwrap();
+		assert_eq!(parsed, url);
+		assert!(is_valid_dns_tree_url(&format!("{}", url)));
+		assert!(!is_valid_dns_tree_url("enrtree://nodes.example.org"));
+		assert!(!is_valid_dns_tree_url("enrtree://AAAA@nodes.example.org"));
+		assert!(!is_valid_dns_tree_url(NODE1));
+	}
+
+	#[test]
+	fn resolves_signed_tree() {
+		let key = Random.generate().unwrap();
+		let resolver = two_node_tree(&key, "nodes.example.org");
+		let mut tree = DnsTree::new(tree_url(&key, "nodes.example.org"));
+
+		assert!(tree.sync(&resolver).unwrap());
+		let mut ids: Vec<_> = tree.nodes().iter().map(|n| n.id.clone()).collect();
+		ids.sort();
+		assert_eq!(ids, vec![
+			NodeId::from_str("a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c").unwrap(),
+			NodeId::from_str("b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c").unwrap(),
+		]);
+		assert!(tree.links().is_empty());
+
+		// same sequence number, nothing to do
+		assert!(!tree.sync(&resolver).unwrap());
+	}
+
+	#[test]
+	fn collects_links() {
+		let key = Random.generate().unwrap();
+		let other = tree_url(&Random.generate().unwrap(), "other.example.org");
+		let mut resolver = StubResolver::default();
+		let nodes_root = resolver.add_entry("nodes.example.org", "enrtree-branch:");
+		let links_root = resolver.add_entry("nodes.example.org", &format!("{}", other));
+		resolver.set_root("nodes.example.org", &key, &nodes_root, &links_root, 3);
+
+		let mut tree = DnsTree::new(tree_url(&key, "nodes.example.org"));
+		assert!(tree.sync(&resolver).unwrap());
+		assert!(tree.nodes().is_empty());
+		assert_eq!(tree.links(), &[other]);
+	}
+
+	#[test]
+	fn rejects_foreign_signature() {
+		let key = Random.generate().unwrap();
+		let resolver = two_node_tree(&Random.generate().unwrap(), "nodes.example.org");
+		let mut tree = DnsTree::new(tree_url(&key, "nodes.example.org"));
+
+		match tree.sync(&resolver) {
+			Err(DnsError::InvalidSignature) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		assert!(tree.nodes().is_empty());
+	}
+
+	#[test]
+	fn rejects_tampered_entry() {
+		let key = Random.generate().unwrap();
+		let mut resolver = two_node_tree(&key, "nodes.example.org");
+		let h1 = entry_hash(NODE1);
+		resolver.records.insert(format!("{}.nodes.example.org", h1), vec![NODE2.to_owned()]);
+		let mut tree = DnsTree::new(tree_url(&key, "nodes.example.org"));
+
+		match tree.sync(&resolver) {
+			Err(DnsError::HashMismatch(_)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn rejects_node_in_link_tree() {
+		let key = Random.generate().unwrap();
+		let mut resolver = StubResolver::default();
+		let nodes_root = resolver.add_entry("nodes.example.org", "enrtree-branch:");
+		let links_root = resolver.add_entry("nodes.example.org", NODE1);
+		resolver.set_root("nodes.example.org", &key, &nodes_root, &links_root, 1);
+		let mut tree = DnsTree::new(tree_url(&key, "nodes.example.org"));
+
+		match tree.sync(&resolver) {
+			Err(DnsError::InvalidEntry(_)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn txt_response_decode() {
+		let query = encode_txt_query(0x1234, "nodes.example.org").unwrap();
+		let mut response = query.clone();
+		// response, recursion available, one answer
+		response[2] = 0x81;
+		response[3] = 0x80;
+		response[7] = 1;
+		// answer: pointer to the question name, TXT, IN, TTL, two character strings
+		response.extend_from_slice(&[0xc0, 12, 0, 16, 0, 1, 0, 0, 0, 60, 0, 9, 3, b'f', b'o', b'o', 4, b'-', b'b', b'a', b'r']);
+		assert_eq!(decode_txt_response(0x1234, &response).unwrap(), vec!["foo-bar".to_owned()]);
+		assert!(decode_txt_response(0x4321, &response).is_err());
+
+		// NXDOMAIN
+		response[3] = 0x83;
+		assert!(decode_txt_response(0x1234, &response).unwrap().is_empty());
+	}
+}
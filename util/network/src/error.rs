@@ -20,6 +20,7 @@ use util::UtilError;
 use std::fmt;
 use ethkey::Error as KeyError;
 use crypto::Error as CryptoError;
+use dns::DnsError;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum DisconnectReason
@@ -106,6 +107,8 @@ pub enum NetworkError {
 	AddressResolve(Option<::std::io::Error>),
 	/// Error concerning the Rust standard library's IO subsystem.
 	StdIo(::std::io::Error),
+	/// Error resolving a DNS node list.
+	Dns(DnsError),
 }
 
 impl fmt::Display for NetworkError {
@@ -124,6 +127,7 @@ impl fmt::Display for NetworkError {
 			AddressResolve(_) => "Failed to resolve network address.".into(),
 			StdIo(ref err) => format!("{}", err),
 			Util(ref err) => format!("{}", err),
+			Dns(ref err) => format!("{}", err),
 		};
 
 		f.write_fmt(format_args!("Network error ({})", msg))
@@ -166,6 +170,12 @@ impl From<CryptoError> for NetworkError {
 	}
 }
 
+impl From<DnsError> for NetworkError {
+	fn from(err: DnsError) -> NetworkError {
+		NetworkError::Dns(err)
+	}
+}
+
 impl From<::std::net::AddrParseError> for NetworkError {
 	fn from(err: ::std::net::AddrParseError) -> NetworkError {
 		NetworkError::AddressParse(err)
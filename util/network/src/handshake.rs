@@ -26,6 +26,7 @@ use host::{HostInfo};
 use node_table::NodeId;
 use error::*;
 use stats::NetworkStats;
+use bandwidth::Bandwidth;
 use io::{IoContext, StreamToken};
 use ethkey::{KeyPair, Public, Secret, recover, sign, Generator, Random};
 use crypto::{ecdh, ecies};
@@ -83,10 +84,10 @@ const ECIES_OVERHEAD: usize = 113;
 
 impl Handshake {
 	/// Create a new handshake object
-	pub fn new(token: StreamToken, id: Option<&NodeId>, socket: TcpStream, nonce: &H256, stats: Arc<NetworkStats>) -> Result<Handshake, NetworkError> {
+	pub fn new(token: StreamToken, id: Option<&NodeId>, socket: TcpStream, nonce: &H256, stats: Arc<NetworkStats>, bandwidth: Arc<Bandwidth>) -> Result<Handshake, NetworkError> {
 		Ok(Handshake {
 			id: if let Some(id) = id { id.clone()} else { NodeId::new() },
-			connection: Connection::new(token, socket, stats),
+			connection: Connection::new(token, socket, stats, bandwidth),
 			originated: false,
 			state: HandshakeState::New,
 			ecdhe: try!(Random.generate()),
@@ -337,6 +338,7 @@ mod test {
 	use io::*;
 	use mio::tcp::TcpStream;
 	use stats::NetworkStats;
+	use bandwidth::Bandwidth;
 	use ethkey::Public;
 
 	fn check_auth(h: &Handshake, version: u64) {
@@ -356,7 +358,7 @@ mod test {
 		let addr = "127.0.0.1:50556".parse().unwrap();
 		let socket = TcpStream::connect(&addr).unwrap();
 		let nonce = H256::new();
-		Handshake::new(0, to, socket, &nonce, Arc::new(NetworkStats::new())).unwrap()
+		Handshake::new(0, to, socket, &nonce, Arc::new(NetworkStats::new()), Arc::new(Bandwidth::unlimited())).unwrap()
 	}
 
 	fn test_io() -> IoContext<i32> {
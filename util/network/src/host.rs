@@ -24,6 +24,7 @@ use std::cmp::min;
 use std::path::{Path, PathBuf};
 use std::io::{Read, Write};
 use std::fs;
+use std::thread;
 use ethkey::{KeyPair, Secret, Random, Generator};
 use mio::*;
 use mio::tcp::*;
@@ -37,7 +38,9 @@ use io::*;
 use {NetworkProtocolHandler, NonReservedPeerMode, PROTOCOL_VERSION};
 use node_table::*;
 use stats::NetworkStats;
+use bandwidth::Bandwidth;
 use discovery::{Discovery, TableUpdates, NodeEntry};
+use dns::{DnsTree, DnsResolver, TreeUrl};
 use ip_utils::{map_external_address, select_public_address};
 use util::path::restrict_permissions_owner;
 use parking_lot::{Mutex, RwLock};
@@ -48,6 +51,10 @@ const MAX_SESSIONS: usize = 1024 + MAX_HANDSHAKES;
 const MAX_HANDSHAKES: usize = 80;
 const MAX_HANDSHAKES_PER_ROUND: usize = 32;
 const MAINTENANCE_TIMEOUT: u64 = 1000;
+const DNS_TREE_TIMEOUT: u64 = 30 * 60 * 1000;
+const MAX_DNS_TREES: usize = 32;
+// Interval for resuming IO on connections stalled by the bandwidth limits.
+const THROTTLE_TIMEOUT: u64 = 100;
 
 #[derive(Debug, PartialEq, Clone)]
 /// Network service configuration
@@ -68,6 +75,8 @@ pub struct NetworkConfiguration {
 	pub discovery_enabled: bool,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
+	/// List of `enrtree://` URLs of signed node lists published over DNS
+	pub dns_trees: Vec<String>,
 	/// Use provided node key instead of default
 	pub use_secret: Option<Secret>,
 	/// Minimum number of connected peers to maintain
@@ -78,6 +87,14 @@ pub struct NetworkConfiguration {
 	pub reserved_nodes: Vec<String>,
 	/// The non-reserved peer mode.
 	pub non_reserved_mode: NonReservedPeerMode,
+	/// Total upload limit in bytes per second. Unlimited if none.
+	pub max_upload: Option<u64>,
+	/// Total download limit in bytes per second. Unlimited if none.
+	pub max_download: Option<u64>,
+	/// Per peer upload limit in bytes per second. Unlimited if none.
+	pub max_peer_upload: Option<u64>,
+	/// Per peer download limit in bytes per second. Unlimited if none.
+	pub max_peer_download: Option<u64>,
 }
 
 impl Default for NetworkConfiguration {
@@ -98,11 +115,16 @@ impl NetworkConfiguration {
 			nat_enabled: true,
 			discovery_enabled: true,
 			boot_nodes: Vec::new(),
+			dns_trees: Vec::new(),
 			use_secret: None,
 			min_peers: 25,
 			max_peers: 50,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			max_upload: None,
+			max_download: None,
+			max_peer_upload: None,
+			max_peer_download: None,
 		}
 	}
 
@@ -129,6 +151,8 @@ const DISCOVERY: usize = SYS_TIMER + 3;
 const DISCOVERY_REFRESH: usize = SYS_TIMER + 4;
 const DISCOVERY_ROUND: usize = SYS_TIMER + 5;
 const NODE_TABLE: usize = SYS_TIMER + 6;
+const DNS_TREE: usize = SYS_TIMER + 7;
+const THROTTLE: usize = SYS_TIMER + 8;
 const FIRST_SESSION: usize = 0;
 const LAST_SESSION: usize = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: usize = LAST_SESSION + 256;
@@ -168,6 +192,8 @@ pub enum NetworkIoMessage {
 	DisablePeer(PeerId),
 	/// Network has been started with the host as the given enode.
 	NetworkStarted(String),
+	/// Nodes resolved from DNS node lists in the background.
+	DnsNodes(Vec<NodeEntry>),
 }
 
 /// Local (temporary) peer session ID.
@@ -348,13 +374,18 @@ pub struct Host {
 	timer_counter: RwLock<usize>,
 	stats: Arc<NetworkStats>,
 	reserved_nodes: RwLock<HashSet<NodeId>>,
+	dns_trees: Arc<Mutex<Vec<DnsTree>>>,
+	dns_resolver: Arc<DnsResolver>,
+	dns_refreshing: Arc<AtomicBool>,
+	bandwidth: Arc<Bandwidth>,
+	throttled: Mutex<HashSet<StreamToken>>,
 	num_sessions: AtomicUsize,
 	stopping: AtomicBool,
 }
 
 impl Host {
 	/// Create a new instance
-	pub fn new(config: NetworkConfiguration, stats: Arc<NetworkStats>) -> Result<Host, NetworkError> {
+	pub fn new(config: NetworkConfiguration, stats: Arc<NetworkStats>, dns_resolver: Arc<DnsResolver>) -> Result<Host, NetworkError> {
 		trace!(target: "host", "Creating new Host object");
 
 		let mut listen_address = match config.listen_address {
@@ -384,6 +415,12 @@ impl Host {
 
 		let boot_nodes = config.boot_nodes.clone();
 		let reserved_nodes = config.reserved_nodes.clone();
+		let mut dns_trees = Vec::new();
+		for url in &config.dns_trees {
+			dns_trees.push(DnsTree::new(try!(TreeUrl::from_str(url))));
+		}
+
+		let bandwidth = Bandwidth::new(config.max_upload, config.max_download, config.max_peer_upload, config.max_peer_download);
 
 		let mut host = Host {
 			info: RwLock::new(HostInfo {
@@ -405,6 +442,11 @@ impl Host {
 			timer_counter: RwLock::new(USER_TIMER),
 			stats: stats,
 			reserved_nodes: RwLock::new(HashSet::new()),
+			dns_trees: Arc::new(Mutex::new(dns_trees)),
+			dns_resolver: dns_resolver,
+			dns_refreshing: Arc::new(AtomicBool::new(false)),
+			bandwidth: Arc::new(bandwidth),
+			throttled: Mutex::new(HashSet::new()),
 			num_sessions: AtomicUsize::new(0),
 			stopping: AtomicBool::new(false),
 		};
@@ -567,9 +609,54 @@ impl Host {
 		}
 		try!(io.register_timer(NODE_TABLE, 300_000));
 		try!(io.register_stream(TCP_ACCEPT));
+		if !self.dns_trees.lock().is_empty() {
+			self.refresh_dns_trees(io);
+			try!(io.register_timer(DNS_TREE, DNS_TREE_TIMEOUT));
+		}
 		Ok(())
 	}
 
+	/// Re-resolve DNS node lists on a background thread. Lookups block, so they must
+	/// not run on the IO thread; resolved nodes come back as `NetworkIoMessage::DnsNodes`.
+	fn refresh_dns_trees(&self, io: &IoContext<NetworkIoMessage>) {
+		if self.dns_refreshing.swap(true, AtomicOrdering::AcqRel) {
+			trace!(target: "network", "DNS trees are still being resolved");
+			return;
+		}
+
+		let trees = self.dns_trees.clone();
+		let resolver = self.dns_resolver.clone();
+		let refreshing = self.dns_refreshing.clone();
+		let channel = io.channel();
+		let spawned = thread::Builder::new().name("dns-trees".into()).spawn(move || {
+			let entries = sync_dns_trees(&mut *trees.lock(), &*resolver);
+			refreshing.store(false, AtomicOrdering::Release);
+			if !entries.is_empty() {
+				channel.send(NetworkIoMessage::DnsNodes(entries))
+					.unwrap_or_else(|e| debug!(target: "network", "Error sending DNS nodes: {:?}", e));
+			}
+		});
+
+		if let Err(e) = spawned {
+			warn!(target: "network", "Error spawning DNS resolver thread: {:?}", e);
+			self.dns_refreshing.store(false, AtomicOrdering::Release);
+		}
+	}
+
+	/// Feed nodes resolved from DNS node lists into the node table and discovery.
+	fn add_dns_nodes(&self, io: &IoContext<NetworkIoMessage>, entries: Vec<NodeEntry>) {
+		{
+			let mut nodes = self.nodes.write();
+			for e in &entries {
+				nodes.add_node(Node::new(e.id.clone(), e.endpoint.clone()));
+			}
+		}
+		if let Some(ref mut discovery) = *self.discovery.lock() {
+			discovery.init_node_list(entries);
+			io.update_registration(DISCOVERY).unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
+		}
+	}
+
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage>) {
 		self.keep_alive(io);
 		self.connect_peers(io);
@@ -696,7 +783,7 @@ impl Host {
 		let mut sessions = self.sessions.write();
 
 		let token = sessions.insert_with_opt(|token| {
-			match Session::new(io, socket, token, id, &nonce, self.stats.clone(), &self.info.read()) {
+			match Session::new(io, socket, token, id, &nonce, self.stats.clone(), self.bandwidth.clone(), &self.info.read()) {
 				Ok(s) => Some(Arc::new(Mutex::new(s))),
 				Err(e) => {
 					debug!(target: "network", "Session create error: {:?}", e);
@@ -739,6 +826,9 @@ impl Host {
 			if let Err(e) = s.writable(io, &self.info.read()) {
 				trace!(target: "network", "Session write error: {}: {:?}", token, e);
 			}
+			if s.is_throttled() {
+				self.throttled.lock().insert(token);
+			}
 			if s.done() {
 				io.deregister_stream(token).unwrap_or_else(|e| debug!("Error deregistering stream: {:?}", e));
 			}
@@ -817,7 +907,12 @@ impl Host {
 						}
 					},
 					Ok(SessionData::Continue) => (),
-					Ok(SessionData::None) => break,
+					Ok(SessionData::None) => {
+						if s.is_throttled() {
+							self.throttled.lock().insert(token);
+						}
+						break;
+					},
 				}
             }
 		}
@@ -838,6 +933,15 @@ impl Host {
 		}
 	}
 
+	/// Resume reading and writing on the connections stalled by the bandwidth limits.
+	fn resume_throttled(&self, io: &IoContext<NetworkIoMessage>) {
+		let tokens: Vec<StreamToken> = self.throttled.lock().drain().collect();
+		for token in tokens {
+			self.session_writable(token, io);
+			self.session_readable(token, io);
+		}
+	}
+
 	fn connection_timeout(&self, token: StreamToken, io: &IoContext<NetworkIoMessage>) {
 		trace!(target: "network", "Connection timeout: {}", token);
 		self.kill_connection(token, io, true)
@@ -915,6 +1019,9 @@ impl IoHandler<NetworkIoMessage> for Host {
 	/// Initialize networking
 	fn initialize(&self, io: &IoContext<NetworkIoMessage>) {
 		io.register_timer(IDLE, MAINTENANCE_TIMEOUT).expect("Error registering Network idle timer");
+		if self.bandwidth.is_limited() {
+			io.register_timer(THROTTLE, THROTTLE_TIMEOUT).expect("Error registering Network throttle timer");
+		}
 		io.message(NetworkIoMessage::InitPublicInterface).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
 		self.maintain_network(io)
 	}
@@ -979,6 +1086,8 @@ impl IoHandler<NetworkIoMessage> for Host {
 				trace!(target: "network", "Refreshing node table");
 				self.nodes.write().clear_useless();
 			},
+			DNS_TREE => self.refresh_dns_trees(io),
+			THROTTLE => self.resume_throttled(io),
 			_ => match self.timers.read().get(&token).cloned() {
 				Some(timer) => match self.handlers.read().get(timer.protocol).cloned() {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
@@ -1047,6 +1156,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 			},
 			NetworkIoMessage::InitPublicInterface =>
 				self.init_public_interface(io).unwrap_or_else(|e| warn!("Error initializing public interface: {:?}", e)),
+			NetworkIoMessage::DnsNodes(ref entries) => self.add_dns_nodes(io, entries.clone()),
 			_ => {}	// ignore others.
 		}
 	}
@@ -1094,6 +1204,33 @@ impl IoHandler<NetworkIoMessage> for Host {
 	}
 }
 
+/// Sync all DNS node lists, following links to other lists, and return nodes of the updated ones.
+fn sync_dns_trees(trees: &mut Vec<DnsTree>, resolver: &DnsResolver) -> Vec<NodeEntry> {
+	let mut entries = Vec::new();
+	let mut i = 0;
+	while i < trees.len() {
+		let links = match trees[i].sync(resolver) {
+			Ok(true) => {
+				debug!(target: "network", "DNS tree {} updated: {} nodes", trees[i].url(), trees[i].nodes().len());
+				entries.extend(trees[i].nodes().iter().cloned());
+				trees[i].links().to_vec()
+			},
+			Ok(false) => Vec::new(),
+			Err(e) => {
+				warn!(target: "network", "Error resolving DNS tree {}: {}", trees[i].url(), e);
+				Vec::new()
+			},
+		};
+		for link in links {
+			if trees.len() < MAX_DNS_TREES && !trees.iter().any(|t| t.url() == &link) {
+				trees.push(DnsTree::new(link));
+			}
+		}
+		i += 1;
+	}
+	entries
+}
+
 fn save_key(path: &Path, key: &Secret) {
 	let mut path_buf = PathBuf::from(path);
 	if let Err(e) = fs::create_dir_all(path_buf.as_path()) {
@@ -1160,6 +1297,6 @@ fn host_client_url() {
 	let mut config = NetworkConfiguration::new();
 	let key = "6f7b0d801bc7b5ce7bbd930b84fd0369b3eb25d09be58d64ba811091046f3aa2".into();
 	config.use_secret = Some(key);
-	let host: Host = Host::new(config, Arc::new(NetworkStats::new())).unwrap();
+	let host: Host = Host::new(config, Arc::new(NetworkStats::new()), Arc::new(::dns::SystemResolver::new())).unwrap();
 	assert!(host.local_url().starts_with("enode://101b3ef5a4ea7a1c7928e24c4c75fd053c235d7b80c22ae5c03d145d0ac7396e2a4ffff9adee3133a7b05044a5cee08115fd65145e5165d646bde371010d803c@"));
 }
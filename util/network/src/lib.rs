@@ -87,6 +87,8 @@ mod error;
 mod node_table;
 mod stats;
 mod ip_utils;
+mod dns;
+mod bandwidth;
 
 #[cfg(test)]
 mod tests;
@@ -98,7 +100,8 @@ pub use service::NetworkService;
 pub use host::NetworkIoMessage;
 pub use error::NetworkError;
 pub use host::NetworkConfiguration;
-pub use stats::NetworkStats;
+pub use stats::{NetworkStats, PacketStats, PacketCounters};
+pub use dns::{DnsResolver, DnsError, SystemResolver, is_valid_dns_tree_url};
 
 use io::TimerToken;
 pub use node_table::is_valid_node_url;
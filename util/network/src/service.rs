@@ -18,6 +18,7 @@ use {NetworkProtocolHandler, NetworkConfiguration, NonReservedPeerMode};
 use error::NetworkError;
 use host::{Host, NetworkContext, NetworkIoMessage, ProtocolId};
 use stats::NetworkStats;
+use dns::{DnsResolver, SystemResolver};
 use io::*;
 use parking_lot::RwLock;
 use std::sync::Arc;
@@ -49,11 +50,17 @@ pub struct NetworkService {
 	panic_handler: Arc<PanicHandler>,
 	host_handler: Arc<HostHandler>,
 	config: NetworkConfiguration,
+	dns_resolver: Arc<DnsResolver>,
 }
 
 impl NetworkService {
 	/// Starts IO event loop
 	pub fn new(config: NetworkConfiguration) -> Result<NetworkService, NetworkError> {
+		NetworkService::with_dns_resolver(config, Arc::new(SystemResolver::new()))
+	}
+
+	/// Starts IO event loop using the given resolver for DNS node lists.
+	pub fn with_dns_resolver(config: NetworkConfiguration, dns_resolver: Arc<DnsResolver>) -> Result<NetworkService, NetworkError> {
 		let host_handler = Arc::new(HostHandler { public_url: RwLock::new(None) });
 		let panic_handler = PanicHandler::new_in_arc();
 		let io_service = try!(IoService::<NetworkIoMessage>::start());
@@ -69,6 +76,7 @@ impl NetworkService {
 			host: RwLock::new(None),
 			config: config,
 			host_handler: host_handler,
+			dns_resolver: dns_resolver,
 		})
 	}
 
@@ -118,7 +126,7 @@ impl NetworkService {
 	pub fn start(&self) -> Result<(), NetworkError> {
 		let mut host = self.host.write();
 		if host.is_none() {
-			let h = Arc::new(try!(Host::new(self.config.clone(), self.stats.clone())));
+			let h = Arc::new(try!(Host::new(self.config.clone(), self.stats.clone(), self.dns_resolver.clone())));
 			try!(self.io_service.register_handler(h.clone()));
 			*host = Some(h);
 		}
@@ -17,6 +17,7 @@
 use std::net::SocketAddr;
 use std::io;
 use std::sync::*;
+use std::collections::HashMap;
 use mio::*;
 use mio::tcp::*;
 use util::hash::*;
@@ -27,7 +28,8 @@ use io::{IoContext, StreamToken};
 use error::{NetworkError, DisconnectReason};
 use host::*;
 use node_table::NodeId;
-use stats::NetworkStats;
+use stats::{NetworkStats, PacketCounters};
+use bandwidth::Bandwidth;
 use time;
 
 const PING_TIMEOUT_SEC: u64 = 30;
@@ -46,6 +48,10 @@ pub struct Session {
 	ping_time_ns: u64,
 	pong_time_ns: Option<u64>,
 	state: State,
+	/// Shared network statistics
+	stats: Arc<NetworkStats>,
+	/// Handles to the shared packet counters used by this session
+	packet_counters: HashMap<(ProtocolId, PacketId), Arc<PacketCounters>>,
 }
 
 enum State {
@@ -124,10 +130,10 @@ impl Session {
 	/// Create a new session out of comepleted handshake. This clones the handshake connection object
 	/// and leaves the handhsake in limbo to be deregistered from the event loop.
 	pub fn new<Message>(io: &IoContext<Message>, socket: TcpStream, token: StreamToken, id: Option<&NodeId>,
-		nonce: &H256, stats: Arc<NetworkStats>, host: &HostInfo) -> Result<Session, NetworkError>
+		nonce: &H256, stats: Arc<NetworkStats>, bandwidth: Arc<Bandwidth>, host: &HostInfo) -> Result<Session, NetworkError>
 		where Message: Send + Clone {
 		let originated = id.is_some();
-		let mut handshake = Handshake::new(token, id, socket, nonce, stats).expect("Can't create handshake");
+		let mut handshake = Handshake::new(token, id, socket, nonce, stats.clone(), bandwidth).expect("Can't create handshake");
 		try!(handshake.start(io, host, originated));
 		Ok(Session {
 			state: State::Handshake(handshake),
@@ -143,6 +149,8 @@ impl Session {
 			ping_time_ns: 0,
 			pong_time_ns: None,
 			expired: false,
+			stats: stats,
+			packet_counters: HashMap::new(),
 		})
 	}
 
@@ -199,6 +207,11 @@ impl Session {
 		self.connection().remote_addr()
 	}
 
+	/// Check if the last IO operation was limited by the bandwidth settings.
+	pub fn is_throttled(&self) -> bool {
+		self.connection().is_throttled()
+	}
+
 	/// Readable IO handler. Returns packet data if available.
 	pub fn readable<Message>(&mut self, io: &IoContext<Message>, host: &HostInfo) -> Result<SessionData, NetworkError>  where Message: Send + Sync + Clone {
 		if self.expired() {
@@ -269,6 +282,11 @@ impl Session {
 		Ok(())
 	}
 
+	fn packet_counters(&mut self, protocol: ProtocolId, packet_id: PacketId) -> &PacketCounters {
+		let stats = &self.stats;
+		self.packet_counters.entry((protocol, packet_id)).or_insert_with(|| stats.packet_counters(protocol, packet_id))
+	}
+
 	/// Send a protocol packet to peer.
 	pub fn send_packet<Message>(&mut self, io: &IoContext<Message>, protocol: &str, packet_id: u8, data: &[u8]) -> Result<(), NetworkError>
         where Message: Send + Sync + Clone {
@@ -288,6 +306,8 @@ impl Session {
 			}
 		}
 		let pid = self.info.capabilities[i].id_offset + packet_id;
+		let protocol_id = self.info.capabilities[i].protocol;
+		self.packet_counters(protocol_id, packet_id).inc_sent(data.len());
 		let mut rlp = RlpStream::new();
 		rlp.append(&(pid as u32));
 		rlp.append_raw(data, 1);
@@ -364,6 +384,7 @@ impl Session {
 				// map to protocol
 				let protocol = self.info.capabilities[i].protocol;
 				let pid = packet_id - self.info.capabilities[i].id_offset;
+				self.packet_counters(protocol, pid).inc_recv(packet.data.len() - 1);
 				Ok(SessionData::Packet { data: packet.data, protocol: protocol, packet_id: pid } )
 			},
 			_ => {
@@ -16,9 +16,69 @@
 
 //! Network Statistics
 use std::sync::atomic::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use parking_lot::RwLock;
+use host::{ProtocolId, PacketId};
+
+/// Traffic counters for a single packet type.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct PacketStats {
+	/// Number of packets sent
+	pub packets_sent: usize,
+	/// Payload bytes sent
+	pub bytes_sent: usize,
+	/// Number of packets received
+	pub packets_received: usize,
+	/// Payload bytes received
+	pub bytes_received: usize,
+}
+
+/// Live traffic counters for a single packet type.
+/// Sessions keep a handle to the counters so that accounting a packet does not take any lock.
+#[derive(Debug)]
+pub struct PacketCounters {
+	packets_sent: AtomicUsize,
+	bytes_sent: AtomicUsize,
+	packets_received: AtomicUsize,
+	bytes_received: AtomicUsize,
+}
+
+impl PacketCounters {
+	fn new() -> PacketCounters {
+		PacketCounters {
+			packets_sent: AtomicUsize::new(0),
+			bytes_sent: AtomicUsize::new(0),
+			packets_received: AtomicUsize::new(0),
+			bytes_received: AtomicUsize::new(0),
+		}
+	}
+
+	/// Account for a packet sent.
+	#[inline]
+	pub fn inc_sent(&self, size: usize) {
+		self.packets_sent.fetch_add(1, Ordering::Relaxed);
+		self.bytes_sent.fetch_add(size, Ordering::Relaxed);
+	}
+
+	/// Account for a packet received.
+	#[inline]
+	pub fn inc_recv(&self, size: usize) {
+		self.packets_received.fetch_add(1, Ordering::Relaxed);
+		self.bytes_received.fetch_add(size, Ordering::Relaxed);
+	}
+
+	fn stats(&self) -> PacketStats {
+		PacketStats {
+			packets_sent: self.packets_sent.load(Ordering::Relaxed),
+			bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+			packets_received: self.packets_received.load(Ordering::Relaxed),
+			bytes_received: self.bytes_received.load(Ordering::Relaxed),
+		}
+	}
+}
 
 /// Network statistics structure
-#[derive(Default, Debug)]
 pub struct NetworkStats {
 	/// Bytes received
 	recv: AtomicUsize,
@@ -26,6 +86,14 @@ pub struct NetworkStats {
 	send: AtomicUsize,
 	/// Total number of sessions created
 	sessions: AtomicUsize,
+	/// Per protocol packet counters
+	packets: RwLock<HashMap<(ProtocolId, PacketId), Arc<PacketCounters>>>,
+}
+
+impl Default for NetworkStats {
+	fn default() -> NetworkStats {
+		NetworkStats::new()
+	}
 }
 
 impl NetworkStats {
@@ -65,12 +133,67 @@ impl NetworkStats {
 		self.sessions.load(Ordering::Relaxed)
 	}
 
+	/// Get shared counters for the protocol packet, creating them when seen for the first time.
+	pub fn packet_counters(&self, protocol: ProtocolId, packet_id: PacketId) -> Arc<PacketCounters> {
+		if let Some(counters) = self.packets.read().get(&(protocol, packet_id)) {
+			return counters.clone();
+		}
+		self.packets.write().entry((protocol, packet_id)).or_insert_with(|| Arc::new(PacketCounters::new())).clone()
+	}
+
+	/// Account for a protocol packet sent.
+	pub fn inc_packet_sent(&self, protocol: ProtocolId, packet_id: PacketId, size: usize) {
+		self.packet_counters(protocol, packet_id).inc_sent(size);
+	}
+
+	/// Account for a protocol packet received.
+	pub fn inc_packet_recv(&self, protocol: ProtocolId, packet_id: PacketId, size: usize) {
+		self.packet_counters(protocol, packet_id).inc_recv(size);
+	}
+
+	/// Get traffic counters for each protocol packet seen so far.
+	pub fn packet_stats(&self) -> Vec<(ProtocolId, PacketId, PacketStats)> {
+		let mut stats: Vec<_> = self.packets.read().iter().map(|(&(p, id), s)| (p, id, s.stats())).collect();
+		stats.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+		stats
+	}
+
 	/// Create a new empty instance.
 	pub fn new() -> NetworkStats {
 		NetworkStats {
 			recv: AtomicUsize::new(0),
 			send: AtomicUsize::new(0),
 			sessions: AtomicUsize::new(0),
+			packets: RwLock::new(HashMap::new()),
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn counts_packets_per_protocol() {
+		let stats = NetworkStats::new();
+		stats.inc_packet_sent("eth", 1, 100);
+		stats.inc_packet_sent("eth", 1, 50);
+		stats.inc_packet_recv("eth", 1, 10);
+		stats.inc_packet_recv("bzz", 0, 20);
+
+		let packets = stats.packet_stats();
+		assert_eq!(packets.len(), 2);
+		assert_eq!(packets[0], ("bzz", 0, PacketStats { packets_sent: 0, bytes_sent: 0, packets_received: 1, bytes_received: 20 }));
+		assert_eq!(packets[1], ("eth", 1, PacketStats { packets_sent: 2, bytes_sent: 150, packets_received: 1, bytes_received: 10 }));
+	}
+
+	#[test]
+	fn shares_counters_between_handles() {
+		let stats = NetworkStats::new();
+		let counters = stats.packet_counters("eth", 2);
+		counters.inc_sent(30);
+		stats.packet_counters("eth", 2).inc_recv(40);
+
+		assert_eq!(stats.packet_stats(), vec![("eth", 2, PacketStats { packets_sent: 1, bytes_sent: 30, packets_received: 1, bytes_received: 40 })]);
+	}
+}
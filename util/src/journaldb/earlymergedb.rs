@@ -66,6 +66,7 @@ pub struct EarlyMergeDB {
 	backing: Arc<Database>,
 	refs: Option<Arc<RwLock<HashMap<H256, RefInfo>>>>,
 	latest_era: Option<u64>,
+	earliest_era: Arc<RwLock<Option<u64>>>,
 	column: Option<u32>,
 }
 
@@ -74,13 +75,14 @@ const PADDING : [u8; 10] = [ 0u8; 10 ];
 impl EarlyMergeDB {
 	/// Create a new instance from file
 	pub fn new(backing: Arc<Database>, col: Option<u32>) -> EarlyMergeDB {
-		let (latest_era, refs) = EarlyMergeDB::read_refs(&backing, col);
+		let (latest_era, earliest_era, refs) = EarlyMergeDB::read_refs(&backing, col);
 		let refs = Some(Arc::new(RwLock::new(refs)));
 		EarlyMergeDB {
 			overlay: MemoryDB::new(),
 			backing: backing,
 			refs: refs,
 			latest_era: latest_era,
+			earliest_era: Arc::new(RwLock::new(earliest_era)),
 			column: col,
 		}
 	}
@@ -213,7 +215,7 @@ impl EarlyMergeDB {
 
 	#[cfg(test)]
 	fn can_reconstruct_refs(&self) -> bool {
-		let (latest_era, reconstructed) = Self::read_refs(&self.backing, self.column);
+		let (latest_era, _, reconstructed) = Self::read_refs(&self.backing, self.column);
 		let refs = self.refs.as_ref().unwrap().write();
 		if *refs != reconstructed || latest_era != self.latest_era {
 			let clean_refs = refs.iter().filter_map(|(k, v)| if reconstructed.get(k) == Some(v) {None} else {Some((k.clone(), v.clone()))}).collect::<HashMap<_, _>>();
@@ -229,9 +231,11 @@ impl EarlyMergeDB {
 		self.backing.get(self.column, key).expect("Low-level database error. Some issue with your hard disk?").map(|v| v.to_vec())
 	}
 
-	fn read_refs(db: &Database, col: Option<u32>) -> (Option<u64>, HashMap<H256, RefInfo>) {
+	// returns the latest era, the earliest era still journalled and the reference counts of journalled keys.
+	fn read_refs(db: &Database, col: Option<u32>) -> (Option<u64>, Option<u64>, HashMap<H256, RefInfo>) {
 		let mut refs = HashMap::new();
 		let mut latest_era = None;
+		let mut earliest_era = None;
 		if let Some(val) = db.get(col, &LATEST_ERA_KEY).expect("Low-level database error.") {
 			let mut era = decode::<u64>(&val);
 			latest_era = Some(era);
@@ -249,13 +253,16 @@ impl EarlyMergeDB {
 					Self::replay_keys(&inserts, db, col, &mut refs);
 					index += 1;
 				};
+				if index > 0 {
+					earliest_era = Some(era);
+				}
 				if index == 0 || era == 0 {
 					break;
 				}
 				era -= 1;
 			}
 		}
-		(latest_era, refs)
+		(latest_era, earliest_era, refs)
 	}
 }
 
@@ -311,6 +318,7 @@ impl JournalDB for EarlyMergeDB {
 			backing: self.backing.clone(),
 			refs: self.refs.clone(),
 			latest_era: self.latest_era.clone(),
+			earliest_era: self.earliest_era.clone(),
 			column: self.column.clone(),
 		})
 	}
@@ -325,6 +333,8 @@ impl JournalDB for EarlyMergeDB {
 
 	fn latest_era(&self) -> Option<u64> { self.latest_era }
 
+	fn earliest_era(&self) -> Option<u64> { *self.earliest_era.read() }
+
 	fn mem_used(&self) -> usize {
 		self.overlay.mem_used() + match self.refs {
 			Some(ref c) => c.read().heap_size_of_children(),
@@ -383,9 +393,9 @@ impl JournalDB for EarlyMergeDB {
 		//
 
 		// record new commit's details.
-		let mut refs = self.refs.as_ref().unwrap().write();
 		let trace = false;
 		{
+			let mut refs = self.refs.as_ref().unwrap().write();
 			let mut index = 0usize;
 			let mut last;
 
@@ -441,76 +451,97 @@ impl JournalDB for EarlyMergeDB {
 				batch.put(self.column, &LATEST_ERA_KEY, &encode(&now));
 				self.latest_era = Some(now);
 			}
+			let mut earliest_era = self.earliest_era.write();
+			if earliest_era.map_or(true, |e| now < e) {
+				*earliest_era = Some(now);
+			}
 		}
 
 		// apply old commits' details
 		if let Some((end_era, canon_id)) = end {
-			let mut index = 0usize;
-			let mut last;
-			while let Some(rlp_data) = try!(self.backing.get(self.column, {
-				let mut r = RlpStream::new_list(3);
-				r.append(&end_era);
-				r.append(&index);
-				r.append(&&PADDING[..]);
-				last = r.drain();
-				&last
-			})) {
-				let rlp = Rlp::new(&rlp_data);
-				let inserts: Vec<H256> = rlp.val_at(1);
+			try!(self.mark_canonical(batch, end_era, &canon_id));
+		}
 
-				if canon_id == rlp.val_at(0) {
-					// Collect keys to be removed. Canon block - remove the (enacted) deletes.
-					let deletes: Vec<H256> = rlp.val_at(2);
-					if trace {
-						trace!(target: "jdb.ops", "  Expunging: {:?}", deletes);
-					}
-					Self::remove_keys(&deletes, &mut refs, batch, self.column, RemoveFrom::Archive, trace);
+		Ok(0)
+	}
 
-					if trace {
-						trace!(target: "jdb.ops", "  Finalising: {:?}", inserts);
-					}
-					for k in &inserts {
-						match refs.get(k).cloned() {
-							None => {
-								// [in archive] -> SHIFT remove -> SHIFT insert None->Some{queue_refs: 1, in_archive: true} -> TAKE remove Some{queue_refs: 1, in_archive: true}->None -> TAKE insert
-								// already expunged from the queue (which is allowed since the key is in the archive).
-								// leave well alone.
-							}
-							Some( RefInfo{queue_refs: 1, in_archive: false} ) => {
-								// just delete the refs entry.
-								refs.remove(k);
-							}
-							Some( RefInfo{queue_refs: x, in_archive: false} ) => {
-								// must set already in; ,
-								Self::set_already_in(batch, self.column, k);
-								refs.insert(k.clone(), RefInfo{ queue_refs: x - 1, in_archive: true });
-							}
-							Some( RefInfo{in_archive: true, ..} ) => {
-								// Invalid! Reinserted the same key twice.
-								warn!("Key {} inserted twice into same fork.", k);
-							}
+	fn mark_canonical(&mut self, batch: &mut DBTransaction, era: u64, id: &H256) -> Result<u32, UtilError> {
+		let mut refs = self.refs.as_ref().unwrap().write();
+		let trace = false;
+
+		let mut index = 0usize;
+		let mut last;
+		while let Some(rlp_data) = try!(self.backing.get(self.column, {
+			let mut r = RlpStream::new_list(3);
+			r.append(&era);
+			r.append(&index);
+			r.append(&&PADDING[..]);
+			last = r.drain();
+			&last
+		})) {
+			let rlp = Rlp::new(&rlp_data);
+			let inserts: Vec<H256> = rlp.val_at(1);
+
+			if *id == rlp.val_at(0) {
+				// Collect keys to be removed. Canon block - remove the (enacted) deletes.
+				let deletes: Vec<H256> = rlp.val_at(2);
+				if trace {
+					trace!(target: "jdb.ops", "  Expunging: {:?}", deletes);
+				}
+				Self::remove_keys(&deletes, &mut refs, batch, self.column, RemoveFrom::Archive, trace);
+
+				if trace {
+					trace!(target: "jdb.ops", "  Finalising: {:?}", inserts);
+				}
+				for k in &inserts {
+					match refs.get(k).cloned() {
+						None => {
+							// [in archive] -> SHIFT remove -> SHIFT insert None->Some{queue_refs: 1, in_archive: true} -> TAKE remove Some{queue_refs: 1, in_archive: true}->None -> TAKE insert
+							// already expunged from the queue (which is allowed since the key is in the archive).
+							// leave well alone.
+						}
+						Some( RefInfo{queue_refs: 1, in_archive: false} ) => {
+							// just delete the refs entry.
+							refs.remove(k);
+						}
+						Some( RefInfo{queue_refs: x, in_archive: false} ) => {
+							// must set already in; ,
+							Self::set_already_in(batch, self.column, k);
+							refs.insert(k.clone(), RefInfo{ queue_refs: x - 1, in_archive: true });
+						}
+						Some( RefInfo{in_archive: true, ..} ) => {
+							// Invalid! Reinserted the same key twice.
+							warn!("Key {} inserted twice into same fork.", k);
 						}
 					}
-				} else {
-					// Collect keys to be removed. Non-canon block - remove the (reverted) inserts.
-					if trace {
-						trace!(target: "jdb.ops", "  Reverting: {:?}", inserts);
-					}
-					Self::remove_keys(&inserts, &mut refs, batch, self.column, RemoveFrom::Queue, trace);
 				}
-
-				batch.delete(self.column, &last);
-				index += 1;
-			}
-			if trace {
-				trace!(target: "jdb", "EarlyMergeDB: delete journal for time #{}.{}, (canon was {})", end_era, index, canon_id);
+			} else {
+				// Collect keys to be removed. Non-canon block - remove the (reverted) inserts.
+				if trace {
+					trace!(target: "jdb.ops", "  Reverting: {:?}", inserts);
+				}
+				Self::remove_keys(&inserts, &mut refs, batch, self.column, RemoveFrom::Queue, trace);
 			}
+
+			batch.delete(self.column, &last);
+			index += 1;
+		}
+		if trace {
+			trace!(target: "jdb", "EarlyMergeDB: delete journal for time #{}.{}, (canon was {})", era, index, id);
 		}
 
 		if trace {
 			trace!(target: "jdb", "OK: {:?}", refs.clone());
 		}
 
+		let mut earliest_era = self.earliest_era.write();
+		if *earliest_era == Some(era) {
+			*earliest_era = match self.latest_era {
+				Some(latest) if latest > era => Some(era + 1),
+				_ => None,
+			};
+		}
+
 		Ok(0)
 	}
 
@@ -851,6 +882,40 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn earliest_era_is_read_on_open() {
+		let mut dir = ::std::env::temp_dir();
+		dir.push(H32::random().hex());
+
+		let foo = {
+			let mut jdb = new_db(&dir);
+			assert_eq!(jdb.earliest_era(), None);
+			let foo = jdb.insert(b"foo");
+			jdb.commit_batch(0, &b"0".sha3(), None).unwrap();
+			jdb.remove(&foo);
+			jdb.commit_batch(1, &b"1".sha3(), None).unwrap();
+			jdb.commit_batch(2, &b"2".sha3(), None).unwrap();
+			assert_eq!(jdb.earliest_era(), Some(0));
+			foo
+		};
+
+		{
+			// eras older than a lowered history window are still journalled.
+			let mut jdb = new_db(&dir);
+			assert_eq!(jdb.earliest_era(), Some(0));
+			let mut batch = jdb.backing().transaction();
+			jdb.mark_canonical(&mut batch, 0, &b"0".sha3()).unwrap();
+			jdb.mark_canonical(&mut batch, 1, &b"1".sha3()).unwrap();
+			jdb.backing().write(batch).unwrap();
+			assert_eq!(jdb.earliest_era(), Some(2));
+			assert!(jdb.can_reconstruct_refs());
+			assert!(!jdb.contains(&foo));
+		}
+
+		let jdb = new_db(&dir);
+		assert_eq!(jdb.earliest_era(), Some(2));
+	}
+
 	#[test]
 	fn insert_delete_insert_delete_insert_expunge() {
 		init_log();
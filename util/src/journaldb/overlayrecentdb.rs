@@ -214,6 +214,13 @@ impl JournalDB for OverlayRecentDB {
 
 	fn latest_era(&self) -> Option<u64> { self.journal_overlay.read().latest_era }
 
+	fn earliest_era(&self) -> Option<u64> { self.journal_overlay.read().journal.keys().min().cloned() }
+
+	fn journal_size(&self) -> usize {
+		let overlay = self.journal_overlay.read();
+		overlay.backing_overlay.mem_used() + overlay.journal.heap_size_of_children()
+	}
+
 	fn state(&self, key: &H256) -> Option<Bytes> {
 		let journal_overlay = self.journal_overlay.read();
 		let key = to_short_key(key);
@@ -225,10 +232,11 @@ impl JournalDB for OverlayRecentDB {
 	fn commit(&mut self, batch: &mut DBTransaction, now: u64, id: &H256, end: Option<(u64, H256)>) -> Result<u32, UtilError> {
 		// record new commit's details.
 		trace!("commit: #{} ({}), end era: {:?}", now, id, end);
-		let mut journal_overlay = self.journal_overlay.write();
-		// flush previous changes
-		journal_overlay.pending_overlay.clear();
 		{
+			let mut journal_overlay = self.journal_overlay.write();
+			// flush previous changes
+			journal_overlay.pending_overlay.clear();
+
 			let mut r = RlpStream::new_list(3);
 			let mut tx = self.transaction_overlay.drain();
 			let inserted_keys: Vec<_> = tx.iter().filter_map(|(k, &(_, c))| if c > 0 { Some(k.clone()) } else { None }).collect();
@@ -258,55 +266,61 @@ impl JournalDB for OverlayRecentDB {
 			journal_overlay.journal.entry(now).or_insert_with(Vec::new).push(JournalEntry { id: id.clone(), insertions: inserted_keys, deletions: removed_keys });
 		}
 
-		let journal_overlay = &mut *journal_overlay;
 		// apply old commits' details
 		if let Some((end_era, canon_id)) = end {
-			if let Some(ref mut records) = journal_overlay.journal.get_mut(&end_era) {
-				let mut canon_insertions: Vec<(H256, Bytes)> = Vec::new();
-				let mut canon_deletions: Vec<H256> = Vec::new();
-				let mut overlay_deletions: Vec<H256> = Vec::new();
-				let mut index = 0usize;
-				for mut journal in records.drain(..) {
-					//delete the record from the db
-					let mut r = RlpStream::new_list(3);
-					r.append(&end_era);
-					r.append(&index);
-					r.append(&&PADDING[..]);
-					batch.delete(self.column, &r.drain());
-					trace!("commit: Delete journal for time #{}.{}: {}, (canon was {}): +{} -{} entries", end_era, index, journal.id, canon_id, journal.insertions.len(), journal.deletions.len());
-					{
-						if canon_id == journal.id {
-							for h in &journal.insertions {
-								if let Some((d, rc)) = journal_overlay.backing_overlay.raw(&to_short_key(h)) {
-									if rc > 0 {
-										canon_insertions.push((h.clone(), d.to_owned())); //TODO: optimize this to avoid data copy
-									}
+			try!(self.mark_canonical(batch, end_era, &canon_id));
+		}
+		Ok(0)
+	}
+
+	fn mark_canonical(&mut self, batch: &mut DBTransaction, era: u64, id: &H256) -> Result<u32, UtilError> {
+		let mut journal_overlay = self.journal_overlay.write();
+		let journal_overlay = &mut *journal_overlay;
+		if let Some(ref mut records) = journal_overlay.journal.get_mut(&era) {
+			let mut canon_insertions: Vec<(H256, Bytes)> = Vec::new();
+			let mut canon_deletions: Vec<H256> = Vec::new();
+			let mut overlay_deletions: Vec<H256> = Vec::new();
+			let mut index = 0usize;
+			for mut journal in records.drain(..) {
+				//delete the record from the db
+				let mut r = RlpStream::new_list(3);
+				r.append(&era);
+				r.append(&index);
+				r.append(&&PADDING[..]);
+				batch.delete(self.column, &r.drain());
+				trace!("commit: Delete journal for time #{}.{}: {}, (canon was {}): +{} -{} entries", era, index, journal.id, id, journal.insertions.len(), journal.deletions.len());
+				{
+					if *id == journal.id {
+						for h in &journal.insertions {
+							if let Some((d, rc)) = journal_overlay.backing_overlay.raw(&to_short_key(h)) {
+								if rc > 0 {
+									canon_insertions.push((h.clone(), d.to_owned())); //TODO: optimize this to avoid data copy
 								}
 							}
-							canon_deletions = journal.deletions;
 						}
-						overlay_deletions.append(&mut journal.insertions);
+						canon_deletions = journal.deletions;
 					}
-					index += 1;
-				}
-				// apply canon inserts first
-				for (k, v) in canon_insertions {
-					batch.put(self.column, &k, &v);
-					journal_overlay.pending_overlay.insert(to_short_key(&k), v);
+					overlay_deletions.append(&mut journal.insertions);
 				}
-				// update the overlay
-				for k in overlay_deletions {
-					journal_overlay.backing_overlay.remove_and_purge(&to_short_key(&k));
-				}
-				// apply canon deletions
-				for k in canon_deletions {
-					if !journal_overlay.backing_overlay.contains(&to_short_key(&k)) {
-						batch.delete(self.column, &k);
-					}
+				index += 1;
+			}
+			// apply canon inserts first
+			for (k, v) in canon_insertions {
+				batch.put(self.column, &k, &v);
+				journal_overlay.pending_overlay.insert(to_short_key(&k), v);
+			}
+			// update the overlay
+			for k in overlay_deletions {
+				journal_overlay.backing_overlay.remove_and_purge(&to_short_key(&k));
+			}
+			// apply canon deletions
+			for k in canon_deletions {
+				if !journal_overlay.backing_overlay.contains(&to_short_key(&k)) {
+					batch.delete(self.column, &k);
 				}
 			}
-			journal_overlay.journal.remove(&end_era);
 		}
+		journal_overlay.journal.remove(&era);
 		Ok(0)
 	}
 
@@ -473,6 +487,28 @@ mod tests {
 		assert!(!jdb.contains(&h));
 	}
 
+	#[test]
+	fn mark_canonical_separately() {
+		let mut jdb = OverlayRecentDB::new_temp();
+		let h = jdb.insert(b"foo");
+		jdb.commit_batch(0, &b"0".sha3(), None).unwrap();
+		jdb.remove(&h);
+		jdb.commit_batch(1, &b"1".sha3(), None).unwrap();
+		jdb.commit_batch(2, &b"2".sha3(), None).unwrap();
+		assert_eq!(jdb.earliest_era(), Some(0));
+		assert!(jdb.journal_size() > 0);
+
+		let mut batch = jdb.backing().transaction();
+		jdb.mark_canonical(&mut batch, 0, &b"0".sha3()).unwrap();
+		jdb.mark_canonical(&mut batch, 1, &b"1".sha3()).unwrap();
+		jdb.backing().write(batch).unwrap();
+		jdb.flush();
+
+		assert!(jdb.can_reconstruct_refs());
+		assert_eq!(jdb.earliest_era(), Some(2));
+		assert!(!jdb.contains(&h));
+	}
+
 	#[test]
 	fn complex() {
 		// history is 1
@@ -38,6 +38,7 @@ pub struct RefCountedDB {
 	forward: OverlayDB,
 	backing: Arc<Database>,
 	latest_era: Option<u64>,
+	earliest_era: Arc<RwLock<Option<u64>>>,
 	inserts: Vec<H256>,
 	removes: Vec<H256>,
 	column: Option<u32>,
@@ -49,6 +50,7 @@ impl RefCountedDB {
 	/// Create a new instance given a `backing` database.
 	pub fn new(backing: Arc<Database>, col: Option<u32>) -> RefCountedDB {
 		let latest_era = backing.get(col, &LATEST_ERA_KEY).expect("Low-level database error.").map(|val| decode::<u64>(&val));
+		let earliest_era = latest_era.and_then(|latest| Self::read_earliest_era(&backing, col, latest));
 
 		RefCountedDB {
 			forward: OverlayDB::new(backing.clone(), col),
@@ -56,6 +58,7 @@ impl RefCountedDB {
 			inserts: vec![],
 			removes: vec![],
 			latest_era: latest_era,
+			earliest_era: Arc::new(RwLock::new(earliest_era)),
 			column: col,
 		}
 	}
@@ -68,6 +71,30 @@ impl RefCountedDB {
 		let backing = Arc::new(Database::open_default(dir.to_str().unwrap()).unwrap());
 		Self::new(backing, None)
 	}
+
+	// walk the journal back from the latest era to find the earliest one still journalled.
+	fn read_earliest_era(db: &Database, col: Option<u32>, latest_era: u64) -> Option<u64> {
+		let mut earliest_era = None;
+		let mut era = latest_era;
+		loop {
+			let journalled = db.get(col, {
+				let mut r = RlpStream::new_list(3);
+				r.append(&era);
+				r.append(&0usize);
+				r.append(&&PADDING[..]);
+				&r.drain()
+			}).expect("Low-level database error.").is_some();
+			if !journalled {
+				break;
+			}
+			earliest_era = Some(era);
+			if era == 0 {
+				break;
+			}
+			era -= 1;
+		}
+		earliest_era
+	}
 }
 
 impl HashDB for RefCountedDB {
@@ -85,6 +112,7 @@ impl JournalDB for RefCountedDB {
 			forward: self.forward.clone(),
 			backing: self.backing.clone(),
 			latest_era: self.latest_era,
+			earliest_era: self.earliest_era.clone(),
 			inserts: self.inserts.clone(),
 			removes: self.removes.clone(),
 			column: self.column.clone(),
@@ -105,6 +133,8 @@ impl JournalDB for RefCountedDB {
 
 	fn latest_era(&self) -> Option<u64> { self.latest_era }
 
+	fn earliest_era(&self) -> Option<u64> { *self.earliest_era.read() }
+
 	fn state(&self, id: &H256) -> Option<Bytes> {
 		self.backing.get_by_prefix(self.column, &id[0..DB_PREFIX_LEN]).map(|b| b.to_vec())
 	}
@@ -153,37 +183,56 @@ impl JournalDB for RefCountedDB {
 				batch.put(self.column, &LATEST_ERA_KEY, &encode(&now));
 				self.latest_era = Some(now);
 			}
+			let mut earliest_era = self.earliest_era.write();
+			if earliest_era.map_or(true, |e| now < e) {
+				*earliest_era = Some(now);
+			}
 		}
 
 		// apply old commits' details
+		let mut ops = 0;
 		if let Some((end_era, canon_id)) = end {
-			let mut index = 0usize;
-			let mut last;
-			while let Some(rlp_data) = {
-//				trace!(target: "rcdb", "checking for journal #{}.{}", end_era, index);
-				try!(self.backing.get(self.column, {
-					let mut r = RlpStream::new_list(3);
-					r.append(&end_era);
-					r.append(&index);
-					r.append(&&PADDING[..]);
-					last = r.drain();
-					&last
-				}))
-			} {
-				let rlp = Rlp::new(&rlp_data);
-				let our_id: H256 = rlp.val_at(0);
-				let to_remove: Vec<H256> = rlp.val_at(if canon_id == our_id {2} else {1});
-				trace!(target: "rcdb", "delete journal for time #{}.{}=>{}, (canon was {}): deleting {:?}", end_era, index, our_id, canon_id, to_remove);
-				for i in &to_remove {
-					self.forward.remove(i);
-				}
-				batch.delete(self.column, &last);
-				index += 1;
+			ops += try!(self.mark_canonical(batch, end_era, &canon_id));
+		}
+
+		ops += try!(self.forward.commit_to_batch(batch));
+		Ok(ops)
+	}
+
+	fn mark_canonical(&mut self, batch: &mut DBTransaction, era: u64, id: &H256) -> Result<u32, UtilError> {
+		let mut index = 0usize;
+		let mut last;
+		while let Some(rlp_data) = {
+//			trace!(target: "rcdb", "checking for journal #{}.{}", era, index);
+			try!(self.backing.get(self.column, {
+				let mut r = RlpStream::new_list(3);
+				r.append(&era);
+				r.append(&index);
+				r.append(&&PADDING[..]);
+				last = r.drain();
+				&last
+			}))
+		} {
+			let rlp = Rlp::new(&rlp_data);
+			let our_id: H256 = rlp.val_at(0);
+			let to_remove: Vec<H256> = rlp.val_at(if *id == our_id {2} else {1});
+			trace!(target: "rcdb", "delete journal for time #{}.{}=>{}, (canon was {}): deleting {:?}", era, index, our_id, id, to_remove);
+			for i in &to_remove {
+				self.forward.remove(i);
 			}
+			batch.delete(self.column, &last);
+			index += 1;
+		}
+
+		let mut earliest_era = self.earliest_era.write();
+		if *earliest_era == Some(era) {
+			*earliest_era = match self.latest_era {
+				Some(latest) if latest > era => Some(era + 1),
+				_ => None,
+			};
 		}
 
-		let r = try!(self.forward.commit_to_batch(batch));
-		Ok(r)
+		self.forward.commit_to_batch(batch)
 	}
 
 	fn inject(&mut self, batch: &mut DBTransaction) -> Result<u32, UtilError> {
@@ -216,6 +265,7 @@ mod tests {
 	use super::*;
 	use super::super::traits::JournalDB;
 	use hashdb::*;
+	use kvdb::Database;
 
 	#[test]
 	fn long_history() {
@@ -235,6 +285,40 @@ mod tests {
 		assert!(!jdb.contains(&h));
 	}
 
+	#[test]
+	fn earliest_era_is_read_on_open() {
+		let mut dir = ::std::env::temp_dir();
+		dir.push(H32::random().hex());
+		let open = |dir: &Path| RefCountedDB::new(Arc::new(Database::open_default(dir.to_str().unwrap()).unwrap()), None);
+
+		let h = {
+			let mut jdb = open(dir.as_path());
+			assert_eq!(jdb.earliest_era(), None);
+			let h = jdb.insert(b"foo");
+			jdb.commit_batch(0, &b"0".sha3(), None).unwrap();
+			jdb.remove(&h);
+			jdb.commit_batch(1, &b"1".sha3(), None).unwrap();
+			jdb.commit_batch(2, &b"2".sha3(), None).unwrap();
+			assert_eq!(jdb.earliest_era(), Some(0));
+			h
+		};
+
+		{
+			// eras older than a lowered history window are still journalled.
+			let mut jdb = open(dir.as_path());
+			assert_eq!(jdb.earliest_era(), Some(0));
+			let mut batch = jdb.backing().transaction();
+			jdb.mark_canonical(&mut batch, 0, &b"0".sha3()).unwrap();
+			jdb.mark_canonical(&mut batch, 1, &b"1".sha3()).unwrap();
+			jdb.backing().write(batch).unwrap();
+			assert_eq!(jdb.earliest_era(), Some(2));
+			assert!(!jdb.contains(&h));
+		}
+
+		let jdb = open(dir.as_path());
+		assert_eq!(jdb.earliest_era(), Some(2));
+	}
+
 	#[test]
 	fn latest_era_should_work() {
 		// history is 3
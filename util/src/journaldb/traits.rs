@@ -35,10 +35,20 @@ pub trait JournalDB: HashDB {
 	/// Get the latest era in the DB. None if there isn't yet any data in there.
 	fn latest_era(&self) -> Option<u64>;
 
+	/// Get the earliest era still journalled. None if the journal is empty or this database doesn't keep a journal.
+	fn earliest_era(&self) -> Option<u64> { None }
+
+	/// Returns heap memory size used by the journal of recent eras.
+	fn journal_size(&self) -> usize { 0 }
+
 	/// Commit all recent insert operations and canonical historical commits' removals from the
 	/// old era to the backing database, reverting any non-canonical historical commit's inserts.
 	fn commit(&mut self, batch: &mut DBTransaction, now: u64, id: &H256, end: Option<(u64, H256)>) -> Result<u32, UtilError>;
 
+	/// Mark the commit with given id in given era as canonical, enacting its removals and reverting
+	/// all other commits of that era. Eras must be marked canonical in order.
+	fn mark_canonical(&mut self, batch: &mut DBTransaction, era: u64, id: &H256) -> Result<u32, UtilError>;
+
 	/// Commit all queued insert and delete operations without affecting any journalling -- this requires that all insertions
 	/// and deletions are indeed canonical and will likely lead to an invalid database if that assumption is violated.
 	///
@@ -104,6 +104,8 @@ impl From<::std::io::Error> for Error {
 
 /// A generalized migration from the given db to a destination db.
 pub trait Migration: 'static {
+	/// Number of columns in database before the migration.
+	fn pre_columns(&self) -> Option<u32>;
 	/// Number of columns in database after the migration.
 	fn columns(&self) -> Option<u32>;
 	/// Version of the database after the migration.
@@ -124,6 +126,8 @@ pub trait SimpleMigration: 'static {
 }
 
 impl<T: SimpleMigration> Migration for T {
+	fn pre_columns(&self) -> Option<u32> { SimpleMigration::columns(self) }
+
 	fn columns(&self) -> Option<u32> { SimpleMigration::columns(self) }
 
 	fn version(&self) -> u32 { SimpleMigration::version(self) }
@@ -269,9 +273,9 @@ impl Manager {
 	}
 
 	fn no_of_columns_at(&self, version: u32) -> Option<u32> {
-		let migration = self.migrations.iter().find(|m| m.version() == version);
+		let migration = self.migrations.iter().find(|m| m.version() > version);
 		match migration {
-			Some(m) => m.columns(),
+			Some(m) => m.pre_columns(),
 			None => None
 		}
 	}
@@ -17,7 +17,7 @@
 use hash::H256;
 use sha3::Hashable;
 use hashdb::HashDB;
-use super::{TrieDB, Trie, TrieDBIterator, TrieItem, Recorder};
+use super::{TrieDB, Trie, TrieDBIterator, TrieItem, TrieIterator, Recorder};
 
 /// A `Trie` implementation which hashes keys and uses a generic `HashDB` backing database.
 /// Additionaly it stores inserted hash-key mappings for later retrieval.
@@ -46,7 +46,7 @@ impl<'db> FatDB<'db> {
 }
 
 impl<'db> Trie for FatDB<'db> {
-	fn iter<'a>(&'a self) -> Box<Iterator<Item = TrieItem> + 'a> {
+	fn iter<'a>(&'a self) -> Box<TrieIterator<Item = TrieItem> + 'a> {
 		Box::new(FatDBIterator::new(&self.raw))
 	}
 
@@ -81,6 +81,12 @@ impl<'db> FatDBIterator<'db> {
 	}
 }
 
+impl<'db> TrieIterator for FatDBIterator<'db> {
+	fn seek(&mut self, key: &[u8]) -> super::Result<()> {
+		self.trie_iterator.seek(&key.sha3())
+	}
+}
+
 impl<'db> Iterator for FatDBIterator<'db> {
 	type Item = (Vec<u8>, &'db [u8]);
 
@@ -107,3 +113,26 @@ fn fatdb_to_trie() {
 	assert_eq!(t.get(&[0x01u8, 0x23]).unwrap().unwrap(), &[0x01u8, 0x23]);
 	assert_eq!(t.iter().collect::<Vec<_>>(), vec![(vec![0x01u8, 0x23], &[0x01u8, 0x23] as &[u8])]);
 }
+
+#[test]
+fn fatdb_seek() {
+	use memorydb::MemoryDB;
+	use trie::{FatDBMut, TrieMut};
+
+	let keys = vec![vec![0x01u8], vec![0x02u8], vec![0x03u8]];
+	let mut memdb = MemoryDB::new();
+	let mut root = H256::default();
+	{
+		let mut t = FatDBMut::new(&mut memdb, &mut root);
+		for k in &keys {
+			t.insert(k, k).unwrap();
+		}
+	}
+	let t = FatDB::new(&memdb, &root).unwrap();
+	let all = t.iter().map(|(k, _)| k).collect::<Vec<_>>();
+	for (i, k) in all.iter().enumerate() {
+		let mut iter = t.iter();
+		iter.seek(k).unwrap();
+		assert_eq!(iter.map(|(k, _)| k).collect::<Vec<_>>(), &all[i..]);
+	}
+}
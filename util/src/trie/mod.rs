@@ -36,6 +36,8 @@ pub mod sectriedb;
 pub mod sectriedbmut;
 /// Trie query recording.
 pub mod recorder;
+/// Range proofs.
+pub mod proof;
 
 
 mod fatdb;
@@ -49,6 +51,7 @@ pub use self::sectriedb::SecTrieDB;
 pub use self::fatdb::{FatDB, FatDBIterator};
 pub use self::fatdbmut::FatDBMut;
 pub use self::recorder::Recorder;
+pub use self::proof::RangeProof;
 
 /// Trie Errors.
 ///
@@ -102,7 +105,14 @@ pub trait Trie {
 		where 'a: 'b, R: Recorder;
 
 	/// Returns an iterator over elements of trie.
-	fn iter<'a>(&'a self) -> Box<Iterator<Item = TrieItem> + 'a>;
+	fn iter<'a>(&'a self) -> Box<TrieIterator<Item = TrieItem> + 'a>;
+}
+
+/// An iterator over trie items which can be moved to an arbitrary position.
+pub trait TrieIterator: Iterator {
+	/// Position the iterator so that the next item is the first one whose key is not less than
+	/// `key` in iteration order. `key` is taken in the same form as the keys yielded by the iterator.
+	fn seek(&mut self, key: &[u8]) -> Result<()>;
 }
 
 /// A key-value datastore implemented as a database-backed modified Merkle tree.
@@ -193,7 +203,7 @@ impl<'db> Trie for TrieKinds<'db> {
 		wrapper!(self, get_recorded, key, r)
 	}
 
-	fn iter<'a>(&'a self) -> Box<Iterator<Item = TrieItem> + 'a> {
+	fn iter<'a>(&'a self) -> Box<TrieIterator<Item = TrieItem> + 'a> {
 		wrapper!(self, iter,)
 	}
 }
@@ -207,6 +217,11 @@ impl TrieFactory {
 		}
 	}
 
+	/// Returns true iff the tries created by this factory store key preimages.
+	pub fn is_fat(&self) -> bool {
+		self.spec == TrieSpec::Fat
+	}
+
 	/// Create new immutable instance of Trie.
 	pub fn readonly<'db>(&self, db: &'db HashDB, root: &'db H256) -> Result<TrieKinds<'db>> {
 		match self.spec {
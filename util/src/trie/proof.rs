@@ -0,0 +1,138 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Merkle proofs for contiguous ranges of trie items.
+
+use hash::H256;
+use hashdb::HashDB;
+use memorydb::MemoryDB;
+use Bytes;
+use super::TrieDB;
+use super::recorder::{Recorder, BasicRecorder, NoOp};
+
+/// Proof that a list of items is exactly the first items of a trie starting at some key.
+///
+/// The proof consists of the nodes on the paths to the first and last key of the range together
+/// with every node in between, so the range can be walked again starting from the trie root
+/// without access to the rest of the trie. A range shorter than requested proves that the trie
+/// has no more items after it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeProof {
+	/// Items of the range, in key order.
+	pub items: Vec<(Bytes, Bytes)>,
+	/// Trie nodes needed to walk the range.
+	pub nodes: Vec<Bytes>,
+}
+
+impl RangeProof {
+	/// Prove up to `count` items of `trie` with keys not less than `start`.
+	pub fn generate(trie: &TrieDB, start: &[u8], count: usize) -> super::Result<Self> {
+		let mut recorder = BasicRecorder::new();
+		let items = try!(trie.range_recorded(start, count, &mut recorder))
+			.into_iter()
+			.map(|(key, value)| (key, value.to_vec()))
+			.collect();
+
+		Ok(RangeProof {
+			items: items,
+			nodes: recorder.drain().into_iter().map(|record| record.data).collect(),
+		})
+	}
+
+	/// Check that the items of this proof are exactly the first `count` items with keys
+	/// not less than `start` in the trie with the given root, or all of them if there are fewer.
+	pub fn verify(&self, root: &H256, start: &[u8], count: usize) -> bool {
+		let mut db = MemoryDB::new();
+		for node in &self.nodes {
+			db.insert(node);
+		}
+
+		let trie = match TrieDB::new(&db, root) {
+			Ok(trie) => trie,
+			Err(_) => return false,
+		};
+
+		match trie.range_recorded(start, count, &mut NoOp) {
+			Ok(items) => items.len() == self.items.len() && items.iter().zip(self.items.iter())
+				.all(|(&(ref key, value), &(ref proven_key, ref proven_value))| key == proven_key && value == &proven_value[..]),
+			Err(_) => false,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hash::H256;
+	use memorydb::MemoryDB;
+	use trie::{TrieDB, TrieDBMut, TrieMut};
+	use super::RangeProof;
+
+	fn populate(memdb: &mut MemoryDB, root: &mut H256) {
+		let mut t = TrieDBMut::new(memdb, root);
+		for i in 0..64u8 {
+			t.insert(&[i, i / 2], &[i; 8]).unwrap();
+		}
+	}
+
+	#[test]
+	fn proves_range() {
+		let mut memdb = MemoryDB::new();
+		let mut root = H256::new();
+		populate(&mut memdb, &mut root);
+		let trie = TrieDB::new(&memdb, &root).unwrap();
+
+		let proof = RangeProof::generate(&trie, &[10], 5).unwrap();
+		assert_eq!(proof.items.iter().map(|i| i.0[0]).collect::<Vec<_>>(), vec![10, 11, 12, 13, 14]);
+		assert!(proof.verify(&root, &[10], 5));
+		assert!(!proof.verify(&root, &[9], 5));
+		assert!(!proof.verify(&root, &[10], 6));
+	}
+
+	#[test]
+	fn proves_end_of_trie() {
+		let mut memdb = MemoryDB::new();
+		let mut root = H256::new();
+		populate(&mut memdb, &mut root);
+		let trie = TrieDB::new(&memdb, &root).unwrap();
+
+		let proof = RangeProof::generate(&trie, &[60], 10).unwrap();
+		assert_eq!(proof.items.len(), 4);
+		assert!(proof.verify(&root, &[60], 10));
+	}
+
+	#[test]
+	fn rejects_tampered_proofs() {
+		let mut memdb = MemoryDB::new();
+		let mut root = H256::new();
+		populate(&mut memdb, &mut root);
+		let trie = TrieDB::new(&memdb, &root).unwrap();
+		let proof = RangeProof::generate(&trie, &[20], 3).unwrap();
+
+		let mut changed_value = proof.clone();
+		changed_value.items[1].1 = vec![0; 8];
+		assert!(!changed_value.verify(&root, &[20], 3));
+
+		let mut dropped_item = proof.clone();
+		dropped_item.items.remove(1);
+		assert!(!dropped_item.verify(&root, &[20], 3));
+
+		let mut missing_node = proof.clone();
+		missing_node.nodes.pop();
+		assert!(!missing_node.verify(&root, &[20], 3));
+
+		assert!(!proof.verify(&H256::from(1), &[20], 3));
+	}
+}
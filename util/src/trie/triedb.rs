@@ -20,7 +20,7 @@ use nibbleslice::*;
 use rlp::*;
 use super::node::Node;
 use super::recorder::{Recorder, NoOp};
-use super::{Trie, TrieItem, TrieError};
+use super::{Trie, TrieItem, TrieError, TrieIterator};
 
 /// A `Trie` implementation using a generic `HashDB` backing database.
 ///
@@ -225,6 +225,87 @@ impl<'db> TrieDB<'db> {
 		}
 	}
 
+	/// Get up to `count` items with keys not less than `start`, in key order, while recording
+	/// every node visited to do so to the given recorder.
+	///
+	/// The recorded nodes are exactly those needed to repeat the query, which makes them a proof
+	/// of the returned range. See `RangeProof`.
+	pub fn range_recorded<R: Recorder>(&'db self, start: &[u8], count: usize, rec: &mut R) -> super::Result<Vec<(Bytes, &'db [u8])>> {
+		let mut items = Vec::new();
+		let root_rlp = try!(self.root_data(rec));
+		let start = NibbleSlice::new(start).iter().collect::<Vec<_>>();
+		try!(self.collect_range(root_rlp, &mut Vec::new(), Some(&start), count, rec, 1, &mut items));
+		Ok(items)
+	}
+
+	/// Recursion helper for `range_recorded`. `bound` holds the nibbles of the start key remaining
+	/// after `prefix`, or `None` if all the items below `node` come after the start key.
+	fn collect_range<R: Recorder>(
+		&'db self,
+		node: &'db [u8],
+		prefix: &mut Vec<u8>,
+		bound: Option<&[u8]>,
+		count: usize,
+		rec: &mut R,
+		d: u32,
+		items: &mut Vec<(Bytes, &'db [u8])>
+	) -> super::Result<()> {
+		if items.len() >= count {
+			return Ok(());
+		}
+
+		match Node::decoded(node) {
+			Node::Leaf(slice, value) => {
+				let nibbles = slice.iter().collect::<Vec<_>>();
+				if bound.map_or(true, |bound| &nibbles[..] >= bound) {
+					prefix.extend_from_slice(&nibbles);
+					items.push((nibbles_to_bytes(prefix), value));
+					let len = prefix.len() - nibbles.len();
+					prefix.truncate(len);
+				}
+			},
+			Node::Extension(slice, item) => {
+				let nibbles = slice.iter().collect::<Vec<_>>();
+				let bound = match bound {
+					Some(bound) if bound.starts_with(&nibbles) => Some(&bound[nibbles.len()..]),
+					Some(bound) if &nibbles[..] < bound => return Ok(()),
+					_ => None,
+				};
+				let data = try!(self.get_raw_or_lookup(item, rec, d));
+				prefix.extend_from_slice(&nibbles);
+				try!(self.collect_range(data, prefix, bound, count, rec, d + 1, items));
+				let len = prefix.len() - nibbles.len();
+				prefix.truncate(len);
+			},
+			Node::Branch(nodes, value) => {
+				let bound = bound.and_then(|bound| if bound.is_empty() { None } else { Some(bound) });
+				if let (None, Some(value)) = (bound, value) {
+					items.push((nibbles_to_bytes(prefix), value));
+				}
+				let first = bound.map_or(0, |bound| bound[0] as usize);
+				for i in first..16 {
+					if items.len() >= count {
+						break;
+					}
+					if nodes[i].is_empty() {
+						continue;
+					}
+					let child_bound = match bound {
+						Some(bound) if i == first => Some(&bound[1..]),
+						_ => None,
+					};
+					let data = try!(self.get_raw_or_lookup(nodes[i], rec, d));
+					prefix.push(i as u8);
+					try!(self.collect_range(data, prefix, child_bound, count, rec, d + 1, items));
+					prefix.pop();
+				}
+			},
+			Node::Empty => {},
+		}
+
+		Ok(())
+	}
+
 	/// Given some node-describing data `node`, return the actual node RLP.
 	/// This could be a simple identity operation in the case that the node is sufficiently small, but
 	/// may require a database lookup.
@@ -242,6 +323,11 @@ impl<'db> TrieDB<'db> {
 	}
 }
 
+/// Collapse a sequence of nibbles down to bytes.
+fn nibbles_to_bytes(nibbles: &[u8]) -> Bytes {
+	nibbles.chunks(2).map(|c| c[0] * 16 + c.get(1).cloned().unwrap_or(0)).collect()
+}
+
 #[derive(Clone, Eq, PartialEq)]
 enum Status {
 	Entering,
@@ -301,6 +387,44 @@ impl<'a> TrieDBIterator<'a> {
 		}
 	}
 
+	/// Descend into a payload, positioning the trail right before the first item with key not
+	/// less than the given nibbles.
+	fn seek_descend(&mut self, d: &'a [u8], key: &[u8]) -> super::Result<()> {
+		let node = try!(self.db.get_node(d, &mut NoOp, 0));
+		match node.clone() {
+			Node::Leaf(slice, _) => {
+				let nibbles = slice.iter().collect::<Vec<_>>();
+				// a leaf left `At` is skipped on the next step.
+				let status = if &nibbles[..] >= key { Status::Entering } else { Status::At };
+				self.key_nibbles.extend(nibbles);
+				self.trail.push(Crumb { status: status, node: node });
+			},
+			Node::Extension(slice, item) => {
+				let nibbles = slice.iter().collect::<Vec<_>>();
+				self.key_nibbles.extend_from_slice(&nibbles);
+				if key.starts_with(&nibbles) {
+					self.trail.push(Crumb { status: Status::At, node: node });
+					return self.seek_descend(item, &key[nibbles.len()..]);
+				}
+				let status = if &nibbles[..] > key { Status::Entering } else { Status::At };
+				self.trail.push(Crumb { status: status, node: node });
+			},
+			Node::Branch(nodes, _) => match key.is_empty() {
+				true => self.trail.push(Crumb { status: Status::Entering, node: node }),
+				false => {
+					let i = key[0] as usize;
+					self.key_nibbles.push(key[0]);
+					self.trail.push(Crumb { status: Status::AtChild(i), node: node });
+					if !nodes[i].is_empty() {
+						return self.seek_descend(nodes[i], &key[1..]);
+					}
+				}
+			},
+			Node::Empty => {},
+		}
+		Ok(())
+	}
+
 	/// Descend into a payload and get the next item.
 	fn descend_next(&mut self, d: &'a [u8]) -> Option<(Bytes, &'a [u8])> { self.descend(d); self.next() }
 
@@ -311,6 +435,15 @@ impl<'a> TrieDBIterator<'a> {
 	}
 }
 
+impl<'a> TrieIterator for TrieDBIterator<'a> {
+	fn seek(&mut self, key: &[u8]) -> super::Result<()> {
+		self.trail.clear();
+		self.key_nibbles.clear();
+		let root_rlp = try!(self.db.root_data(&mut NoOp));
+		self.seek_descend(root_rlp, &NibbleSlice::new(key).iter().collect::<Vec<_>>())
+	}
+}
+
 impl<'a> Iterator for TrieDBIterator<'a> {
 	type Item = (Bytes, &'a [u8]);
 
@@ -352,7 +485,7 @@ impl<'a> Iterator for TrieDBIterator<'a> {
 }
 
 impl<'db> Trie for TrieDB<'db> {
-	fn iter<'a>(&'a self) -> Box<Iterator<Item = TrieItem> + 'a> {
+	fn iter<'a>(&'a self) -> Box<TrieIterator<Item = TrieItem> + 'a> {
 		Box::new(TrieDBIterator::new(self))
 	}
 
@@ -395,3 +528,67 @@ fn iterator() {
 	assert_eq!(d.iter().map(|i|i.to_vec()).collect::<Vec<_>>(), t.iter().map(|x|x.0).collect::<Vec<_>>());
 	assert_eq!(d, t.iter().map(|x|x.1).collect::<Vec<_>>());
 }
+
+#[test]
+fn iterator_seek() {
+	use memorydb::*;
+	use super::TrieMut;
+	use super::triedbmut::*;
+
+	let d = vec![ &b"A"[..], &b"AA"[..], &b"AB"[..], &b"B"[..] ];
+
+	let mut memdb = MemoryDB::new();
+	let mut root = H256::new();
+	{
+		let mut t = TrieDBMut::new(&mut memdb, &mut root);
+		for x in &d {
+			t.insert(x, x).unwrap();
+		}
+	}
+
+	let t = TrieDB::new(&memdb, &root).unwrap();
+	let mut iter = TrieDBIterator::new(&t);
+	assert_eq!(iter.next(), Some((b"A".to_vec(), &b"A"[..])));
+	iter.seek(b"!").unwrap();
+	assert_eq!(d.iter().map(|i|i.to_vec()).collect::<Vec<_>>(), iter.clone().map(|x|x.0).collect::<Vec<_>>());
+	iter.seek(b"A").unwrap();
+	assert_eq!(&d[..], &iter.clone().map(|x|x.1).collect::<Vec<_>>()[..]);
+	iter.seek(b"AA").unwrap();
+	assert_eq!(&d[1..], &iter.clone().map(|x|x.1).collect::<Vec<_>>()[..]);
+	iter.seek(b"A!").unwrap();
+	assert_eq!(&d[1..], &iter.clone().map(|x|x.1).collect::<Vec<_>>()[..]);
+	iter.seek(b"AB").unwrap();
+	assert_eq!(&d[2..], &iter.clone().map(|x|x.1).collect::<Vec<_>>()[..]);
+	iter.seek(b"AB!").unwrap();
+	assert_eq!(&d[3..], &iter.clone().map(|x|x.1).collect::<Vec<_>>()[..]);
+	iter.seek(b"B").unwrap();
+	assert_eq!(&d[3..], &iter.clone().map(|x|x.1).collect::<Vec<_>>()[..]);
+	iter.seek(b"C").unwrap();
+	assert_eq!(&d[4..], &iter.clone().map(|x|x.1).collect::<Vec<_>>()[..]);
+}
+
+#[test]
+fn range_recorded() {
+	use memorydb::*;
+	use super::TrieMut;
+	use super::triedbmut::*;
+	use super::recorder::BasicRecorder;
+
+	let d = vec![ &b"A"[..], &b"AA"[..], &b"AB"[..], &b"B"[..] ];
+
+	let mut memdb = MemoryDB::new();
+	let mut root = H256::new();
+	{
+		let mut t = TrieDBMut::new(&mut memdb, &mut root);
+		for x in &d {
+			t.insert(x, x).unwrap();
+		}
+	}
+
+	let t = TrieDB::new(&memdb, &root).unwrap();
+	let mut recorder = BasicRecorder::new();
+	assert_eq!(t.range_recorded(b"A!", 2, &mut recorder).unwrap(), vec![(b"AA".to_vec(), &b"AA"[..]), (b"AB".to_vec(), &b"AB"[..])]);
+	assert!(!recorder.drain().is_empty());
+	assert_eq!(t.range_recorded(b"", 10, &mut NoOp).unwrap().len(), 4);
+	assert_eq!(t.range_recorded(b"C", 10, &mut NoOp).unwrap(), vec![]);
+}